@@ -1,4 +1,6 @@
 use core::fmt;
+use std::path::Path;
+use std::time::Duration;
 use std::time::Instant;
 
 use anyhow::Result;
@@ -12,18 +14,34 @@ use tracing::instrument;
 
 use crate::ComponentInputResult;
 use crate::commander::new_commander;
+use crate::commander::set_snapshot_paused;
+use crate::commander::take_jj_command_ran;
+use crate::commander::take_repo_changed;
+use crate::env::get_env;
 use crate::ui::Component;
 use crate::ui::ComponentAction;
+use crate::ui::EditorRequest;
+use crate::ui::annotate_tab::AnnotateTab;
+use crate::ui::auto_fetch::AutoFetch;
 use crate::ui::bookmarks_tab::BookmarksTab;
 use crate::ui::dialog::CommandPopup;
+use crate::ui::dialog::MessagePopup;
 use crate::ui::files_tab::FilesTab;
 use crate::ui::log_tab::LogTab;
+use crate::ui::op_watch::OperationWatch;
+use crate::ui::toast::FetchToast;
+use crate::ui::toast::OperationToast;
+use crate::ui::toast::ReloadToast;
+use crate::ui::toast::UndoToast;
+use crate::ui::undo::UndoHistory;
 
 #[derive(PartialEq, Copy, Clone)]
 pub enum Tab {
     Log,
     Files,
     Bookmarks,
+    Status,
+    Annotate,
 }
 
 impl fmt::Display for Tab {
@@ -32,12 +50,20 @@ impl fmt::Display for Tab {
             Tab::Log => write!(f, "Log"),
             Tab::Files => write!(f, "Files"),
             Tab::Bookmarks => write!(f, "Bookmarks"),
+            Tab::Status => write!(f, "Status"),
+            Tab::Annotate => write!(f, "Annotate"),
         }
     }
 }
 
 impl Tab {
-    pub const VALUES: [Self; 3] = [Tab::Log, Tab::Files, Tab::Bookmarks];
+    pub const VALUES: [Self; 5] = [
+        Tab::Log,
+        Tab::Files,
+        Tab::Bookmarks,
+        Tab::Status,
+        Tab::Annotate,
+    ];
 }
 
 pub struct Stats {
@@ -49,18 +75,55 @@ pub struct App<'a> {
     pub log: Option<LogTab<'a>>,
     pub files: Option<FilesTab>,
     pub bookmarks: Option<BookmarksTab<'a>>,
+    /// Files tab in the status view of the working copy
+    pub status: Option<FilesTab>,
+    pub annotate: Option<AnnotateTab>,
     pub popup: Option<Box<dyn Component>>,
+    /// File to open once the terminal has been handed over to the editor
+    pub editor_request: Option<EditorRequest>,
+    /// Arguments of a jj command to run once the terminal has been
+    /// handed over to it
+    pub interactive_request: Option<Vec<String>>,
+    /// Operation of the last command that changed the repository
+    pub toast: Option<OperationToast>,
+    /// Operations undone with `U`, to redo with `.`
+    undo_history: UndoHistory,
+    /// Operation of the last undo or redo
+    pub undo_toast: Option<UndoToast>,
+    /// Fetches in the background, if `blazingjj.auto-fetch-minutes` is set
+    auto_fetch: Option<AutoFetch>,
+    /// Remote bookmarks moved by the last background fetch
+    pub fetch_toast: Option<FetchToast>,
+    /// Notices the operations of other processes
+    op_watch: Option<OperationWatch>,
+    /// Operation of another process the tabs were reloaded after
+    pub reload_toast: Option<ReloadToast>,
     pub stats: Stats,
 }
 
 impl<'a> App<'a> {
     pub fn new() -> Result<App<'a>> {
+        set_snapshot_paused(!get_env().jj_config.snapshot());
         Ok(App {
             current_tab: Tab::Log,
             log: None,
             files: None,
             bookmarks: None,
+            status: None,
+            annotate: None,
             popup: None,
+            editor_request: None,
+            interactive_request: None,
+            toast: None,
+            undo_history: UndoHistory::default(),
+            undo_toast: None,
+            auto_fetch: get_env()
+                .jj_config
+                .auto_fetch_interval()
+                .map(AutoFetch::new),
+            fetch_toast: None,
+            op_watch: OperationWatch::new(Path::new(&get_env().root)),
+            reload_toast: None,
             stats: Stats {
                 start_time: Instant::now(),
             },
@@ -88,6 +151,14 @@ impl<'a> App<'a> {
     pub fn set_tab(&mut self, tab: Tab) -> Result<()> {
         info!("Setting tab to {}", tab);
         self.current_tab = tab;
+        if tab == Tab::Bookmarks {
+            // Bookmarks are moved to the change selected in the log
+            let head = self
+                .log
+                .as_mut()
+                .and_then(|log_tab| log_tab.selection().head);
+            self.get_bookmarks_tab()?.set_selected_change(head);
+        }
         self.get_or_init_current_tab()?.focus()?;
         Ok(())
     }
@@ -113,6 +184,20 @@ impl<'a> App<'a> {
             .ok_or_else(|| anyhow!("Failed to get mutable reference to FilesTab"))
     }
 
+    pub fn get_status_tab(&mut self) -> Result<&mut FilesTab> {
+        if self.status.is_none() {
+            self.status = Some(FilesTab::new_status()?);
+        }
+
+        self.status
+            .as_mut()
+            .ok_or_else(|| anyhow!("Failed to get mutable reference to the status FilesTab"))
+    }
+
+    pub fn get_annotate_tab(&mut self) -> &mut AnnotateTab {
+        self.annotate.get_or_insert_with(AnnotateTab::new)
+    }
+
     pub fn get_bookmarks_tab(&mut self) -> Result<&mut BookmarksTab<'a>> {
         if self.bookmarks.is_none() {
             self.bookmarks = Some(BookmarksTab::new()?);
@@ -128,9 +213,41 @@ impl<'a> App<'a> {
             Tab::Log => self.get_log_tab()?,
             Tab::Files => self.get_files_tab()?,
             Tab::Bookmarks => self.get_bookmarks_tab()?,
+            Tab::Status => self.get_status_tab()?,
+            Tab::Annotate => self.get_annotate_tab(),
         })
     }
 
+    /// The current tab has work to do at the next update, e.g. loading
+    /// more of the log, so the app should not wait for input
+    pub fn is_loading(&self) -> bool {
+        self.current_tab == Tab::Log && self.log.as_ref().is_some_and(LogTab::is_loading)
+    }
+
+    /// The current tab has work to do after a short delay, e.g. checking
+    /// a revset being typed, so the app should update again soon
+    pub fn is_ticking(&self) -> bool {
+        self.toast.is_some()
+            || self.undo_toast.is_some()
+            || self.fetch_toast.is_some()
+            || self.reload_toast.is_some()
+            || self.auto_fetch.as_ref().is_some_and(AutoFetch::is_running)
+            || self.current_tab == Tab::Log && self.log.as_ref().is_some_and(LogTab::is_ticking)
+    }
+
+    /// Time until the next background fetch, check of the log tab or
+    /// check for operations of other processes, which the app should not
+    /// wait for input longer than
+    pub fn time_until_background_work(&self) -> Option<Duration> {
+        let fetch = self.auto_fetch.as_ref().map(AutoFetch::time_until_fetch);
+        let log_check = self
+            .log
+            .as_ref()
+            .and_then(LogTab::time_until_background_check);
+        let op_check = self.op_watch.as_ref().map(OperationWatch::time_until_check);
+        fetch.into_iter().chain(log_check).chain(op_check).min()
+    }
+
     pub fn get_tab(&mut self, tab: Tab) -> Option<&mut dyn Component> {
         match tab {
             Tab::Log => self
@@ -145,6 +262,14 @@ impl<'a> App<'a> {
                 .bookmarks
                 .as_mut()
                 .map(|bookmarks_tab| bookmarks_tab as &mut dyn Component),
+            Tab::Status => self
+                .status
+                .as_mut()
+                .map(|status_tab| status_tab as &mut dyn Component),
+            Tab::Annotate => self
+                .annotate
+                .as_mut()
+                .map(|annotate_tab| annotate_tab as &mut dyn Component),
         }
     }
 
@@ -158,6 +283,14 @@ impl<'a> App<'a> {
                 self.get_log_tab()?.set_head(head);
                 self.set_tab(Tab::Log)?;
             }
+            ComponentAction::GoToChange(head) => {
+                self.get_log_tab()?.go_to_head(head)?;
+                self.set_tab(Tab::Log)?;
+            }
+            ComponentAction::ViewAnnotate(head, path) => {
+                self.get_annotate_tab().set_file(head, path);
+                self.set_tab(Tab::Annotate)?;
+            }
             ComponentAction::ChangeHead(head) => {
                 self.get_files_tab()?.set_head(&head)?;
             }
@@ -176,6 +309,12 @@ impl<'a> App<'a> {
                     self.get_log_tab()?.set_head(head);
                 };
             }
+            ComponentAction::OpenEditor(request) => {
+                self.editor_request = Some(request);
+            }
+            ComponentAction::RunInteractive(args) => {
+                self.interactive_request = Some(args);
+            }
         }
 
         Ok(())
@@ -193,9 +332,106 @@ impl<'a> App<'a> {
             self.handle_action(component_action)?;
         }
 
+        if take_repo_changed() {
+            self.toast = new_commander()
+                .get_last_operation()
+                .ok()
+                .map(OperationToast::new);
+            self.undo_toast = None;
+            self.reload_toast = None;
+        } else if self.toast.as_ref().is_some_and(OperationToast::is_expired) {
+            self.toast = None;
+        }
+        if self.undo_toast.as_ref().is_some_and(UndoToast::is_expired) {
+            self.undo_toast = None;
+        }
+
+        if let Some(auto_fetch) = self.auto_fetch.as_mut() {
+            let updates = auto_fetch.update();
+            if !updates.is_empty() {
+                self.fetch_toast = Some(FetchToast::new(&updates));
+            }
+        }
+        if self
+            .fetch_toast
+            .as_ref()
+            .is_some_and(FetchToast::is_expired)
+        {
+            self.fetch_toast = None;
+        }
+
+        let operations_changed = self.op_watch.as_mut().is_some_and(OperationWatch::update);
+        // The operations of the app's own commands, including loads on
+        // worker threads and the background fetch, are not reloaded
+        if take_jj_command_ran() {
+            if let Some(op_watch) = self.op_watch.as_mut() {
+                op_watch.sync();
+            }
+        } else if operations_changed {
+            self.reload()?;
+        } else if self
+            .reload_toast
+            .as_ref()
+            .is_some_and(ReloadToast::is_expired)
+        {
+            self.reload_toast = None;
+        }
+
+        Ok(())
+    }
+
+    /// Reload the current tab after another process changed the
+    /// repository, and tell which operation did
+    fn reload(&mut self) -> Result<()> {
+        let mut commander = new_commander();
+        commander.ignore_working_copy = true;
+        if let Ok(operation) = commander.get_last_operation() {
+            self.reload_toast = Some(ReloadToast::new(operation));
+        }
+        if self.current_tab == Tab::Log {
+            self.get_log_tab()?.reload()?;
+        } else {
+            self.get_or_init_current_tab()?.focus()?;
+        }
         Ok(())
     }
 
+    /// Undo the operation of the toast, or else the next operation of the
+    /// undo history, and show the repository as it is after
+    fn undo(&mut self) -> Result<()> {
+        let operation = self.toast.take().map(|toast| toast.operation.id);
+        let result = self
+            .undo_history
+            .undo(&new_commander(), operation.as_deref());
+        self.show_undo_result("Undo operation", result.map(UndoToast::undone))
+    }
+
+    /// Redo the last undone operation, and show the repository as it is
+    /// after
+    fn redo(&mut self) -> Result<()> {
+        self.toast = None;
+        let result = self.undo_history.redo(&new_commander());
+        self.show_undo_result("Redo operation", result.map(UndoToast::redone))
+    }
+
+    fn show_undo_result(&mut self, title: &str, result: Result<UndoToast>) -> Result<()> {
+        match result {
+            Ok(undo_toast) => {
+                // The undo toast replaces the operation toast of the revert
+                take_repo_changed();
+                self.undo_toast = Some(undo_toast);
+                self.handle_action(ComponentAction::RefreshTab())
+            }
+            Err(err) => {
+                self.popup = Some(Box::new(MessagePopup::new(
+                    title.to_owned(),
+                    format!("{err:#}"),
+                )));
+                Ok(())
+            }
+        }
+    }
+
     #[instrument(level = "trace", skip(self))]
     pub fn input(&mut self, event: Event) -> Result<bool> {
         if let Some(popup) = self.popup.as_mut() {
@@ -262,7 +498,16 @@ impl<'a> App<'a> {
                         }
                         // General jj command runner
                         else if key.code == KeyCode::Char(':') {
-                            self.popup = Some(Box::new(CommandPopup::new()));
+                            let selection = self.get_or_init_current_tab()?.selection();
+                            self.popup = Some(Box::new(CommandPopup::new(selection)));
+                        }
+                        // Undo the operation of the toast, or the last one
+                        else if key.code == KeyCode::Char('U') {
+                            self.undo()?;
+                        }
+                        // Redo the last undone operation
+                        else if key.code == KeyCode::Char('.') {
+                            self.redo()?;
                         }
                     }
                 }
@@ -6,6 +6,7 @@ other jj bookmark commands are defined in module [jj][super::jj].
 
 It is mostly used in the [bookmarks_tab][crate::ui::bookmarks_tab] module.
 */
+use std::collections::HashMap;
 use std::fmt::Display;
 use std::sync::LazyLock;
 
@@ -41,8 +42,35 @@ impl Display for Bookmark {
     }
 }
 
+/// State of a bookmark, besides its target
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BookmarkStatus {
+    /// The bookmark points to several commits
+    pub conflict: bool,
+    /// For a tracked remote bookmark, how far it is from the local one
+    pub tracking: Option<TrackingDistance>,
+}
+
+/// Commits between a tracked remote bookmark and its local bookmark
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TrackingDistance {
+    /// Commits of the remote bookmark that the local one lacks, to fetch
+    pub ahead: usize,
+    /// Commits of the local bookmark that the remote one lacks, to push
+    pub behind: usize,
+}
+
 // Template which outputs `[name@remote]`. Used to parse data from bookmark list
-const BRANCH_TEMPLATE: &str = r#""[" ++ name ++ "@" ++ remote ++ "|" ++ present ++ "|" ++ self.normal_target().committer().timestamp().format("%s") ++ "]""#;
+const BRANCH_TEMPLATE: &str = r#""[" ++ name ++ "@" ++ remote ++ "|" ++ present ++ "|" ++ if(self.normal_target(), self.normal_target().committer().timestamp().format("%s"), "0") ++ "]""#;
+// Template which outputs `[conflict|ahead|behind]`, put before BRANCH_TEMPLATE. The distances
+// are empty unless the bookmark is a tracked remote bookmark.
+const STATUS_TEMPLATE: &str = r#""[" ++ conflict ++ "|" ++ if(tracked, self.tracking_ahead_count().lower() ++ "|" ++ self.tracking_behind_count().lower(), "|") ++ "]""#;
+// Template which outputs the distance of each tracked remote bookmark to
+// its local bookmark, see parse_tracking_distances
+const TRACKING_TEMPLATE: &str = r#"if(tracked, name ++ "\t" ++ remote ++ "\t" ++ self.tracking_ahead_count().lower() ++ "\t" ++ self.tracking_behind_count().lower() ++ "\n")"#;
+// Regex to parse the status, followed by the bookmark
+static STATUS_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\[(true|false)\|(\d*)\|(\d*)\](.*)$").unwrap());
 // Regex to parse bookmark
 static BRANCH_REGEX: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^\[(.*)@(.*)\|(true|false)\|(\d+)\]$").unwrap());
@@ -74,10 +102,53 @@ fn parse_bookmark(text: &str) -> Option<Bookmark> {
     })
 }
 
+/// Parse the status of a bookmark list line, and return it with the rest
+/// of the line
+fn parse_bookmark_status(text: &str) -> Option<(BookmarkStatus, &str)> {
+    let captured = STATUS_REGEX.captures(text)?;
+    let tracking = match (captured[2].parse(), captured[3].parse()) {
+        (Ok(ahead), Ok(behind)) => Some(TrackingDistance { ahead, behind }),
+        _ => None,
+    };
+    let status = BookmarkStatus {
+        conflict: &captured[1] == "true",
+        tracking,
+    };
+    Some((status, captured.get(4)?.as_str()))
+}
+
+/// Parse the output of TRACKING_TEMPLATE into the distance of each local
+/// bookmark to its tracked remote bookmarks. With several remotes, the
+/// furthest one counts. The git remote of colocated repositories is left
+/// out.
+fn parse_tracking_distances(text: &str) -> HashMap<String, TrackingDistance> {
+    let mut distances: HashMap<String, TrackingDistance> = HashMap::new();
+    for line in text.lines() {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [name, remote, ahead, behind] = fields[..] else {
+            continue;
+        };
+        let (Ok(ahead), Ok(behind)) = (ahead.parse(), behind.parse()) else {
+            continue;
+        };
+        if remote == "git" {
+            continue;
+        }
+        let distance = distances.entry(name.to_owned()).or_default();
+        distance.ahead = distance.ahead.max(ahead);
+        distance.behind = distance.behind.max(behind);
+    }
+    distances
+}
+
 #[derive(Clone, Debug)]
 pub enum BookmarkLine {
     Unparsable(String),
-    Parsed { text: String, bookmark: Bookmark },
+    Parsed {
+        text: String,
+        bookmark: Bookmark,
+        status: BookmarkStatus,
+    },
 }
 
 impl BookmarkLine {
@@ -113,6 +184,9 @@ impl Commander {
                           ": " ++ format_commit_summary_with_refs(ref.normal_target(), ""),
                         )
                     '''"#,
+                    "--config",
+                    // The distances to the local bookmarks are drawn by blazingjj
+                    r#"template-aliases.'format_tracked_remote_ref_distances(ref)'='""'"#,
                 ],
                 args.clone(),
             ]
@@ -128,7 +202,7 @@ impl Commander {
                         "bookmark",
                         "list",
                         "-T",
-                        &format!(r#"{BRANCH_TEMPLATE} ++ "\n""#),
+                        &format!(r#"{STATUS_TEMPLATE} ++ {BRANCH_TEMPLATE} ++ "\n""#),
                     ],
                     args,
                 ]
@@ -138,12 +212,17 @@ impl Commander {
             )?
             .lines()
             .zip(bookmarks_colored.lines())
-            .map(|(line, line_colored)| match parse_bookmark(line) {
-                Some(bookmark) => BookmarkLine::Parsed {
-                    text: line_colored.to_owned(),
-                    bookmark,
-                },
-                None => BookmarkLine::Unparsable(line_colored.to_owned()),
+            .map(|(line, line_colored)| {
+                let parsed = parse_bookmark_status(line)
+                    .and_then(|(status, line)| Some((status, parse_bookmark(line)?)));
+                match parsed {
+                    Some((status, bookmark)) => BookmarkLine::Parsed {
+                        text: line_colored.to_owned(),
+                        bookmark,
+                        status,
+                    },
+                    None => BookmarkLine::Unparsable(line_colored.to_owned()),
+                }
             })
             .collect();
 
@@ -172,8 +251,23 @@ impl Commander {
         Ok(bookmarks)
     }
 
+    /// Get how far the local bookmarks are from their tracked remote
+    /// bookmarks, by name. Maps to `jj bookmark list --tracked`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_tracking_distances(
+        &self,
+    ) -> Result<HashMap<String, TrackingDistance>, CommandError> {
+        let output = self.execute_jj_command(
+            ["bookmark", "list", "--tracked", "-T", TRACKING_TEMPLATE],
+            false,
+            true,
+        )?;
+        Ok(parse_tracking_distances(&output))
+    }
+
     /// Get bookmark details.
-    /// Maps to `jj show <bookmark>`
+    /// Maps to `jj show <bookmark>`, with the configured
+    /// `blazingjj.show-template` if any
     #[instrument(level = "trace", skip(self))]
     pub fn get_bookmark_show(
         &self,
@@ -183,6 +277,9 @@ impl Commander {
     ) -> Result<String, CommandError> {
         let bookmark_arg = &bookmark.to_string();
         let mut args = vec!["show", bookmark_arg];
+        if let Some(template) = self.env.jj_config.show_template() {
+            args.extend(["-T", template]);
+        }
         args.append(&mut diff_format.get_args());
         if ignore_working_copy {
             args.push("--ignore-working-copy");
@@ -245,6 +342,48 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn parse_status() {
+        assert_eq!(
+            parse_bookmark_status("[false||][main@|true|1700000000]"),
+            Some((BookmarkStatus::default(), "[main@|true|1700000000]"))
+        );
+        assert_eq!(
+            parse_bookmark_status("[true|2|1][main@origin|true|0]"),
+            Some((
+                BookmarkStatus {
+                    conflict: true,
+                    tracking: Some(TrackingDistance {
+                        ahead: 2,
+                        behind: 1
+                    }),
+                },
+                "[main@origin|true|0]"
+            ))
+        );
+        assert_eq!(parse_bookmark_status("main: abc"), None);
+    }
+
+    #[test]
+    fn parse_tracking() {
+        let distances = parse_tracking_distances(
+            "main\torigin\t2\t0\nmain\tupstream\t0\t1\nmain\tgit\t5\t5\nfix\torigin\t0\t0\nbad\n",
+        );
+        assert_eq!(
+            distances,
+            HashMap::from([
+                (
+                    "main".to_owned(),
+                    TrackingDistance {
+                        ahead: 2,
+                        behind: 1
+                    }
+                ),
+                ("fix".to_owned(), TrackingDistance::default()),
+            ])
+        );
+    }
+
     #[test]
     fn get_bookmarks_list() -> Result<()> {
         let test_repo = TestRepo::new()?;
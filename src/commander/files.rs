@@ -4,12 +4,16 @@
 This module has features to parse the diff output.
 It is mostly used in the [files_tab][crate::ui::files_tab] module.
 */
+use std::io::Write;
+use std::process::Command;
 use std::sync::LazyLock;
 
 use anyhow::Context;
 use anyhow::Result;
+use anyhow::bail;
 use ratatui::style::Color;
 use regex::Regex;
+use tempfile::NamedTempFile;
 use tracing::instrument;
 
 use crate::commander::CommandError;
@@ -31,6 +35,23 @@ pub enum DiffType {
     Modified,
     Deleted,
     Renamed,
+    /// In the working copy, but not tracked, e.g. because of
+    /// `snapshot.auto-track`
+    Untracked,
+}
+
+/// Totals of `jj diff --stat`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DiffStat {
+    pub files: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+impl DiffStat {
+    pub fn is_empty(&self) -> bool {
+        self.files == 0
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -38,6 +59,52 @@ pub struct Conflict {
     pub path: String,
 }
 
+/// A file of the working copy that is ignored, with the pattern that
+/// ignores it
+#[derive(Clone, Debug, PartialEq)]
+pub struct IgnoredFile {
+    /// Path relative to the root, ending with `/` for a directory
+    pub path: String,
+    /// Ignore file, line and pattern, e.g. `.gitignore:1:/target`
+    pub source: String,
+}
+
+/// A line of `jj file annotate`, with the change that last modified it
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnnotatedLine {
+    /// Short change id
+    pub change_id: String,
+    pub commit_id: CommitId,
+    pub author: String,
+    /// Author timestamp, in seconds since the epoch
+    pub timestamp: i64,
+    /// Content of the line, without the line ending
+    pub content: String,
+}
+
+impl File {
+    /// Paths of the file in the change. A renamed file, shown like
+    /// `src/{old.rs => new.rs}`, has its old and new path.
+    pub fn paths(&self) -> Vec<String> {
+        let Some(path) = &self.path else {
+            return vec![];
+        };
+        if self.diff_type == Some(DiffType::Renamed) && RENAME_REGEX.is_match(path) {
+            return ["$1", "$2"]
+                .iter()
+                .map(|side| {
+                    RENAME_REGEX
+                        .replace(path, *side)
+                        .replace("//", "/")
+                        .trim_start_matches('/')
+                        .to_owned()
+                })
+                .collect();
+        }
+        vec![path.clone()]
+    }
+}
+
 impl DiffType {
     pub fn parse(value: &str) -> Option<Self> {
         match value {
@@ -45,16 +112,29 @@ impl DiffType {
             "M" => Some(DiffType::Modified),
             "D" => Some(DiffType::Deleted),
             "R" => Some(DiffType::Renamed),
+            "?" => Some(DiffType::Untracked),
             _ => None,
         }
     }
 
+    /// Status marker of `jj diff --summary`, e.g. `M`
+    pub fn marker(&self) -> &'static str {
+        match self {
+            DiffType::Added => "A",
+            DiffType::Modified => "M",
+            DiffType::Deleted => "D",
+            DiffType::Renamed => "R",
+            DiffType::Untracked => "?",
+        }
+    }
+
     pub fn color(&self) -> Color {
         match self {
             DiffType::Added => Color::Green,
             DiffType::Modified => Color::Cyan,
             DiffType::Renamed => Color::Cyan,
             DiffType::Deleted => Color::Red,
+            DiffType::Untracked => Color::Magenta,
         }
     }
 }
@@ -62,40 +142,176 @@ impl DiffType {
 // Example line: `A README.md`, `M src/main.rs`, `D Hello World`
 static FILES_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(.) (.*)").unwrap());
 static RENAME_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{(.*?) => (.*?)\}").unwrap());
+// Last line of `jj diff --stat`, e.g. `2 files changed, 5 insertions(+), 1 deletion(-)`
+static DIFF_STAT_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(\d+) files? changed, (\d+) insertions?\(\+\), (\d+) deletions?\(-\)").unwrap()
+});
+// File line of `jj diff --stat`, e.g. `src/main.rs | 12 ++++++------`
+static DIFF_STAT_FILE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(.*\S) +\| +(\d+)(?: |$)").unwrap());
 static CONFLICTS_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(.*)    .*").unwrap());
+/// Fields of an annotated line separated by tabs, the content last as it
+/// can have tabs
+static ANNOTATE_TEMPLATE: &str = r#"commit.change_id().short(8) ++ "\t" ++ commit.commit_id() ++ "\t" ++ commit.author().name() ++ "\t" ++ commit.author().timestamp().format("%s") ++ "\t" ++ content"#;
+/// Merge tool of `jj resolve` writing the content resolved in the
+/// conflict popup
+const RESOLVE_TOOL: &str = "blazingjj-resolved";
+/// Program and first arguments of the merge tool, copying a file to
+/// another. Windows has no `cp`, but the `copy` of cmd.
+#[cfg(not(windows))]
+const COPY_COMMAND: (&str, &[&str]) = ("cp", &[]);
+#[cfg(windows)]
+const COPY_COMMAND: (&str, &[&str]) = ("cmd", &["/C", "copy", "/Y"]);
 
 impl Commander {
     /// Get list of changes files in a change. Parses the output.
     /// Maps to `jj diff --summary -r <revision>`
     #[instrument(level = "trace", skip(self))]
     pub fn get_files(&self, head: &Head) -> Result<Vec<File>, CommandError> {
+        Ok(parse_files(&self.execute_jj_command(
+            vec!["diff", "-r", head.commit_id.as_str(), "--summary"],
+            false,
+            true,
+        )?))
+    }
+
+    /// Get the paths of all files in a revision, relative to the root.
+    /// Maps to `jj file list -r <revision>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_file_list(&self, commit_id: &CommitId) -> Result<Vec<String>, CommandError> {
         Ok(self
-            .execute_jj_command(
-                vec!["diff", "-r", head.commit_id.as_str(), "--summary"],
-                false,
-                true,
-            )?
+            .execute_jj_command(vec!["file", "list", "-r", commit_id.as_str()], false, true)?
             .lines()
-            .map(|line| {
-                let captured = FILES_REGEX.captures(line);
-                let diff_type = captured
-                    .as_ref()
-                    .and_then(|captured| captured.get(1))
-                    .and_then(|inner_text| DiffType::parse(inner_text.as_str()));
-                let path = captured
-                    .as_ref()
-                    .and_then(|captured| captured.get(2))
-                    .map(|inner_text| inner_text.as_str().to_owned());
-
-                File {
-                    line: line.to_string(),
-                    path,
-                    diff_type,
-                }
-            })
+            .map(str::to_owned)
             .collect())
     }
 
+    /// Get the lines of a file in a revision, with the change that last
+    /// modified each. Maps to `jj file annotate -r <revision> <path>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_annotation(
+        &self,
+        commit_id: &CommitId,
+        path: &str,
+    ) -> Result<Vec<AnnotatedLine>, CommandError> {
+        Ok(parse_annotation(&self.execute_jj_command(
+            vec![
+                "file",
+                "annotate",
+                "-r",
+                commit_id.as_str(),
+                "-T",
+                ANNOTATE_TEMPLATE,
+                &Self::get_file_revset(path),
+            ],
+            false,
+            true,
+        )?))
+    }
+
+    /// Get the files of the working copy that are not tracked. Parses the
+    /// output. Maps to `jj status`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_untracked_files(&self) -> Result<Vec<File>, CommandError> {
+        Ok(parse_untracked_files(&self.execute_jj_command(
+            vec!["status"],
+            false,
+            true,
+        )?))
+    }
+
+    /// Get the files and directories of the working copy that are
+    /// ignored, so not snapshotted, with the pattern ignoring each. jj
+    /// can't list them, so git does, which only works in repositories
+    /// colocated with git. The paths are separated by NUL, so git
+    /// doesn't quote the ones with special characters.
+    /// Maps to `git ls-files --others --ignored -z` and
+    /// `git check-ignore -v -z --stdin`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_ignored_files(&self) -> Result<Vec<IgnoredFile>> {
+        if !self.is_colocated() {
+            bail!("Ignored files can only be listed in repositories colocated with git");
+        }
+        let paths: Vec<String> = self
+            .execute_command(Command::new("git").args([
+                "ls-files",
+                "--others",
+                "--ignored",
+                "--exclude-standard",
+                "--directory",
+                "--no-empty-directory",
+                "-z",
+            ]))
+            .context("Failed executing git ls-files")?
+            .split('\0')
+            // jj ignores its own directory
+            .filter(|path| !path.is_empty() && !path.starts_with(".jj/"))
+            .map(str::to_owned)
+            .collect();
+        if paths.is_empty() {
+            return Ok(vec![]);
+        }
+        // The paths are given on stdin, there can be too many for the
+        // arguments
+        let mut input = NamedTempFile::new().context("Failed creating the list of paths")?;
+        input
+            .write_all(paths.join("\0").as_bytes())
+            .context("Failed writing the list of paths")?;
+        let sources = self
+            .execute_command(
+                Command::new("git")
+                    .args(["check-ignore", "--verbose", "--no-index", "-z", "--stdin"])
+                    .stdin(input.reopen().context("Failed reading the list of paths")?),
+            )
+            .context("Failed executing git check-ignore")?;
+        Ok(parse_ignored_files(&sources))
+    }
+
+    /// Get the files that differ between two revisions, i.e. that
+    /// restoring from one into the other would change.
+    /// Maps to `jj diff --from <from> --to <to> --summary`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_files_between(&self, from: &str, to: &str) -> Result<Vec<File>, CommandError> {
+        Ok(parse_files(&self.execute_jj_command(
+            vec!["diff", "--from", from, "--to", to, "--summary"],
+            false,
+            true,
+        )?))
+    }
+
+    /// Get the number of changed files and lines of a revision.
+    /// Maps to `jj diff --stat -r <revision>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_diff_stat(&self, revision: &str) -> Result<DiffStat, CommandError> {
+        Ok(parse_diff_stat(&self.execute_jj_command(
+            vec!["diff", "-r", revision, "--stat"],
+            false,
+            true,
+        )?))
+    }
+
+    /// Get the number of changed lines of each file of a diff, given the
+    /// revision and fileset arguments, e.g. `["-r", <commit>]`. Renamed
+    /// and copied files are left out.
+    /// Maps to `jj diff --stat <revisions>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_diff_file_sizes(
+        &mut self,
+        revisions: &[&str],
+        ignore_working_copy: bool,
+    ) -> Result<Vec<(String, usize)>, CommandError> {
+        // Wide enough that jj does not shorten the paths
+        self.set_env("COLUMNS", "1000");
+        let mut args = vec!["diff", "--stat"];
+        args.extend(revisions);
+        if ignore_working_copy {
+            args.push("--ignore-working-copy");
+        }
+        Ok(parse_diff_file_sizes(
+            &self.execute_jj_command(args, false, true)?,
+        ))
+    }
+
     /// Get list of changes files in a change. Parses the output.
     /// Maps to `jj diff --summary -r <revision>`
     #[instrument(level = "trace", skip(self))]
@@ -140,6 +356,9 @@ impl Commander {
         let Some(path) = current_file.path.as_ref() else {
             return Ok(None);
         };
+        if current_file.diff_type == Some(DiffType::Untracked) {
+            return Ok(None);
+        }
 
         let path = if let (true, Some(captures)) = (
             current_file.diff_type == Some(DiffType::Renamed),
@@ -163,29 +382,49 @@ impl Commander {
         self.execute_jj_command(args, true, true).map(Some)
     }
 
+    /// Split a change in two: the first with the changes of the paths,
+    /// the second with the rest. Each gets its description.
+    /// Maps to `jj split -r <revision> -m <message> <paths>` and
+    /// `jj describe <second> -m <message>`
     #[instrument(level = "trace", skip(self))]
-    pub fn untrack_file(&self, current_file: &File) -> Result<Option<String>, CommandError> {
-        let Some(path) = current_file.path.as_ref() else {
-            return Ok(None);
-        };
+    pub fn run_split(
+        &self,
+        head: &Head,
+        paths: &[String],
+        first_message: &str,
+        second_message: &str,
+    ) -> Result<()> {
+        // The parents are not rewritten, so they tell the first change
+        // from the second afterwards, whichever keeps the change id
+        let parents = self.get_commit_ids(&format!("{}-", head.commit_id))?;
+        let filesets: Vec<String> = paths
+            .iter()
+            .map(|path| Self::get_file_revset(path))
+            .collect();
+        let mut args = vec![
+            "split",
+            "-r",
+            head.commit_id.as_str(),
+            "-m",
+            first_message,
+            "--",
+        ];
+        args.extend(filesets.iter().map(String::as_str));
+        self.execute_void_jj_command(args)
+            .context("Failed executing jj split")?;
 
-        let path = if let Some(DiffType::Renamed) = current_file.diff_type
-            && let Some(captures) = RENAME_REGEX.captures(path)
-        {
-            match captures.get(2) {
-                Some(path) => path.as_str(),
-                None => return Ok(None),
-            }
+        let kept = self.get_revision_head(&format!("change_id({})", head.change_id))?;
+        let second = if self.get_commit_ids(&format!("{}-", kept.commit_id))? == parents {
+            // The change id was kept by the first change, the second is
+            // its only child
+            self.get_commit_ids(&format!("{}+", kept.commit_id))?
+                .into_iter()
+                .next()
+                .context("Failed finding the second change of the split")?
         } else {
-            path
+            kept.commit_id
         };
-
-        let fileset = Self::get_file_revset(path);
-        Ok(Some(self.execute_jj_command(
-            vec!["file", "untrack", &fileset],
-            false,
-            true,
-        )?))
+        self.run_describe(second.as_str(), second_message, false)
     }
 
     #[instrument(level = "trace", skip(self))]
@@ -213,12 +452,283 @@ impl Commander {
         )?))
     }
 
-    fn get_file_revset(path: &str) -> String {
+    /// Get the content of a file in a revision.
+    /// Maps to `jj file show -r <revision> <path>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_file_content(
+        &self,
+        commit_id: &CommitId,
+        path: &str,
+    ) -> Result<String, CommandError> {
+        self.execute_jj_command(
+            vec![
+                "file",
+                "show",
+                "-r",
+                commit_id.as_str(),
+                &Self::get_file_revset(path),
+            ],
+            false,
+            true,
+        )
+    }
+
+    /// Get the content of a conflicted file, with snapshot style conflict
+    /// markers, which list the sides in full.
+    /// Maps to `jj file show -r <revision> <path>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_conflicted_file(
+        &self,
+        commit_id: &CommitId,
+        path: &str,
+    ) -> Result<String, CommandError> {
+        self.execute_jj_command(
+            vec![
+                "file",
+                "show",
+                "-r",
+                commit_id.as_str(),
+                "--config",
+                "ui.conflict-marker-style=snapshot",
+                &Self::get_file_revset(path),
+            ],
+            false,
+            true,
+        )
+    }
+
+    /// Resolve the conflicts of a file with its resolved content, with a
+    /// merge tool copying the content to the output, see [COPY_COMMAND].
+    /// Maps to `jj resolve -r <revision> --tool <tool> <path>`
+    #[instrument(level = "trace", skip(self, content))]
+    pub fn run_resolve_with(&self, commit_id: &CommitId, path: &str, content: &str) -> Result<()> {
+        let mut resolved = NamedTempFile::new().context("Failed creating the resolved file")?;
+        resolved
+            .write_all(content.as_bytes())
+            .context("Failed writing the resolved file")?;
+        let [program, merge_args] = resolve_tool_config(&resolved.path().to_string_lossy());
+        self.execute_void_jj_command([
+            "resolve",
+            "-r",
+            commit_id.as_str(),
+            "--tool",
+            RESOLVE_TOOL,
+            "--config",
+            &program,
+            "--config",
+            &merge_args,
+            &Self::get_file_revset(path),
+        ])
+        .context("Failed executing jj resolve")
+    }
+
+    /// Track files or directories of the working copy, like `tmp/`.
+    /// Maps to `jj file track root:<path>...`
+    #[instrument(level = "trace", skip(self))]
+    pub fn track_files(&self, paths: &[String]) -> Result<()> {
+        let patterns = paths.iter().map(|path| {
+            format!(
+                "root:\"{}\"",
+                path.trim_end_matches('/')
+                    .replace("\\", "\\\\")
+                    .replace('"', "\\\"")
+            )
+        });
+        self.execute_void_jj_command(
+            ["file".to_owned(), "track".to_owned()]
+                .into_iter()
+                .chain(patterns),
+        )
+        .context("Failed executing jj file track")
+    }
+
+    /// Stop tracking files of the working copy, which must be ignored.
+    /// Maps to `jj file untrack <paths>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn untrack_files(&self, paths: &[String]) -> Result<()> {
+        let filesets = paths.iter().map(|path| Self::get_file_revset(path));
+        self.execute_void_jj_command(
+            ["file".to_owned(), "untrack".to_owned()]
+                .into_iter()
+                .chain(filesets),
+        )
+        .context("Failed executing jj file untrack")
+    }
+
+    /// Make files of a revision executable or not.
+    /// Maps to `jj file chmod x|n -r <revision> <paths>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn chmod_files(
+        &self,
+        commit_id: &CommitId,
+        paths: &[String],
+        executable: bool,
+    ) -> Result<()> {
+        let mode = if executable { "x" } else { "n" };
+        let args = ["file", "chmod", mode, "-r", commit_id.as_str()].map(str::to_owned);
+        let filesets = paths.iter().map(|path| Self::get_file_revset(path));
+        self.execute_void_jj_command(args.into_iter().chain(filesets))
+            .context("Failed executing jj file chmod")
+    }
+
+    /// Restore paths of a revision to their content in another.
+    /// Maps to `jj restore --from <from> --into <into> <paths>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn run_restore_from(&self, from: &str, into: &str, paths: &[String]) -> Result<()> {
+        let filesets: Vec<String> = paths
+            .iter()
+            .map(|path| Self::get_file_revset(path))
+            .collect();
+        let mut args = vec!["restore", "--from", from, "--into", into];
+        args.extend(filesets.iter().map(String::as_str));
+        self.execute_void_jj_command(args)
+            .context("Failed executing jj restore")
+    }
+
+    /// Discard the changes of a revision to a path, restoring it from
+    /// the parents. Maps to `jj restore --changes-in <revision> <path>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn run_discard_file(&self, revision: &str, path: &str) -> Result<()> {
+        self.execute_void_jj_command([
+            "restore",
+            "--changes-in",
+            revision,
+            &Self::get_file_revset(path),
+        ])
+        .context("Failed executing jj restore")
+    }
+
+    pub fn get_file_revset(path: &str) -> String {
         format!(
             "file:\"{}\"",
             path.replace("\\", "\\\\").replace('"', "\\\"")
         )
     }
+
+    /// Fileset of all files except the paths
+    pub fn get_files_except_revset(paths: &[String]) -> String {
+        let files: Vec<String> = paths
+            .iter()
+            .map(|path| Self::get_file_revset(path))
+            .collect();
+        format!("~({})", files.join(" | "))
+    }
+}
+
+/// Parse the lines of `jj diff --summary`
+fn parse_files(text: &str) -> Vec<File> {
+    text.lines()
+        .map(|line| {
+            let captured = FILES_REGEX.captures(line);
+            let diff_type = captured
+                .as_ref()
+                .and_then(|captured| captured.get(1))
+                .and_then(|inner_text| DiffType::parse(inner_text.as_str()));
+            let path = captured
+                .as_ref()
+                .and_then(|captured| captured.get(2))
+                .map(|inner_text| inner_text.as_str().to_owned());
+
+            File {
+                line: line.to_string(),
+                path,
+                diff_type,
+            }
+        })
+        .collect()
+}
+
+/// Parse the untracked paths of `jj status`, listed after the changes
+/// like `? notes.txt`
+fn parse_untracked_files(text: &str) -> Vec<File> {
+    let lines: Vec<&str> = text
+        .lines()
+        .skip_while(|line| *line != "Untracked paths:")
+        .skip(1)
+        .take_while(|line| line.starts_with("? "))
+        .collect();
+    parse_files(&lines.join("\n"))
+}
+
+/// Parse the lines of `git check-ignore --verbose`, like
+/// `.gitignore:1:/target<tab>target/`
+fn parse_ignored_files(text: &str) -> Vec<IgnoredFile> {
+    let fields: Vec<&str> = text.split('\0').collect();
+    fields
+        .chunks_exact(4)
+        .map(|fields| IgnoredFile {
+            path: fields[3].to_owned(),
+            source: format!("{}:{}:{}", fields[0], fields[1], fields[2]),
+        })
+        .collect()
+}
+
+/// Parse the lines of `jj file annotate` with ANNOTATE_TEMPLATE
+fn parse_annotation(text: &str) -> Vec<AnnotatedLine> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(5, '\t');
+            Some(AnnotatedLine {
+                change_id: fields.next()?.to_owned(),
+                commit_id: CommitId(fields.next()?.to_owned()),
+                author: fields.next()?.to_owned(),
+                timestamp: fields.next()?.parse().unwrap_or_default(),
+                content: fields.next()?.to_owned(),
+            })
+        })
+        .collect()
+}
+
+/// Parse the file lines of `jj diff --stat` into paths and numbers of
+/// changed lines. Renames are left out, their paths can't be used as
+/// filesets.
+fn parse_diff_file_sizes(text: &str) -> Vec<(String, usize)> {
+    text.lines()
+        .filter_map(|line| {
+            let captured = DIFF_STAT_FILE_REGEX.captures(line)?;
+            let path = &captured[1];
+            (!path.contains(" => ")).then(|| (path.to_owned(), captured[2].parse().unwrap_or(0)))
+        })
+        .collect()
+}
+
+/// Parse the totals line of `jj diff --stat`. An empty diff has no
+/// totals line with some jj versions.
+fn parse_diff_stat(text: &str) -> DiffStat {
+    let Some(captured) = text
+        .lines()
+        .rev()
+        .find_map(|line| DIFF_STAT_REGEX.captures(line))
+    else {
+        return DiffStat::default();
+    };
+    let number = |index: usize| captured[index].parse().unwrap_or_default();
+    DiffStat {
+        files: number(1),
+        insertions: number(2),
+        deletions: number(3),
+    }
+}
+
+/// Config of the merge tool copying the resolved file to the output
+fn resolve_tool_config(resolved_path: &str) -> [String; 2] {
+    let (program, args) = COPY_COMMAND;
+    let merge_args: Vec<String> = args
+        .iter()
+        .copied()
+        .chain([resolved_path, "$output"])
+        .map(str::to_owned)
+        .collect();
+    [
+        format!(
+            "merge-tools.{RESOLVE_TOOL}.program={}",
+            toml::Value::from(program)
+        ),
+        format!(
+            "merge-tools.{RESOLVE_TOOL}.merge-args={}",
+            toml::Value::from(merge_args)
+        ),
+    ]
 }
 
 #[cfg(test)]
@@ -230,6 +740,172 @@ mod tests {
     use super::*;
     use crate::commander::tests::TestRepo;
 
+    #[test]
+    fn run_split() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+        fs::write(test_repo.directory.path().join("README"), b"AAA")?;
+        fs::write(test_repo.directory.path().join("LICENSE"), b"BBB")?;
+
+        let head = test_repo.commander.get_current_head()?;
+        test_repo
+            .commander
+            .run_split(&head, &["README".to_owned()], "first", "second")?;
+
+        let second = test_repo.commander.get_current_head()?;
+        let first = test_repo.commander.get_commit_parent(&second.commit_id)?;
+        assert_eq!(
+            test_repo
+                .commander
+                .get_commit_description(&first.commit_id)?,
+            "first"
+        );
+        assert_eq!(
+            test_repo
+                .commander
+                .get_commit_description(&second.commit_id)?,
+            "second"
+        );
+        let paths = |head| -> Result<Vec<Option<String>>> {
+            Ok(test_repo
+                .commander
+                .get_files(head)?
+                .into_iter()
+                .map(|file| file.path)
+                .collect())
+        };
+        assert_eq!(paths(&first)?, [Some("README".to_owned())]);
+        assert_eq!(paths(&second)?, [Some("LICENSE".to_owned())]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_restore_from() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+        fs::write(test_repo.directory.path().join("README"), b"AAA")?;
+        fs::write(test_repo.directory.path().join("LICENSE"), b"BBB")?;
+        let first = test_repo.commander.get_current_head()?;
+        test_repo.commander.execute_void_jj_command(vec!["new"])?;
+        fs::write(test_repo.directory.path().join("README"), b"CCC")?;
+        fs::write(test_repo.directory.path().join("LICENSE"), b"DDD")?;
+
+        let paths = |from: &str| -> Result<Vec<Option<String>>> {
+            Ok(test_repo
+                .commander
+                .get_files_between(from, "@")?
+                .into_iter()
+                .map(|file| file.path)
+                .collect())
+        };
+        assert_eq!(
+            paths(first.commit_id.as_str())?,
+            [Some("LICENSE".to_owned()), Some("README".to_owned())]
+        );
+
+        test_repo.commander.run_restore_from(
+            first.commit_id.as_str(),
+            "@",
+            &["README".to_owned()],
+        )?;
+        assert_eq!(
+            paths(first.commit_id.as_str())?,
+            [Some("LICENSE".to_owned())]
+        );
+
+        test_repo.commander.run_discard_file("@", "LICENSE")?;
+        assert_eq!(paths(first.commit_id.as_str())?, []);
+
+        Ok(())
+    }
+
+    #[test]
+    fn renamed_file_paths() {
+        let file = |line: &str, diff_type| File {
+            line: line.to_owned(),
+            path: Some(line[2..].to_owned()),
+            diff_type: Some(diff_type),
+        };
+        assert_eq!(
+            file("M src/main.rs", DiffType::Modified).paths(),
+            ["src/main.rs"]
+        );
+        assert_eq!(
+            file("R src/{old.rs => new.rs}", DiffType::Renamed).paths(),
+            ["src/old.rs", "src/new.rs"]
+        );
+        assert_eq!(
+            file("R {src => lib}/main.rs", DiffType::Renamed).paths(),
+            ["src/main.rs", "lib/main.rs"]
+        );
+        assert_eq!(
+            file("R src/{ => ui}/mod.rs", DiffType::Renamed).paths(),
+            ["src/mod.rs", "src/ui/mod.rs"]
+        );
+    }
+
+    #[test]
+    fn parse_untracked() {
+        assert_eq!(
+            parse_untracked_files(
+                "Working copy changes:\nM README\nUntracked paths:\n? notes.txt\n? tmp/\n\
+                 Working copy  (@) : kxqpzmnu 3ba7e7fd (no description set)\n"
+            ),
+            [
+                File {
+                    line: "? notes.txt".to_owned(),
+                    path: Some("notes.txt".to_owned()),
+                    diff_type: Some(DiffType::Untracked),
+                },
+                File {
+                    line: "? tmp/".to_owned(),
+                    path: Some("tmp/".to_owned()),
+                    diff_type: Some(DiffType::Untracked),
+                },
+            ]
+        );
+        assert_eq!(
+            parse_untracked_files("The working copy has no changes.\n"),
+            []
+        );
+    }
+
+    #[test]
+    fn parse_diff_stats() {
+        assert_eq!(
+            parse_diff_stat(
+                "README    | 5 +++--\nsrc/a.rs  | 1 +\n2 files changed, 4 insertions(+), 2 deletions(-)\n"
+            ),
+            DiffStat {
+                files: 2,
+                insertions: 4,
+                deletions: 2,
+            }
+        );
+        assert_eq!(
+            parse_diff_stat("README | 1 -\n1 file changed, 0 insertions(+), 1 deletion(-)\n"),
+            DiffStat {
+                files: 1,
+                insertions: 0,
+                deletions: 1,
+            }
+        );
+        assert!(parse_diff_stat("").is_empty());
+        assert!(parse_diff_stat("0 files changed, 0 insertions(+), 0 deletions(-)\n").is_empty());
+    }
+
+    #[test]
+    fn parse_diff_file_sizes() {
+        assert_eq!(
+            super::parse_diff_file_sizes(
+                "README       | 5 +++--\n\
+                 src/{a => b}.rs | 1 +\n\
+                 my file.txt  | 0\n\
+                 3 files changed, 4 insertions(+), 2 deletions(-)\n"
+            ),
+            [("README".to_owned(), 5), ("my file.txt".to_owned(), 0)]
+        );
+    }
+
     #[test]
     fn get_files() -> Result<()> {
         let test_repo = TestRepo::new()?;
@@ -296,6 +972,166 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn parse_ignored() {
+        assert_eq!(
+            parse_ignored_files(
+                [
+                    ".gitignore",
+                    "1",
+                    "/target",
+                    "target/",
+                    "/home/alice/.config/git/ignore",
+                    "2",
+                    "*.log",
+                    "logs/dé\tbug.log",
+                    "",
+                ]
+                .join("\0")
+                .as_str()
+            ),
+            [
+                IgnoredFile {
+                    path: "target/".to_owned(),
+                    source: ".gitignore:1:/target".to_owned(),
+                },
+                IgnoredFile {
+                    path: "logs/dé\tbug.log".to_owned(),
+                    source: "/home/alice/.config/git/ignore:2:*.log".to_owned(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn get_ignored_files() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+        let root = test_repo.directory.path();
+        fs::write(root.join(".gitignore"), b"/target\n*.log\n")?;
+        fs::create_dir(root.join("target"))?;
+        fs::write(root.join("target/out"), b"AAA")?;
+        fs::write(root.join("debug.log"), b"BBB")?;
+        fs::write(root.join("débogage \"1\".log"), b"DDD")?;
+        fs::write(root.join("README"), b"CCC")?;
+
+        assert_eq!(
+            test_repo.commander.get_ignored_files()?,
+            [
+                IgnoredFile {
+                    path: "debug.log".to_owned(),
+                    source: ".gitignore:2:*.log".to_owned(),
+                },
+                IgnoredFile {
+                    path: "débogage \"1\".log".to_owned(),
+                    source: ".gitignore:2:*.log".to_owned(),
+                },
+                IgnoredFile {
+                    path: "target/".to_owned(),
+                    source: ".gitignore:1:/target".to_owned(),
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_annotated_lines() {
+        assert_eq!(
+            parse_annotation(
+                "qpvuntsm\t3ba7e7fd\tAlice\t1714564800\tfn main() {\n\
+                 kxqpzmnu\t90fe0a96\tBob\t1714568400\t\tprintln!(\"a\tb\");\n"
+            ),
+            [
+                AnnotatedLine {
+                    change_id: "qpvuntsm".to_owned(),
+                    commit_id: CommitId("3ba7e7fd".to_owned()),
+                    author: "Alice".to_owned(),
+                    timestamp: 1714564800,
+                    content: "fn main() {".to_owned(),
+                },
+                AnnotatedLine {
+                    change_id: "kxqpzmnu".to_owned(),
+                    commit_id: CommitId("90fe0a96".to_owned()),
+                    author: "Bob".to_owned(),
+                    timestamp: 1714568400,
+                    content: "\tprintln!(\"a\tb\");".to_owned(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn get_annotation() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+        let file_path = test_repo.directory.path().join("README");
+        fs::write(&file_path, b"AAA\n")?;
+        let first = test_repo.commander.get_current_head()?;
+        test_repo.commander.run_new([first.commit_id.as_str()])?;
+        fs::write(&file_path, b"AAA\nBBB\n")?;
+
+        let head = test_repo.commander.get_current_head()?;
+        let lines = test_repo
+            .commander
+            .get_annotation(&head.commit_id, "README")?;
+        assert_eq!(
+            lines
+                .iter()
+                .map(|line| (line.content.as_str(), &line.commit_id))
+                .collect::<Vec<_>>(),
+            [("AAA", &first.commit_id), ("BBB", &head.commit_id)]
+        );
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn chmod_files() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let test_repo = TestRepo::new()?;
+        let paths = ["a.sh".to_owned(), "b.sh".to_owned()];
+        for path in &paths {
+            fs::write(test_repo.directory.path().join(path), b"echo")?;
+        }
+        let is_executable = |path: &str| -> Result<bool> {
+            let mode = fs::metadata(test_repo.directory.path().join(path))?
+                .permissions()
+                .mode();
+            Ok(mode & 0o111 != 0)
+        };
+
+        let head = test_repo.commander.get_current_head()?;
+        test_repo
+            .commander
+            .chmod_files(&head.commit_id, &paths, true)?;
+        assert!(is_executable("a.sh")? && is_executable("b.sh")?);
+        let head = test_repo.commander.get_current_head()?;
+        test_repo
+            .commander
+            .chmod_files(&head.commit_id, &paths[..1], false)?;
+        assert!(!is_executable("a.sh")? && is_executable("b.sh")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn get_file_list() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+        fs::create_dir(test_repo.directory.path().join("src"))?;
+        fs::write(test_repo.directory.path().join("README"), b"AAA")?;
+        fs::write(test_repo.directory.path().join("src/main.rs"), b"BBB")?;
+
+        let head = test_repo.commander.get_current_head()?;
+        assert_eq!(
+            test_repo.commander.get_file_list(&head.commit_id)?,
+            ["README", "src/main.rs"]
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn get_file_diff() -> Result<()> {
         let test_repo = TestRepo::new()?;
@@ -449,6 +1285,42 @@ mod tests {
             }]
         );
 
+        let content = test_repo
+            .commander
+            .get_conflicted_file(&head.commit_id, "README")?;
+        assert!(
+            test_repo
+                .commander
+                .get_file_content(&head.commit_id, "README")?
+                .starts_with("<<<<<<<")
+        );
+        assert!(content.contains("+++++++ Contents of side #1"));
+        test_repo
+            .commander
+            .run_resolve_with(&head.commit_id, "README", "CCC")?;
+        let head = test_repo.commander.get_current_head()?;
+        assert_eq!(test_repo.commander.get_conflicts(&head.commit_id)?, []);
+
         Ok(())
     }
+
+    #[test]
+    fn resolve_tool() {
+        let [program, merge_args] = resolve_tool_config("/tmp/resolved \"file\"");
+        let (program_name, args) = COPY_COMMAND;
+        assert_eq!(
+            program,
+            format!("merge-tools.{RESOLVE_TOOL}.program=\"{program_name}\"")
+        );
+        let args = args
+            .iter()
+            .map(|arg| format!("\"{arg}\", "))
+            .collect::<String>();
+        assert_eq!(
+            merge_args,
+            format!(
+                r#"merge-tools.{RESOLVE_TOOL}.merge-args=[{args}'/tmp/resolved "file"', "$output"]"#
+            )
+        );
+    }
 }
@@ -20,6 +20,11 @@ impl ChangeId {
     pub fn as_string(&self) -> String {
         self.0.to_owned()
     }
+
+    /// Change ID shortened to 8 characters, like in the log
+    pub fn short(&self) -> &str {
+        self.0.get(..8).unwrap_or(&self.0)
+    }
 }
 
 impl AsRef<OsStr> for ChangeId {
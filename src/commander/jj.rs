@@ -5,14 +5,96 @@ The module implementes a number of jj commands.
 Surprisingly, this module also contains jj bookmark commands.
 These functions are used everywhere (bookmark tab, log tab).
 */
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+
 use anyhow::Context;
 use anyhow::Result;
+use anyhow::bail;
+use regex::Regex;
+use tempfile::TempDir;
 use tracing::instrument;
 
 use crate::commander::CommandError;
 use crate::commander::Commander;
 use crate::commander::bookmarks::Bookmark;
+use crate::commander::ids::ChangeId;
 use crate::commander::ids::CommitId;
+use crate::commander::log::Head;
+use crate::commander::substitute_placeholders;
+use crate::commander::take_repo_changed;
+
+/// Bookmarks with their remote and the commit they point to, to find
+/// the git branches jj imported last, the ones of the `git` remote
+const GIT_REFS_TEMPLATE: &str = r#"name ++ "\t" ++ remote ++ "\t" ++ if(self.normal_target(), self.normal_target().commit_id()) ++ "\n""#;
+
+/// An operation of the operation log
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Operation {
+    pub id: String,
+    /// First line of the description, e.g. `new empty commit`
+    pub description: String,
+}
+
+/// Changes that a command rewrites and abandons, as summary lines like
+/// `qpvuntsm 3ba7e7fd feat: a`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Rewrites {
+    pub rewritten: Vec<String>,
+    pub abandoned: Vec<String>,
+}
+
+/// What `jj git push` pushes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PushTarget {
+    /// The bookmarks pointing to a commit, `-r`
+    Bookmarks(CommitId),
+    /// A change, with a bookmark created from the bookmark template,
+    /// `--change`
+    Change(ChangeId),
+    /// All bookmarks, `--all`
+    All,
+}
+
+/// Remotes `jj git fetch` fetches from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FetchFrom {
+    /// The remotes of `git.fetch`, `origin` by default
+    Default,
+    /// A remote, `--remote`
+    Remote(String),
+    /// All remotes, `--all-remotes`
+    AllRemotes,
+}
+
+// Template of the summary lines of [Rewrites]
+const REWRITE_SUMMARY_TEMPLATE: &str = r#"change_id.short(8) ++ " " ++ commit_id.short(8) ++ " " ++ if(description, description.first_line(), "(no description set)") ++ "\n""#;
+
+impl Operation {
+    /// Operation id shortened to 12 characters, like in `jj op log`
+    pub fn short_id(&self) -> &str {
+        self.id.get(..12).unwrap_or(&self.id)
+    }
+}
+
+/// Reverts of the operations made by [Commander::simulate],
+/// with the operation each simulation started from
+static SIMULATIONS: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+/// The operation that stands for `operation` in the undo history. The
+/// revert of a simulation stands for the operation the simulation
+/// started from, so a preview that was not confirmed is never undone or
+/// redone.
+pub fn skip_simulations(mut operation: String) -> String {
+    let simulations = SIMULATIONS.lock().unwrap();
+    while let Some((_, before)) = simulations.iter().find(|(revert, _)| *revert == operation) {
+        operation = before.clone();
+    }
+    operation
+}
 
 impl Commander {
     /// Create a new change after revisions. Maps to `jj new <revision>...`
@@ -23,9 +105,49 @@ impl Commander {
             .context("Failed executing jj new")
     }
 
-    /// Duplicate a change. Maps to `jj duplicate`
-    pub fn run_duplicate(&self, revision: &str) -> Result<()> {
-        self.execute_void_jj_command(vec!["duplicate", revision])
+    /// Create a new change and insert it before or after a revision in
+    /// its stack, rebasing the revision or its children onto it.
+    /// Maps to `jj new --insert-before <revision>` or
+    /// `jj new --insert-after <revision>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn run_new_insert(&self, revision: &str, before: bool) -> Result<()> {
+        let mode = if before {
+            "--insert-before"
+        } else {
+            "--insert-after"
+        };
+        self.execute_void_jj_command(["new", mode, revision])
+            .context("Failed executing jj new")
+    }
+
+    /// Duplicate changes. Maps to `jj duplicate <revision>...`
+    #[instrument(level = "trace", skip(self, revisions))]
+    pub fn run_duplicate<'a, T: IntoIterator<Item = &'a str>>(&self, revisions: T) -> Result<()> {
+        let args = ["duplicate"].into_iter().chain::<T>(revisions);
+        self.execute_void_jj_command(args)
+            .context("Failed executing jj duplicate")
+    }
+
+    /// Duplicate changes onto a destination, as new children of it or
+    /// inserted after it.
+    /// Maps to `jj duplicate <revision>... --destination <destination>` or
+    /// `jj duplicate <revision>... --insert-after <destination>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn run_duplicate_onto(
+        &self,
+        commit_ids: &[CommitId],
+        destination: &str,
+        insert_after: bool,
+    ) -> Result<()> {
+        let mut args = vec!["duplicate"];
+        args.extend(commit_ids.iter().map(CommitId::as_str));
+        args.push(if insert_after {
+            "--insert-after"
+        } else {
+            "--destination"
+        });
+        args.push(destination);
+        self.execute_void_jj_command(args)
             .context("Failed executing jj duplicate")
     }
 
@@ -41,20 +163,90 @@ impl Commander {
             .context("Failed executing jj edit")
     }
 
-    /// Abandon change. Maps to `jj abandon <revision>`
+    /// Abandon change. With `retain_bookmarks`, the bookmarks of the
+    /// changes move to their parents instead of being deleted.
+    /// Maps to `jj abandon <revision>`
     #[instrument(level = "trace", skip(self))]
-    pub fn run_abandon(&self, commit_ids: &[CommitId]) -> Result<()> {
-        let args = ["abandon"]
-            .into_iter()
-            .chain(commit_ids.iter().map(CommitId::as_str));
+    pub fn run_abandon(
+        &self,
+        commit_ids: &[CommitId],
+        ignore_immutable: bool,
+        retain_bookmarks: bool,
+    ) -> Result<()> {
+        let mut args = vec!["abandon"];
+        args.extend(commit_ids.iter().map(CommitId::as_str));
+        if ignore_immutable {
+            args.push("--ignore-immutable");
+        }
+        if retain_bookmarks {
+            args.push("--retain-bookmarks");
+        }
         self.execute_void_jj_command(args)
             .context("Failed executing jj abandon")
     }
 
     /// Describe change. Maps to `jj describe <revision> -m <message>`
     #[instrument(level = "trace", skip(self))]
-    pub fn run_describe(&self, revision: &str, message: &str) -> Result<()> {
-        self.execute_void_jj_command(vec!["describe", revision, "-m", message])
+    pub fn run_describe(
+        &self,
+        revision: &str,
+        message: &str,
+        ignore_immutable: bool,
+    ) -> Result<()> {
+        let mut args = vec!["describe", revision, "-m", message];
+        if ignore_immutable {
+            args.push("--ignore-immutable");
+        }
+        self.execute_void_jj_command(args)
+            .context("Failed executing jj describe")
+    }
+
+    /// Describe the working copy change and start a new empty one on
+    /// top of it. Maps to `jj commit -m <message>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn run_commit(&self, message: &str) -> Result<()> {
+        self.execute_void_jj_command(["commit", "-m", message])
+            .context("Failed executing jj commit")
+    }
+
+    /// Sign changes with the configured backend, or drop their
+    /// signatures, and tell what was done.
+    /// Maps to `jj sign -r <revisions>` or `jj unsign -r <revisions>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn run_sign(&self, commit_ids: &[CommitId], unsign: bool) -> Result<String> {
+        let command = if unsign { "unsign" } else { "sign" };
+        let mut args = vec![command];
+        for commit_id in commit_ids {
+            args.extend(["-r", commit_id.as_str()]);
+        }
+        Ok(self
+            .execute_jj_command_messages(args)
+            .with_context(|| format!("Failed executing jj {command}"))?
+            .trim_end()
+            .to_owned())
+    }
+
+    /// Change the author of a change, keeping its description. Without
+    /// author, it is reset to the configured user and the date to now.
+    /// Maps to `jj describe <revision> -m <message> --author <author>` or
+    /// `jj describe <revision> -m <message> --reset-author`
+    #[instrument(level = "trace", skip(self))]
+    pub fn run_set_author(
+        &self,
+        revision: &str,
+        author: Option<&str>,
+        message: &str,
+        ignore_immutable: bool,
+    ) -> Result<()> {
+        let mut args = vec!["describe", revision, "-m", message];
+        match author {
+            Some(author) => args.extend(["--author", author]),
+            None => args.push("--reset-author"),
+        }
+        if ignore_immutable {
+            args.push("--ignore-immutable");
+        }
+        self.execute_void_jj_command(args)
             .context("Failed executing jj describe")
     }
 
@@ -70,6 +262,47 @@ impl Commander {
         Ok(self.execute_void_jj_command(vec!["rebase", src_mode, src_rev, tgt_mode, tgt_rev])?)
     }
 
+    /// Move a change one place up a linear stack, after its child, or
+    /// down, before its parent. Fails if the change doesn't have
+    /// exactly one child or parent to swap with.
+    /// Maps to `jj rebase -r <revision> -A <child>` or
+    /// `jj rebase -r <revision> -B <parent>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn run_move_change(&self, commit_id: &CommitId, up: bool) -> Result<()> {
+        let (neighbors, mode, relation) = if up {
+            (format!("{commit_id}+"), "-A", "child")
+        } else {
+            (format!("{commit_id}-"), "-B", "parent")
+        };
+        let neighbors = self.get_commit_ids(&neighbors)?;
+        let [neighbor] = neighbors.as_slice() else {
+            bail!(
+                "The change has {} {relation}s, it can only be moved in a linear stack",
+                neighbors.len()
+            );
+        };
+        self.execute_void_jj_command(["rebase", "-r", commit_id.as_str(), mode, neighbor.as_str()])
+            .context("Failed executing jj rebase")
+    }
+
+    /// Make changes siblings, keeping the parents of the first and the
+    /// children of the last. Maps to `jj parallelize <revisions>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn run_parallelize(&self, commit_ids: &[CommitId]) -> Result<()> {
+        let mut args = vec!["parallelize"];
+        args.extend(commit_ids.iter().map(CommitId::as_str));
+        self.execute_void_jj_command(args)
+            .context("Failed executing jj parallelize")
+    }
+
+    /// Remove the parents of a change that are ancestors of its other
+    /// parents. Maps to `jj simplify-parents -r <revision>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn run_simplify_parents(&self, revision: &str) -> Result<()> {
+        self.execute_void_jj_command(["simplify-parents", "-r", revision])
+            .context("Failed executing jj simplify-parents")
+    }
+
     /// Squash changes. Maps to `jj squash -u --into <revision>`
     #[instrument(level = "trace", skip(self))]
     pub fn run_squash(&mut self, revision: &str, ignore_immutable: bool) -> Result<()> {
@@ -78,143 +311,1332 @@ impl Commander {
             args.push("--ignore-immutable");
         }
 
-        self.execute_void_jj_command(args)
-            .context("Failed executing jj squash")
+        self.execute_void_jj_command(args)
+            .context("Failed executing jj squash")
+    }
+
+    /// Squash a change into another one with a message, e.g. combined
+    /// with combine_descriptions. Without destination, the change is
+    /// squashed into its parent.
+    /// Maps to `jj squash -r <revision> -m <message>` or
+    /// `jj squash --from <revision> --into <destination> -m <message>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn run_squash_change(
+        &self,
+        revision: &str,
+        destination: Option<&str>,
+        message: &str,
+        ignore_immutable: bool,
+    ) -> Result<()> {
+        let mut args = vec!["squash"];
+        match destination {
+            Some(destination) => args.extend(["--from", revision, "--into", destination]),
+            None => args.extend(["-r", revision]),
+        }
+        args.extend(["-m", message]);
+        if ignore_immutable {
+            args.push("--ignore-immutable");
+        }
+        self.execute_void_jj_command(args)
+            .context("Failed executing jj squash")
+    }
+
+    /// Absorb a change's diff into its mutable ancestors, and tell the
+    /// revisions it was absorbed into, as summary lines like
+    /// `qpvuntsm 3ba7e7fd feat: a`.
+    /// Maps to `jj absorb --from <revision>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn run_absorb(&self, revision: &str) -> Result<Vec<String>> {
+        let messages = self
+            .execute_jj_command_messages(["absorb", "--from", revision])
+            .context("Failed executing jj absorb")?;
+        Ok(parse_absorbed_revisions(&messages))
+    }
+
+    /// Get the files that the last absorb changed in a revision,
+    /// comparing it to its version at the operation before.
+    /// Maps to `jj interdiff --from 'at_operation(<operation>, <revision>)' --to <revision> --summary`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_absorbed_files(&self, operation: &str, revision: &str) -> Result<Vec<String>> {
+        Ok(self
+            .execute_jj_command(
+                [
+                    "interdiff",
+                    "--from",
+                    &format!("at_operation({operation}, {revision})"),
+                    "--to",
+                    revision,
+                    "--summary",
+                ],
+                false,
+                true,
+            )
+            .context("Failed executing jj interdiff")?
+            .lines()
+            .map(str::to_owned)
+            .collect())
+    }
+
+    /// Get the current operation, e.g. to undo it.
+    /// Maps to `jj op log --no-graph --limit 1`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_last_operation(&self) -> Result<Operation> {
+        let output = self
+            .execute_jj_command(
+                [
+                    "op",
+                    "log",
+                    "--no-graph",
+                    "--limit",
+                    "1",
+                    "-T",
+                    r#"id ++ "\t" ++ description.first_line()"#,
+                ],
+                false,
+                true,
+            )
+            .context("Failed getting the current operation")?;
+        let (id, description) = output.trim_end().split_once('\t').unwrap_or((&output, ""));
+        Ok(Operation {
+            id: id.trim().to_owned(),
+            description: description.to_owned(),
+        })
+    }
+
+    /// Get an operation and the id of the one before it, None for the
+    /// root operation. Maps to `jj op log --at-op <operation> --limit 2`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_operation_with_parent(
+        &self,
+        operation: &str,
+    ) -> Result<(Operation, Option<String>)> {
+        let output = self
+            .execute_jj_command(
+                [
+                    "op",
+                    "log",
+                    "--no-graph",
+                    "--at-op",
+                    operation,
+                    "--limit",
+                    "2",
+                    "-T",
+                    r#"id ++ "\t" ++ description.first_line() ++ "\n""#,
+                ],
+                false,
+                true,
+            )
+            .with_context(|| format!("Failed getting operation {operation}"))?;
+        let mut operations = output.lines().filter_map(|line| line.split_once('\t'));
+        let Some((id, description)) = operations.next() else {
+            bail!("Operation {operation} not found");
+        };
+        let operation = Operation {
+            id: id.to_owned(),
+            description: description.to_owned(),
+        };
+        Ok((operation, operations.next().map(|(id, _)| id.to_owned())))
+    }
+
+    /// Get the last operations, newest first, with how long ago each
+    /// ended, e.g. `3 minutes ago`. Maps to `jj op log --limit <limit>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_operations(&self, limit: usize) -> Result<Vec<(Operation, String)>> {
+        let output = self
+            .execute_jj_command(
+                [
+                    "op",
+                    "log",
+                    "--no-graph",
+                    "--limit",
+                    &limit.to_string(),
+                    "-T",
+                    r#"id ++ "\t" ++ time.end().ago() ++ "\t" ++ description.first_line() ++ "\n""#,
+                ],
+                false,
+                true,
+            )
+            .context("Failed getting the operation log")?;
+        Ok(output
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, '\t');
+                let id = fields.next()?;
+                let ago = fields.next()?;
+                let operation = Operation {
+                    id: id.to_owned(),
+                    description: fields.next().unwrap_or_default().to_owned(),
+                };
+                Some((operation, ago.to_owned()))
+            })
+            .collect())
+    }
+
+    /// Get what changed in the repository between two operations, e.g.
+    /// the bookmarks a fetch moved.
+    /// Maps to `jj op diff --from <from> --to <to>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_operation_diff(&self, from: &str, to: &str) -> Result<String, CommandError> {
+        self.execute_jj_command(["op", "diff", "--from", from, "--to", to], true, true)
+    }
+
+    /// Remove the objects that no operation can reach anymore.
+    /// Maps to `jj util gc`
+    #[instrument(level = "trace", skip(self))]
+    pub fn run_gc(&self) -> Result<String, CommandError> {
+        self.execute_jj_command_messages(["util", "gc"])
+    }
+
+    /// Get the sparse patterns of the working copy, the paths of the
+    /// files it has. `.` is all files.
+    /// Maps to `jj sparse list`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_sparse_patterns(&self) -> Result<Vec<String>, CommandError> {
+        Ok(self
+            .execute_jj_command(["sparse", "list"], false, true)?
+            .lines()
+            .map(str::to_owned)
+            .collect())
+    }
+
+    /// Replace the sparse patterns of the working copy, which adds and
+    /// removes files of the working copy. Returns the messages of jj.
+    /// Maps to `jj sparse set --clear --add <pattern>...`
+    #[instrument(level = "trace", skip(self))]
+    pub fn set_sparse_patterns(&self, patterns: &[String]) -> Result<String, CommandError> {
+        let mut args = vec!["sparse", "set", "--clear"];
+        for pattern in patterns {
+            args.extend(["--add", pattern]);
+        }
+        self.execute_jj_command_messages(args)
+    }
+
+    /// Abandon an operation and the ones before it, e.g. to keep the
+    /// operation log short. Maps to `jj op abandon ..<operation>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn run_op_abandon_until(&self, operation: &str) -> Result<String, CommandError> {
+        self.execute_jj_command_messages(["op", "abandon", &format!("..{operation}")])
+    }
+
+    /// Undo an operation, keeping the operations after it.
+    /// Maps to `jj op revert <operation>`, the name of `jj op undo`
+    /// since jj 0.33
+    #[instrument(level = "trace", skip(self))]
+    pub fn run_op_revert(&self, operation: &str) -> Result<()> {
+        self.execute_void_jj_command(["op", "revert", operation])
+            .context("Failed executing jj op revert")
+    }
+
+    /// Run a command to see its result without keeping it, since jj
+    /// has no dry run for most commands. `run` runs the command, then
+    /// reads the repository as the command left it. Only the operation
+    /// of the command is reverted, so operations of other processes in
+    /// the meantime are kept. The simulation ignores the working copy,
+    /// so the files are not touched. The two operations are left out of
+    /// the undo history, see [skip_simulations].
+    pub fn simulate<T>(&self, run: impl FnOnce(&mut Commander) -> Result<T>) -> Result<T> {
+        let mut simulation = Commander::new(&self.env);
+        simulation.jj_config_toml = self.jj_config_toml.clone();
+        simulation.force_no_color = self.force_no_color;
+        simulation.ignore_working_copy = true;
+
+        let before = simulation.get_operation_id()?;
+        let result = run(&mut simulation);
+        let simulated = simulation.get_operation_id()?;
+        if simulated == before {
+            // The command did nothing
+            return result;
+        }
+        let reverted = simulation.run_op_revert(&simulated);
+        // The simulation is not a change to show
+        take_repo_changed();
+        reverted?;
+        let revert = simulation.get_operation_id()?;
+        SIMULATIONS.lock().unwrap().push((revert, before));
+        result
+    }
+
+    /// Find the changes that a command rewrites and abandons, by
+    /// simulating it
+    pub fn simulate_rewrites(
+        &self,
+        run: impl FnOnce(&mut Commander) -> Result<()>,
+    ) -> Result<Rewrites> {
+        self.simulate(|simulation| {
+            let before = simulation.get_operation_id()?;
+            run(simulation)?;
+            let simulated = simulation.get_operation_id()?;
+            if simulated == before {
+                return Ok(Rewrites::default());
+            }
+            let (_, parent) = simulation.get_operation_with_parent(&simulated)?;
+            simulation.get_rewrites_between(parent.as_deref().unwrap_or(&before), &simulated)
+        })
+    }
+
+    /// Get the changes rewritten and abandoned from an operation to
+    /// another. The new commits of a rewritten change replace its old
+    /// ones.
+    fn get_rewrites_between(&self, from: &str, to: &str) -> Result<Rewrites> {
+        let summaries = |revset: &str| -> Result<Vec<String>> {
+            Ok(self
+                .execute_jj_command(
+                    [
+                        "log",
+                        "--no-graph",
+                        "-r",
+                        revset,
+                        "-T",
+                        REWRITE_SUMMARY_TEMPLATE,
+                    ],
+                    false,
+                    true,
+                )
+                .context("Failed getting rewritten changes")?
+                .lines()
+                .map(str::to_owned)
+                .collect())
+        };
+        let rewritten = summaries(&format!(
+            "at_operation({to}, mutable()) ~ at_operation({from}, mutable())"
+        ))?;
+        let gone = summaries(&format!(
+            "at_operation({from}, mutable()) ~ at_operation({to}, mutable())"
+        ))?;
+        let change_id = |summary: &str| summary.split(' ').next().unwrap_or_default().to_owned();
+        let rewritten_changes: Vec<String> = rewritten.iter().map(|line| change_id(line)).collect();
+        let abandoned = gone
+            .into_iter()
+            .filter(|line| !rewritten_changes.contains(&change_id(line)))
+            .collect();
+        Ok(Rewrites {
+            rewritten,
+            abandoned,
+        })
+    }
+
+    /// Get the id of the current operation, to restore it later.
+    /// Maps to `jj op log --no-graph --limit 1 -T id`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_operation_id(&self) -> Result<String> {
+        Ok(self
+            .execute_jj_command(
+                ["op", "log", "--no-graph", "--limit", "1", "-T", "id"],
+                false,
+                true,
+            )
+            .context("Failed getting the current operation")?
+            .trim()
+            .to_owned())
+    }
+
+    /// Run the formatters of the config on the changes of a revset and
+    /// their descendants, and tell what was fixed. A command of the
+    /// config runs instead, with `$revset` replaced by the revset.
+    /// Maps to `jj fix -s <revset>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn run_fix(&self, revset: &str, command: Option<&[String]>) -> Result<String> {
+        let args: Vec<&str> = match command {
+            Some(command) => command
+                .iter()
+                .map(|arg| {
+                    if arg == "$revset" {
+                        revset
+                    } else {
+                        arg.as_str()
+                    }
+                })
+                .collect(),
+            None => vec!["fix", "-s", revset],
+        };
+        let output = self
+            .execute_command_output(&mut self.jj_command(&args, false, false))
+            .with_context(|| format!("Failed executing jj {}", args.join(" ")))?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Ok(format!("{stdout}{stderr}").trim_end().to_owned())
+    }
+
+    /// Run a command of `blazingjj.custom-commands` on a change, with the
+    /// placeholders of [substitute_placeholders] replaced in its
+    /// arguments. jj runs in the repository root, other programs in a
+    /// temporary workspace checked out at the change. Returns the stdout
+    /// and stderr of the command.
+    pub fn run_custom_command(
+        &self,
+        command: &[String],
+        head: &Head,
+        file: Option<&str>,
+    ) -> Result<String, CommandError> {
+        let args = substitute_placeholders(command.to_vec(), Some(head), file)
+            .map_err(|err| CommandError::Status(err.to_string(), None))?;
+        let Some((program, args)) = args.split_first() else {
+            return Err(CommandError::Status(
+                "The custom command is empty".to_owned(),
+                None,
+            ));
+        };
+        let output = if program == "jj" {
+            self.execute_command_output(&mut self.jj_command(args, false, false))?
+        } else {
+            let directory = TempDir::with_prefix("blazingjj")?;
+            let workspace = directory.path().to_string_lossy().to_string();
+            let name = directory
+                .path()
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            self.execute_void_jj_command([
+                "workspace",
+                "add",
+                "--name",
+                &name,
+                "-r",
+                head.commit_id.as_str(),
+                &workspace,
+            ])?;
+            let output = self
+                .execute_command_output(Command::new(program).args(args).current_dir(&workspace));
+            // Forget the workspace even if the command failed
+            self.execute_void_jj_command(["workspace", "forget", &name])?;
+            output?
+        };
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Ok(format!("{stdout}{stderr}").trim_end().to_owned())
+    }
+
+    /// Tell if the repository is colocated with git, i.e. has a `.git`
+    /// next to `.jj`
+    pub fn is_colocated(&self) -> bool {
+        Path::new(&self.env.root).join(".git").exists()
+    }
+
+    /// Names of the tags. Maps to `jj tag list`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_tags_list(&self) -> Result<Vec<String>, CommandError> {
+        let output =
+            self.execute_jj_command(["tag", "list", "-T", r#"name ++ "\n""#], false, true)?;
+        Ok(output.lines().map(str::to_owned).collect())
+    }
+
+    /// Create a git tag pointing to commit, and import it into jj. Maps
+    /// to `git tag <name> <commit>`, since jj can't create tags. Only
+    /// works in colocated repositories.
+    #[instrument(level = "trace", skip(self))]
+    pub fn create_git_tag(&self, name: &str, commit_id: &CommitId) -> Result<()> {
+        if !self.is_colocated() {
+            bail!("Tags can only be created in repositories colocated with git");
+        }
+        self.execute_command(Command::new("git").args(["tag", name, commit_id.as_str()]))
+            .context("Failed executing git tag")?;
+        self.execute_void_jj_command(["git", "import"])
+            .context("Failed executing jj git import")
+    }
+
+    /// How the HEAD and branches of git differ from the ones jj imported
+    /// last, e.g. after git commands in a colocated repository. jj
+    /// imports them at the start of its next command. Empty if they
+    /// don't differ or the repository is not colocated.
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_git_drift(&self) -> Result<Vec<String>> {
+        if !self.is_colocated() {
+            return Ok(vec![]);
+        }
+        // HEAD is unborn in a repository without commits
+        let git_head = self
+            .execute_command(Command::new("git").args(["rev-parse", "--verify", "--quiet", "HEAD"]))
+            .unwrap_or_default();
+        let git_branches = self
+            .execute_command(Command::new("git").args([
+                "for-each-ref",
+                "--format=%(refname:short)\t%(objectname)",
+                "refs/heads",
+            ]))
+            .context("Failed executing git for-each-ref")?;
+        // Without --ignore-working-copy, jj would import them first
+        let jj_head = self.execute_jj_command(
+            [
+                "log",
+                "--no-graph",
+                "--ignore-working-copy",
+                "-r",
+                "git_head()",
+                "-T",
+                "commit_id",
+            ],
+            false,
+            true,
+        )?;
+        let jj_refs = self.execute_jj_command(
+            [
+                "bookmark",
+                "list",
+                "--all-remotes",
+                "--ignore-working-copy",
+                "-T",
+                GIT_REFS_TEMPLATE,
+            ],
+            false,
+            true,
+        )?;
+        let git_branches: BTreeMap<&str, &str> = git_branches
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .collect();
+        let jj_branches: BTreeMap<&str, &str> = jj_refs
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                let (name, remote, commit_id) = (fields.next()?, fields.next()?, fields.next()?);
+                (remote == "git").then_some((name, commit_id))
+            })
+            .collect();
+        Ok(describe_git_drift(
+            git_head.trim(),
+            jj_head.trim(),
+            &git_branches,
+            &jj_branches,
+        ))
+    }
+
+    /// Import the refs of git, e.g. after git commands in a colocated
+    /// repository. Maps to `jj git import`
+    #[instrument(level = "trace", skip(self))]
+    pub fn git_import(&self) -> Result<String, CommandError> {
+        self.execute_jj_command_messages(["git", "import"])
+    }
+
+    /// Export the bookmarks to git, overwriting the branches changed in
+    /// git. Maps to `jj git export`
+    #[instrument(level = "trace", skip(self))]
+    pub fn git_export(&self) -> Result<String, CommandError> {
+        self.execute_jj_command_messages(["git", "export"])
+    }
+
+    /// Run another program in the repository root, e.g. the CLI of a
+    /// forge, and return its output
+    #[instrument(level = "trace", skip(self))]
+    pub fn execute_external_command(&self, mut command: Command) -> Result<String, CommandError> {
+        self.execute_command(&mut command)
+    }
+
+    /// Create bookmark. Maps to `jj bookmark create <name>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn create_bookmark(&self, name: &str) -> Result<Bookmark, CommandError> {
+        self.execute_void_jj_command(vec!["bookmark", "create", name])?;
+        // jj only creates local bookmarks
+        Ok(Bookmark {
+            name: name.to_owned(),
+            remote: None,
+            present: true,
+            timestamp: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// Create bookmark pointing to commit. Maps to `jj bookmark create <name> -r <revision>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn create_bookmark_commit(
+        &self,
+        name: &str,
+        commit_id: &CommitId,
+    ) -> Result<Bookmark, CommandError> {
+        self.execute_void_jj_command(vec!["bookmark", "create", name, "-r", commit_id.as_str()])?;
+        // jj only creates local bookmarks
+        Ok(Bookmark {
+            name: name.to_owned(),
+            remote: None,
+            present: true,
+            timestamp: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// Set bookmark pointing to commit. Maps to `jj bookmark set <name> -r <revision>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn set_bookmark_commit(
+        &self,
+        name: &str,
+        commit_id: &CommitId,
+    ) -> Result<(), CommandError> {
+        // TODO: Maybe don't do --allow-backwards by default?
+        self.execute_void_jj_command(vec![
+            "bookmark",
+            "set",
+            name,
+            "-r",
+            commit_id.as_str(),
+            "--allow-backwards",
+        ])
+    }
+
+    /// Move an existing bookmark to a commit. Maps to
+    /// `jj bookmark move <name> --to <revision>`, which refuses to move it
+    /// backwards or sideways unless `allow_backwards`
+    #[instrument(level = "trace", skip(self))]
+    pub fn move_bookmark(
+        &self,
+        name: &str,
+        commit_id: &CommitId,
+        allow_backwards: bool,
+    ) -> Result<(), CommandError> {
+        let mut args = vec!["bookmark", "move", name, "--to", commit_id.as_str()];
+        if allow_backwards {
+            args.push("--allow-backwards");
+        }
+        self.execute_void_jj_command(args)
+    }
+
+    /// Rename bookmark. Maps to `jj bookmark rename <old> <new>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn rename_bookmark(&self, old: &str, new: &str) -> Result<(), CommandError> {
+        self.execute_void_jj_command(vec!["bookmark", "rename", old, new])
+    }
+
+    /// Delete bookmark. Maps to `jj bookmark delete <name>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn delete_bookmark(&self, name: &str) -> Result<(), CommandError> {
+        self.execute_void_jj_command(vec!["bookmark", "delete", name])
+    }
+
+    /// Forget bookmark. Maps to `jj bookmark forget <name>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn forget_bookmark(&self, name: &str) -> Result<(), CommandError> {
+        self.execute_void_jj_command(vec!["bookmark", "forget", name])
+    }
+
+    /// Track bookmark. Maps to `jj bookmark track <bookmark>@<remote>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn track_bookmark(&self, bookmark: &Bookmark) -> Result<(), CommandError> {
+        self.execute_void_jj_command(vec!["bookmark", "track", &bookmark.to_string()])
+    }
+
+    /// Untrack bookmark. Maps to `jj bookmark untrack <bookmark>@<remote>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn untrack_bookmark(&self, bookmark: &Bookmark) -> Result<(), CommandError> {
+        self.execute_void_jj_command(vec!["bookmark", "untrack", &bookmark.to_string()])
+    }
+
+    /// Git push. Maps to `jj git push`
+    #[instrument(level = "trace", skip(self))]
+    pub fn git_push(&self, target: &PushTarget, allow_new: bool) -> Result<String, CommandError> {
+        self.execute_jj_command(self.git_push_args(target, allow_new, false), true, true)
+    }
+
+    /// Tell what [Self::git_push] would push, e.g.
+    /// `Add bookmark push-qpvuntsm to 3ba7e7fd`. Maps to
+    /// `jj git push --dry-run`
+    #[instrument(level = "trace", skip(self))]
+    pub fn git_push_dry_run(
+        &self,
+        target: &PushTarget,
+        allow_new: bool,
+    ) -> Result<String, CommandError> {
+        self.execute_jj_command_messages(self.git_push_args(target, allow_new, true))
+    }
+
+    fn git_push_args(&self, target: &PushTarget, allow_new: bool, dry_run: bool) -> Vec<String> {
+        let mut args = vec!["git".to_owned(), "push".to_owned()];
+        if dry_run {
+            args.push("--dry-run".to_owned());
+        }
+        if allow_new {
+            args.push("--allow-new".to_owned());
+        }
+        match target {
+            PushTarget::Bookmarks(commit_id) => {
+                args.extend(["-r".to_owned(), commit_id.to_string()]);
+            }
+            PushTarget::Change(change_id) => {
+                // Name the bookmark like the generated bookmark names
+                let template = toml::Value::String(self.env.jj_config.bookmark_template());
+                args.extend([
+                    "--config".to_owned(),
+                    format!("templates.git_push_bookmark={template}"),
+                    "--change".to_owned(),
+                    change_id.to_string(),
+                ]);
+            }
+            PushTarget::All => args.push("--all".to_owned()),
+        }
+        args
+    }
+
+    /// Names of the git remotes. Maps to `jj git remote list`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_git_remotes(&self) -> Result<Vec<String>, CommandError> {
+        Ok(self
+            .get_git_remote_urls()?
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect())
+    }
+
+    /// Names and URLs of the git remotes. Maps to `jj git remote list`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_git_remote_urls(&self) -> Result<Vec<(String, String)>, CommandError> {
+        let output = self.execute_jj_command(["git", "remote", "list"], false, true)?;
+        Ok(output
+            .lines()
+            .filter_map(|line| {
+                let (name, url) = line.split_once(' ')?;
+                Some((name.to_owned(), url.trim().to_owned()))
+            })
+            .collect())
+    }
+
+    /// Commits that bookmarks of a remote point to. Maps to
+    /// `jj log -r 'remote_bookmarks(remote=<remote>)'`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_remote_bookmark_commits(&self, remote: &str) -> Result<Vec<CommitId>, CommandError> {
+        let revset = format!(r#"remote_bookmarks(remote=exact:"{remote}")"#);
+        let output = self.execute_jj_command(
+            [
+                "log",
+                "--no-graph",
+                "-r",
+                &revset,
+                "-T",
+                r#"commit_id ++ "\n""#,
+            ],
+            false,
+            true,
+        )?;
+        Ok(output
+            .lines()
+            .map(|line| CommitId(line.to_owned()))
+            .collect())
+    }
+
+    /// Names of the bookmarks of a remote that point to a commit. Maps to
+    /// `jj log -r <commit> -T remote_bookmarks`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_remote_bookmarks_at(
+        &self,
+        commit_id: &CommitId,
+        remote: &str,
+    ) -> Result<Vec<String>, CommandError> {
+        let output = self.execute_jj_command(
+            [
+                "log",
+                "--no-graph",
+                "-r",
+                commit_id.as_str(),
+                "-T",
+                r#"remote_bookmarks.map(|b| b.name() ++ "\t" ++ b.remote() ++ "\n").join("")"#,
+            ],
+            false,
+            true,
+        )?;
+        Ok(output
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .filter(|(_, bookmark_remote)| *bookmark_remote == remote)
+            .map(|(name, _)| name.to_owned())
+            .collect())
+    }
+
+    /// Git fetch and summarize the bookmarks and tags it updated. Maps to
+    /// `jj git fetch`
+    #[instrument(level = "trace", skip(self))]
+    pub fn git_fetch(&self, from: &FetchFrom) -> Result<String, CommandError> {
+        let updates = self.git_fetch_updates(from)?;
+        if updates.is_empty() {
+            Ok("No bookmarks were updated".to_owned())
+        } else {
+            Ok(updates.join("\n"))
+        }
+    }
+
+    /// Git fetch and return the lines of the bookmarks and tags it
+    /// updated, e.g. `bookmark: main@origin [updated] tracked`. Maps to
+    /// `jj git fetch`
+    #[instrument(level = "trace", skip(self))]
+    pub fn git_fetch_updates(&self, from: &FetchFrom) -> Result<Vec<String>, CommandError> {
+        let mut args = vec!["git", "fetch"];
+        match from {
+            FetchFrom::Default => (),
+            FetchFrom::Remote(remote) => args.extend(["--remote", remote]),
+            FetchFrom::AllRemotes => args.push("--all-remotes"),
+        }
+
+        let messages = self.execute_jj_command_messages(args)?;
+        Ok(parse_fetch_updates(&messages))
+    }
+}
+
+/// Describe how the HEAD and branches of git differ from the ones jj
+/// imported last, e.g. `branch main moved`. Branches are maps from
+/// their name to their commit, conflicted ones have none in jj.
+fn describe_git_drift(
+    git_head: &str,
+    jj_head: &str,
+    git_branches: &BTreeMap<&str, &str>,
+    jj_branches: &BTreeMap<&str, &str>,
+) -> Vec<String> {
+    let mut drift = vec![];
+    if git_head != jj_head {
+        drift.push("HEAD moved".to_owned());
+    }
+    for (name, commit_id) in git_branches {
+        match jj_branches.get(name) {
+            None => drift.push(format!("branch {name} created")),
+            Some(jj_commit_id) if !jj_commit_id.is_empty() && jj_commit_id != commit_id => {
+                drift.push(format!("branch {name} moved"));
+            }
+            Some(_) => (),
+        }
+    }
+    for name in jj_branches.keys() {
+        if !git_branches.contains_key(name) {
+            drift.push(format!("branch {name} deleted"));
+        }
+    }
+    drift
+}
+
+/// Lines of the bookmarks and tags `jj git fetch` reports as updated
+fn parse_fetch_updates(messages: &str) -> Vec<String> {
+    messages
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with("bookmark:") || line.starts_with("tag:"))
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Description of a change squashed into another one: the description
+/// of the destination followed by the one of the source, like jj
+/// combines them in the editor
+pub fn combine_descriptions(destination: &str, source: &str) -> String {
+    match (destination.trim(), source.trim()) {
+        ("", description) | (description, "") => description.to_owned(),
+        (destination, source) => format!("{destination}\n\n{source}"),
+    }
+}
+
+/// Summary lines of the revisions listed by `jj absorb` after
+/// "Absorbed changes into ...", e.g. `  qpvuntsm 3ba7e7fd feat: a`
+fn parse_absorbed_revisions(messages: &str) -> Vec<String> {
+    messages
+        .lines()
+        .skip_while(|line| !line.starts_with("Absorbed changes into"))
+        .skip(1)
+        .take_while(|line| line.starts_with("  "))
+        .map(|line| line.trim().to_owned())
+        .collect()
+}
+
+/// Add a trailer, like `Signed-off-by: Name <email>`, to the end of a
+/// description. It goes into the trailing block of trailers, or a new
+/// block after a blank line. A trailer that is already there is not
+/// added again.
+pub fn add_trailer(description: &str, trailer: &str) -> String {
+    let description = description.trim_end();
+    if description.lines().any(|line| line == trailer) {
+        return description.to_owned();
+    }
+    let last_paragraph = description.rsplit("\n\n").next().unwrap_or("");
+    let in_trailers = !last_paragraph.is_empty()
+        && description.contains("\n\n")
+        && last_paragraph
+            .lines()
+            .all(|line| TRAILER_REGEX.is_match(line));
+    match description {
+        "" => trailer.to_owned(),
+        description if in_trailers => format!("{description}\n{trailer}"),
+        description => format!("{description}\n\n{trailer}"),
+    }
+}
+
+/// Line of a block of trailers, like `Co-authored-by: Name <email>`
+static TRAILER_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Za-z0-9-]+: \S").unwrap());
+
+#[cfg(test)]
+mod tests {
+    use core::slice;
+    use std::fs;
+
+    use super::*;
+    use crate::commander::tests::TestRepo;
+
+    #[test]
+    fn combine_squash_descriptions() {
+        assert_eq!(combine_descriptions("", ""), "");
+        assert_eq!(combine_descriptions("feat: a\n", ""), "feat: a");
+        assert_eq!(combine_descriptions("", "fix: b\n"), "fix: b");
+        assert_eq!(
+            combine_descriptions("feat: a\n\nbody\n", "fix: b\n"),
+            "feat: a\n\nbody\n\nfix: b"
+        );
+    }
+
+    #[test]
+    fn parse_absorb_messages() {
+        let messages = "Absorbed changes into 2 revisions:\n  \
+                        qpvuntsm 3ba7e7fd feat: a\n  \
+                        zsztoxlv 90fe0a96 fix: b\n\
+                        Rebased 1 descendant commits.\n\
+                        Working copy  (@) now at: umrpslui 1c8a2a2e (empty) (no description set)\n";
+        assert_eq!(
+            parse_absorbed_revisions(messages),
+            ["qpvuntsm 3ba7e7fd feat: a", "zsztoxlv 90fe0a96 fix: b"]
+        );
+        assert!(parse_absorbed_revisions("Nothing changed.\n").is_empty());
+    }
+
+    #[test]
+    fn parse_fetch_messages() {
+        let messages = "bookmark: main@origin    [updated] tracked\n\
+                        bookmark: feature@origin [new] untracked\n\
+                        Abandoned 1 commits that are no longer reachable.\n";
+        assert_eq!(
+            parse_fetch_updates(messages),
+            [
+                "bookmark: main@origin    [updated] tracked",
+                "bookmark: feature@origin [new] untracked"
+            ]
+        );
+        assert!(parse_fetch_updates("Nothing changed.\n").is_empty());
+    }
+
+    #[test]
+    fn git_drift() {
+        let main = ("main", "3ba7e7fd");
+        let branches = BTreeMap::from([main, ("feature", "90fe0a96")]);
+        assert!(describe_git_drift("3ba7e7fd", "3ba7e7fd", &branches, &branches).is_empty());
+        assert_eq!(
+            describe_git_drift(
+                "90fe0a96",
+                "3ba7e7fd",
+                &BTreeMap::from([main, ("feature", "c0ffee00"), ("new", "90fe0a96")]),
+                &BTreeMap::from([main, ("feature", "90fe0a96"), ("old", "3ba7e7fd")]),
+            ),
+            [
+                "HEAD moved",
+                "branch feature moved",
+                "branch new created",
+                "branch old deleted"
+            ]
+        );
+        // Conflicted bookmarks have no commit in jj
+        let conflicted = BTreeMap::from([("main", "")]);
+        assert!(describe_git_drift("", "", &BTreeMap::from([main]), &conflicted).is_empty());
+    }
+
+    #[test]
+    fn add_trailers() {
+        let signed_off = "Signed-off-by: Alice <alice@example.com>";
+        let co_author = "Co-authored-by: Bob <bob@example.com>";
+        assert_eq!(add_trailer("", signed_off), signed_off);
+        assert_eq!(
+            add_trailer("feat: a\n", signed_off),
+            format!("feat: a\n\n{signed_off}")
+        );
+        assert_eq!(
+            add_trailer(&format!("feat: a\n\n{signed_off}\n"), co_author),
+            format!("feat: a\n\n{signed_off}\n{co_author}")
+        );
+        assert_eq!(
+            add_trailer(&format!("feat: a\n\n{signed_off}"), signed_off),
+            format!("feat: a\n\n{signed_off}")
+        );
+        // A summary that looks like a trailer is not a block of trailers
+        assert_eq!(
+            add_trailer("docs: readme", signed_off),
+            format!("docs: readme\n\n{signed_off}")
+        );
+    }
+
+    #[test]
+    fn run_squash_change() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+
+        let parent = test_repo.commander.get_current_head()?;
+        test_repo
+            .commander
+            .run_describe(parent.commit_id.as_str(), "parent", false)?;
+        test_repo.commander.run_new(["@"])?;
+        let head = test_repo.commander.get_current_head()?;
+        test_repo
+            .commander
+            .run_squash_change(head.commit_id.as_str(), None, "squashed", false)?;
+        let parent = test_repo.commander.get_head_latest(&parent)?;
+        assert_eq!(
+            test_repo
+                .commander
+                .get_commit_description(&parent.commit_id)?,
+            "squashed"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_move_change() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+
+        let first = test_repo.commander.get_current_head()?;
+        test_repo.commander.run_new([first.commit_id.as_str()])?;
+        let second = test_repo.commander.get_current_head()?;
+        test_repo
+            .commander
+            .run_move_change(&second.commit_id, false)?;
+        let first = test_repo.commander.get_head_latest(&first)?;
+        let parent = test_repo.commander.get_commit_parent(&first.commit_id)?;
+        assert_eq!(parent.change_id, second.change_id);
+
+        // And back up, after its child
+        test_repo
+            .commander
+            .run_move_change(&parent.commit_id, true)?;
+        let second = test_repo.commander.get_head_latest(&second)?;
+        let parent = test_repo.commander.get_commit_parent(&second.commit_id)?;
+        assert_eq!(parent.change_id, first.change_id);
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_parallelize() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+
+        let first = test_repo.commander.get_current_head()?;
+        test_repo.commander.run_new([first.commit_id.as_str()])?;
+        let second = test_repo.commander.get_current_head()?;
+        test_repo
+            .commander
+            .run_parallelize(&[first.commit_id.clone(), second.commit_id.clone()])?;
+        let second = test_repo.commander.get_head_latest(&second)?;
+        assert_eq!(
+            test_repo.commander.get_parents(second.commit_id.as_str())?,
+            test_repo.commander.get_parents(first.commit_id.as_str())?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_simplify_parents() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+
+        let first = test_repo.commander.get_current_head()?;
+        test_repo.commander.run_new([first.commit_id.as_str()])?;
+        let second = test_repo.commander.get_current_head()?;
+        // A merge of the first change and its child, the first is redundant
+        test_repo
+            .commander
+            .run_new([first.commit_id.as_str(), second.commit_id.as_str()])?;
+        let merge = test_repo.commander.get_current_head()?;
+        assert_eq!(
+            test_repo
+                .commander
+                .get_parents(merge.commit_id.as_str())?
+                .len(),
+            2
+        );
+        test_repo
+            .commander
+            .run_simplify_parents(merge.commit_id.as_str())?;
+        let merge = test_repo.commander.get_current_head()?;
+        assert_eq!(
+            test_repo.commander.get_parents(merge.commit_id.as_str())?,
+            [second.change_id.short()]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_new() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+
+        let head = test_repo.commander.get_current_head()?;
+        test_repo.commander.run_new([head.commit_id.as_str()])?;
+        assert_ne!(head, test_repo.commander.get_current_head()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_op_revert() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+
+        let head = test_repo.commander.get_current_head()?;
+        test_repo.commander.run_new([head.commit_id.as_str()])?;
+        let operation = test_repo.commander.get_last_operation()?;
+        assert_eq!(operation.description, "new empty commit");
+        test_repo.commander.run_op_revert(&operation.id)?;
+        assert_eq!(test_repo.commander.get_current_head()?, head);
+
+        Ok(())
+    }
+
+    #[test]
+    fn get_operation_with_parent() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+
+        let head = test_repo.commander.get_current_head()?;
+        let before = test_repo.commander.get_operation_id()?;
+        test_repo.commander.run_new([head.commit_id.as_str()])?;
+        let last = test_repo.commander.get_operation_id()?;
+        let (operation, parent) = test_repo.commander.get_operation_with_parent(&last)?;
+        assert_eq!(operation.id, last);
+        assert_eq!(operation.description, "new empty commit");
+        assert_eq!(parent, Some(before));
+
+        Ok(())
+    }
+
+    #[test]
+    fn get_operation_diff() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+
+        let head = test_repo.commander.get_current_head()?;
+        let before = test_repo.commander.get_operation_id()?;
+        test_repo.commander.run_new([head.commit_id.as_str()])?;
+        let last = test_repo.commander.get_operation_id()?;
+        let diff = test_repo.commander.get_operation_diff(&before, &last)?;
+        assert!(diff.contains("Changed commits"));
+        let diff = test_repo.commander.get_operation_diff(&last, &last)?;
+        assert!(!diff.contains("Changed commits"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_op_abandon_until() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+
+        let head = test_repo.commander.get_current_head()?;
+        test_repo.commander.run_new([head.commit_id.as_str()])?;
+        test_repo.commander.run_new([head.commit_id.as_str()])?;
+        let operations = test_repo.commander.get_operations(10)?;
+        test_repo
+            .commander
+            .run_op_abandon_until(&operations[1].0.id)?;
+        let kept = test_repo.commander.get_operations(10)?;
+        // The last operation and the root operation
+        assert_eq!(kept.len(), 2);
+        assert_eq!(kept[0].0.description, "new empty commit");
+        test_repo.commander.run_gc()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn get_operations() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+
+        let head = test_repo.commander.get_current_head()?;
+        let before = test_repo.commander.get_operation_id()?;
+        test_repo.commander.run_new([head.commit_id.as_str()])?;
+        let last = test_repo.commander.get_operation_id()?;
+        let operations = test_repo.commander.get_operations(2)?;
+        assert_eq!(operations.len(), 2);
+        assert_eq!(operations[0].0.id, last);
+        assert_eq!(operations[0].0.description, "new empty commit");
+        assert_eq!(operations[1].0.id, before);
+
+        Ok(())
     }
 
-    /// Absorb a change's diff into its mutable ancestors. Maps to `jj absorb --from <revision>`
-    #[instrument(level = "trace", skip(self))]
-    pub fn run_absorb(&mut self, revision: &str) -> Result<()> {
-        self.execute_void_jj_command(vec!["absorb", "--from", revision])
-            .context("Failed executing jj absorb")
-    }
+    #[test]
+    fn simulate_rewrites() -> Result<()> {
+        let test_repo = TestRepo::new()?;
 
-    /// Create bookmark. Maps to `jj bookmark create <name>`
-    #[instrument(level = "trace", skip(self))]
-    pub fn create_bookmark(&self, name: &str) -> Result<Bookmark, CommandError> {
-        self.execute_void_jj_command(vec!["bookmark", "create", name])?;
-        // jj only creates local bookmarks
-        Ok(Bookmark {
-            name: name.to_owned(),
-            remote: None,
-            present: true,
-            timestamp: chrono::Utc::now().timestamp(),
-        })
-    }
+        let first = test_repo.commander.get_current_head()?;
+        test_repo.commander.run_new([first.commit_id.as_str()])?;
+        let second = test_repo.commander.get_current_head()?;
+        let rewrites = test_repo.commander.simulate_rewrites(|commander| {
+            commander.run_abandon(std::slice::from_ref(&first.commit_id), false, false)
+        })?;
+        assert_eq!(rewrites.rewritten.len(), 1);
+        assert!(rewrites.rewritten[0].starts_with(second.change_id.short()));
+        assert_eq!(rewrites.abandoned.len(), 1);
+        assert!(rewrites.abandoned[0].starts_with(first.change_id.short()));
+        // Nothing was abandoned, and the simulation was reverted
+        assert_eq!(test_repo.commander.get_current_head()?, second);
+        let operation = test_repo.commander.get_last_operation()?;
+        assert!(!operation.description.starts_with("abandon"));
 
-    /// Create bookmark pointing to commit. Maps to `jj bookmark create <name> -r <revision>`
-    #[instrument(level = "trace", skip(self))]
-    pub fn create_bookmark_commit(
-        &self,
-        name: &str,
-        commit_id: &CommitId,
-    ) -> Result<Bookmark, CommandError> {
-        self.execute_void_jj_command(vec!["bookmark", "create", name, "-r", commit_id.as_str()])?;
-        // jj only creates local bookmarks
-        Ok(Bookmark {
-            name: name.to_owned(),
-            remote: None,
-            present: true,
-            timestamp: chrono::Utc::now().timestamp(),
-        })
+        Ok(())
     }
 
-    /// Set bookmark pointing to commit. Maps to `jj bookmark set <name> -r <revision>`
-    #[instrument(level = "trace", skip(self))]
-    pub fn set_bookmark_commit(
-        &self,
-        name: &str,
-        commit_id: &CommitId,
-    ) -> Result<(), CommandError> {
-        // TODO: Maybe don't do --allow-backwards by default?
-        self.execute_void_jj_command(vec![
-            "bookmark",
-            "set",
-            name,
-            "-r",
-            commit_id.as_str(),
-            "--allow-backwards",
-        ])
-    }
+    #[test]
+    fn simulate_absorb() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+        let file = test_repo.directory.path().join("file");
+
+        fs::write(&file, "a\n")?;
+        let first = test_repo.commander.get_current_head()?;
+        test_repo.commander.run_new([first.commit_id.as_str()])?;
+        fs::write(&file, "b\n")?;
+        let second = test_repo.commander.get_current_head()?;
+        let (destinations, files) = test_repo.commander.simulate(|commander| {
+            let operation = commander.get_operation_id()?;
+            let destinations = commander.run_absorb(second.commit_id.as_str())?;
+            let files = commander.get_absorbed_files(&operation, first.change_id.as_str())?;
+            Ok((destinations, files))
+        })?;
+        assert_eq!(destinations.len(), 1);
+        assert!(destinations[0].starts_with(first.change_id.short()));
+        assert_eq!(files, ["M file"]);
+        // Nothing was absorbed
+        assert_eq!(test_repo.commander.get_current_head()?, second);
 
-    /// Rename bookmark. Maps to `jj bookmark rename <old> <new>`
-    #[instrument(level = "trace", skip(self))]
-    pub fn rename_bookmark(&self, old: &str, new: &str) -> Result<(), CommandError> {
-        self.execute_void_jj_command(vec!["bookmark", "rename", old, new])
+        Ok(())
     }
 
-    /// Delete bookmark. Maps to `jj bookmark delete <name>`
-    #[instrument(level = "trace", skip(self))]
-    pub fn delete_bookmark(&self, name: &str) -> Result<(), CommandError> {
-        self.execute_void_jj_command(vec!["bookmark", "delete", name])
-    }
+    #[test]
+    fn simulate_nothing() -> Result<()> {
+        let test_repo = TestRepo::new()?;
 
-    /// Forget bookmark. Maps to `jj bookmark forget <name>`
-    #[instrument(level = "trace", skip(self))]
-    pub fn forget_bookmark(&self, name: &str) -> Result<(), CommandError> {
-        self.execute_void_jj_command(vec!["bookmark", "forget", name])
-    }
+        let head = test_repo.commander.get_current_head()?;
+        let before = test_repo.commander.get_operation_id()?;
+        // A change with one parent has no redundant parents
+        let parents = test_repo.commander.simulate(|commander| {
+            commander.run_simplify_parents(head.commit_id.as_str())?;
+            commander.get_parents(head.change_id.as_str())
+        })?;
+        assert_eq!(
+            parents,
+            test_repo.commander.get_parents(head.commit_id.as_str())?
+        );
+        assert_eq!(test_repo.commander.get_operation_id()?, before);
 
-    /// Track bookmark. Maps to `jj bookmark track <bookmark>@<remote>`
-    #[instrument(level = "trace", skip(self))]
-    pub fn track_bookmark(&self, bookmark: &Bookmark) -> Result<(), CommandError> {
-        self.execute_void_jj_command(vec!["bookmark", "track", &bookmark.to_string()])
+        Ok(())
     }
 
-    /// Untrack bookmark. Maps to `jj bookmark untrack <bookmark>@<remote>`
-    #[instrument(level = "trace", skip(self))]
-    pub fn untrack_bookmark(&self, bookmark: &Bookmark) -> Result<(), CommandError> {
-        self.execute_void_jj_command(vec!["bookmark", "untrack", &bookmark.to_string()])
+    #[test]
+    fn run_commit() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+
+        let head = test_repo.commander.get_current_head()?;
+        test_repo.commander.run_commit("feat: a")?;
+        let new_head = test_repo.commander.get_current_head()?;
+        assert_ne!(new_head.change_id, head.change_id);
+        let parent = test_repo.commander.get_commit_parent(&new_head.commit_id)?;
+        assert_eq!(parent.change_id, head.change_id);
+        assert_eq!(
+            test_repo
+                .commander
+                .get_commit_description(&parent.commit_id)?,
+            "feat: a"
+        );
+
+        Ok(())
     }
 
-    /// Git push. Maps to `jj git push`
-    #[instrument(level = "trace", skip(self))]
-    pub fn git_push(
-        &self,
-        all_bookmarks: bool,
-        allow_new: bool,
-        commit_id: &CommitId,
-    ) -> Result<String, CommandError> {
-        let mut args = vec!["git", "push"];
-        if allow_new {
-            args.push("--allow-new");
-        }
-        if all_bookmarks {
-            args.push("--all");
-        } else {
-            args.push("-r");
-            args.push(commit_id.as_str());
-        }
+    #[test]
+    fn run_fix_command() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+
+        let head = test_repo.commander.get_current_head()?;
+        let command = ["log", "--no-graph", "-r", "$revset", "-T", "change_id"].map(String::from);
+        assert_eq!(
+            test_repo.commander.run_fix("@", Some(&command))?,
+            head.change_id.as_str()
+        );
 
-        self.execute_jj_command(args, true, true)
+        Ok(())
     }
 
-    /// Git fetch. Maps to `jj git fetch`
-    #[instrument(level = "trace", skip(self))]
-    pub fn git_fetch(&self, all_remotes: bool) -> Result<String, CommandError> {
-        let mut args = vec!["git", "fetch"];
-        if all_remotes {
-            args.push("--all-remotes");
-        }
+    #[test]
+    fn run_custom_command() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+
+        let head = test_repo.commander.get_current_head()?;
+        let command = [
+            "jj",
+            "log",
+            "--no-graph",
+            "-r",
+            "{change_id}",
+            "-T",
+            "commit_id",
+        ]
+        .map(String::from);
+        assert_eq!(
+            test_repo
+                .commander
+                .run_custom_command(&command, &head, None)?,
+            head.commit_id.as_str()
+        );
+        let command = ["echo", "{file}"].map(String::from);
+        assert!(
+            test_repo
+                .commander
+                .run_custom_command(&command, &head, None)
+                .is_err()
+        );
+        assert_eq!(
+            test_repo
+                .commander
+                .run_custom_command(&command, &head, Some("README.md"))?,
+            "README.md"
+        );
+
+        // Other programs see the files of the change, not of the working copy
+        fs::write(test_repo.directory.path().join("file"), "content")?;
+        let head = test_repo.commander.get_current_head()?;
+        test_repo.commander.execute_void_jj_command(["new"])?;
+        fs::remove_file(test_repo.directory.path().join("file"))?;
+        let command = ["cat", "file"].map(String::from);
+        assert_eq!(
+            test_repo
+                .commander
+                .run_custom_command(&command, &head, None)?,
+            "content"
+        );
+        let workspaces =
+            test_repo
+                .commander
+                .execute_jj_command(["workspace", "list"], false, true)?;
+        assert_eq!(workspaces.lines().count(), 1);
 
-        self.execute_jj_command(args, true, true)
+        Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use core::slice;
+    #[test]
+    fn run_set_author() -> Result<()> {
+        let test_repo = TestRepo::new()?;
 
-    use super::*;
-    use crate::commander::tests::TestRepo;
+        let head = test_repo.commander.get_current_head()?;
+        test_repo.commander.run_set_author(
+            head.commit_id.as_str(),
+            Some("Alice <alice@example.com>"),
+            "message",
+            false,
+        )?;
+        let head = test_repo.commander.get_head_latest(&head)?;
+        let author = test_repo.commander.get_commit_author(&head.commit_id)?;
+        assert_eq!(author.name, "Alice");
+        assert_eq!(author.email, "alice@example.com");
+        assert_eq!(
+            test_repo
+                .commander
+                .get_commit_description(&head.commit_id)?,
+            "message"
+        );
+
+        Ok(())
+    }
 
     #[test]
-    fn run_new() -> Result<()> {
+    fn run_new_insert() -> Result<()> {
         let test_repo = TestRepo::new()?;
 
         let head = test_repo.commander.get_current_head()?;
-        test_repo.commander.run_new([head.commit_id.as_str()])?;
-        assert_ne!(head, test_repo.commander.get_current_head()?);
+        test_repo
+            .commander
+            .run_new_insert(head.commit_id.as_str(), true)?;
+        let inserted = test_repo.commander.get_current_head()?;
+        let head = test_repo.commander.get_head_latest(&head)?;
+        assert_eq!(
+            test_repo.commander.get_commit_parent(&head.commit_id)?,
+            inserted
+        );
 
         Ok(())
     }
@@ -241,12 +1663,50 @@ mod tests {
         let head = test_repo.commander.get_current_head()?;
         test_repo
             .commander
-            .run_abandon(slice::from_ref(&head.commit_id))?;
+            .run_abandon(slice::from_ref(&head.commit_id), false, false)?;
         assert_ne!(head, test_repo.commander.get_current_head()?);
 
         Ok(())
     }
 
+    #[test]
+    fn run_duplicate() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+
+        let parent = test_repo.commander.get_current_head()?;
+        test_repo.commander.run_new([parent.commit_id.as_str()])?;
+        let head = test_repo.commander.get_current_head()?;
+        let count = test_repo.commander.count_revset("all()", 100)?;
+        test_repo
+            .commander
+            .run_duplicate([parent.commit_id.as_str(), head.commit_id.as_str()])?;
+        assert_eq!(test_repo.commander.count_revset("all()", 100)?, count + 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_duplicate_onto() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+
+        let parent = test_repo.commander.get_current_head()?;
+        test_repo.commander.run_new([parent.commit_id.as_str()])?;
+        let head = test_repo.commander.get_current_head()?;
+        test_repo.commander.run_duplicate_onto(
+            slice::from_ref(&parent.commit_id),
+            head.commit_id.as_str(),
+            false,
+        )?;
+        let copy = test_repo.commander.get_created_head()?;
+        assert_ne!(copy.change_id, parent.change_id);
+        assert_eq!(
+            test_repo.commander.get_commit_parent(&copy.commit_id)?,
+            head
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn run_describe() -> Result<()> {
         let test_repo = TestRepo::new()?;
@@ -254,7 +1714,7 @@ mod tests {
         let head = test_repo.commander.get_current_head()?;
         test_repo
             .commander
-            .run_describe(head.commit_id.as_str(), "AAA")?;
+            .run_describe(head.commit_id.as_str(), "AAA", false)?;
 
         let head = test_repo.commander.get_current_head()?.commit_id;
         assert_eq!(test_repo.commander.get_commit_description(&head)?, "AAA");
@@ -368,6 +1828,42 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn move_bookmark() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+
+        let bookmark = test_repo.commander.create_bookmark("test")?;
+        let old_head = test_repo.commander.get_current_head()?;
+        test_repo.commander.run_new([old_head.commit_id.as_str()])?;
+        let new_head = test_repo.commander.get_current_head()?;
+        let bookmark_commit = || {
+            test_repo.commander.execute_jj_command(
+                ["log", "--no-graph", "-T", "commit_id", "-r", &bookmark.name],
+                false,
+                true,
+            )
+        };
+
+        // Forwards
+        test_repo
+            .commander
+            .move_bookmark(&bookmark.name, &new_head.commit_id, false)?;
+        assert_eq!(bookmark_commit()?, new_head.commit_id.to_string());
+
+        // Backwards, only when allowed
+        let error = test_repo
+            .commander
+            .move_bookmark(&bookmark.name, &old_head.commit_id, false)
+            .unwrap_err();
+        assert!(error.to_string().contains("backwards or sideways"));
+        test_repo
+            .commander
+            .move_bookmark(&bookmark.name, &old_head.commit_id, true)?;
+        assert_eq!(bookmark_commit()?, old_head.commit_id.to_string());
+
+        Ok(())
+    }
+
     #[test]
     fn rename_bookmark() -> Result<()> {
         let test_repo = TestRepo::new()?;
@@ -452,4 +1948,160 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn git_push_dry_run() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+        let remote = TestRepo::new()?;
+        test_repo.commander.execute_void_jj_command([
+            "git",
+            "remote",
+            "add",
+            "origin",
+            &remote.directory.path().to_string_lossy(),
+        ])?;
+
+        let head = test_repo.commander.get_current_head()?;
+        test_repo
+            .commander
+            .run_describe(head.commit_id.as_str(), "AAA", false)?;
+        let head = test_repo.commander.get_current_head()?;
+
+        // The bookmark is named with the bookmark template
+        let messages = test_repo
+            .commander
+            .git_push_dry_run(&PushTarget::Change(head.change_id.clone()), false)?;
+        let bookmark = format!("push-{}", head.change_id.short());
+        assert!(messages.contains(&format!("Add bookmark {bookmark}")));
+        assert!(messages.contains("Dry-run requested, not pushing."));
+
+        // Neither the bookmark is created nor anything pushed
+        assert_eq!(test_repo.commander.get_bookmarks_list(true)?, []);
+
+        Ok(())
+    }
+
+    #[test]
+    fn git_fetch() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+        let remote = TestRepo::new()?;
+        let head = remote.commander.get_current_head()?;
+        remote
+            .commander
+            .run_describe(head.commit_id.as_str(), "AAA", false)?;
+        remote.commander.create_bookmark("main")?;
+        test_repo.commander.execute_void_jj_command([
+            "git",
+            "remote",
+            "add",
+            "origin",
+            &remote.directory.path().to_string_lossy(),
+        ])?;
+
+        assert_eq!(test_repo.commander.get_git_remotes()?, ["origin"]);
+        let remote_url = remote.directory.path().to_string_lossy().to_string();
+        assert_eq!(
+            test_repo.commander.get_git_remote_urls()?,
+            [("origin".to_owned(), remote_url)]
+        );
+
+        let summary = test_repo
+            .commander
+            .git_fetch(&FetchFrom::Remote("origin".to_owned()))?;
+        assert!(summary.starts_with("bookmark: main@origin"));
+        let summary = test_repo.commander.git_fetch(&FetchFrom::AllRemotes)?;
+        assert_eq!(summary, "No bookmarks were updated");
+        let updates = test_repo.commander.git_fetch_updates(&FetchFrom::Default)?;
+        assert!(updates.is_empty());
+
+        let main = test_repo.commander.get_revision_head("main@origin")?;
+        assert_eq!(
+            test_repo
+                .commander
+                .get_remote_bookmarks_at(&main.commit_id, "origin")?,
+            ["main"]
+        );
+        assert_eq!(
+            test_repo.commander.get_remote_bookmark_commits("origin")?,
+            [main.commit_id]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn get_git_drift() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+        let head = test_repo.commander.get_current_head()?;
+        test_repo
+            .commander
+            .run_describe(head.commit_id.as_str(), "AAA", false)?;
+        test_repo.commander.create_bookmark("main")?;
+        test_repo.commander.execute_void_jj_command(["new"])?;
+        assert_eq!(test_repo.commander.get_git_drift()?, Vec::<String>::new());
+
+        // A branch created with git is imported by the next jj command
+        test_repo
+            .commander
+            .execute_command(Command::new("git").args(["branch", "feature", "main"]))?;
+        assert_eq!(
+            test_repo.commander.get_git_drift()?,
+            ["branch feature created"]
+        );
+        test_repo.commander.git_import()?;
+        assert_eq!(test_repo.commander.get_git_drift()?, Vec::<String>::new());
+
+        Ok(())
+    }
+
+    #[test]
+    fn create_git_tag() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+        let head = test_repo.commander.get_current_head()?;
+        test_repo
+            .commander
+            .run_describe(head.commit_id.as_str(), "AAA", false)?;
+        let head = test_repo.commander.get_current_head()?;
+
+        assert!(test_repo.commander.is_colocated());
+        assert_eq!(test_repo.commander.get_tags_list()?, Vec::<String>::new());
+        test_repo
+            .commander
+            .create_git_tag("v1.0", &head.commit_id)?;
+        assert_eq!(test_repo.commander.get_tags_list()?, ["v1.0"]);
+
+        // Already exists
+        assert!(
+            test_repo
+                .commander
+                .create_git_tag("v1.0", &head.commit_id)
+                .is_err()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn sparse_patterns() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+        let root = test_repo.directory.path();
+        fs::create_dir_all(root.join("src"))?;
+        fs::create_dir_all(root.join("docs"))?;
+        fs::write(root.join("src/main.rs"), b"AAA")?;
+        fs::write(root.join("docs/guide.md"), b"BBB")?;
+        test_repo.commander.get_current_head()?;
+
+        assert_eq!(test_repo.commander.get_sparse_patterns()?, ["."]);
+        test_repo
+            .commander
+            .set_sparse_patterns(&["src".to_owned()])?;
+        assert_eq!(test_repo.commander.get_sparse_patterns()?, ["src"]);
+        assert!(root.join("src/main.rs").exists());
+        assert!(!root.join("docs/guide.md").exists());
+
+        test_repo.commander.set_sparse_patterns(&[".".to_owned()])?;
+        assert!(root.join("docs/guide.md").exists());
+
+        Ok(())
+    }
 }
@@ -5,6 +5,7 @@ This module has features to parse the log output to extract change id and commit
 It is mostly used in the [log_tab][crate::ui::log_tab] module.
 */
 
+use std::collections::HashMap;
 use std::fmt::Display;
 use std::sync::LazyLock;
 
@@ -21,6 +22,7 @@ use crate::commander::CommandError;
 use crate::commander::Commander;
 use crate::commander::RemoveEndLine;
 use crate::commander::bookmarks::Bookmark;
+use crate::commander::bookmarks::TrackingDistance;
 use crate::commander::ids::ChangeId;
 use crate::commander::ids::CommitId;
 use crate::env::DiffFormat;
@@ -31,6 +33,7 @@ pub struct Head {
     pub commit_id: CommitId,
     pub divergent: bool,
     pub immutable: bool,
+    pub conflict: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -39,6 +42,47 @@ pub struct LogOutput {
     // Maps graph line -> heads
     pub graph_heads: Vec<Option<Head>>,
     pub heads: Vec<Head>,
+    /// The log was cut off at the limit, there may be more changes
+    pub truncated: bool,
+    /// Bookmarks and tags of the changes, if they are shown as chips
+    pub refs: HashMap<CommitId, Vec<LogRef>>,
+    /// How far the local bookmarks are from their tracked remote
+    /// bookmarks, if bookmarks are shown as chips
+    pub tracking: HashMap<String, TrackingDistance>,
+    /// Inserted and deleted lines of the changes, if they are shown
+    pub sizes: HashMap<CommitId, ChangeSize>,
+}
+
+/// Lines inserted and deleted by a change
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChangeSize {
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+/// Author of a change, with the author date
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitAuthor {
+    pub name: String,
+    pub email: String,
+    pub timestamp: String,
+}
+
+/// Kind of a ref in the log, which decides the color of its chip
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RefKind {
+    Local,
+    Remote,
+    Conflicted,
+    Tag,
+}
+
+/// A bookmark or tag of a change, named as jj shows it in the log,
+/// e.g. `main@origin` or `main??`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogRef {
+    pub name: String,
+    pub kind: RefKind,
 }
 
 impl LogOutput {
@@ -56,14 +100,29 @@ impl Display for HeadParseError {
     }
 }
 
-// Template which outputs `[change_id|commit_id|divergent]`. Used to parse data from log and other
-// commands which supports templating.
-const HEAD_TEMPLATE: &str =
-    r#""[" ++ change_id ++ "|" ++ commit_id ++ "|" ++ divergent ++ "|" ++ immutable ++ "]""#;
-const HEAD_TEMPLATE_NL: &str = r#""[" ++ change_id ++ "|" ++ commit_id ++ "|" ++ divergent ++ "|" ++ immutable ++ "]" ++ "\n""#;
+// Template which outputs `[change_id|commit_id|divergent|immutable|conflict]`. Used to parse data
+// from log and other commands which supports templating.
+const HEAD_TEMPLATE: &str = r#""[" ++ change_id ++ "|" ++ commit_id ++ "|" ++ divergent ++ "|" ++ immutable ++ "|" ++ conflict ++ "]""#;
+const HEAD_TEMPLATE_NL: &str = r#""[" ++ change_id ++ "|" ++ commit_id ++ "|" ++ divergent ++ "|" ++ immutable ++ "|" ++ conflict ++ "]" ++ "\n""#;
+// Show the timestamps of the builtin templates in ISO 8601 format
+const ISO_TIMESTAMP_CONFIG: &str = r#"template-aliases.'format_timestamp(timestamp)'='timestamp.local().format("%Y-%m-%dT%H:%M:%S%:z")'"#;
+// Show the signature status with the builtin templates, e.g. `[✓︎]` for
+// a good signature
+const SIGNATURES_CONFIG: &str = "ui.show-cryptographic-signatures=true";
+// Template which outputs the refs of a change, each prefixed with its kind, see parse_log_refs
+const REFS_TEMPLATE: &str = r#"commit_id ++ "\t" ++ bookmarks.map(|b| if(b.conflict(), "c", if(b.remote(), "r", "l")) ++ stringify(b)).join("\t") ++ "\t" ++ tags.map(|t| "t" ++ stringify(t)).join("\t") ++ "\n""#;
+// Template which outputs a head and a one line summary of a commit, see parse_change_commits
+const CHANGE_COMMIT_TEMPLATE: &str = r#""[" ++ change_id ++ "|" ++ commit_id ++ "|" ++ divergent ++ "|" ++ immutable ++ "|" ++ conflict ++ "]" ++ "\t" ++ separate(" ", commit_id.shortest(8), author.name(), committer.timestamp().ago(), coalesce(description.first_line(), "(no description set)")) ++ "\n""#;
+// Template which outputs a version of a change from the evolution log, like CHANGE_COMMIT_TEMPLATE,
+// with the operation that created it
+const EVOLOG_TEMPLATE: &str = r#""[" ++ commit.change_id() ++ "|" ++ commit.commit_id() ++ "|" ++ commit.divergent() ++ "|" ++ commit.immutable() ++ "|" ++ commit.conflict() ++ "]" ++ "\t" ++ separate(" ", commit.commit_id().shortest(8), commit.committer().timestamp().ago(), coalesce(commit.description().first_line(), "(no description set)"), "(" ++ operation.description().first_line() ++ ")") ++ "\n""#;
+// Template which outputs the inserted and deleted lines of a change, see parse_change_sizes
+const AUTHOR_TEMPLATE: &str = r#"separate("\t", author.name(), author.email(), author.timestamp().local().format("%Y-%m-%d %H:%M:%S"))"#;
+
+const CHANGE_SIZE_TEMPLATE: &str = r#"commit_id ++ "\t" ++ diff().stat(80).total_added() ++ "\t" ++ diff().stat(80).total_removed() ++ "\n""#;
 // Regex to parse HEAD_TEMPLATE
 static HEAD_TEMPLATE_REGEX: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"\[(.*)\|(.*)\|(.*)\|(.*)\]").unwrap());
+    LazyLock::new(|| Regex::new(r"\[(.*)\|(.*)\|(.*)\|(.*)\|(.*)\]").unwrap());
 
 // Parse a head with HEAD_TEMPLATE.
 fn parse_head(text: &str) -> Result<Head> {
@@ -71,17 +130,25 @@ fn parse_head(text: &str) -> Result<Head> {
     captured
         .as_ref()
         .map_or(Err(anyhow!(HeadParseError(text.to_owned()))), |captured| {
-            if let (Some(change_id), Some(commit_id), Some(divergent), Some(immutable)) = (
+            if let (
+                Some(change_id),
+                Some(commit_id),
+                Some(divergent),
+                Some(immutable),
+                Some(conflict),
+            ) = (
                 captured.get(1),
                 captured.get(2),
                 captured.get(3),
                 captured.get(4),
+                captured.get(5),
             ) {
                 Ok(Head {
                     change_id: ChangeId(change_id.as_str().to_string()),
                     commit_id: CommitId(commit_id.as_str().to_string()),
                     divergent: divergent.as_str() == "true",
                     immutable: immutable.as_str() == "true",
+                    conflict: conflict.as_str() == "true",
                 })
             } else {
                 bail!(HeadParseError(text.to_owned()))
@@ -89,6 +156,75 @@ fn parse_head(text: &str) -> Result<Head> {
         })
 }
 
+/// Parse the output of CHANGE_COMMIT_TEMPLATE. The head is split off
+/// at the tab, so brackets in the description don't confuse the
+/// head regex.
+fn parse_change_commits(text: &str) -> Result<Vec<(Head, String)>> {
+    text.lines()
+        .map(|line| {
+            let (head, summary) = line
+                .split_once('\t')
+                .ok_or_else(|| anyhow!(HeadParseError(line.to_owned())))?;
+            Ok((parse_head(head)?, summary.to_owned()))
+        })
+        .collect()
+}
+
+/// Parse the output of REFS_TEMPLATE
+fn parse_log_refs(text: &str) -> HashMap<CommitId, Vec<LogRef>> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let commit_id = CommitId(fields.next()?.to_owned());
+            let refs: Vec<LogRef> = fields
+                .filter_map(|field| {
+                    let kind = match field.chars().next()? {
+                        'l' => RefKind::Local,
+                        'r' => RefKind::Remote,
+                        'c' => RefKind::Conflicted,
+                        't' => RefKind::Tag,
+                        _ => return None,
+                    };
+                    Some(LogRef {
+                        name: field[1..].to_owned(),
+                        kind,
+                    })
+                })
+                .collect();
+            (!refs.is_empty()).then_some((commit_id, refs))
+        })
+        .collect()
+}
+
+/// Parse the output of CHANGE_SIZE_TEMPLATE
+fn parse_change_sizes(text: &str) -> HashMap<CommitId, ChangeSize> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let commit_id = CommitId(fields.next()?.to_owned());
+            let insertions = fields.next()?.parse().ok()?;
+            let deletions = fields.next()?.parse().ok()?;
+            Some((
+                commit_id,
+                ChangeSize {
+                    insertions,
+                    deletions,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Fileset of a path typed by the user. A path with wildcards is a
+/// glob, unless it already is a fileset like `glob:"*.rs"`.
+fn path_fileset(path: &str) -> String {
+    if path.contains(['*', '?', '[']) && !path.contains(':') {
+        format!("glob:{path:?}")
+    } else {
+        path.to_owned()
+    }
+}
+
 impl Commander {
     fn execute_jj_log(&self, revset: &str, template: &str) -> Result<String, CommandError> {
         self.execute_jj_command(
@@ -116,46 +252,79 @@ impl Commander {
     }
 
     /// Get log. Returns human readable log and mapping to log line to head.
-    /// Maps to `jj log`
+    /// With paths, only changes touching them are included, and with a
+    /// limit, only the first `limit` changes. Changes are shown with the
+    /// configured `blazingjj.log-template`, if any. Timestamps are in
+    /// ISO 8601 format, so they can be shown relative to now without
+    /// running jj again. With `blazingjj.log-show-signatures`, the
+    /// signature status is shown by the templates that support it.
+    /// Maps to `jj log --limit <limit> <paths>`
     #[instrument(level = "trace", skip(self))]
-    pub fn get_log(&self, revset: &Option<String>) -> Result<LogOutput, CommandError> {
+    pub fn get_log(
+        &self,
+        revset: &Option<String>,
+        paths: &[String],
+        limit: Option<usize>,
+    ) -> Result<LogOutput, CommandError> {
         let mut args = vec![];
 
+        // The graph style changes the lines of the graph, so both log
+        // commands need it
+        let graph_style_config = self
+            .env
+            .jj_config
+            .graph_style()
+            .map(|style| format!("ui.graph.style={}", style.as_str()));
+        if let Some(graph_style_config) = graph_style_config.as_ref() {
+            args.push("--config");
+            args.push(graph_style_config);
+        }
         if let Some(revset) = revset {
             args.push("-r");
             args.push(revset);
         }
+        let limit_arg = limit.map(|limit| limit.to_string());
+        if let Some(limit_arg) = limit_arg.as_ref() {
+            args.push("--limit");
+            args.push(limit_arg);
+        }
+        let filesets: Vec<String> = paths.iter().map(|path| path_fileset(path)).collect();
+        if !filesets.is_empty() {
+            args.push("--");
+            args.extend(filesets.iter().map(String::as_str));
+        }
 
-        // Force builtin_log_compact which uses 2 lines per change
-        let graph = self.execute_jj_command(
-            [
-                vec!["log", "--template", "builtin_log_compact"],
-                args.clone(),
-            ]
-            .concat(),
-            true,
-            true,
-        )?;
+        // Use builtin_log_compact which uses 2 lines per change, unless
+        // another template is configured
+        let log_template = self.env.jj_config.log_template();
+        let mut graph_args = vec![
+            "log",
+            "--template",
+            log_template.unwrap_or("builtin_log_compact"),
+            "--config",
+            ISO_TIMESTAMP_CONFIG,
+        ];
+        if self.env.jj_config.log_show_signatures() {
+            graph_args.extend(["--config", SIGNATURES_CONFIG]);
+        }
+        let graph = self.execute_jj_command([graph_args, args.clone()].concat(), true, true)?;
 
         // Extract the log one more time, but this time use a template
         // where each line begins with Head information. Since jj has
         // 2 lines per change, there will also be two lines with head info.
         // The number of lines in graph and the number of items in graph_heads
         // should be identical.
+        let heads_template = match log_template {
+            // Match builtin_log_compact with 2 lines per change
+            None => format!(r#"{HEAD_TEMPLATE} ++ " " ++ bookmarks ++"\n" ++ {HEAD_TEMPLATE}"#),
+            // Match the configured template with a head per line
+            Some(log_template) => format!(
+                r#"stringify({log_template}).lines().map(|_| {HEAD_TEMPLATE}).join("\n") ++ "\n""#
+            ),
+        };
         let graph_heads: Vec<Option<Head>> = self
             .execute_jj_command(
-                [
-                    vec![
-                        "log",
-                        "--template",
-                        // Match builtin_log_compact with 2 lines per change
-                        &format!(
-                            r#"{HEAD_TEMPLATE} ++ " " ++ bookmarks ++"\n" ++ {HEAD_TEMPLATE}"#
-                        ),
-                    ],
-                    args,
-                ]
-                .concat(),
+                [vec!["log", "--template", &heads_template], args.clone()].concat(),
                 false,
                 true,
             )?
@@ -163,29 +332,217 @@ impl Commander {
             .map(|line| parse_head(line).ok())
             .collect();
 
-        let heads = graph_heads.clone().into_iter().flatten().unique().collect();
+        let heads: Vec<Head> = graph_heads.clone().into_iter().flatten().unique().collect();
+        let truncated = limit.is_some_and(|limit| heads.len() >= limit);
+
+        // Find the bookmarks and tags, to show them as chips
+        let (refs, tracking) = if self.env.jj_config.log_ref_chips() {
+            let refs = parse_log_refs(
+                &self.execute_jj_command(
+                    [
+                        vec!["log", "--no-graph", "--template", REFS_TEMPLATE],
+                        args.clone(),
+                    ]
+                    .concat(),
+                    false,
+                    true,
+                )?,
+            );
+            (refs, self.get_tracking_distances()?)
+        } else {
+            (HashMap::new(), HashMap::new())
+        };
+
+        // Count the lines changed by all changes at once, to show their
+        // size
+        let sizes = if self.env.jj_config.log_change_size() {
+            parse_change_sizes(
+                &self.execute_jj_command(
+                    [
+                        vec!["log", "--no-graph", "--template", CHANGE_SIZE_TEMPLATE],
+                        args,
+                    ]
+                    .concat(),
+                    false,
+                    true,
+                )?,
+            )
+        } else {
+            HashMap::new()
+        };
 
         Ok(LogOutput {
             graph,
             graph_heads,
             heads,
+            truncated,
+            refs,
+            tracking,
+            sizes,
         })
     }
 
-    /// Get commit details.
-    /// Maps to `jj show <commit>`
+    /// Get commit details, with `context` lines around each change
+    /// if set. Maps to `jj show <commit>`, with the configured
+    /// `blazingjj.show-template` if any
     #[instrument(level = "trace", skip(self))]
     pub fn get_commit_show(
         &self,
         commit_id: &CommitId,
         diff_format: &DiffFormat,
+        context: Option<usize>,
         ignore_working_copy: bool,
     ) -> Result<String, CommandError> {
         let mut args = vec!["show", commit_id.as_str()];
+        if let Some(template) = self.env.jj_config.show_template() {
+            args.extend(["-T", template]);
+        }
+        args.append(&mut diff_format.get_args());
+        let context_arg;
+        if let Some(context) = context
+            && diff_format.uses_context()
+        {
+            context_arg = context.to_string();
+            args.extend(["--context", &context_arg]);
+        }
+        if ignore_working_copy {
+            args.push("--ignore-working-copy");
+        }
+
+        Ok(self.execute_jj_command(args, true, true)?.remove_end_line())
+    }
+
+    /// Like [Commander::get_commit_show], but without the diff of the
+    /// `excluded` paths, e.g. files with a diff too long to show.
+    /// Maps to `jj show --no-patch <commit>` and
+    /// `jj diff -r <commit> ~<excluded>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_commit_show_excluding(
+        &self,
+        commit_id: &CommitId,
+        diff_format: &DiffFormat,
+        context: Option<usize>,
+        excluded: &[String],
+        ignore_working_copy: bool,
+    ) -> Result<String, CommandError> {
+        let mut args = vec!["show", "--no-patch", commit_id.as_str()];
+        if let Some(template) = self.env.jj_config.show_template() {
+            args.extend(["-T", template]);
+        }
+        if ignore_working_copy {
+            args.push("--ignore-working-copy");
+        }
+        let header = self.execute_jj_command(args, true, true)?;
+        let diff = self.get_diff(
+            &["-r", commit_id.as_str()],
+            diff_format,
+            context,
+            &[Self::get_files_except_revset(excluded)],
+            ignore_working_copy,
+        )?;
+        Ok(header + &diff)
+    }
+
+    /// Get the diff of a commit to some paths, with `context` lines
+    /// around each change if set, for viewing a large commit file by
+    /// file. Maps to `jj diff -r <commit> <paths>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_commit_diff(
+        &self,
+        commit_id: &CommitId,
+        diff_format: &DiffFormat,
+        context: Option<usize>,
+        paths: &[String],
+        ignore_working_copy: bool,
+    ) -> Result<String, CommandError> {
+        self.get_diff(
+            &["-r", commit_id.as_str()],
+            diff_format,
+            context,
+            &Self::get_file_revsets(paths),
+            ignore_working_copy,
+        )
+    }
+
+    /// Get the diff between two commits, with `context` lines around
+    /// each change if set, limited to `paths` unless empty.
+    /// Maps to `jj diff --from <from> --to <to> <paths>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_commits_diff(
+        &self,
+        from: &CommitId,
+        to: &CommitId,
+        diff_format: &DiffFormat,
+        context: Option<usize>,
+        paths: &[String],
+        ignore_working_copy: bool,
+    ) -> Result<String, CommandError> {
+        self.get_diff(
+            &["--from", from.as_str(), "--to", to.as_str()],
+            diff_format,
+            context,
+            &Self::get_file_revsets(paths),
+            ignore_working_copy,
+        )
+    }
+
+    /// Like [Commander::get_commits_diff] of all files, but without the
+    /// diff of the `excluded` paths.
+    /// Maps to `jj diff --from <from> --to <to> ~<excluded>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_commits_diff_excluding(
+        &self,
+        from: &CommitId,
+        to: &CommitId,
+        diff_format: &DiffFormat,
+        context: Option<usize>,
+        excluded: &[String],
+        ignore_working_copy: bool,
+    ) -> Result<String, CommandError> {
+        self.get_diff(
+            &["--from", from.as_str(), "--to", to.as_str()],
+            diff_format,
+            context,
+            &[Self::get_files_except_revset(excluded)],
+            ignore_working_copy,
+        )
+    }
+
+    /// Filesets of the paths
+    fn get_file_revsets(paths: &[String]) -> Vec<String> {
+        paths
+            .iter()
+            .map(|path| Self::get_file_revset(path))
+            .collect()
+    }
+
+    /// Run `jj diff` with the revision arguments, limited to the
+    /// filesets unless empty
+    fn get_diff(
+        &self,
+        revisions: &[&str],
+        diff_format: &DiffFormat,
+        context: Option<usize>,
+        filesets: &[String],
+        ignore_working_copy: bool,
+    ) -> Result<String, CommandError> {
+        let mut args = vec!["diff"];
+        args.extend(revisions);
         args.append(&mut diff_format.get_args());
+        let context_arg;
+        if let Some(context) = context
+            && diff_format.uses_context()
+        {
+            context_arg = context.to_string();
+            args.extend(["--context", &context_arg]);
+        }
         if ignore_working_copy {
             args.push("--ignore-working-copy");
         }
+        if !filesets.is_empty() {
+            args.push("--");
+            args.extend(filesets.iter().map(String::as_str));
+        }
 
         Ok(self.execute_jj_command(args, true, true)?.remove_end_line())
     }
@@ -275,6 +632,22 @@ impl Commander {
         )
     }
 
+    /// Get the short change ids of the parents of a revision, e.g. to
+    /// show how they changed.
+    /// Maps to `jj log -r <revision> -T 'parents.map(|c| c.change_id().short(8))'`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_parents(&self, revision: &str) -> Result<Vec<String>> {
+        Ok(self
+            .execute_jj_log_one(
+                revision,
+                r#"parents.map(|c| c.change_id().short(8)).join(" ")"#,
+            )
+            .with_context(|| format!("Failed getting parents: {revision}"))?
+            .split_whitespace()
+            .map(str::to_owned)
+            .collect())
+    }
+
     /// Get commit's description.
     /// Maps to `jj log -r <revision> -T description`
     #[instrument(level = "trace", skip(self))]
@@ -285,6 +658,22 @@ impl Commander {
             .remove_end_line())
     }
 
+    /// Get the author of a commit
+    /// Maps to `jj log -r <revision> -T 'author.name() ...'`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_commit_author(&self, commit_id: &CommitId) -> Result<CommitAuthor> {
+        let output = self
+            .execute_jj_log_one(commit_id.as_str(), AUTHOR_TEMPLATE)
+            .with_context(|| format!("Failed getting commit author: {commit_id}"))?;
+        let mut fields = output.trim_end_matches('\n').splitn(3, '\t');
+        let mut field = || fields.next().unwrap_or_default().to_owned();
+        Ok(CommitAuthor {
+            name: field(),
+            email: field(),
+            timestamp: field(),
+        })
+    }
+
     /// Check if a revision is immutable
     /// Maps to `jj log -r <revision> -T immutable`
     #[instrument(level = "trace", skip(self))]
@@ -296,6 +685,199 @@ impl Commander {
             == "true")
     }
 
+    /// Check if a revision was pushed, i.e. a remote bookmark points to
+    /// it or to one of its descendants
+    /// Maps to `jj log -r '<revision> & ::remote_bookmarks()'`
+    #[instrument(level = "trace", skip(self))]
+    pub fn check_revision_pushed(&self, revision: &str) -> Result<bool> {
+        Ok(self
+            .count_revset(&format!("({revision}) & ::remote_bookmarks()"), 1)
+            .with_context(|| format!("Failed checking if revision is pushed: {revision}"))?
+            > 0)
+    }
+
+    /// Get the head of a change id, commit id or bookmark typed by the
+    /// user. If it is a revset, the first change of it.
+    /// Maps to `jj log -r <revision>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_revision_head(&self, revision: &str) -> Result<Head> {
+        parse_head(
+            &self
+                .execute_jj_log_one(revision, HEAD_TEMPLATE_NL)
+                .with_context(|| format!("Failed getting revision: {revision}"))?
+                .remove_end_line(),
+        )
+    }
+
+    /// Get the first of the changes created by the last operation, e.g.
+    /// the copy made by jj duplicate.
+    /// Maps to `jj log -r 'roots(mutable() ~ at_operation(@-, mutable()))'`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_created_head(&self) -> Result<Head> {
+        self.get_revision_head("roots(mutable() ~ at_operation(@-, mutable()))")
+    }
+
+    /// Get the revset shown by the log without `-r`.
+    /// Maps to `jj config get revsets.log`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_default_log_revset(&self) -> Result<String, CommandError> {
+        Ok(self
+            .execute_jj_command(["config", "get", "revsets.log"], false, true)?
+            .remove_end_line())
+    }
+
+    /// Count the changes of a revset, up to a limit, e.g. to check a
+    /// revset while it is typed.
+    /// Maps to `jj log --no-graph -r <revset> --limit <limit>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn count_revset(&self, revset: &str, limit: usize) -> Result<usize, CommandError> {
+        Ok(self
+            .execute_jj_command(
+                [
+                    "log",
+                    "--no-graph",
+                    "--template",
+                    r#"commit_id ++ "\n""#,
+                    "-r",
+                    revset,
+                    "--limit",
+                    &limit.to_string(),
+                ],
+                false,
+                true,
+            )?
+            .lines()
+            .count())
+    }
+
+    /// Count the descendants of commits that are rewritten when the
+    /// commits are, e.g. rebased when they are abandoned.
+    /// Maps to `jj log --no-graph -r '(<commit ids>):: ~ (<commit ids>)'`
+    #[instrument(level = "trace", skip(self))]
+    pub fn count_descendants(&self, commit_ids: &[CommitId]) -> Result<usize, CommandError> {
+        let commits = commit_ids.iter().map(CommitId::as_str).join(" | ");
+        Ok(self
+            .get_commit_ids(&format!("({commits}):: ~ ({commits})"))?
+            .len())
+    }
+
+    /// Get the authors of the latest ancestors of @ as `Name <email>`,
+    /// most recent first and each once, e.g. for Co-authored-by trailers.
+    /// Maps to `jj log --no-graph -r ::@ --limit <limit>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_recent_authors(&self, limit: usize) -> Result<Vec<String>, CommandError> {
+        Ok(self
+            .execute_jj_command(
+                [
+                    "log",
+                    "--no-graph",
+                    "--template",
+                    r#"if(author.email(), author.name() ++ " <" ++ author.email() ++ ">\n")"#,
+                    "-r",
+                    "::@",
+                    "--limit",
+                    &limit.to_string(),
+                ],
+                false,
+                true,
+            )?
+            .lines()
+            .map(str::to_owned)
+            .unique()
+            .collect())
+    }
+
+    /// Get the changes of a revset, in the order of the log.
+    /// Maps to `jj log --no-graph -r <revset>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_heads(&self, revset: &str) -> Result<Vec<Head>> {
+        self.execute_jj_log(revset, HEAD_TEMPLATE_NL)
+            .with_context(|| format!("Failed getting changes: {revset}"))?
+            .lines()
+            .map(parse_head)
+            .collect()
+    }
+
+    /// Get the commit ids of a revset.
+    /// Maps to `jj log --no-graph -r <revset>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_commit_ids(&self, revset: &str) -> Result<Vec<CommitId>, CommandError> {
+        Ok(self
+            .execute_jj_command(
+                [
+                    "log",
+                    "--no-graph",
+                    "--template",
+                    r#"commit_id ++ "\n""#,
+                    "-r",
+                    revset,
+                ],
+                false,
+                true,
+            )?
+            .lines()
+            .map(|line| CommitId(line.to_owned()))
+            .collect())
+    }
+
+    /// Get the visible commits of a change with a one line summary
+    /// each, e.g. to pick one of the copies of a divergent change.
+    /// Maps to `jj log --no-graph -r change_id(<change id>)`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_change_commits(&self, change_id: &ChangeId) -> Result<Vec<(Head, String)>> {
+        parse_change_commits(
+            &self
+                .execute_jj_log(
+                    &format!("change_id({})", change_id.as_str()),
+                    CHANGE_COMMIT_TEMPLATE,
+                )
+                .with_context(|| format!("Failed getting commits of change: {change_id}"))?,
+        )
+    }
+
+    /// Get the visible commits of some changes with a one line summary
+    /// each, e.g. of the pinned changes. Changes that are not visible
+    /// anymore are left out.
+    /// Maps to `jj log --no-graph -r 'present(change_id(<change id>)) | ...'`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_changes_commits(&self, change_ids: &[ChangeId]) -> Result<Vec<(Head, String)>> {
+        if change_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        let revset = change_ids
+            .iter()
+            .map(|change_id| format!("present(change_id({change_id}))"))
+            .join(" | ");
+        parse_change_commits(
+            &self
+                .execute_jj_log(&revset, CHANGE_COMMIT_TEMPLATE)
+                .context("Failed getting commits of changes")?,
+        )
+    }
+
+    /// Get the versions of a change, newest first, with a one line
+    /// summary each, from the evolution log.
+    /// Maps to `jj evolog --no-graph -r <commit id>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_evolog(&self, commit_id: &CommitId) -> Result<Vec<(Head, String)>> {
+        parse_change_commits(
+            &self
+                .execute_jj_command(
+                    [
+                        "evolog",
+                        "--no-graph",
+                        "-T",
+                        EVOLOG_TEMPLATE,
+                        "-r",
+                        commit_id.as_str(),
+                    ],
+                    false,
+                    true,
+                )
+                .with_context(|| format!("Failed getting evolution log of: {commit_id}"))?,
+        )
+    }
+
     /// Get bookmark head
     /// Maps to `jj log -r <bookmark>[@<remote>]`
     #[instrument(level = "trace", skip(self))]
@@ -322,7 +904,7 @@ mod tests {
     fn get_log() -> Result<()> {
         let test_repo = TestRepo::new()?;
 
-        let log = test_repo.commander.get_log(&None)?;
+        let log = test_repo.commander.get_log(&None, &[], None)?;
 
         let mut settings = insta::Settings::clone_current();
         settings.add_filter(r"[k-z]{8} .*? [0-9a-fA-F]{8}", "[LINE]");
@@ -335,6 +917,101 @@ mod tests {
                 .as_ref()
                 .is_none_or(|graph_head| log.heads.contains(graph_head))
         }));
+        assert!(!log.truncated);
+
+        let log = test_repo.commander.get_log(&None, &[], Some(1))?;
+        assert_eq!(log.heads.len(), 1);
+        assert!(log.truncated);
+
+        let log = test_repo
+            .commander
+            .get_log(&None, &["README".to_owned()], None)?;
+        assert!(log.heads.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn path_filesets() {
+        assert_eq!(path_fileset("src/main.rs"), "src/main.rs");
+        assert_eq!(path_fileset("src/*.rs"), r#"glob:"src/*.rs""#);
+        assert_eq!(path_fileset(r#"glob:"*.md""#), r#"glob:"*.md""#);
+    }
+
+    #[test]
+    fn parse_refs() {
+        let refs = parse_log_refs("aaa\tlmain\trmain@origin\tcdev??\ttv1.0\nbbb\t\n");
+        assert_eq!(
+            refs,
+            HashMap::from([(
+                CommitId("aaa".to_owned()),
+                vec![
+                    LogRef {
+                        name: "main".to_owned(),
+                        kind: RefKind::Local
+                    },
+                    LogRef {
+                        name: "main@origin".to_owned(),
+                        kind: RefKind::Remote
+                    },
+                    LogRef {
+                        name: "dev??".to_owned(),
+                        kind: RefKind::Conflicted
+                    },
+                    LogRef {
+                        name: "v1.0".to_owned(),
+                        kind: RefKind::Tag
+                    },
+                ]
+            )])
+        );
+    }
+
+    #[test]
+    fn parse_sizes() {
+        assert_eq!(
+            parse_change_sizes("aaa\t120\t4\nbbb\t0\t0\nccc\n"),
+            HashMap::from([
+                (
+                    CommitId("aaa".to_owned()),
+                    ChangeSize {
+                        insertions: 120,
+                        deletions: 4
+                    }
+                ),
+                (CommitId("bbb".to_owned()), ChangeSize::default()),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_conflicted_head() -> Result<()> {
+        assert_eq!(
+            parse_head("[qpvuntsm|3ba7e7fd|false|false|true] main")?,
+            Head {
+                change_id: ChangeId("qpvuntsm".to_owned()),
+                commit_id: CommitId("3ba7e7fd".to_owned()),
+                divergent: false,
+                immutable: false,
+                conflict: true,
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_divergent_commits() -> Result<()> {
+        let commits = parse_change_commits(
+            "[qpvuntsm|3ba7e7fd|true|false|false]\t3ba7 alice 2 hours ago [wip] first\n\
+             [qpvuntsm|90fe0a96|true|false|false]\t90fe alice 1 hour ago second\n",
+        )?;
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].0.commit_id, CommitId("3ba7e7fd".to_owned()));
+        assert!(commits[0].0.divergent);
+        assert_eq!(commits[0].1, "3ba7 alice 2 hours ago [wip] first");
+        assert_eq!(commits[1].0.commit_id, CommitId("90fe0a96".to_owned()));
+        assert!(parse_change_commits("[qpvuntsm|3ba7e7fd|true|false|false]").is_err());
 
         Ok(())
     }
@@ -346,10 +1023,12 @@ mod tests {
         fs::write(test_repo.directory.path().join("README"), b"AAA")?;
 
         let head = test_repo.commander.get_current_head()?;
-        let show =
-            test_repo
-                .commander
-                .get_commit_show(&head.commit_id, &DiffFormat::ColorWords, false)?;
+        let show = test_repo.commander.get_commit_show(
+            &head.commit_id,
+            &DiffFormat::ColorWords,
+            None,
+            false,
+        )?;
 
         let mut settings = insta::Settings::clone_current();
         settings.add_filter(r"Commit ID: [0-9a-fA-F]{40}", "Commit ID: [COMMIT_ID]");
@@ -362,6 +1041,31 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn get_commit_diff() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+        fs::write(test_repo.directory.path().join("README"), b"AAA")?;
+        fs::write(test_repo.directory.path().join("LICENSE"), b"BBB")?;
+
+        let head = test_repo.commander.get_current_head()?;
+        let diff = |paths: &[String]| {
+            test_repo.commander.get_commit_diff(
+                &head.commit_id,
+                &DiffFormat::Git,
+                None,
+                paths,
+                false,
+            )
+        };
+        let readme = diff(&["README".to_owned()])?;
+        assert!(readme.contains("README"));
+        assert!(!readme.contains("LICENSE"));
+        let all = diff(&[])?;
+        assert!(all.contains("README") && all.contains("LICENSE"));
+
+        Ok(())
+    }
+
     #[test]
     fn get_commit_parent() -> Result<()> {
         let test_repo = TestRepo::new()?;
@@ -375,12 +1079,81 @@ mod tests {
                 change_id: ChangeId("zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzz".to_owned()),
                 divergent: false,
                 immutable: true,
+                conflict: false,
             }
         );
 
         Ok(())
     }
 
+    #[test]
+    fn get_revision_head() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+
+        let head = test_repo.commander.get_current_head()?;
+
+        assert_eq!(
+            test_repo
+                .commander
+                .get_revision_head(head.change_id.as_str())?,
+            head
+        );
+        assert_eq!(
+            test_repo
+                .commander
+                .get_revision_head(&head.commit_id.as_str()[..8])?,
+            head
+        );
+        assert!(
+            test_repo
+                .commander
+                .get_revision_head("nonexistent")
+                .is_err()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn count_revset() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+
+        assert_eq!(test_repo.commander.count_revset("@", 10)?, 1);
+        assert_eq!(test_repo.commander.count_revset("::@", 10)?, 2);
+        assert_eq!(test_repo.commander.count_revset("::@", 1)?, 1);
+        assert!(test_repo.commander.count_revset("::@ &", 10).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn get_commit_ids() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+
+        let head = test_repo.commander.get_current_head()?;
+        assert_eq!(
+            test_repo.commander.get_commit_ids("@ | root()")?,
+            [
+                head.commit_id,
+                CommitId("0000000000000000000000000000000000000000".to_owned())
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn get_heads() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+
+        let head = test_repo.commander.get_current_head()?;
+        let heads = test_repo.commander.get_heads("@ | root()")?;
+        assert_eq!(heads.len(), 2);
+        assert_eq!(heads[0], head);
+
+        Ok(())
+    }
+
     #[test]
     fn get_head_latest() -> Result<()> {
         let test_repo = TestRepo::new()?;
@@ -398,6 +1171,23 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn get_evolog() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+
+        let old_head = test_repo.commander.get_current_head()?;
+        fs::write(test_repo.directory.path().join("README"), b"AAA")?;
+        let new_head = test_repo.commander.get_current_head()?;
+
+        let versions = test_repo.commander.get_evolog(&new_head.commit_id)?;
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].0, new_head);
+        assert_eq!(versions[1].0, old_head);
+        assert!(versions[0].1.contains("(snapshot working copy)"));
+
+        Ok(())
+    }
+
     #[test]
     fn check_revision_immutable() -> Result<()> {
         let test_repo = TestRepo::new()?;
@@ -407,6 +1197,40 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn get_recent_authors() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+
+        test_repo.commander.run_new(["@"])?;
+        assert_eq!(
+            test_repo.commander.get_recent_authors(10)?,
+            ["blazingjj <blazingjj@example.com>"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn get_commit_author() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+
+        let head = test_repo.commander.get_current_head()?;
+        let author = test_repo.commander.get_commit_author(&head.commit_id)?;
+        assert_eq!(author.name, "blazingjj");
+        assert_eq!(author.email, "blazingjj@example.com");
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_revision_pushed() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+
+        assert!(!(test_repo.commander.check_revision_pushed("@")?));
+
+        Ok(())
+    }
+
     #[test]
     fn get_bookmark_head() -> Result<()> {
         let test_repo = TestRepo::new()?;
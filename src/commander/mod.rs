@@ -17,6 +17,20 @@ command implementation functions:
 * [Commander::execute_command] - Execute any command and log the result
 * [Commander::execute_jj_command] - Execute a jj command.
 * [Commander::execute_void_jj_command] - Execute a jj command and discard the output.
+* [Commander::execute_jj_command_messages] - Execute a jj command and return its messages.
+
+Successful jj commands that may change the repository are noted, for
+the app to show the resulting operation, see [take_repo_changed]. Any
+jj command may create an operation, see [take_jj_command_ran].
+
+Snapshots of the working copy can be paused, see [set_snapshot_paused].
+
+Slow jj commands can run on a worker thread without blocking the UI,
+see [worker::CommandJob].
+
+The repository can be viewed at an old operation, see [set_at_operation].
+jj commands then load it at that operation and the ones that may change
+it are refused, like any other program.
 
 */
 
@@ -25,13 +39,18 @@ pub mod files;
 pub mod ids;
 pub mod jj;
 pub mod log;
+pub mod range_diff;
+pub mod worker;
 
 use std::ffi::OsStr;
 use std::io;
 use std::process::Command;
+use std::process::Output;
 use std::string::FromUtf8Error;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 
 use ansi_to_tui::IntoText;
 use anyhow::Context;
@@ -47,6 +66,8 @@ use tracing::trace;
 use version_compare::Cmp;
 use version_compare::compare;
 
+use crate::commander::jj::Operation;
+use crate::commander::log::Head;
 use crate::env::DiffFormat;
 use crate::env::Env;
 use crate::env::get_env;
@@ -60,7 +81,7 @@ impl DiffFormat {
     pub fn get_args(&self) -> Vec<&str> {
         match self {
             DiffFormat::ColorWords => vec!["--color-words"],
-            DiffFormat::Git => vec!["--git"],
+            DiffFormat::Git | DiffFormat::SideBySide => vec!["--git"],
             DiffFormat::Summary => vec!["--summary"],
             DiffFormat::Stat => vec!["--stat"],
             DiffFormat::DiffTool(Some(tool)) => vec!["--tool", tool],
@@ -77,6 +98,9 @@ pub enum CommandError {
     Status(String, Option<i32>),
     #[error("Error parsing UTF-8 output: {0}")]
     FromUtf8(#[from] FromUtf8Error),
+    /// The command was killed, see [worker::CommandJob::cancel]
+    #[error("Cancelled")]
+    Cancelled,
 }
 
 impl CommandError {
@@ -93,6 +117,122 @@ impl CommandError {
     }
 }
 
+/// Set when a jj command that may change the repository succeeded
+static REPO_CHANGED: AtomicBool = AtomicBool::new(false);
+
+/// Tell if a jj command may have changed the repository since the last
+/// call, e.g. to show the resulting operation
+pub fn take_repo_changed() -> bool {
+    REPO_CHANGED.swap(false, Ordering::Relaxed)
+}
+
+/// Set when a jj command of the app that may have created an operation
+/// ran, even one that failed or only read the repository
+static JJ_COMMAND_RAN: AtomicBool = AtomicBool::new(false);
+
+/// Tell if a jj command of the app ran since the last call. The
+/// operations created since are the app's own, e.g. snapshots of the
+/// working copy taken while loading the log.
+pub fn take_jj_command_ran() -> bool {
+    JJ_COMMAND_RAN.swap(false, Ordering::Relaxed)
+}
+
+/// Set while jj commands that only read the repository don't snapshot
+/// the working copy
+static SNAPSHOT_PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// Tell if the working copy is not snapshotted when reading the
+/// repository
+pub fn snapshot_paused() -> bool {
+    SNAPSHOT_PAUSED.load(Ordering::Relaxed)
+}
+
+/// Run jj commands that only read the repository with
+/// `--ignore-working-copy`, e.g. while editing files whose snapshot is
+/// slow. Commands that change the repository still snapshot it first.
+pub fn set_snapshot_paused(paused: bool) {
+    SNAPSHOT_PAUSED.store(paused, Ordering::Relaxed);
+}
+
+/// Operation the repository is viewed at, None for the present
+static AT_OPERATION: Mutex<Option<Operation>> = Mutex::new(None);
+
+/// Operation the repository is viewed at, if it is not the present
+pub fn at_operation() -> Option<Operation> {
+    AT_OPERATION.lock().unwrap().clone()
+}
+
+/// View the repository at an operation, read-only, or at the present
+/// with None. Background commands always see the present.
+pub fn set_at_operation(operation: Option<Operation>) {
+    *AT_OPERATION.lock().unwrap() = operation;
+}
+
+/// Refuse to run a command while the repository is viewed at an old
+/// operation, unless it is a jj command that only reads the repository
+fn check_read_only(command: &Command, jj_bin: &str) -> Result<(), CommandError> {
+    match at_operation() {
+        Some(operation)
+            if command.get_program() != OsStr::new(jj_bin) || !is_read_only(command.get_args()) =>
+        {
+            Err(CommandError::Status(
+                format!(
+                    "The repository is viewed at operation {}, where it is read-only",
+                    operation.short_id()
+                ),
+                None,
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Note a successful jj command, unless it only reads the repository.
+/// jj may still snapshot the working copy while reading, that is not
+/// counted as a change.
+fn note_jj_command<'a>(args: impl IntoIterator<Item = &'a OsStr>) {
+    if !is_read_only(args) {
+        REPO_CHANGED.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Tell if a jj command may create an operation. Commands that only
+/// read the repository still snapshot the working copy, unless it is
+/// ignored.
+fn may_snapshot(command: &Command) -> bool {
+    !is_read_only(command.get_args())
+        || !command.get_args().any(|arg| arg == "--ignore-working-copy")
+}
+
+/// Tell if a jj command only reads the repository, from its arguments
+fn is_read_only<'a>(args: impl IntoIterator<Item = &'a OsStr>) -> bool {
+    let args: Vec<&str> = args.into_iter().filter_map(OsStr::to_str).take(3).collect();
+    matches!(
+        args.as_slice(),
+        [
+            "log"
+                | "show"
+                | "diff"
+                | "interdiff"
+                | "status"
+                | "evolog"
+                | "root"
+                | "version"
+                | "help"
+                | "config",
+            ..
+        ] | ["op" | "operation", "log" | "show" | "diff", ..]
+            | ["git", "push", "--dry-run", ..]
+            | ["git", "remote", "list", ..]
+            | ["file", "show" | "list" | "annotate" | "search", ..]
+            | ["bookmark" | "tag", "list", ..]
+            | ["workspace", "list" | "root", ..]
+            | ["sparse", "list", ..]
+            | ["resolve", "--list", ..]
+            | ["util", "completion" | "config-schema" | "markdown-help", ..]
+    )
+}
+
 /// Struct used to interact with the jj cli using commanders.
 ///
 /// Handles arguments and recording of history.
@@ -105,6 +245,15 @@ pub struct Commander {
     // Used for testing
     pub jj_config_toml: Option<Vec<String>>,
     pub force_no_color: bool,
+    /// Run jj with `--ignore-working-copy`, e.g. to simulate a command
+    /// without touching the files
+    pub ignore_working_copy: bool,
+    /// Running in the background, e.g. to fetch periodically, where the
+    /// changes to the repository are not shown as the last operation
+    pub background: bool,
+    /// Set when the job running the commands is cancelled, see
+    /// [worker::CommandJob]
+    cancelled: Option<Arc<AtomicBool>>,
 }
 
 /// Initialize a new [Commander] using [ENV]
@@ -120,6 +269,9 @@ impl Commander {
             env_var: Arc::new(Mutex::new(Vec::new())),
             jj_config_toml: None,
             force_no_color: false,
+            ignore_working_copy: false,
+            background: false,
+            cancelled: None,
         }
     }
 
@@ -147,15 +299,34 @@ impl Commander {
     /// Environment variables can be set with set_env.
     /// They are cleared after execution.
     fn execute_command(&self, command: &mut Command) -> Result<String, CommandError> {
-        // Set current directory to root
-        command.current_dir(&self.env.root);
+        Ok(String::from_utf8(
+            self.execute_command_output(command)?.stdout,
+        )?)
+    }
+
+    /// Execute a command like [Commander::execute_command], returning
+    /// all of its output
+    fn execute_command_output(&self, command: &mut Command) -> Result<Output, CommandError> {
+        if !self.background {
+            check_read_only(command, &self.env.jj_bin)?;
+        }
+
+        // Set current directory to root, unless the command runs elsewhere
+        if command.get_current_dir().is_none() {
+            command.current_dir(&self.env.root);
+        }
 
         // Set environment variables and clear them for the next command
         command.envs(self.env_var.lock().unwrap().iter().cloned());
         self.env_var.lock().unwrap().clear();
 
-        let output = command.output();
-        let output = output?;
+        let output = match &self.cancelled {
+            Some(cancelled) => worker::output_cancellable(command, cancelled)?,
+            None => command.output()?,
+        };
+        if command.get_program() == OsStr::new(&self.env.jj_bin) && may_snapshot(command) {
+            JJ_COMMAND_RAN.store(true, Ordering::Relaxed);
+        }
 
         if !output.status.success() {
             // Return JjError if non-zero status code
@@ -164,17 +335,15 @@ impl Commander {
                 output.status.code(),
             ));
         }
+        if command.get_program() == OsStr::new(&self.env.jj_bin) && !self.background {
+            note_jj_command(command.get_args());
+        }
 
-        Ok(String::from_utf8(output.stdout)?)
+        Ok(output)
     }
 
-    /// Execute a jj command with color/quiet arguments.
-    pub fn execute_jj_command<I, S>(
-        &self,
-        args: I,
-        color: bool,
-        quiet: bool,
-    ) -> Result<String, CommandError>
+    /// Build a jj command with color/quiet arguments.
+    fn jj_command<I, S>(&self, args: I, color: bool, quiet: bool) -> Command
     where
         I: IntoIterator<Item = S>,
         S: AsRef<OsStr>,
@@ -182,14 +351,52 @@ impl Commander {
         let mut command = Command::new(&self.env.jj_bin);
         command.args(args);
         command.args(get_output_args(!self.force_no_color && color, quiet));
+        if self.ignore_working_copy
+            || snapshot_paused()
+                && is_read_only(command.get_args())
+                && !command.get_args().any(|arg| arg == "--ignore-working-copy")
+        {
+            command.arg("--ignore-working-copy");
+        }
+        // The operation log itself is always shown from the present
+        if !self.background
+            && let Some(operation) = at_operation()
+            && command.get_args().next() != Some(OsStr::new("op"))
+        {
+            command.args(["--at-op", &operation.id]);
+        }
 
         if let Some(jj_config_toml) = &self.jj_config_toml {
             for cfg in jj_config_toml {
                 command.args(["--config", cfg]);
             }
         }
+        command
+    }
+
+    /// Execute a jj command with color/quiet arguments.
+    pub fn execute_jj_command<I, S>(
+        &self,
+        args: I,
+        color: bool,
+        quiet: bool,
+    ) -> Result<String, CommandError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.execute_command(&mut self.jj_command(args, color, quiet))
+    }
 
-        self.execute_command(&mut command)
+    /// Execute a jj command and return the messages it writes to stderr
+    /// for the user, e.g. the revisions `jj absorb` rewrote.
+    pub fn execute_jj_command_messages<I, S>(&self, args: I) -> Result<String, CommandError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let output = self.execute_command_output(&mut self.jj_command(args, false, false))?;
+        Ok(String::from_utf8(output.stderr)?)
     }
 
     /// Execute a jj command without using the output.
@@ -203,6 +410,33 @@ impl Commander {
         Ok(())
     }
 
+    /// Execute a jj command that talks to the user in the terminal, e.g.
+    /// with the builtin diff editor. The terminal UI must be suspended.
+    pub fn run_interactive_jj_command<I, S>(&self, args: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let mut command = Command::new(&self.env.jj_bin);
+        command.args(args).current_dir(&self.env.root);
+        if let Some(jj_config_toml) = &self.jj_config_toml {
+            for cfg in jj_config_toml {
+                command.args(["--config", cfg]);
+            }
+        }
+        check_read_only(&command, &self.env.jj_bin)?;
+        let status = command.status().context("Failed running jj");
+        if may_snapshot(&command) {
+            JJ_COMMAND_RAN.store(true, Ordering::Relaxed);
+        }
+        let status = status?;
+        if !status.success() {
+            bail!("jj failed: {status}");
+        }
+        note_jj_command(command.get_args());
+        Ok(())
+    }
+
     /// Check that the version of jj is recent enough to work with blazingjj
     ///
     /// See also [JJ_MIN_VERSION]
@@ -256,6 +490,35 @@ impl RemoveEndLine for String {
     }
 }
 
+/// Replace `{change_id}`, `{commit_id}` and `{file}` in the arguments
+/// with the selected change and file. Fails if a placeholder is used
+/// while nothing is selected for it.
+pub fn substitute_placeholders(
+    args: Vec<String>,
+    head: Option<&Head>,
+    file: Option<&str>,
+) -> Result<Vec<String>> {
+    let values = [
+        ("{change_id}", head.map(|head| head.change_id.as_str())),
+        ("{commit_id}", head.map(|head| head.commit_id.as_str())),
+        ("{file}", file),
+    ];
+    args.into_iter()
+        .map(|mut arg| {
+            for (placeholder, value) in values {
+                if !arg.contains(placeholder) {
+                    continue;
+                }
+                let Some(value) = value else {
+                    bail!("Nothing is selected for {placeholder}");
+                };
+                arg = arg.replace(placeholder, value);
+            }
+            Ok(arg)
+        })
+        .collect()
+}
+
 pub fn get_output_args(color: bool, quiet: bool) -> Vec<String> {
     vec![
         "--no-pager",
@@ -274,6 +537,8 @@ pub mod tests {
     use tempfile::TempDir;
 
     use super::*;
+    use crate::commander::ids::ChangeId;
+    use crate::commander::ids::CommitId;
     use crate::env::Env;
     use crate::env::JjConfig;
 
@@ -323,6 +588,56 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn read_only_commands() {
+        let read_only = |args: &[&str]| is_read_only(args.iter().map(OsStr::new));
+        for args in [
+            &["log", "-r", "@"][..],
+            &["show", "--no-patch"],
+            &["diff", "--stat"],
+            &["op", "log"],
+            &["operation", "show"],
+            &["git", "push", "--dry-run"],
+            &["git", "remote", "list"],
+            &["file", "annotate", "README"],
+            &["bookmark", "list"],
+            &["workspace", "root"],
+            &["sparse", "list"],
+            &["resolve", "--list"],
+            &["config", "get", "user.name"],
+        ] {
+            assert!(read_only(args), "{args:?}");
+        }
+        for args in [
+            &["new"][..],
+            &["describe", "-m", "log"],
+            &["op", "restore"],
+            &["op", "abandon", "..@-"],
+            &["git", "push"],
+            &["git", "fetch"],
+            &["file", "chmod", "x"],
+            &["bookmark", "set", "main"],
+            &["sparse", "set", "--clear"],
+            &["resolve"],
+            &["util", "gc"],
+        ] {
+            assert!(!read_only(args), "{args:?}");
+        }
+    }
+
+    #[test]
+    fn snapshotting_commands() {
+        let jj = |args: &[&str]| {
+            let mut command = Command::new("jj");
+            command.args(args);
+            may_snapshot(&command)
+        };
+        assert!(jj(&["log"]));
+        assert!(jj(&["new"]));
+        assert!(jj(&["new", "--ignore-working-copy"]));
+        assert!(!jj(&["log", "--ignore-working-copy"]));
+    }
+
     #[test]
     fn test_repo() -> Result<()> {
         apply_common_filters!();
@@ -335,4 +650,35 @@ pub mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn substitute_selection() -> Result<()> {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        let head = Head {
+            change_id: ChangeId("qpvuntsm".to_owned()),
+            commit_id: CommitId("3ba7e7fd".to_owned()),
+            divergent: false,
+            immutable: false,
+            conflict: false,
+        };
+        let file = Some("src/main.rs");
+        assert_eq!(
+            substitute_placeholders(
+                args(&["file", "annotate", "-r", "{change_id}", "{file}"]),
+                Some(&head),
+                file
+            )?,
+            args(&["file", "annotate", "-r", "qpvuntsm", "src/main.rs"])
+        );
+        assert_eq!(
+            substitute_placeholders(args(&["log", "-r", "{commit_id}-"]), Some(&head), file)?,
+            args(&["log", "-r", "3ba7e7fd-"])
+        );
+        assert_eq!(
+            substitute_placeholders(args(&["log"]), None, None)?,
+            args(&["log"])
+        );
+        assert!(substitute_placeholders(args(&["show", "{change_id}"]), None, None).is_err());
+        Ok(())
+    }
 }
@@ -0,0 +1,242 @@
+/*!
+[Commander] member functions to compare two versions of a stack of
+changes, like `git range-diff`.
+
+The old version is the changes of `::old ~ ::new` and the new version
+the changes of `::new ~ ::old`, so changes both versions have in common
+are left out. The commits of the two versions are paired by change id,
+and `jj interdiff` shows how the patch of each pair changed.
+
+It is used in the [bookmarks_tab][crate::ui::bookmarks_tab] module to
+compare two bookmarks, e.g. before and after a rebase.
+*/
+use tracing::instrument;
+
+use crate::commander::CommandError;
+use crate::commander::Commander;
+use crate::commander::RemoveEndLine;
+use crate::commander::ids::ChangeId;
+use crate::commander::ids::CommitId;
+use crate::env::DiffFormat;
+
+/// A commit of one version of a stack
+#[derive(Clone, Debug, PartialEq)]
+pub struct StackCommit {
+    pub change_id: ChangeId,
+    pub commit_id: CommitId,
+    /// First line of the description
+    pub subject: String,
+}
+
+/// A change of the stack and the versions it is in. The numbers are
+/// the positions in the old and new stack, starting at 1.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RangeDiffEntry {
+    Both(usize, StackCommit, usize, StackCommit),
+    Added(usize, StackCommit),
+    Removed(usize, StackCommit),
+}
+
+// Template with change id, commit id and subject of a commit
+const STACK_TEMPLATE: &str =
+    r#"change_id ++ " " ++ commit_id ++ " " ++ description.first_line() ++ "\n""#;
+
+/// Pair the commits of two versions of a stack, both oldest first, by
+/// change id. The result follows the new stack, and changes that were
+/// removed come last.
+pub fn pair_stacks(old: Vec<StackCommit>, new: Vec<StackCommit>) -> Vec<RangeDiffEntry> {
+    let mut old: Vec<Option<StackCommit>> = old.into_iter().map(Some).collect();
+    let mut entries = vec![];
+    for (new_index, new_commit) in new.into_iter().enumerate() {
+        let old_index = old.iter().position(|old_commit| {
+            old_commit
+                .as_ref()
+                .is_some_and(|old_commit| old_commit.change_id == new_commit.change_id)
+        });
+        match old_index.and_then(|index| old[index].take().map(|commit| (index, commit))) {
+            Some((old_index, old_commit)) => entries.push(RangeDiffEntry::Both(
+                old_index + 1,
+                old_commit,
+                new_index + 1,
+                new_commit,
+            )),
+            None => entries.push(RangeDiffEntry::Added(new_index + 1, new_commit)),
+        }
+    }
+    for (old_index, old_commit) in old.into_iter().enumerate() {
+        if let Some(old_commit) = old_commit {
+            entries.push(RangeDiffEntry::Removed(old_index + 1, old_commit));
+        }
+    }
+    entries
+}
+
+/// Short form of an id, as shown in the range diff
+fn short(id: &str) -> &str {
+    &id[..id.len().min(8)]
+}
+
+/// Line of the range diff for one change, like
+/// `1: 1234abcd ! 1: 5678ef01 Subject` where the status is `=` for
+/// an unchanged patch, `!` for a changed one, `>` for an added change
+/// and `<` for a removed change.
+fn entry_line(entry: &RangeDiffEntry, changed: bool) -> String {
+    const NONE: &str = "-: --------";
+    let side = |index: usize, commit: &StackCommit| {
+        format!("{index}: {}", short(commit.commit_id.as_str()))
+    };
+    let (old, status, new, commit) = match entry {
+        RangeDiffEntry::Both(old_index, old_commit, new_index, new_commit) => (
+            side(*old_index, old_commit),
+            if changed { "\x1b[33m!\x1b[0m" } else { "=" },
+            side(*new_index, new_commit),
+            new_commit,
+        ),
+        RangeDiffEntry::Added(index, commit) => (
+            NONE.to_owned(),
+            "\x1b[32m>\x1b[0m",
+            side(*index, commit),
+            commit,
+        ),
+        RangeDiffEntry::Removed(index, commit) => (
+            side(*index, commit),
+            "\x1b[31m<\x1b[0m",
+            NONE.to_owned(),
+            commit,
+        ),
+    };
+    format!(
+        "{old} {status} {new} \x1b[1m{}\x1b[0m {}",
+        short(commit.change_id.as_str()),
+        commit.subject
+    )
+}
+
+impl Commander {
+    /// Get the commits of a revset, oldest first.
+    /// Maps to `jj log --no-graph --reversed -r <revset>`
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_stack(&self, revset: &str) -> Result<Vec<StackCommit>, CommandError> {
+        let output = self.execute_jj_command(
+            [
+                "log",
+                "--no-graph",
+                "--reversed",
+                "--template",
+                STACK_TEMPLATE,
+                "-r",
+                revset,
+            ],
+            false,
+            true,
+        )?;
+        Ok(output
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(3, ' ');
+                Some(StackCommit {
+                    change_id: ChangeId(parts.next()?.to_owned()),
+                    commit_id: CommitId(parts.next()?.to_owned()),
+                    subject: parts.next().unwrap_or_default().to_owned(),
+                })
+            })
+            .collect())
+    }
+
+    /// Compare two versions of a stack of changes, `old` and `new`, like
+    /// `git range-diff`. Each change in both versions is followed by the
+    /// changes to its patch. Maps to `jj interdiff --from <old> --to <new>`
+    /// for each change.
+    #[instrument(level = "trace", skip(self))]
+    pub fn get_range_diff(
+        &self,
+        old: &str,
+        new: &str,
+        diff_format: &DiffFormat,
+    ) -> Result<String, CommandError> {
+        let old_stack = self.get_stack(&format!("::({old}) ~ ::({new})"))?;
+        let new_stack = self.get_stack(&format!("::({new}) ~ ::({old})"))?;
+        let mut output = format!("Range diff of {old} → {new}\n\n");
+        if old_stack.is_empty() && new_stack.is_empty() {
+            output.push_str("Both versions have the same changes\n");
+        }
+        for entry in pair_stacks(old_stack, new_stack) {
+            let interdiff = match &entry {
+                RangeDiffEntry::Both(_, old_commit, _, new_commit) => {
+                    let mut args = vec![
+                        "interdiff",
+                        "--from",
+                        old_commit.commit_id.as_str(),
+                        "--to",
+                        new_commit.commit_id.as_str(),
+                    ];
+                    args.append(&mut diff_format.get_args());
+                    self.execute_jj_command(args, true, true)?.remove_end_line()
+                }
+                _ => String::new(),
+            };
+            output.push_str(&entry_line(&entry, !interdiff.is_empty()));
+            output.push('\n');
+            if !interdiff.is_empty() {
+                output.push_str(&interdiff);
+                output.push_str("\n\n");
+            }
+        }
+        Ok(output.remove_end_line())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(change_id: &str, commit_id: &str) -> StackCommit {
+        StackCommit {
+            change_id: ChangeId(change_id.to_owned()),
+            commit_id: CommitId(commit_id.to_owned()),
+            subject: format!("Change {change_id}"),
+        }
+    }
+
+    #[test]
+    fn pair_by_change_id() {
+        let old = vec![
+            commit("aaa", "111"),
+            commit("bbb", "222"),
+            commit("ccc", "333"),
+        ];
+        let new = vec![
+            commit("ccc", "444"),
+            commit("aaa", "555"),
+            commit("ddd", "666"),
+        ];
+        assert_eq!(
+            pair_stacks(old, new),
+            [
+                RangeDiffEntry::Both(3, commit("ccc", "333"), 1, commit("ccc", "444")),
+                RangeDiffEntry::Both(1, commit("aaa", "111"), 2, commit("aaa", "555")),
+                RangeDiffEntry::Added(3, commit("ddd", "666")),
+                RangeDiffEntry::Removed(2, commit("bbb", "222")),
+            ]
+        );
+    }
+
+    #[test]
+    fn format_entries() {
+        let entry = RangeDiffEntry::Both(
+            1,
+            commit("aaaaaaaaaa", "1111111111"),
+            2,
+            commit("aaaaaaaaaa", "2222222222"),
+        );
+        assert_eq!(
+            entry_line(&entry, false),
+            "1: 11111111 = 2: 22222222 \x1b[1maaaaaaaa\x1b[0m Change aaaaaaaaaa"
+        );
+        let entry = RangeDiffEntry::Removed(3, commit("bbb", "333"));
+        assert_eq!(
+            entry_line(&entry, false),
+            "3: 333 \x1b[31m<\x1b[0m -: -------- \x1b[1mbbb\x1b[0m Change bbb"
+        );
+    }
+}
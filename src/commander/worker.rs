@@ -0,0 +1,180 @@
+/*! Jobs run jj commands on a worker thread, so the UI keeps drawing and
+taking input while a slow command, like `jj show` of a huge change or
+`jj log` of a big repository, is running.
+
+The result is delivered through a channel and polled with
+[CommandJob::try_result]. A job is cancelled with [CommandJob::cancel] or
+by dropping it, which kills the jj process it is running.
+*/
+
+use std::io::Read;
+use std::process::Child;
+use std::process::Command;
+use std::process::ExitStatus;
+use std::process::Output;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::TryRecvError;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::commander::CommandError;
+use crate::commander::Commander;
+
+/// How often a running command is checked for cancellation
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A jj command running on a worker thread
+pub struct CommandJob<T> {
+    result: Receiver<Result<T, CommandError>>,
+    cancelled: Arc<AtomicBool>,
+    started_at: Instant,
+}
+
+impl<T: Send + 'static> CommandJob<T> {
+    /// Run `job` with `commander` on a worker thread. The commands it
+    /// runs are killed when the job is cancelled.
+    pub fn spawn<F>(mut commander: Commander, job: F) -> Self
+    where
+        F: FnOnce(&mut Commander) -> Result<T, CommandError> + Send + 'static,
+    {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        commander.cancelled = Some(cancelled.clone());
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || tx.send(job(&mut commander)));
+        Self {
+            result: rx,
+            cancelled,
+            started_at: Instant::now(),
+        }
+    }
+}
+
+impl<T> CommandJob<T> {
+    /// The result of the job once it is done, None while it is running
+    pub fn try_result(&self) -> Option<Result<T, CommandError>> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(CommandError::Cancelled)),
+        }
+    }
+
+    /// Stop the job, killing the command it is running. Its result is
+    /// [CommandError::Cancelled].
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Time since the job was started
+    pub fn elapsed(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+}
+
+impl<T> Drop for CommandJob<T> {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// Run a command like [Command::output], killing it as soon as
+/// `cancelled` is set
+pub(super) fn output_cancellable(
+    command: &mut Command,
+    cancelled: &AtomicBool,
+) -> Result<Output, CommandError> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // Read the output while waiting, so the command doesn't block on a
+    // full pipe
+    let stdout = read_to_end(child.stdout.take());
+    let stderr = read_to_end(child.stderr.take());
+    let status = wait_cancellable(&mut child, cancelled)?;
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+fn wait_cancellable(child: &mut Child, cancelled: &AtomicBool) -> Result<ExitStatus, CommandError> {
+    loop {
+        if cancelled.load(Ordering::Relaxed) {
+            // The command may have exited already
+            let _ = child.kill();
+            child.wait()?;
+            return Err(CommandError::Cancelled);
+        }
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        thread::sleep(CANCEL_POLL_INTERVAL);
+    }
+}
+
+fn read_to_end(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut output = vec![];
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut output);
+        }
+        output
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    fn wait_for<T>(job: &CommandJob<T>) -> Result<T, CommandError> {
+        loop {
+            if let Some(result) = job.try_result() {
+                return result;
+            }
+            thread::sleep(CANCEL_POLL_INTERVAL);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn output_of_command() {
+        let cancelled = AtomicBool::new(false);
+        let output = output_cancellable(
+            Command::new("sh").args(["-c", "echo out; echo err >&2"]),
+            &cancelled,
+        )
+        .unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"out\n");
+        assert_eq!(output.stderr, b"err\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn cancel_kills_command() {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = mpsc::channel();
+        let job = CommandJob {
+            result: rx,
+            cancelled: cancelled.clone(),
+            started_at: Instant::now(),
+        };
+        thread::spawn(move || {
+            tx.send(output_cancellable(Command::new("sleep").arg("60"), &cancelled).map(|_| ()))
+        });
+        assert!(job.try_result().is_none());
+        job.cancel();
+        assert!(matches!(wait_for(&job), Err(CommandError::Cancelled)));
+        assert!(job.elapsed() < Duration::from_secs(60));
+    }
+}
@@ -5,9 +5,11 @@ It is a combination of
 - environment variables
 - command line arguments
 */
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::process::Command;
 use std::sync::OnceLock;
+use std::time::Duration;
 
 use anyhow::Context;
 use anyhow::Result;
@@ -38,6 +40,8 @@ pub struct JjConfig {
     pub blazingjj: JjConfigBlazingjj,
     pub ui: JjConfigUi,
     pub templates: JjConfigTemplates,
+    pub user: JjConfigUser,
+    pub signing: JjConfigSigning,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -47,8 +51,28 @@ pub struct JjConfigBlazingjj {
     diff_format: Option<DiffFormat>,
     diff_tool: Option<String>,
     bookmark_template: Option<String>,
+    show_template: Option<String>,
+    log_template: Option<String>,
+    describe_template: Option<String>,
+    fix_command: Option<Vec<String>>,
+    custom_commands: BTreeMap<String, CustomCommand>,
     layout: JJLayout,
     layout_percent: u16,
+    syntax_highlighting: bool,
+    diff_line_numbers: bool,
+    diff_max_lines: usize,
+    log_page_size: usize,
+    log_relative_timestamps: bool,
+    log_show_signatures: bool,
+    graph_style: Option<GraphStyle>,
+    graph_lane_colors: bool,
+    log_ref_chips: bool,
+    log_working_copy_summary: bool,
+    log_change_size: bool,
+    auto_fetch_minutes: u64,
+    ci_status_minutes: u64,
+    snapshot: bool,
+    op_log_keep: usize,
     keybinds: Option<KeybindsConfig>,
 }
 
@@ -57,16 +81,55 @@ impl Default for JjConfigBlazingjj {
         Self {
             highlight_color: Color::Rgb(50, 50, 150),
             layout_percent: 50,
+            syntax_highlighting: true,
+            diff_line_numbers: false,
+            diff_max_lines: 2000,
+            log_page_size: 500,
+            log_relative_timestamps: false,
+            log_show_signatures: false,
+            graph_lane_colors: false,
+            log_ref_chips: true,
+            log_working_copy_summary: true,
+            log_change_size: false,
+            auto_fetch_minutes: 0,
+            ci_status_minutes: 0,
+            snapshot: true,
+            op_log_keep: 1000,
             // Standard defaults for the rest
             diff_format: None,
             diff_tool: None,
             bookmark_template: None,
+            show_template: None,
+            log_template: None,
+            describe_template: None,
+            fix_command: None,
+            custom_commands: BTreeMap::new(),
+            graph_style: None,
             layout: JJLayout::default(),
             keybinds: None,
         }
     }
 }
 
+/// A command of `blazingjj.custom-commands`, run from the log tab with
+/// `!` followed by its key
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct CustomCommand {
+    /// Shown in the list of commands and as the title of the output,
+    /// instead of the command
+    pub name: Option<String>,
+    /// Program and arguments, with `{change_id}`, `{commit_id}` and
+    /// `{file}` standing for the selection. `jj` runs the jj of blazingjj.
+    pub command: Vec<String>,
+}
+
+impl CustomCommand {
+    pub fn name(&self) -> String {
+        self.name.clone().unwrap_or_else(|| self.command.join(" "))
+    }
+}
+
 #[derive(Deserialize, Debug, Clone, Default)]
 #[serde(rename_all = "kebab-case", default)]
 pub struct JjConfigUi {
@@ -80,9 +143,21 @@ pub struct JjConfigUiDiff {
     tool: Option<toml::Value>,
 }
 
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct JjConfigUser {
+    name: Option<String>,
+    email: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct JjConfigSigning {
+    backend: Option<String>,
+}
+
 #[derive(Deserialize, Debug, Clone, Default)]
 pub struct JjConfigTemplates {
     git_push_bookmark: Option<String>,
+    log: Option<String>,
 }
 
 impl JjConfig {
@@ -115,6 +190,53 @@ impl JjConfig {
             .unwrap_or("'push-' ++ change_id.short()".to_string())
     }
 
+    /// Template for the header of `jj show`, before the diff
+    pub fn show_template(&self) -> Option<&str> {
+        self.blazingjj.show_template.as_deref()
+    }
+
+    /// Template for the changes of the log, instead of `builtin_log_compact`.
+    /// Falls back to the `templates.log` of jj.
+    pub fn log_template(&self) -> Option<&str> {
+        self.blazingjj
+            .log_template
+            .as_deref()
+            .or(self.templates.log.as_deref())
+    }
+
+    /// Text to start the description editor with, when the change has
+    /// no description yet
+    pub fn describe_template(&self) -> Option<&str> {
+        self.blazingjj.describe_template.as_deref()
+    }
+
+    /// jj arguments to run instead of `jj fix`, with `$revset` standing
+    /// for the changes to fix
+    pub fn fix_command(&self) -> Option<&[String]> {
+        self.blazingjj.fix_command.as_deref()
+    }
+
+    /// Commands run with `!` and a key, by key
+    pub fn custom_commands(&self) -> &BTreeMap<String, CustomCommand> {
+        &self.blazingjj.custom_commands
+    }
+
+    /// Backend that jj sign uses, e.g. `gpg` or `ssh`
+    pub fn signing_backend(&self) -> Option<&str> {
+        self.signing
+            .backend
+            .as_deref()
+            .filter(|backend| *backend != "none")
+    }
+
+    /// The user as `Name <email>`, e.g. for a Signed-off-by trailer
+    pub fn user_signature(&self) -> Option<String> {
+        match (&self.user.name, &self.user.email) {
+            (Some(name), Some(email)) => Some(format!("{name} <{email}>")),
+            _ => None,
+        }
+    }
+
     pub fn layout(&self) -> JJLayout {
         self.blazingjj.layout
     }
@@ -123,6 +245,83 @@ impl JjConfig {
         self.blazingjj.layout_percent
     }
 
+    pub fn syntax_highlighting(&self) -> bool {
+        self.blazingjj.syntax_highlighting
+    }
+
+    pub fn diff_line_numbers(&self) -> bool {
+        self.blazingjj.diff_line_numbers
+    }
+
+    /// Diffs of files with more lines are not shown until asked for,
+    /// 0 shows all diffs
+    pub fn diff_max_lines(&self) -> usize {
+        self.blazingjj.diff_max_lines
+    }
+
+    /// Number of changes to load into the log at a time, 0 loads
+    /// the whole log at once
+    pub fn log_page_size(&self) -> usize {
+        self.blazingjj.log_page_size
+    }
+
+    pub fn log_relative_timestamps(&self) -> bool {
+        self.blazingjj.log_relative_timestamps
+    }
+
+    /// Show the signature status of changes in the log
+    pub fn log_show_signatures(&self) -> bool {
+        self.blazingjj.log_show_signatures
+    }
+
+    /// Style of the log graph, instead of the `ui.graph.style` of jj
+    pub fn graph_style(&self) -> Option<GraphStyle> {
+        self.blazingjj.graph_style
+    }
+
+    /// Show bookmarks and tags in the log as colored chips
+    pub fn log_ref_chips(&self) -> bool {
+        self.blazingjj.log_ref_chips
+    }
+
+    /// Show a row above the log with the uncommitted changes of @
+    pub fn log_working_copy_summary(&self) -> bool {
+        self.blazingjj.log_working_copy_summary
+    }
+
+    /// Show the inserted and deleted lines of each change in the log
+    pub fn log_change_size(&self) -> bool {
+        self.blazingjj.log_change_size
+    }
+
+    /// Color each lane of the log graph differently
+    pub fn graph_lane_colors(&self) -> bool {
+        self.blazingjj.graph_lane_colors
+    }
+
+    /// How often to fetch in the background, None to not fetch
+    pub fn auto_fetch_interval(&self) -> Option<Duration> {
+        (self.blazingjj.auto_fetch_minutes > 0)
+            .then(|| Duration::from_secs(self.blazingjj.auto_fetch_minutes * 60))
+    }
+
+    /// How often to poll the checks of the pushed changes, None to not
+    /// show them
+    pub fn ci_status_interval(&self) -> Option<Duration> {
+        (self.blazingjj.ci_status_minutes > 0)
+            .then(|| Duration::from_secs(self.blazingjj.ci_status_minutes * 60))
+    }
+
+    /// Snapshot the working copy when reading the repository, at start
+    pub fn snapshot(&self) -> bool {
+        self.blazingjj.snapshot
+    }
+
+    /// Operations kept when the operation log is pruned
+    pub fn op_log_keep(&self) -> usize {
+        self.blazingjj.op_log_keep
+    }
+
     pub fn keybinds(&self) -> Option<&KeybindsConfig> {
         self.blazingjj.keybinds.as_ref()
     }
@@ -175,9 +374,12 @@ pub enum DiffFormat {
     #[default]
     ColorWords,
     Git,
+    /// Git diff rearranged into two columns by blazingjj
+    SideBySide,
     DiffTool(Option<String>),
     // Unused
     Summary,
+    /// Insertions and deletions per file, with a total
     Stat,
 }
 
@@ -185,7 +387,9 @@ impl DiffFormat {
     pub fn get_next(&self, diff_tool: Option<Option<String>>) -> DiffFormat {
         match self {
             DiffFormat::ColorWords => DiffFormat::Git,
-            DiffFormat::Git => {
+            DiffFormat::Git => DiffFormat::SideBySide,
+            DiffFormat::SideBySide => DiffFormat::Stat,
+            DiffFormat::Stat => {
                 if let Some(diff_tool) = diff_tool {
                     DiffFormat::DiffTool(diff_tool)
                 } else {
@@ -195,6 +399,53 @@ impl DiffFormat {
             _ => DiffFormat::ColorWords,
         }
     }
+
+    /// True if the output depends on the width of the details panel.
+    /// The graph of the stat format is scaled to fit the width.
+    pub fn uses_width(&self) -> bool {
+        matches!(
+            self,
+            DiffFormat::SideBySide | DiffFormat::Stat | DiffFormat::DiffTool(_)
+        )
+    }
+
+    /// True if the diff of each file starts with a header line
+    pub fn has_file_headers(&self) -> bool {
+        matches!(
+            self,
+            DiffFormat::ColorWords | DiffFormat::Git | DiffFormat::SideBySide
+        )
+    }
+
+    /// True if the number of context lines can be set with `--context`
+    pub fn uses_context(&self) -> bool {
+        matches!(
+            self,
+            DiffFormat::ColorWords | DiffFormat::Git | DiffFormat::SideBySide
+        )
+    }
+}
+
+/// Graph styles of jj, see `ui.graph.style`
+#[derive(Clone, Debug, Deserialize, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum GraphStyle {
+    Ascii,
+    AsciiLarge,
+    Curved,
+    Square,
+}
+
+impl GraphStyle {
+    /// Name of the style in the jj config
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GraphStyle::Ascii => "ascii",
+            GraphStyle::AsciiLarge => "ascii-large",
+            GraphStyle::Curved => "curved",
+            GraphStyle::Square => "square",
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Default, Copy, PartialEq)]
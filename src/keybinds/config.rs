@@ -45,30 +45,92 @@ pub struct LogTabKeybindsConfig {
     pub scroll_up_half: Option<Keybind>,
 
     pub focus_current: Option<Keybind>,
+    pub toggle_follow: Option<Keybind>,
+    pub toggle_related: Option<Keybind>,
     pub toggle_diff_format: Option<Keybind>,
+    pub toggle_unified_diff: Option<Keybind>,
+    pub toggle_timestamps: Option<Keybind>,
+    pub toggle_conflicts: Option<Keybind>,
+    pub toggle_hidden: Option<Keybind>,
+    pub toggle_snapshot: Option<Keybind>,
+    pub toggle_visual_mode: Option<Keybind>,
+    pub expand_elided: Option<Keybind>,
+    pub resolve_divergence: Option<Keybind>,
+    pub increase_context: Option<Keybind>,
+    pub decrease_context: Option<Keybind>,
+    pub toggle_compare_base: Option<Keybind>,
 
     pub refresh: Option<Keybind>,
     pub duplicate: Option<Keybind>,
+    pub duplicate_onto: Option<Keybind>,
+    pub duplicate_insert_after: Option<Keybind>,
     pub create_new: Option<Keybind>,
     pub create_new_describe: Option<Keybind>,
     pub squash: Option<Keybind>,
     pub squash_ignore_immutable: Option<Keybind>,
+    pub split: Option<Keybind>,
+    pub split_hunks: Option<Keybind>,
+    pub squash_hunks: Option<Keybind>,
+    pub squash_into_parent: Option<Keybind>,
+    pub squash_into: Option<Keybind>,
     pub edit_change: Option<Keybind>,
     pub edit_change_ignore_immutable: Option<Keybind>,
     pub abandon: Option<Keybind>,
     pub absorb: Option<Keybind>,
+    pub fix: Option<Keybind>,
+    pub custom_command: Option<Keybind>,
+    pub sign: Option<Keybind>,
+    pub unsign: Option<Keybind>,
+    pub parallelize: Option<Keybind>,
+    pub simplify_parents: Option<Keybind>,
     pub describe: Option<Keybind>,
+    pub commit: Option<Keybind>,
+    pub reword: Option<Keybind>,
+    pub edit_author: Option<Keybind>,
     pub edit_revset: Option<Keybind>,
+    pub filter_paths: Option<Keybind>,
+    pub clear_path_filter: Option<Keybind>,
+    pub filter_log: Option<Keybind>,
+    pub go_to_change: Option<Keybind>,
+    pub go_to_trunk: Option<Keybind>,
+    pub go_to_bookmark: Option<Keybind>,
+    pub toggle_pin: Option<Keybind>,
+    pub go_to_pin: Option<Keybind>,
+    pub search_log: Option<Keybind>,
+    pub search_details: Option<Keybind>,
     pub set_bookmark: Option<Keybind>,
+    pub move_bookmark: Option<Keybind>,
+    pub create_tag: Option<Keybind>,
     pub open_files: Option<Keybind>,
     pub copy_change_id: Option<Keybind>,
     pub copy_rev: Option<Keybind>,
+    pub copy_file_path: Option<Keybind>,
+    pub open_file: Option<Keybind>,
+    pub show_file: Option<Keybind>,
+    pub open_in_browser: Option<Keybind>,
+    pub show_checks: Option<Keybind>,
+    pub view_at_operation: Option<Keybind>,
+    pub diff_operations: Option<Keybind>,
+    pub restore_from: Option<Keybind>,
+    pub show_evolog: Option<Keybind>,
+    pub discard_file: Option<Keybind>,
     pub rebase: Option<Keybind>,
+    pub rebase_change: Option<Keybind>,
+    pub insert_new_before: Option<Keybind>,
+    pub insert_new_after: Option<Keybind>,
+    pub insert_new_before_describe: Option<Keybind>,
+    pub insert_new_after_describe: Option<Keybind>,
+    pub move_change_up: Option<Keybind>,
+    pub move_change_down: Option<Keybind>,
 
     pub push: Option<Keybind>,
     pub push_new: Option<Keybind>,
     pub push_all: Option<Keybind>,
     pub push_all_new: Option<Keybind>,
+    pub push_change: Option<Keybind>,
+    pub git_import: Option<Keybind>,
+    pub git_export: Option<Keybind>,
+    pub maintenance: Option<Keybind>,
     pub fetch: Option<Keybind>,
     pub fetch_all: Option<Keybind>,
 
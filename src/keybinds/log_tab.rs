@@ -31,34 +31,98 @@ pub enum LogTabEvent {
     ScrollToTop,
 
     FocusCurrent,
+    ToggleFollow,
+    ToggleRelated,
     ToggleHeadMark,
+    ToggleCompareBase,
     ToggleDiffFormat,
+    ToggleUnifiedDiff,
+    ToggleTimestamps,
+    ToggleConflicts,
+    ToggleHidden,
+    ToggleSnapshot,
+    ToggleVisualMode,
+    ExpandElided,
+    ResolveDivergence,
+    IncreaseContext,
+    DecreaseContext,
 
     Refresh,
     CreateNew {
         describe: bool,
     },
+    InsertNew {
+        before: bool,
+        describe: bool,
+    },
     Duplicate,
+    DuplicateOnto {
+        insert_after: bool,
+    },
     Rebase,
+    RebaseChange,
+    MoveChangeUp,
+    MoveChangeDown,
     Squash {
         ignore_immutable: bool,
     },
+    Split,
+    SplitHunks,
+    SquashHunks,
+    SquashIntoParent,
+    SquashInto,
     EditChange {
         ignore_immutable: bool,
     },
     Abandon,
     Absorb,
+    Fix,
+    CustomCommand,
+    Sign {
+        unsign: bool,
+    },
+    Parallelize,
+    SimplifyParents,
     Describe,
+    Commit,
+    Reword,
+    EditAuthor,
     EditRevset,
+    FilterPaths,
+    ClearPathFilter,
+    FilterLog,
+    GoToChange,
+    GoToTrunk,
+    GoToBookmark,
+    TogglePin,
+    GoToPin,
+    SearchLog,
+    SearchDetails,
     SetBookmark,
+    MoveBookmark,
+    CreateTag,
     OpenFiles,
     CopyChangeId,
     CopyRev,
+    CopyFilePath,
+    OpenFile,
+    ShowFile,
+    OpenInBrowser,
+    ShowChecks,
+    ViewAtOperation,
+    DiffOperations,
+    RestoreFrom,
+    ShowEvolog,
+    DiscardFile,
 
     Push {
         all_bookmarks: bool,
         allow_new: bool,
     },
+    PushChange,
+    GitImport,
+    GitExport,
+    Maintenance,
     Fetch {
         all_remotes: bool,
     },
@@ -85,31 +149,93 @@ impl Default for LogTabKeybinds {
             LogTabEvent::ScrollToBottom => "ctrl+end",
             LogTabEvent::ScrollToTop => "ctrl+home",
             LogTabEvent::FocusCurrent => "@",
+            LogTabEvent::ToggleFollow => "ctrl+w",
+            LogTabEvent::ToggleRelated => "shift+h",
             LogTabEvent::ToggleHeadMark => "space",
+            LogTabEvent::ToggleCompareBase => "c",
             // todo: move to DetailsKeybindings
             LogTabEvent::ToggleDiffFormat => "w",
+            LogTabEvent::ToggleUnifiedDiff => "u",
+            LogTabEvent::ToggleTimestamps => "shift+t",
+            LogTabEvent::ToggleConflicts => "#",
+            LogTabEvent::ToggleHidden => "shift+z",
+            LogTabEvent::ToggleSnapshot => "_",
+            LogTabEvent::ToggleVisualMode => "shift+v",
+            LogTabEvent::ExpandElided => "m",
+            LogTabEvent::ResolveDivergence => "shift+o",
+            LogTabEvent::IncreaseContext => "plus",
+            LogTabEvent::DecreaseContext => "minus",
             LogTabEvent::Refresh => "shift+r",
             LogTabEvent::Refresh => "f5",
             LogTabEvent::Duplicate => "shift+d",
+            LogTabEvent::DuplicateOnto { insert_after: false } => "ctrl+o",
+            LogTabEvent::DuplicateOnto { insert_after: true } => "ctrl+shift+o",
             LogTabEvent::CreateNew { describe: false } => "n",
             LogTabEvent::CreateNew { describe: true } => "shift+n",
+            event_insert_new(true, false) => "(",
+            event_insert_new(false, false) => ")",
+            event_insert_new(true, true) => "{",
+            event_insert_new(false, true) => "}",
             LogTabEvent::Rebase => "ctrl+r",
+            LogTabEvent::RebaseChange => "ctrl+shift+r",
+            LogTabEvent::MoveChangeUp => "shift+up",
+            LogTabEvent::MoveChangeDown => "shift+down",
             LogTabEvent::Squash { ignore_immutable: false } => "s",
             LogTabEvent::Squash { ignore_immutable: true } => "shift+s",
+            LogTabEvent::Split => "|",
+            LogTabEvent::SplitHunks => "ctrl+x",
+            LogTabEvent::SquashHunks => "ctrl+q",
+            LogTabEvent::SquashIntoParent => "shift+q",
+            LogTabEvent::SquashInto => "shift+i",
             LogTabEvent::EditChange { ignore_immutable: false } => "e",
             LogTabEvent::EditChange { ignore_immutable: true } => "shift+e",
             LogTabEvent::Abandon => "a",
             LogTabEvent::Absorb => "shift+a",
+            LogTabEvent::Fix => "=",
+            LogTabEvent::CustomCommand => "!",
+            LogTabEvent::Sign { unsign: false } => "ctrl+k",
+            LogTabEvent::Sign { unsign: true } => "ctrl+shift+k",
+            LogTabEvent::Parallelize => "ctrl+l",
+            LogTabEvent::SimplifyParents => "ctrl+shift+l",
             LogTabEvent::Describe => "d",
+            LogTabEvent::Commit => "ctrl+n",
+            LogTabEvent::Reword => "ctrl+v",
+            LogTabEvent::EditAuthor => "ctrl+a",
             LogTabEvent::EditRevset => "r",
+            LogTabEvent::FilterPaths => "x",
+            LogTabEvent::ClearPathFilter => "shift+x",
+            LogTabEvent::FilterLog => "i",
+            LogTabEvent::GoToChange => "t",
+            LogTabEvent::GoToTrunk => "ctrl+t",
+            LogTabEvent::GoToBookmark => "shift+b",
+            LogTabEvent::TogglePin => "shift+m",
+            LogTabEvent::GoToPin => "'",
+            LogTabEvent::SearchLog => "/",
+            LogTabEvent::SearchDetails => "\\",
             LogTabEvent::SetBookmark => "b",
+            LogTabEvent::MoveBookmark => ">",
+            LogTabEvent::CreateTag => "&",
             LogTabEvent::OpenFiles => "enter",
             LogTabEvent::CopyChangeId => "y",
             LogTabEvent::CopyRev => "shift+y",
+            LogTabEvent::CopyFilePath => "shift+c",
+            LogTabEvent::OpenFile => "o",
+            LogTabEvent::ShowFile => "\"",
+            LogTabEvent::OpenInBrowser => "%",
+            LogTabEvent::ShowChecks => "*",
+            LogTabEvent::ViewAtOperation => "$",
+            LogTabEvent::DiffOperations => ";",
+            LogTabEvent::RestoreFrom => "<",
+            LogTabEvent::ShowEvolog => ",",
+            LogTabEvent::DiscardFile => "ctrl+z",
             event_push(false, false) => "p",
             event_push(false, true) => "ctrl+p",
             event_push(true, false) => "shift+p",
             event_push(true, true) => "ctrl+shift+p",
+            LogTabEvent::PushChange => "^",
+            LogTabEvent::GitImport => "~",
+            LogTabEvent::GitExport => "`",
+            LogTabEvent::Maintenance => "ctrl+shift+g",
             LogTabEvent::Fetch { all_remotes: false } => "f",
             LogTabEvent::Fetch { all_remotes: true } => "shift+f",
             LogTabEvent::OpenHelp => "?",
@@ -127,6 +253,11 @@ impl LogTabKeybinds {
             LogTabEvent::Unbound
         }
     }
+    /// First shortcut of an action, e.g. to tell it in a hint
+    pub fn shortcut(&self, action: LogTabEvent) -> Option<String> {
+        let shortcuts = self.keys.get_shortcuts(action);
+        shortcuts.iter().map(ToString::to_string).min()
+    }
     pub fn extend_from_config(&mut self, config: &KeybindsConfig) {
         update_keybinds!(
             self.keys,
@@ -151,28 +282,90 @@ impl LogTabKeybinds {
             LogTabEvent::ScrollDownHalf => config.scroll_down_half,
             LogTabEvent::ScrollUpHalf => config.scroll_up_half,
             LogTabEvent::FocusCurrent => config.focus_current,
+            LogTabEvent::ToggleFollow => config.toggle_follow,
+            LogTabEvent::ToggleRelated => config.toggle_related,
             LogTabEvent::ToggleDiffFormat => config.toggle_diff_format,
+            LogTabEvent::ToggleUnifiedDiff => config.toggle_unified_diff,
+            LogTabEvent::ToggleTimestamps => config.toggle_timestamps,
+            LogTabEvent::ToggleConflicts => config.toggle_conflicts,
+            LogTabEvent::ToggleHidden => config.toggle_hidden,
+            LogTabEvent::ToggleSnapshot => config.toggle_snapshot,
+            LogTabEvent::ToggleVisualMode => config.toggle_visual_mode,
+            LogTabEvent::ExpandElided => config.expand_elided,
+            LogTabEvent::ResolveDivergence => config.resolve_divergence,
+            LogTabEvent::IncreaseContext => config.increase_context,
+            LogTabEvent::DecreaseContext => config.decrease_context,
+            LogTabEvent::ToggleCompareBase => config.toggle_compare_base,
             LogTabEvent::Refresh => config.refresh,
             LogTabEvent::Duplicate => config.duplicate,
+            LogTabEvent::DuplicateOnto { insert_after: false } => config.duplicate_onto,
+            LogTabEvent::DuplicateOnto { insert_after: true } => config.duplicate_insert_after,
             LogTabEvent::CreateNew { describe: false } => config.create_new,
             LogTabEvent::CreateNew { describe: true } => config.create_new_describe,
+            event_insert_new(true, false) => config.insert_new_before,
+            event_insert_new(false, false) => config.insert_new_after,
+            event_insert_new(true, true) => config.insert_new_before_describe,
+            event_insert_new(false, true) => config.insert_new_after_describe,
+            LogTabEvent::RebaseChange => config.rebase_change,
+            LogTabEvent::MoveChangeUp => config.move_change_up,
+            LogTabEvent::MoveChangeDown => config.move_change_down,
             LogTabEvent::Squash { ignore_immutable: false } => config.squash,
             LogTabEvent::Squash { ignore_immutable: true } => config.squash_ignore_immutable,
+            LogTabEvent::Split => config.split,
+            LogTabEvent::SplitHunks => config.split_hunks,
+            LogTabEvent::SquashHunks => config.squash_hunks,
+            LogTabEvent::SquashIntoParent => config.squash_into_parent,
+            LogTabEvent::SquashInto => config.squash_into,
             LogTabEvent::EditChange { ignore_immutable: false } => config.edit_change,
             LogTabEvent::EditChange { ignore_immutable: true } => config.edit_change_ignore_immutable,
             LogTabEvent::Abandon => config.abandon,
             LogTabEvent::Absorb => config.absorb,
+            LogTabEvent::Fix => config.fix,
+            LogTabEvent::CustomCommand => config.custom_command,
+            LogTabEvent::Sign { unsign: false } => config.sign,
+            LogTabEvent::Sign { unsign: true } => config.unsign,
+            LogTabEvent::Parallelize => config.parallelize,
+            LogTabEvent::SimplifyParents => config.simplify_parents,
             LogTabEvent::Describe => config.describe,
+            LogTabEvent::Commit => config.commit,
+            LogTabEvent::Reword => config.reword,
+            LogTabEvent::EditAuthor => config.edit_author,
             LogTabEvent::EditRevset => config.edit_revset,
+            LogTabEvent::FilterPaths => config.filter_paths,
+            LogTabEvent::ClearPathFilter => config.clear_path_filter,
+            LogTabEvent::FilterLog => config.filter_log,
+            LogTabEvent::GoToChange => config.go_to_change,
+            LogTabEvent::GoToTrunk => config.go_to_trunk,
+            LogTabEvent::GoToBookmark => config.go_to_bookmark,
+            LogTabEvent::TogglePin => config.toggle_pin,
+            LogTabEvent::GoToPin => config.go_to_pin,
+            LogTabEvent::SearchLog => config.search_log,
+            LogTabEvent::SearchDetails => config.search_details,
             LogTabEvent::SetBookmark => config.set_bookmark,
+            LogTabEvent::MoveBookmark => config.move_bookmark,
+            LogTabEvent::CreateTag => config.create_tag,
             LogTabEvent::OpenFiles => config.open_files,
             LogTabEvent::CopyChangeId => config.copy_change_id,
             LogTabEvent::CopyRev => config.copy_rev,
+            LogTabEvent::CopyFilePath => config.copy_file_path,
+            LogTabEvent::OpenFile => config.open_file,
+            LogTabEvent::ShowFile => config.show_file,
+            LogTabEvent::OpenInBrowser => config.open_in_browser,
+            LogTabEvent::ShowChecks => config.show_checks,
+            LogTabEvent::ViewAtOperation => config.view_at_operation,
+            LogTabEvent::DiffOperations => config.diff_operations,
+            LogTabEvent::RestoreFrom => config.restore_from,
+            LogTabEvent::ShowEvolog => config.show_evolog,
+            LogTabEvent::DiscardFile => config.discard_file,
             LogTabEvent::Rebase => config.rebase,
             event_push(false, false) => config.push,
             event_push(false, true) => config.push_new,
             event_push(true, false) => config.push_all,
             event_push(true, true) => config.push_all_new,
+            LogTabEvent::PushChange => config.push_change,
+            LogTabEvent::GitImport => config.git_import,
+            LogTabEvent::GitExport => config.git_export,
+            LogTabEvent::Maintenance => config.maintenance,
             LogTabEvent::Fetch { all_remotes: false } => config.fetch,
             LogTabEvent::Fetch { all_remotes: true } => config.fetch_all,
             LogTabEvent::OpenHelp => config.open_help,
@@ -187,27 +380,88 @@ impl LogTabKeybinds {
             LogTabEvent::ScrollUpHalf => "scroll up by ½ page",
             LogTabEvent::OpenFiles => "see files",
             LogTabEvent::FocusCurrent => "current change",
+            LogTabEvent::ToggleFollow => "keep the current change selected (again to stop)",
+            LogTabEvent::ToggleRelated => "dim changes not related to the selected change",
             LogTabEvent::EditRevset => "set revset",
+            LogTabEvent::FilterPaths => "only changes touching paths",
+            LogTabEvent::ClearPathFilter => "clear path filter",
+            LogTabEvent::FilterLog => "filter by author/date",
+            LogTabEvent::GoToChange => "go to change id, commit id or bookmark",
+            LogTabEvent::GoToTrunk => "go to trunk()",
+            LogTabEvent::GoToBookmark => "pick a bookmark or tag to go to",
+            LogTabEvent::TogglePin => "pin change for quick access (again to unpin)",
+            LogTabEvent::GoToPin => "pick a pinned change to go to",
+            LogTabEvent::SearchLog => "search the log, n/N: next/previous match",
+            LogTabEvent::IncreaseContext => "more context lines in diff",
+            LogTabEvent::DecreaseContext => "fewer context lines in diff",
+            LogTabEvent::ToggleCompareBase => "compare changes to this one (again to clear)",
+            LogTabEvent::ToggleUnifiedDiff => "flip git/color words diff of this change",
+            LogTabEvent::ToggleTimestamps => "toggle relative/absolute timestamps",
+            LogTabEvent::ToggleConflicts => "only changes with conflicts (again to go back)",
+            LogTabEvent::ToggleHidden => "show changes hidden by the last operation (again to go back)",
+            LogTabEvent::ToggleSnapshot => "pause or resume snapshotting the working copy",
+            LogTabEvent::ToggleVisualMode => "select a range of changes (again to end)",
+            LogTabEvent::ExpandElided => "expand elided revisions below the change",
             LogTabEvent::Describe => "describe change",
+            LogTabEvent::Commit => "describe the working copy and start a new change on top",
+            LogTabEvent::Reword => "describe the marked changes or the stack one after another",
+            LogTabEvent::EditAuthor => "change or reset the author of the change",
             LogTabEvent::Duplicate => "duplicate change",
+            LogTabEvent::DuplicateOnto { insert_after: false } => "pick changes to duplicate, then the destination of the copies",
+            LogTabEvent::DuplicateOnto { insert_after: true } => "pick changes to duplicate, then the change to insert the copies after",
             LogTabEvent::EditChange { ignore_immutable: false } => "edit change",
             LogTabEvent::EditChange { ignore_immutable: true } => "edit change ignoring immutability",
             LogTabEvent::CreateNew { describe: false } => "new change",
             LogTabEvent::CreateNew { describe: true } => "new with message",
+            event_insert_new(true, false) => "insert new change before",
+            event_insert_new(false, false) => "insert new change after",
+            event_insert_new(true, true) => "insert new change before with message",
+            event_insert_new(false, true) => "insert new change after with message",
             LogTabEvent::Abandon => "abandon change",
             LogTabEvent::Absorb => "absorb selected change into its mutable ancestors",
-            LogTabEvent::Rebase => "rebase @ to the selected change",
+            LogTabEvent::Fix => "run jj fix on selected or marked changes and descendants",
+            LogTabEvent::CustomCommand => "run a custom command on selected change",
+            LogTabEvent::Sign { unsign: false } => "sign selected or marked changes",
+            LogTabEvent::Sign { unsign: true } => "drop signatures of selected or marked changes",
+            LogTabEvent::Parallelize => "make marked changes siblings",
+            LogTabEvent::SimplifyParents => "remove redundant parents of selected change",
+            LogTabEvent::ResolveDivergence => "resolve divergent change by picking the copy to keep",
+            LogTabEvent::Rebase => "rebase @ or the marked changes to the selected change",
+            LogTabEvent::RebaseChange => "pick the selected change to rebase, then its destination",
+            LogTabEvent::MoveChangeUp => "move change up the stack, swapping it with its child",
+            LogTabEvent::MoveChangeDown => "move change down the stack, swapping it with its parent",
             LogTabEvent::Squash { ignore_immutable: false } => "squash @ into the selected change",
             LogTabEvent::Squash { ignore_immutable: true } => "squash @ into the selected change ignoring immutability",
+            LogTabEvent::Split => "split change by picking the files of the first half",
+            LogTabEvent::SplitHunks => "split change by picking hunks in the builtin diff editor",
+            LogTabEvent::SquashHunks => "squash hunks of the change into its parent or the change picked with squash into",
+            LogTabEvent::SquashIntoParent => "squash the selected change into its parent",
+            LogTabEvent::SquashInto => "squash the selected change into the change picked next",
             LogTabEvent::SetBookmark => "set bookmark",
+            LogTabEvent::MoveBookmark => "move a bookmark to selected change",
+            LogTabEvent::CreateTag => "create git tag at selected change",
             LogTabEvent::CopyChangeId => "yank change id to clipboard",
             LogTabEvent::CopyRev => "yank revision to clipboard",
-            LogTabEvent::Fetch { all_remotes: false } => "git fetch",
+            LogTabEvent::CopyFilePath => "yank path of file in details to clipboard",
+            LogTabEvent::OpenFile => "open file in details in $EDITOR at the line",
+            LogTabEvent::ShowFile => "show the diff of one file of selected change, or all files",
+            LogTabEvent::OpenInBrowser => "open change, file or bookmark on the forge in the browser",
+            LogTabEvent::ShowChecks => "list the CI checks of the pushed change",
+            LogTabEvent::ViewAtOperation => "view the repository at an old operation, read-only",
+            LogTabEvent::DiffOperations => "diff two operations picked one after the other",
+            LogTabEvent::RestoreFrom => "restore files of selected change from the change picked next",
+            LogTabEvent::ShowEvolog => "browse the versions of selected change, and restore from one",
+            LogTabEvent::DiscardFile => "discard changes to file in details in selected change",
+            LogTabEvent::Fetch { all_remotes: false } => "git fetch from the remote picked",
             LogTabEvent::Fetch { all_remotes: true } => "git fetch all remotes",
             event_push(false, false) => "git push",
             event_push(false, true) => "git push with new bookmarks",
             event_push(true, false) => "git push all bookmarks, except new",
             event_push(true, true) => "git push all bookmarks",
+            LogTabEvent::PushChange => "git push selected change, creating its bookmark",
+            LogTabEvent::GitImport => "import the changes to git branches and HEAD made with git",
+            LogTabEvent::GitExport => "export the bookmarks to git, overwriting changes made with git",
+            LogTabEvent::Maintenance => "maintenance: collect garbage or prune the operation log",
         )
     }
 }
@@ -219,6 +473,10 @@ fn event_push(all_bookmarks: bool, allow_new: bool) -> LogTabEvent {
     }
 }
 
+fn event_insert_new(before: bool, describe: bool) -> LogTabEvent {
+    LogTabEvent::InsertNew { before, describe }
+}
+
 #[test]
 fn test_log_tab_keybinds_default() {
     let _ = LogTabKeybinds::default();
@@ -116,6 +116,9 @@ impl FromStr for Shortcut {
                 "end" => key = Some(KeyCode::End),
                 "pagedown" => key = Some(KeyCode::PageDown),
                 "pageup" => key = Some(KeyCode::PageUp),
+                // `+` separates keys, so it needs a name
+                "plus" => key = Some(KeyCode::Char('+')),
+                "minus" => key = Some(KeyCode::Char('-')),
                 s if s.starts_with('f') && s.chars().count() > 1 => {
                     let s = s.trim_start_matches('f');
                     match s.parse::<u8>() {
@@ -228,6 +231,9 @@ mod tests {
             ("down", Ok(Shortcut::new_key(KeyCode::Down))),
             ("pagedown", Ok(Shortcut::new_key(KeyCode::PageDown))),
             ("pageup", Ok(Shortcut::new_key(KeyCode::PageUp))),
+            ("plus", Ok(Shortcut::new_char('+'))),
+            ("minus", Ok(Shortcut::new_char('-'))),
+            ("-", Ok(Shortcut::new_char('-'))),
             ("ctrl+ff", Err(ShortcutParseError::InvalidF)),
             ("qq", Err(ShortcutParseError::NoKey)),
             ("", Err(ShortcutParseError::NoKey)),
@@ -1,6 +1,7 @@
 extern crate thiserror;
 
 use std::env::current_dir;
+use std::env::var;
 use std::fs::OpenOptions;
 use std::fs::canonicalize;
 use std::io::ErrorKind;
@@ -42,8 +43,11 @@ mod ui;
 use crate::app::App;
 use crate::commander::Commander;
 use crate::env::Env;
+use crate::env::get_env;
 use crate::env::set_env;
 use crate::ui::ComponentAction;
+use crate::ui::EditorRequest;
+use crate::ui::dialog::MessagePopup;
 use crate::ui::ui;
 
 /// Command line arguments
@@ -174,7 +178,76 @@ fn run_app(terminal: &mut DefaultTerminal, app: &mut App) -> Result<()> {
         if should_stop {
             return Ok(());
         }
+
+        if let Some(request) = app.editor_request.take()
+            && let Err(err) = open_editor(terminal, &request)
+        {
+            app.popup = Some(Box::new(MessagePopup::new(
+                "Open in editor",
+                format!("{err:#}"),
+            )));
+        }
+
+        if let Some(args) = app.interactive_request.take() {
+            let result = run_interactive(terminal, &args);
+            // The command may have changed the repository
+            app.set_tab(app.current_tab)?;
+            if let Err(err) = result {
+                app.popup = Some(Box::new(MessagePopup::new(
+                    format!("jj {}", args.join(" ")),
+                    format!("{err:#}"),
+                )));
+            }
+        }
+    }
+}
+
+/// Suspend the terminal UI while an interactive jj command runs in the
+/// foreground
+fn run_interactive(terminal: &mut DefaultTerminal, args: &[String]) -> Result<()> {
+    restore_terminal()?;
+    let result = Commander::new(get_env()).run_interactive_jj_command(args);
+    *terminal = setup_terminal()?;
+    result
+}
+
+/// Suspend the terminal UI while the user's editor runs in the foreground
+fn open_editor(terminal: &mut DefaultTerminal, request: &EditorRequest) -> Result<()> {
+    restore_terminal()?;
+    let result = run_editor(request);
+    // A new terminal redraws everything the editor has drawn over
+    *terminal = setup_terminal()?;
+    result
+}
+
+/// Run `$VISUAL` or `$EDITOR` on the requested file from the repository root
+fn run_editor(request: &EditorRequest) -> Result<()> {
+    let editor = ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(|name| var(name).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .context("Set $EDITOR to open files in an editor")?;
+    let words = shell_words::split(&editor)
+        .with_context(|| format!("Could not parse editor command {editor}"))?;
+    let Some((program, args)) = words.split_first() else {
+        bail!("Set $EDITOR to open files in an editor");
+    };
+
+    let mut command = Command::new(program);
+    command.args(args);
+    if let Some(line) = request.line {
+        command.arg(format!("+{line}"));
+    }
+    let status = command
+        .arg(&request.path)
+        .current_dir(&get_env().root)
+        .status()
+        .with_context(|| format!("Could not run editor {program}"))?;
+    if !status.success() {
+        bail!("Editor {program} failed: {status}");
     }
+
+    Ok(())
 }
 
 /// Let app process all input events in queue before returning
@@ -185,12 +258,18 @@ fn input_to_app(app: &mut App) -> Result<bool> {
     // causing EINVAL (os error 22). Use a safe large value instead.
     const FOREVER: Duration = Duration::from_secs(24 * 3600);
 
-    // Allow popups like the fetch animation to update every 100ms.
-    let wait_duration = if app.popup.is_some() {
+    // Allow popups like the fetch animation to update every 100ms, and
+    // a revset being typed to be checked.
+    // Don't wait at all while the log is loading more changes.
+    let wait_duration = if app.popup.is_some() || app.is_ticking() {
         Duration::from_millis(100)
+    } else if app.is_loading() {
+        Duration::ZERO
     } else {
         FOREVER
     };
+    // Wake up for the next background fetch
+    let wait_duration = wait_duration.min(app.time_until_background_work().unwrap_or(FOREVER));
     // If no event arrives, return and draw next frame.
     let event_arrived = event::poll(wait_duration)?;
     app.stats.start_time = Instant::now();
@@ -201,7 +280,8 @@ fn input_to_app(app: &mut App) -> Result<bool> {
     // Handle all pending events in the queue.
     // Stop if an event requested the app to stop.
     let mut should_stop: bool = false;
-    while event::poll(Duration::ZERO)? && !should_stop {
+    // Stop as well when a file should be opened, the editor gets the rest.
+    while event::poll(Duration::ZERO)? && !should_stop && app.editor_request.is_none() {
         let event = event::read()?;
         should_stop = app.input(event)?;
     }
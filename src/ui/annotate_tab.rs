@@ -0,0 +1,254 @@
+/*! The annotate tab shows a file of a change with the change that last
+modified each line (`jj file annotate`), colored by how old it is. Enter
+jumps to the change of the selected line in the log tab.
+
+A file is opened from the files tab. Only the lines in view are
+rendered, so big files scroll as fast as small ones.
+*/
+
+use anyhow::Result;
+use chrono::Utc;
+use ratatui::crossterm::event::Event;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::crossterm::event::KeyEventKind;
+use ratatui::crossterm::event::MouseEventKind;
+use ratatui::prelude::*;
+use ratatui::widgets::*;
+use tracing::instrument;
+
+use crate::ComponentInputResult;
+use crate::commander::CommandError;
+use crate::commander::files::AnnotatedLine;
+use crate::commander::log::Head;
+use crate::commander::new_commander;
+use crate::env::JjConfig;
+use crate::env::get_env;
+use crate::ui::Component;
+use crate::ui::ComponentAction;
+use crate::ui::Selection;
+use crate::ui::dialog::HelpPopup;
+use crate::ui::dialog::MessagePopup;
+use crate::ui::utils::age_color;
+use crate::ui::utils::format_ago;
+
+/// Columns of the author name at most
+const MAX_AUTHOR_WIDTH: usize = 16;
+
+/// Annotate tab. Shows the lines of a file with the change that last
+/// modified each
+pub struct AnnotateTab {
+    /// Change and path of the annotated file, None until a file is
+    /// opened from the files tab
+    file: Option<(Head, String)>,
+    lines: Result<Vec<AnnotatedLine>, CommandError>,
+    selected: usize,
+    /// First line in view
+    scroll: usize,
+    height: usize,
+    config: JjConfig,
+}
+
+impl AnnotateTab {
+    pub fn new() -> Self {
+        Self {
+            file: None,
+            lines: Ok(vec![]),
+            selected: 0,
+            scroll: 0,
+            height: 0,
+            config: get_env().jj_config.clone(),
+        }
+    }
+
+    /// Annotate a file of a change, from its first line. The file is
+    /// loaded when the tab is focused.
+    #[instrument(level = "trace", skip(self))]
+    pub fn set_file(&mut self, head: Head, path: String) {
+        self.file = Some((head, path));
+        self.selected = 0;
+        self.scroll = 0;
+    }
+
+    fn refresh(&mut self) {
+        let Some((head, path)) = self.file.as_ref() else {
+            return;
+        };
+        self.lines = new_commander().get_annotation(&head.commit_id, path);
+        self.select(self.selected);
+    }
+
+    fn line_count(&self) -> usize {
+        self.lines.as_ref().map_or(0, Vec::len)
+    }
+
+    fn selected_line(&self) -> Option<&AnnotatedLine> {
+        self.lines.as_ref().ok()?.get(self.selected)
+    }
+
+    /// Select a line, scrolling it into view
+    fn select(&mut self, index: usize) {
+        self.selected = index.min(self.line_count().saturating_sub(1));
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if self.height > 0 && self.selected >= self.scroll + self.height {
+            self.scroll = self.selected + 1 - self.height;
+        }
+    }
+
+    fn scroll_lines(&mut self, scroll: isize) {
+        self.select(self.selected.saturating_add_signed(scroll));
+    }
+
+    /// Rows of the lines in view, with the change of the first line of
+    /// each run of lines from the same change
+    fn visible_rows(&self, lines: &[AnnotatedLine]) -> Vec<Line<'_>> {
+        let now = Utc::now().timestamp();
+        let author_width = lines
+            .iter()
+            .map(|line| line.author.chars().count())
+            .max()
+            .unwrap_or_default()
+            .min(MAX_AUTHOR_WIDTH);
+        let number_width = lines.len().to_string().len();
+        let end = (self.scroll + self.height.max(1)).min(lines.len());
+        (self.scroll..end)
+            .map(|index| {
+                let line = &lines[index];
+                let age = now - line.timestamp;
+                let first_of_run =
+                    index == self.scroll || lines[index - 1].commit_id != line.commit_id;
+                let info = if first_of_run {
+                    let author: String = line.author.chars().take(author_width).collect();
+                    format!(
+                        "{} {author:author_width$} {:>14}",
+                        line.change_id,
+                        format_ago(age)
+                    )
+                } else {
+                    " ".repeat(line.change_id.chars().count() + author_width + 16)
+                };
+                let row = Line::from(vec![
+                    Span::from(format!(" {info} ")).fg(age_color(age)),
+                    Span::from(format!("{:>number_width$} │ ", index + 1)).fg(Color::DarkGray),
+                    Span::from(line.content.replace('\t', "    ")),
+                ]);
+                if index == self.selected {
+                    row.bg(self.config.highlight_color())
+                } else {
+                    row
+                }
+            })
+            .collect()
+    }
+}
+
+impl Component for AnnotateTab {
+    fn selection(&mut self) -> Selection {
+        let head = match (self.selected_line(), self.file.as_ref()) {
+            (Some(line), _) => new_commander()
+                .get_revision_head(line.commit_id.as_str())
+                .ok(),
+            (None, Some((head, _))) => Some(head.clone()),
+            (None, None) => None,
+        };
+        Selection {
+            head,
+            file: self.file.as_ref().map(|(_, path)| path.clone()),
+        }
+    }
+
+    fn focus(&mut self) -> Result<()> {
+        if let Some((head, _)) = self.file.as_mut() {
+            *head = new_commander().get_head_latest(head)?;
+        }
+        self.refresh();
+        Ok(())
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let title = match self.file.as_ref() {
+            Some((head, path)) => format!(" Annotate {path} at {} ", head.change_id),
+            None => " Annotate ".to_owned(),
+        };
+        let block = Block::bordered()
+            .title(title)
+            .border_type(BorderType::Rounded);
+        self.height = block.inner(area).height as usize;
+        self.select(self.selected);
+
+        let lines = match (self.file.as_ref(), self.lines.as_ref()) {
+            (None, _) => vec![
+                Line::from(" Select a file in the files tab and press a to annotate it")
+                    .fg(Color::DarkGray)
+                    .italic(),
+            ],
+            (Some(_), Ok(lines)) if lines.is_empty() => vec![
+                Line::from(" The file is empty")
+                    .fg(Color::DarkGray)
+                    .italic(),
+            ],
+            (Some(_), Ok(lines)) => self.visible_rows(lines),
+            (Some(_), Err(err)) => err.into_text("Error annotating file")?.lines,
+        };
+        f.render_widget(Paragraph::new(lines).block(block), area);
+        Ok(())
+    }
+
+    fn input(&mut self, event: Event) -> Result<ComponentInputResult> {
+        if let Event::Mouse(mouse) = event {
+            match mouse.kind {
+                MouseEventKind::ScrollDown => self.scroll_lines(3),
+                MouseEventKind::ScrollUp => self.scroll_lines(-3),
+                _ => return Ok(ComponentInputResult::NotHandled),
+            }
+            return Ok(ComponentInputResult::Handled);
+        }
+        let Event::Key(key) = event else {
+            return Ok(ComponentInputResult::NotHandled);
+        };
+        if key.kind != KeyEventKind::Press {
+            return Ok(ComponentInputResult::Handled);
+        }
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => self.scroll_lines(1),
+            KeyCode::Char('k') | KeyCode::Up => self.scroll_lines(-1),
+            KeyCode::Char('J') => self.scroll_lines(self.height as isize / 2),
+            KeyCode::Char('K') => self.scroll_lines((self.height as isize / 2).saturating_neg()),
+            KeyCode::Char('g') => self.select(0),
+            KeyCode::Char('G') => self.select(self.line_count()),
+            KeyCode::Enter => {
+                let Some(line) = self.selected_line() else {
+                    return Ok(ComponentInputResult::Handled);
+                };
+                let action = match new_commander().get_revision_head(line.commit_id.as_str()) {
+                    Ok(head) => ComponentAction::GoToChange(head),
+                    Err(err) => ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                        "Go to change",
+                        format!("{err:#}"),
+                    )))),
+                };
+                return Ok(ComponentInputResult::HandledAction(action));
+            }
+            KeyCode::Char('R') | KeyCode::F(5) => self.focus()?,
+            KeyCode::Char('?') => {
+                return Ok(ComponentInputResult::HandledAction(
+                    ComponentAction::SetPopup(Some(Box::new(HelpPopup::new(
+                        vec![
+                            ("j/k".to_owned(), "scroll down/up".to_owned()),
+                            ("J/K".to_owned(), "scroll down by ½ page".to_owned()),
+                            ("g/G".to_owned(), "go to top/bottom".to_owned()),
+                            (
+                                "Enter".to_owned(),
+                                "go to the change of the line in the log".to_owned(),
+                            ),
+                            ("R".to_owned(), "refresh".to_owned()),
+                        ],
+                        vec![],
+                    )))),
+                ));
+            }
+            _ => return Ok(ComponentInputResult::NotHandled),
+        }
+        Ok(ComponentInputResult::Handled)
+    }
+}
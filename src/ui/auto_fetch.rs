@@ -0,0 +1,79 @@
+/*! Auto-fetch runs `jj git fetch` every `blazingjj.auto-fetch-minutes`
+in the background.
+
+It doesn't refresh the tabs or block the input. When the fetch updated
+remote bookmarks, the app shows them in a toast instead.
+*/
+
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::TryRecvError;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+use tracing::warn;
+
+use crate::commander::CommandError;
+use crate::commander::jj::FetchFrom;
+use crate::commander::new_commander;
+
+type FetchResult = Result<Vec<String>, CommandError>;
+
+/// Timer of the background fetches
+pub struct AutoFetch {
+    interval: Duration,
+    next_fetch: Instant,
+    /// Result of the fetch running in the background
+    running: Option<Receiver<FetchResult>>,
+}
+
+impl AutoFetch {
+    /// Fetch every `interval`, starting after the first one
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next_fetch: Instant::now() + interval,
+            running: None,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /// Time until the next fetch is started
+    pub fn time_until_fetch(&self) -> Duration {
+        self.next_fetch.saturating_duration_since(Instant::now())
+    }
+
+    /// Start a fetch when it is due, and return the bookmarks and tags
+    /// updated by a fetch that finished, e.g.
+    /// `bookmark: main@origin [updated] tracked`
+    pub fn update(&mut self) -> Vec<String> {
+        if let Some(running) = &self.running {
+            let result = match running.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => return vec![],
+                Err(TryRecvError::Disconnected) => Ok(vec![]),
+            };
+            self.running = None;
+            return result.unwrap_or_else(|err| {
+                warn!("Auto-fetch failed: {err}");
+                vec![]
+            });
+        }
+
+        if Instant::now() >= self.next_fetch {
+            let (tx, rx) = mpsc::channel();
+            thread::spawn(move || {
+                let mut commander = new_commander();
+                commander.background = true;
+                tx.send(commander.git_fetch_updates(&FetchFrom::Default))
+            });
+            self.running = Some(rx);
+            self.next_fetch = Instant::now() + self.interval;
+        }
+        vec![]
+    }
+}
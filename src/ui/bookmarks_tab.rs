@@ -18,22 +18,31 @@ use tui_confirm_dialog::Listener;
 
 use crate::ComponentInputResult;
 use crate::commander::CommandError;
+use crate::commander::bookmarks::Bookmark;
 use crate::commander::bookmarks::BookmarkLine;
 use crate::commander::ids::ChangeId;
+use crate::commander::log::Head;
 use crate::commander::new_commander;
 use crate::env::DiffFormat;
 use crate::env::JjConfig;
 use crate::env::get_env;
 use crate::ui::Component;
 use crate::ui::ComponentAction;
+use crate::ui::EditorRequest;
 use crate::ui::dialog::HelpPopup;
+use crate::ui::dialog::LoaderPopup;
 use crate::ui::dialog::MessagePopup;
+use crate::ui::dialog::PullRequestAction;
+use crate::ui::dialog::PullRequestPopup;
 use crate::ui::panel::DetailsPanel;
 use crate::ui::panel::TextContent;
+use crate::ui::utils::Forge;
 use crate::ui::utils::PaneDivider;
 use crate::ui::utils::centered_rect;
 use crate::ui::utils::centered_rect_line_height;
-use crate::ui::utils::tabs_to_spaces;
+use crate::ui::utils::copy_to_clipboard;
+use crate::ui::utils::find_forge;
+use crate::ui::utils::format_diff_output;
 
 struct CreateBookmark<'a> {
     textarea: TextArea<'a>,
@@ -54,10 +63,16 @@ struct ForgetBookmark {
     name: String,
 }
 
+struct MoveBookmark {
+    name: String,
+    head: Head,
+}
+
 const DELETE_BRANCH_POPUP_ID: u16 = 1;
 const FORGET_BRANCH_POPUP_ID: u16 = 2;
 const NEW_POPUP_ID: u16 = 3;
 const EDIT_POPUP_ID: u16 = 4;
+const MOVE_POPUP_ID: u16 = 5;
 
 /// Bookmarks tab. Shows bookmarks in main panel and selected bookmark current change in details panel.
 pub struct BookmarksTab<'a> {
@@ -68,14 +83,23 @@ pub struct BookmarksTab<'a> {
     show_all: bool,
 
     bookmark: Option<BookmarkLine>,
+    /// Old version of the stack to compare the current bookmark to
+    range_diff_base: Option<Bookmark>,
 
     bookmark_panel: DetailsPanel,
     bookmark_output: Option<Result<String, CommandError>>,
+    /// Panel width used to render bookmark_output
+    bookmark_width: usize,
 
     create: Option<CreateBookmark<'a>>,
     rename: Option<RenameBookmark<'a>>,
     delete: Option<DeleteBookmark>,
     forget: Option<ForgetBookmark>,
+    move_bookmark: Option<MoveBookmark>,
+    /// Pull request being created, with the forge of its remote
+    pull_request: Option<(PullRequestPopup<'a>, Forge)>,
+    /// Change selected in the log tab, to move bookmarks to
+    selected_change: Option<Head>,
 
     describe_textarea: Option<TextArea<'a>>,
     describe_after_new: bool,
@@ -146,7 +170,7 @@ impl BookmarksTab<'_> {
             BookmarkLine::Parsed { bookmark, .. } => Some(
                 new_commander()
                     .get_bookmark_show(bookmark, &diff_format, true)
-                    .map(|diff| tabs_to_spaces(&diff)),
+                    .map(|diff| format_diff_output(&diff, &diff_format, 0)),
             ),
             _ => None,
         });
@@ -161,18 +185,23 @@ impl BookmarksTab<'_> {
             bookmark,
             bookmarks_list_state,
             bookmarks_height: 0,
+            range_diff_base: None,
 
             show_all,
 
             bookmark_panel: DetailsPanel::new(),
             bookmark_output,
+            bookmark_width: 0,
 
             create: None,
             rename: None,
             delete: None,
             forget: None,
+            move_bookmark: None,
+            selected_change: None,
 
             describe_after_new: false,
+            pull_request: None,
             describe_textarea: None,
             describe_after_new_change: None,
 
@@ -189,6 +218,86 @@ impl BookmarksTab<'_> {
         })
     }
 
+    /// Set the change that bookmarks are moved to, selected in the log
+    /// tab
+    pub fn set_selected_change(&mut self, head: Option<Head>) {
+        self.selected_change = head;
+    }
+
+    /// Ask to confirm moving the current local bookmark to the change
+    /// selected in the log tab, or to @
+    fn confirm_move(&mut self) -> Result<ComponentInputResult> {
+        let Some(BookmarkLine::Parsed { bookmark, .. }) = self.bookmark.as_ref() else {
+            return Ok(ComponentInputResult::Handled);
+        };
+        if bookmark.remote.is_some() {
+            return Ok(ComponentInputResult::HandledAction(
+                ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                    "Move",
+                    "Remote bookmarks can't be moved, select the local bookmark",
+                )))),
+            ));
+        }
+        let head = match self.selected_change.as_ref() {
+            Some(head) => new_commander().get_head_latest(head)?,
+            None => new_commander().get_current_head()?,
+        };
+        self.popup = ConfirmDialogState::new(
+            MOVE_POPUP_ID,
+            Span::styled(" Move ", Style::new().bold().cyan()),
+            Text::from(vec![
+                Line::from(format!(
+                    "Are you sure you want to move the {} bookmark?",
+                    bookmark.name
+                )),
+                Line::from(format!("Move to {}", head.change_id.as_str())),
+            ]),
+        );
+        self.popup
+            .with_yes_button(ButtonLabel::YES.clone())
+            .with_no_button(ButtonLabel::NO.clone())
+            .with_listener(Some(self.popup_tx.clone()))
+            .open();
+        self.move_bookmark = Some(MoveBookmark {
+            name: bookmark.name.clone(),
+            head,
+        });
+        Ok(ComponentInputResult::Handled)
+    }
+
+    /// Open the popup creating a pull request from the current bookmark,
+    /// prefilled with the description of its change. A local bookmark
+    /// is pushed to `origin`, or to the first remote.
+    fn open_pull_request(&mut self) -> Result<ComponentInputResult> {
+        let Some(BookmarkLine::Parsed { bookmark, .. }) = self.bookmark.as_ref() else {
+            return Ok(ComponentInputResult::Handled);
+        };
+        if !bookmark.present {
+            return Ok(ComponentInputResult::Handled);
+        }
+        let forge = match find_forge(bookmark.remote.as_deref()) {
+            Ok((_, forge)) => forge,
+            Err(err) => {
+                return Ok(ComponentInputResult::HandledAction(
+                    ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                        "Pull request",
+                        err.to_string(),
+                    )))),
+                ));
+            }
+        };
+        let commander = new_commander();
+        let head = commander.get_bookmark_head(bookmark)?;
+        let description = commander.get_commit_description(&head.commit_id)?;
+        let popup = PullRequestPopup::new(
+            bookmark.name.clone(),
+            forge.pull_request_name(),
+            &description,
+        );
+        self.pull_request = Some((popup, forge));
+        Ok(ComponentInputResult::Handled)
+    }
+
     pub fn get_current_bookmark_index(&self) -> Option<usize> {
         get_current_bookmark_index(self.bookmark.as_ref(), &self.bookmarks_output)
     }
@@ -198,19 +307,91 @@ impl BookmarksTab<'_> {
     }
 
     pub fn refresh_bookmark(&mut self) {
+        self.load_bookmark();
+        self.bookmark_panel.scroll_to(0);
+    }
+
+    /// Get the content of the current bookmark, rendered for the current panel width
+    fn load_bookmark(&mut self) {
         let mut commander = new_commander();
         let inner_width = self.bookmark_panel.columns() as usize;
         commander.limit_width(inner_width);
         self.bookmark_output = self.bookmark.as_ref().and_then(|bookmark| match bookmark {
             BookmarkLine::Parsed { bookmark, .. } => Some(
-                commander
-                    .get_bookmark_show(bookmark, &self.diff_format, true)
-                    .map(|diff| tabs_to_spaces(&diff)),
+                match self.range_diff_base() {
+                    Some(base) => commander.get_range_diff(
+                        &base.to_string(),
+                        &bookmark.to_string(),
+                        &self.diff_format,
+                    ),
+                    None => commander.get_bookmark_show(bookmark, &self.diff_format, true),
+                }
+                .map(|diff| format_diff_output(&diff, &self.diff_format, inner_width)),
             ),
             _ => None,
         });
+        self.bookmark_width = inner_width;
+    }
 
-        self.bookmark_panel.scroll_to(0);
+    /// Range diff base, unless it is the current bookmark
+    fn range_diff_base(&self) -> Option<&Bookmark> {
+        match self.bookmark.as_ref() {
+            Some(BookmarkLine::Parsed { bookmark, .. }) => self
+                .range_diff_base
+                .as_ref()
+                .filter(|base| *base != bookmark),
+            _ => None,
+        }
+    }
+
+    /// Mark the current bookmark as range diff base, or clear the base
+    /// if it is the current bookmark
+    fn toggle_range_diff_base(&mut self) {
+        if let Some(BookmarkLine::Parsed { bookmark, .. }) = self.bookmark.as_ref() {
+            self.range_diff_base = if self.range_diff_base.as_ref() == Some(bookmark) {
+                None
+            } else {
+                Some(bookmark.clone())
+            };
+        }
+    }
+
+    /// Content of the current bookmark as shown in the bookmark panel
+    fn bookmark_lines(&self) -> Result<Vec<Line<'static>>> {
+        Ok(match self.bookmark_output.as_ref() {
+            Some(Ok(bookmark_output)) => bookmark_output.into_text()?.lines,
+            Some(Err(err)) => err.into_text("Error getting bookmark")?.lines,
+            None => vec![],
+        })
+    }
+
+    /// File and line at the copy cursor or the top of the bookmark panel
+    fn bookmark_file(&self) -> Result<Option<EditorRequest>> {
+        let content = TextContent::from(self.bookmark_lines()?);
+        let line = self.bookmark_panel.current_file_line(&content);
+        Ok(self
+            .bookmark_panel
+            .current_file(&content)
+            .map(|path| EditorRequest { path, line }))
+    }
+
+    /// Draw the content of the current bookmark
+    fn draw_bookmark(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let title = if let Some(BookmarkLine::Parsed { bookmark, .. }) = self.bookmark.as_ref() {
+            match self.range_diff_base() {
+                Some(base) => format!(" Range diff {base} → {bookmark} "),
+                None => format!(" Bookmark {bookmark} "),
+            }
+        } else {
+            " Bookmark ".to_owned()
+        };
+        let bookmark_content = self.bookmark_lines()?;
+        self.bookmark_panel
+            .render_context::<TextContent>(bookmark_content)
+            .title(title)
+            .git_diff(self.diff_format == DiffFormat::Git)
+            .draw(f, area);
+        Ok(())
     }
 
     fn scroll_bookmarks(&mut self, scroll: isize) {
@@ -302,6 +483,19 @@ impl Component for BookmarksTab<'_> {
                         }
                     }
                 }
+                MOVE_POPUP_ID => {
+                    if let Some(move_bookmark) = self.move_bookmark.take() {
+                        if let Err(err) = new_commander()
+                            .set_bookmark_commit(&move_bookmark.name, &move_bookmark.head.commit_id)
+                        {
+                            return Ok(Some(ComponentAction::SetPopup(Some(Box::new(
+                                MessagePopup::new("Move error", err.to_string()),
+                            )))));
+                        }
+                        self.refresh_bookmarks();
+                        self.refresh_bookmark();
+                    }
+                }
                 EDIT_POPUP_ID => {
                     if let Some(BookmarkLine::Parsed { bookmark, .. }) = self.bookmark.as_ref() {
                         new_commander()
@@ -334,13 +528,47 @@ impl Component for BookmarksTab<'_> {
                     .enumerate()
                     .map(|(i, bookmark)| -> Result<Vec<Line>, ansi_to_tui::Error> {
                         let bookmark_text = bookmark.to_text()?;
+                        let status = match bookmark {
+                            BookmarkLine::Parsed { status, .. } => Some(status),
+                            BookmarkLine::Unparsable(_) => None,
+                        };
                         Ok(bookmark_text
                             .iter()
-                            .map(|line| {
+                            .enumerate()
+                            .map(|(line_index, line)| {
                                 let mut line = line.to_owned();
 
-                                // Add padding at start
-                                line.spans.insert(0, Span::from(" "));
+                                // Add padding at start, or mark the range diff base or a
+                                // conflict
+                                let is_base = matches!(
+                                    bookmark,
+                                    BookmarkLine::Parsed { bookmark, .. }
+                                        if self.range_diff_base.as_ref() == Some(bookmark)
+                                );
+                                if is_base {
+                                    line.spans.insert(0, Span::from("=").yellow());
+                                } else if status.is_some_and(|status| status.conflict) {
+                                    line.spans.insert(0, Span::from("×").red());
+                                } else {
+                                    line.spans.insert(0, Span::from(" "));
+                                }
+
+                                // Commits to fetch and to push of a tracked remote bookmark
+                                if line_index == 0
+                                    && let Some(tracking) =
+                                        status.and_then(|status| status.tracking)
+                                {
+                                    if tracking.ahead > 0 {
+                                        line.spans.push(
+                                            Span::from(format!(" ↓{}", tracking.ahead)).yellow(),
+                                        );
+                                    }
+                                    if tracking.behind > 0 {
+                                        line.spans.push(
+                                            Span::from(format!(" ↑{}", tracking.behind)).cyan(),
+                                        );
+                                    }
+                                }
 
                                 if current_bookmark_index == Some(i) {
                                     line = line.bg(self.config.highlight_color());
@@ -419,23 +647,14 @@ impl Component for BookmarksTab<'_> {
             }
         }
 
-        // Draw bookmark
+        // Draw bookmark. Width dependent formats are rendered again
+        // when the panel changes size.
+        self.draw_bookmark(f, chunks[1])?;
+        if self.diff_format.uses_width()
+            && self.bookmark_width != self.bookmark_panel.columns() as usize
         {
-            let title = if let Some(BookmarkLine::Parsed { bookmark, .. }) = self.bookmark.as_ref()
-            {
-                format!(" Bookmark {bookmark} ")
-            } else {
-                " Bookmark ".to_owned()
-            };
-            let bookmark_content: Vec<Line> = match self.bookmark_output.as_ref() {
-                Some(Ok(bookmark_output)) => bookmark_output.into_text()?.lines,
-                Some(Err(err)) => err.into_text("Error getting bookmark")?.lines,
-                None => vec![],
-            };
-            self.bookmark_panel
-                .render_context::<TextContent>(bookmark_content)
-                .title(title)
-                .draw(f, chunks[1]);
+            self.load_bookmark();
+            self.draw_bookmark(f, chunks[1])?;
         }
 
         // Draw popup
@@ -605,6 +824,10 @@ impl Component for BookmarksTab<'_> {
             }
         }
 
+        if let Some((popup, _)) = self.pull_request.as_ref() {
+            popup.render_widget(f);
+        }
+
         Ok(())
     }
 
@@ -720,6 +943,25 @@ impl Component for BookmarksTab<'_> {
             return Ok(ComponentInputResult::Handled);
         }
 
+        if let Some((popup, forge)) = self.pull_request.as_mut() {
+            match popup.handle_input(event) {
+                PullRequestAction::None => (),
+                PullRequestAction::Cancel => self.pull_request = None,
+                PullRequestAction::Create { title, body } => {
+                    let command = forge.pull_request_command(&popup.bookmark, &title, &body);
+                    let name = format!("Creating {}", forge.pull_request_name());
+                    self.pull_request = None;
+                    let loader = LoaderPopup::new(name, move || {
+                        new_commander().execute_external_command(command)
+                    });
+                    return Ok(ComponentInputResult::HandledAction(
+                        ComponentAction::SetPopup(Some(Box::new(loader))),
+                    ));
+                }
+            }
+            return Ok(ComponentInputResult::Handled);
+        }
+
         if let (Some(describe_textarea), Some(describe_after_new_change)) = (
             self.describe_textarea.as_mut(),
             self.describe_after_new_change.as_ref(),
@@ -731,6 +973,7 @@ impl Component for BookmarksTab<'_> {
                         new_commander().run_describe(
                             describe_after_new_change.as_str(),
                             &describe_textarea.lines().join("\n"),
+                            false,
                         )?;
                         self.describe_textarea = None;
                         self.describe_after_new_change = None;
@@ -781,6 +1024,22 @@ impl Component for BookmarksTab<'_> {
                     self.diff_format = self.diff_format.get_next(self.config.diff_tool());
                     self.refresh_bookmark();
                 }
+                KeyCode::Char('C') => {
+                    if let Some(file) = self.bookmark_file()? {
+                        copy_to_clipboard(&file.path);
+                    }
+                }
+                KeyCode::Char('o') => {
+                    if let Some(file) = self.bookmark_file()? {
+                        return Ok(ComponentInputResult::HandledAction(
+                            ComponentAction::OpenEditor(file),
+                        ));
+                    }
+                }
+                KeyCode::Char('m') => {
+                    self.toggle_range_diff_base();
+                    self.refresh_bookmark();
+                }
                 KeyCode::Char('R') | KeyCode::F(5) => {
                     self.refresh_bookmarks();
                     self.refresh_bookmark();
@@ -797,6 +1056,8 @@ impl Component for BookmarksTab<'_> {
                     });
                     return Ok(ComponentInputResult::Handled);
                 }
+                KeyCode::Char('b') => return self.confirm_move(),
+                KeyCode::Char('p') => return self.open_pull_request(),
                 KeyCode::Char('r') => {
                     if let Some(BookmarkLine::Parsed { bookmark, .. }) = self.bookmark.as_ref() {
                         let mut textarea = TextArea::new(vec![bookmark.name.clone()]);
@@ -942,11 +1203,17 @@ impl Component for BookmarksTab<'_> {
                                 ("c".to_owned(), "create bookmark".to_owned()),
                                 ("r".to_owned(), "rename bookmark".to_owned()),
                                 ("d/f".to_owned(), "delete/forget bookmark".to_owned()),
+                                (
+                                    "b".to_owned(),
+                                    "move bookmark to change selected in log".to_owned(),
+                                ),
                                 ("t/T".to_owned(), "track/untrack bookmark".to_owned()),
+                                ("p".to_owned(), "create pull request".to_owned()),
                                 ("Enter".to_owned(), "view in log".to_owned()),
                                 ("n".to_owned(), "new from bookmark".to_owned()),
                                 ("N".to_owned(), "new and describe".to_owned()),
                                 ("e".to_owned(), "edit bookmark".to_owned()),
+                                ("m".to_owned(), "range diff from bookmark".to_owned()),
                             ],
                             vec![
                                 ("Ctrl+e/Ctrl+y".to_owned(), "scroll down/up".to_owned()),
@@ -958,8 +1225,22 @@ impl Component for BookmarksTab<'_> {
                                     "Ctrl+f/Ctrl+b".to_owned(),
                                     "scroll down/up by page".to_owned(),
                                 ),
+                                ("g/G".to_owned(), "go to top/bottom".to_owned()),
                                 ("w".to_owned(), "toggle diff format".to_owned()),
                                 ("W".to_owned(), "toggle wrapping".to_owned()),
+                                ("L".to_owned(), "toggle line numbers".to_owned()),
+                                ("/".to_owned(), "search".to_owned()),
+                                ("n/N".to_owned(), "next/previous match".to_owned()),
+                                ("]f/[f".to_owned(), "next/previous file".to_owned()),
+                                ("]h/[h".to_owned(), "next/previous hunk".to_owned()),
+                                ("]c/[c".to_owned(), "next/previous conflict".to_owned()),
+                                ("Ctrl+g".to_owned(), "list files".to_owned()),
+                                ("v".to_owned(), "copy mode".to_owned()),
+                                ("C".to_owned(), "yank file path".to_owned()),
+                                (
+                                    "o".to_owned(),
+                                    "open file in $EDITOR at the line".to_owned(),
+                                ),
                             ],
                         )))),
                     ));
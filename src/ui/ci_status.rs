@@ -0,0 +1,236 @@
+/*! CI status polls the checks of the pushed changes in the log every
+`blazingjj.ci-status-minutes`, in the background.
+
+The changes polled are the ones in the log with a bookmark on the remote
+of the forge, `origin` or else the first remote. Only GitHub is
+supported, through `gh api`, which has to be installed and logged in.
+*/
+
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::TryRecvError;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::Result;
+use anyhow::anyhow;
+use ratatui::style::Color;
+use tracing::warn;
+
+use crate::commander::ids::CommitId;
+use crate::commander::new_commander;
+use crate::ui::utils::find_forge;
+
+/// Changes polled at most, from the top of the log
+const MAX_POLLED_COMMITS: usize = 20;
+
+type Checks = HashMap<CommitId, Vec<CheckRun>>;
+
+/// State of a check run, or of all the check runs of a change
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckState {
+    Pending,
+    Success,
+    Failure,
+}
+
+impl CheckState {
+    /// State of a check run from its status and conclusion on GitHub
+    fn parse(status: &str, conclusion: &str) -> Self {
+        match (status, conclusion) {
+            ("completed", "success" | "neutral" | "skipped") => CheckState::Success,
+            ("completed", _) => CheckState::Failure,
+            _ => CheckState::Pending,
+        }
+    }
+
+    /// State of all the check runs: failed if any failed, else pending
+    /// if any is pending. None without check runs.
+    pub fn overall(runs: &[CheckRun]) -> Option<Self> {
+        let states = || runs.iter().map(|run| run.state);
+        if runs.is_empty() {
+            None
+        } else if states().any(|state| state == CheckState::Failure) {
+            Some(CheckState::Failure)
+        } else if states().any(|state| state == CheckState::Pending) {
+            Some(CheckState::Pending)
+        } else {
+            Some(CheckState::Success)
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            CheckState::Pending => "●",
+            CheckState::Success => "✓",
+            CheckState::Failure => "✗",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            CheckState::Pending => Color::Yellow,
+            CheckState::Success => Color::Green,
+            CheckState::Failure => Color::Red,
+        }
+    }
+}
+
+/// A check run of a commit, e.g. a CI job
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CheckRun {
+    pub name: String,
+    pub state: CheckState,
+}
+
+/// Parse the check runs listed by [Forge::checks_command](crate::ui::utils::Forge::checks_command)
+fn parse_check_runs(output: &str) -> Vec<CheckRun> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let name = fields.next().filter(|name| !name.is_empty())?;
+            let status = fields.next().unwrap_or_default();
+            let conclusion = fields.next().unwrap_or_default();
+            Some(CheckRun {
+                name: name.to_owned(),
+                state: CheckState::parse(status, conclusion),
+            })
+        })
+        .collect()
+}
+
+/// Poll the check runs of the commits with a bookmark on the remote
+fn poll_checks(commits: Vec<CommitId>) -> Result<Checks> {
+    let (remote, forge) = find_forge(None)?;
+    let mut commander = new_commander();
+    commander.background = true;
+    let pushed = commander.get_remote_bookmark_commits(&remote)?;
+    let mut checks = HashMap::new();
+    for commit_id in commits
+        .into_iter()
+        .filter(|commit_id| pushed.contains(commit_id))
+        .take(MAX_POLLED_COMMITS)
+    {
+        let Some(command) = forge.checks_command(commit_id.as_str()) else {
+            break;
+        };
+        let output = commander.execute_external_command(command)?;
+        checks.insert(commit_id, parse_check_runs(&output));
+    }
+    Ok(checks)
+}
+
+/// Timer of the background polls, with the check runs of the last one
+pub struct CiStatus {
+    interval: Duration,
+    next_poll: Instant,
+    /// Result of the poll running in the background
+    running: Option<Receiver<Result<Checks>>>,
+    checks: Checks,
+}
+
+impl CiStatus {
+    /// Poll every `interval`, starting with the first update
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next_poll: Instant::now(),
+            running: None,
+            checks: HashMap::new(),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /// Time until the next poll is started
+    pub fn time_until_poll(&self) -> Duration {
+        self.next_poll.saturating_duration_since(Instant::now())
+    }
+
+    /// Check runs of a commit, if it was polled
+    pub fn checks(&self, commit_id: &CommitId) -> Option<&[CheckRun]> {
+        self.checks.get(commit_id).map(Vec::as_slice)
+    }
+
+    /// State of the check runs of each commit polled
+    pub fn states(&self) -> HashMap<CommitId, CheckState> {
+        self.checks
+            .iter()
+            .filter_map(|(commit_id, runs)| Some((commit_id.clone(), CheckState::overall(runs)?)))
+            .collect()
+    }
+
+    /// Start polling the commits when it is due, and tell if a poll
+    /// finished with new check runs
+    pub fn update(&mut self, commits: impl FnOnce() -> Vec<CommitId>) -> bool {
+        if let Some(running) = &self.running {
+            let result = match running.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => return false,
+                Err(TryRecvError::Disconnected) => Err(anyhow!("The poll stopped")),
+            };
+            self.running = None;
+            return match result {
+                Ok(checks) if checks != self.checks => {
+                    self.checks = checks;
+                    true
+                }
+                Ok(_) => false,
+                Err(err) => {
+                    warn!("Polling CI status failed: {err}");
+                    false
+                }
+            };
+        }
+
+        if Instant::now() >= self.next_poll {
+            let (tx, rx) = mpsc::channel();
+            let commits = commits();
+            thread::spawn(move || tx.send(poll_checks(commits)));
+            self.running = Some(rx);
+            self.next_poll = Instant::now() + self.interval;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_runs() {
+        let runs = parse_check_runs(
+            "build\tcompleted\tsuccess\n\
+             lint\tcompleted\tskipped\n\
+             test\tin_progress\t\n",
+        );
+        assert_eq!(
+            runs,
+            [
+                CheckRun {
+                    name: "build".to_owned(),
+                    state: CheckState::Success
+                },
+                CheckRun {
+                    name: "lint".to_owned(),
+                    state: CheckState::Success
+                },
+                CheckRun {
+                    name: "test".to_owned(),
+                    state: CheckState::Pending
+                },
+            ]
+        );
+        assert_eq!(CheckState::overall(&runs), Some(CheckState::Pending));
+        let failed = parse_check_runs("build\tcompleted\tfailure\ntest\tqueued\t\n");
+        assert_eq!(CheckState::overall(&failed), Some(CheckState::Failure));
+        assert_eq!(CheckState::overall(&runs[..2]), Some(CheckState::Success));
+        assert_eq!(CheckState::overall(&[]), None);
+    }
+}
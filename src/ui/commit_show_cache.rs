@@ -10,37 +10,78 @@ an ancester causes it to be rebased without modification lots of time.
 
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::collections::VecDeque;
 
 use crate::commander::ids::ChangeId;
+use crate::commander::ids::CommitId;
 use crate::commander::log::Head;
 use crate::env::DiffFormat;
+use crate::env::get_env;
+use crate::ui::utils::FileFolds;
 use crate::ui::utils::LargeString;
+use crate::ui::utils::section_end;
 
 /// 'jj show' output depends on all these values
 #[derive(PartialEq, Eq, Hash, Clone)]
 pub struct CommitShowKey {
     /// Commit id of shown change
     id: Head,
+    /// Base of a comparison. If set, the content is the diff from the
+    /// base to the change instead of 'jj show' output.
+    base: Option<CommitId>,
+    /// Paths the diff is limited to, e.g. the old and new path of a
+    /// renamed file. If empty, the content is the whole change.
+    paths: Vec<String>,
     /// Formatting used to render change
     format: DiffFormat,
+    /// Number of context lines, if not the default of jj.
+    /// Set to None for all formats where it can't be changed.
+    context: Option<usize>,
     /// Render width.
-    /// Set to 0 for all except format=DiffTool.
+    /// Set to 0 for all formats where the output does not depend on width.
     /// For DiffTool it is set to the inner with of the details panel,
     /// which is given to the tool via the COLUMNS environment variable.
+    /// For SideBySide it is the width the two columns must fit into.
+    /// For Stat it is the width the graph of changed lines is scaled to.
     width: usize,
 }
 
 impl CommitShowKey {
-    /// Create a new key. If DiffFormat is not DiffTool, then width
-    /// will be set to zero.
-    pub fn new(id: Head, format: DiffFormat, width: usize) -> Self {
-        // Keep with only for the DiffTool format
-        let width = if let DiffFormat::DiffTool(_) = format {
-            width
-        } else {
-            0
-        };
-        Self { id, format, width }
+    /// Create a new key. If DiffFormat does not use width or context,
+    /// then width will be set to zero and context to None.
+    pub fn new(id: Head, format: DiffFormat, context: Option<usize>, width: usize) -> Self {
+        // Keep width and context only for formats that use them
+        let width = if format.uses_width() { width } else { 0 };
+        let context = context.filter(|_| format.uses_context());
+        Self {
+            id,
+            base: None,
+            paths: vec![],
+            format,
+            context,
+            width,
+        }
+    }
+
+    /// The same key, for the diff from `base` to the change
+    pub fn compared_to(self, base: Option<CommitId>) -> Self {
+        Self { base, ..self }
+    }
+
+    /// The same key, for the diff limited to `paths`
+    pub fn for_paths(self, paths: Vec<String>) -> Self {
+        Self { paths, ..self }
+    }
+
+    /// True if the keys describe the same content, only rendered
+    /// with a different width.
+    pub fn is_resize_of(&self, other: &CommitShowKey) -> bool {
+        self.id == other.id
+            && self.base == other.base
+            && self.paths == other.paths
+            && self.format == other.format
+            && self.context == other.context
+            && self.width != other.width
     }
 }
 
@@ -49,18 +90,63 @@ impl CommitShowKey {
 pub struct CommitShowValue {
     key: CommitShowKey,
     jj_output: LargeString,
+    /// Files folded by the user. Kept with the output, so the folds
+    /// are still there when the change is shown again.
+    folds: FileFolds,
 }
 
 impl CommitShowValue {
-    /// Index value, and store both key and value
+    /// Index value, and store both key and value. Binary and very long
+    /// files start out folded.
     pub fn new(key: CommitShowKey, value: String) -> Self {
+        let jj_output = LargeString::new(value);
+        let folds = FileFolds::collapsed(&jj_output, get_env().jj_config.diff_max_lines());
         Self {
             key,
-            jj_output: LargeString::new(value),
+            jj_output,
+            folds,
         }
     }
-    pub fn value(&self) -> &LargeString {
-        &self.jj_output
+    /// Like [new](Self::new), for output with the headers of files left
+    /// out because of their long diff, with the path and number of lines
+    /// of each.
+    pub fn with_unloaded(key: CommitShowKey, value: String, unloaded: &[(String, usize)]) -> Self {
+        let mut show_value = Self::new(key, value);
+        for (path, lines) in unloaded {
+            let index = show_value
+                .jj_output
+                .diff_files()
+                .iter()
+                .position(|file| file.path == *path);
+            if let Some(index) = index {
+                show_value.folds.set_unloaded(index, *lines);
+            }
+        }
+        show_value
+    }
+    /// Put the loaded diff of a file that was left out below its header,
+    /// and unfold it
+    pub fn load_file(&mut self, file_index: usize, diff: &str) {
+        let files = self.jj_output.diff_files();
+        let Some(file) = files.get(file_index) else {
+            return;
+        };
+        let end = section_end(files, file_index, self.jj_output.lines());
+        self.jj_output = self.jj_output.replace_lines(file.line + 1..end, diff);
+        self.folds.set_loaded(file_index);
+    }
+    /// The output together with the folds that can be changed
+    pub fn value_with_folds(&mut self) -> (&LargeString, &mut FileFolds) {
+        (&self.jj_output, &mut self.folds)
+    }
+    /// Files folded by the user
+    pub fn folds(&self) -> &FileFolds {
+        &self.folds
+    }
+    /// Replace the folds, e.g. with those of the same content
+    /// rendered with another width
+    pub fn set_folds(&mut self, folds: FileFolds) {
+        self.folds = folds;
     }
 }
 
@@ -124,11 +210,17 @@ impl CommitShowCache {
 
     /// Mark all active heads as dirty by changing their width to 1.
     /// This way they will all be seen as old next time [set_active] is called.
+    /// Every cached rendering of an active change is marked, including
+    /// those with another diff format than the active key.
     pub fn mark_dirty(&mut self) {
-        // Collect all keys for active commits
         // std::mem::take moves the map out of self and leaves an empty one in its place
         let active_commits = std::mem::take(&mut self.active_commits);
-        let active_keys: Vec<CommitShowKey> = active_commits.values().flatten().cloned().collect();
+        let active_keys: Vec<CommitShowKey> = self
+            .commit_document
+            .keys()
+            .filter(|key| active_commits.contains_key(&key.id.change_id))
+            .cloned()
+            .collect();
         // Mark document as dirty
         for ac_key in active_keys {
             let Some(mut value) = self.commit_document.remove(&ac_key) else {
@@ -158,6 +250,16 @@ impl CommitShowCache {
         None
     }
 
+    /// Like [get](Self::get), but the value can be modified
+    pub fn get_mut(&mut self, key: &CommitShowKey) -> Option<&mut CommitShowValue> {
+        let key = if self.has_exact_match(key) {
+            key
+        } else {
+            self.old_commits.get(&key.id.change_id)?
+        };
+        self.commit_document.get_mut(key)
+    }
+
     /// Move the specified value into the cache as the active value
     /// of the key. Will remove any old values with the same change id.
     pub fn insert_document(&mut self, value: CommitShowValue) {
@@ -169,6 +271,11 @@ impl CommitShowCache {
         self.commit_document.insert(key.clone(), value);
     }
 
+    /// Remove the value of the key from the cache, if present
+    pub fn remove(&mut self, key: &CommitShowKey) {
+        self.commit_document.remove(key);
+    }
+
     /// If key is cached, return a reference to that value,
     /// otherwise generate the value,
     /// insert it, and return a reference to the new inserted value.
@@ -190,3 +297,45 @@ impl CommitShowCache {
         self.get(key).unwrap()
     }
 }
+
+/// Scroll positions of the most recently shown changes, so
+/// returning to a change shows the same part of it.
+pub struct ScrollMemory {
+    positions: HashMap<CommitShowKey, usize>,
+    /// Keys of positions, least recently used first
+    order: VecDeque<CommitShowKey>,
+}
+
+impl ScrollMemory {
+    /// Number of positions to remember
+    const CAPACITY: usize = 100;
+
+    pub fn new() -> Self {
+        Self {
+            positions: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Remember the scroll position of a key. A position at the top is
+    /// the default, so it is forgotten instead.
+    pub fn insert(&mut self, key: &CommitShowKey, scroll: usize) {
+        self.order.retain(|old_key| old_key != key);
+        if scroll == 0 {
+            self.positions.remove(key);
+            return;
+        }
+        if self.order.len() >= Self::CAPACITY
+            && let Some(oldest) = self.order.pop_front()
+        {
+            self.positions.remove(&oldest);
+        }
+        self.positions.insert(key.clone(), scroll);
+        self.order.push_back(key.clone());
+    }
+
+    /// The remembered scroll position of a key, or 0 if there is none
+    pub fn get(&self, key: &CommitShowKey) -> usize {
+        self.positions.get(key).copied().unwrap_or(0)
+    }
+}
@@ -0,0 +1,113 @@
+/*! The abandon popup confirms abandoning changes.
+
+ The UI looks like this
+ ~~~
+    Are you sure you want to abandon 2 marked changes?
+    3 descendants will be rebased onto the parents
+
+    [ ] Move their bookmarks to the parents (--retain-bookmarks)
+
+    Enter: abandon  b: bookmarks  Esc: cancel
+~~~
+Without `--retain-bookmarks`, jj deletes the bookmarks of the abandoned
+changes.
+*/
+
+use ratatui::Frame;
+use ratatui::crossterm::event::Event;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::crossterm::event::KeyEventKind;
+use ratatui::layout::Constraint;
+use ratatui::layout::Layout;
+use ratatui::style::Color;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Text;
+use ratatui::widgets::Clear;
+use ratatui::widgets::Paragraph;
+
+use crate::ui::styles::create_popup_block;
+use crate::ui::utils::centered_rect_fixed;
+
+/// What the log tab should do after an input to the popup
+pub enum AbandonAction {
+    /// Keep the popup open
+    None,
+    /// Close the popup without abandoning
+    Cancel,
+    /// Close the popup and abandon the changes
+    Abandon { retain_bookmarks: bool },
+}
+
+/// A transient popup for confirming to abandon changes
+pub struct AbandonPopup {
+    /// What is abandoned and what happens to the descendants
+    lines: Vec<Line<'static>>,
+    retain_bookmarks: bool,
+}
+
+impl AbandonPopup {
+    pub fn new(lines: Vec<Line<'static>>) -> Self {
+        Self {
+            lines,
+            retain_bookmarks: false,
+        }
+    }
+
+    /// Process the input event, and tell if the popup should close
+    pub fn handle_input(&mut self, event: Event) -> AbandonAction {
+        let Event::Key(key) = event else {
+            return AbandonAction::None;
+        };
+        if key.kind != KeyEventKind::Press {
+            return AbandonAction::None;
+        }
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('n') => AbandonAction::Cancel,
+            KeyCode::Enter | KeyCode::Char('y') => AbandonAction::Abandon {
+                retain_bookmarks: self.retain_bookmarks,
+            },
+            KeyCode::Char('b') | KeyCode::Char(' ') => {
+                self.retain_bookmarks = !self.retain_bookmarks;
+                AbandonAction::None
+            }
+            _ => AbandonAction::None,
+        }
+    }
+
+    /// Render the popup in the middle of the frame
+    pub fn render_widget(&self, frame: &mut Frame) {
+        let height = self.lines.len() as u16 + 6;
+        let area = centered_rect_fixed(frame.area(), 72, height);
+        let block = create_popup_block("Abandon");
+        frame.render_widget(Clear, area);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [content_area, bookmarks_area, help_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(2),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+
+        frame.render_widget(
+            Paragraph::new(Text::from(self.lines.clone())).centered(),
+            content_area,
+        );
+        let check = if self.retain_bookmarks { "[x]" } else { "[ ]" };
+        frame.render_widget(
+            Line::from(format!(
+                "{check} Move their bookmarks to the parents (--retain-bookmarks)"
+            ))
+            .centered(),
+            bookmarks_area,
+        );
+        frame.render_widget(
+            Paragraph::new("Enter: abandon  b: bookmarks  Esc: cancel")
+                .fg(Color::DarkGray)
+                .centered(),
+            help_area,
+        );
+    }
+}
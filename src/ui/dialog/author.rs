@@ -0,0 +1,161 @@
+/*! The author popup changes the author of a change with a small form.
+
+ The UI looks like this
+ ~~~
+    Name   Alice
+    Email  alice@example.com
+    Date   2024-05-01 12:00:00
+
+    Tab: next field  Enter: set author  Ctrl+r: reset author  Esc: cancel
+~~~
+Enter sets the author to the name and email of the form, keeping the
+date. Ctrl+r resets the author to the configured user and the date to
+now, like `jj describe --reset-author`.
+*/
+
+use ratatui::Frame;
+use ratatui::crossterm::event::Event;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::crossterm::event::KeyEventKind;
+use ratatui::crossterm::event::KeyModifiers;
+use ratatui::layout::Constraint;
+use ratatui::layout::Layout;
+use ratatui::style::Color;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Clear;
+use ratatui::widgets::Paragraph;
+use ratatui_textarea::TextArea;
+
+use crate::commander::log::CommitAuthor;
+use crate::ui::styles::create_popup_block;
+use crate::ui::utils::centered_rect_fixed;
+
+/// Fields of the form, in the order they are shown
+const FIELDS: [&str; 2] = ["Name", "Email"];
+
+/// What the log tab should do after an input to the popup
+pub enum AuthorAction {
+    /// Keep the popup open
+    None,
+    /// Close the popup without changing the author
+    Cancel,
+    /// Close the popup and set the author to `Name <email>`
+    SetAuthor(String),
+    /// Close the popup and reset the author to the configured user
+    ResetAuthor,
+}
+
+/// A transient popup for changing the author of a change
+pub struct AuthorPopup<'a> {
+    /// Text field of each of the [FIELDS]
+    fields: Vec<TextArea<'a>>,
+    /// Index of the field being edited
+    focus: usize,
+    /// Author date, which is kept when the author is set
+    timestamp: String,
+    /// Shown below the form, e.g. that the change is immutable
+    warning: Option<Line<'static>>,
+    /// Why the author can't be set, if Enter was pressed
+    error: Option<&'static str>,
+}
+
+impl AuthorPopup<'_> {
+    /// Create the form with the current author of the change
+    pub fn new(author: CommitAuthor, warning: Option<Line<'static>>) -> Self {
+        let fields = [author.name, author.email]
+            .into_iter()
+            .map(|value| TextArea::new(vec![value]))
+            .collect();
+        Self {
+            fields,
+            focus: 0,
+            timestamp: author.timestamp,
+            warning,
+            error: None,
+        }
+    }
+
+    /// Process the input event, and tell if the popup should close
+    pub fn handle_input(&mut self, event: Event) -> AuthorAction {
+        let Event::Key(key) = event else {
+            return AuthorAction::None;
+        };
+        if key.kind != KeyEventKind::Press {
+            return AuthorAction::None;
+        }
+        match key.code {
+            KeyCode::Esc => return AuthorAction::Cancel,
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return AuthorAction::ResetAuthor;
+            }
+            KeyCode::Enter => {
+                let [name, email] = [0, 1].map(|index| self.fields[index].lines().join(" "));
+                let (name, email) = (name.trim(), email.trim());
+                if name.is_empty() || email.is_empty() {
+                    self.error = Some("Fill in the name and email");
+                } else {
+                    return AuthorAction::SetAuthor(format!("{name} <{email}>"));
+                }
+            }
+            KeyCode::Tab | KeyCode::Down | KeyCode::BackTab | KeyCode::Up => {
+                self.focus = (self.focus + 1) % FIELDS.len()
+            }
+            _ => {
+                self.fields[self.focus].input(event);
+            }
+        }
+        AuthorAction::None
+    }
+
+    /// Render the popup in the middle of the frame
+    pub fn render_widget(&mut self, frame: &mut Frame) {
+        let area = centered_rect_fixed(frame.area(), 74, FIELDS.len() as u16 + 7);
+        let block = create_popup_block("Author");
+        frame.render_widget(Clear, area);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        // Fields, date, blank, warning, blank and help
+        let constraints = vec![Constraint::Length(1); FIELDS.len() + 5];
+        let rows = Layout::vertical(constraints).split(inner);
+        for (index, label) in FIELDS.iter().enumerate() {
+            let [label_area, field_area] =
+                Layout::horizontal([Constraint::Length(7), Constraint::Fill(1)]).areas(rows[index]);
+            let focused = index == self.focus;
+            let label = Line::from(*label);
+            frame.render_widget(
+                if focused { label.cyan().bold() } else { label },
+                label_area,
+            );
+
+            let field = &mut self.fields[index];
+            field.set_cursor_line_style(Style::default());
+            field.set_cursor_style(if focused {
+                Style::default().reversed()
+            } else {
+                Style::default()
+            });
+            frame.render_widget(&*field, field_area);
+        }
+        frame.render_widget(
+            Line::from(format!("Date   {}", self.timestamp)),
+            rows[FIELDS.len()],
+        );
+
+        if let Some(error) = self.error {
+            frame.render_widget(
+                Line::from(error).fg(Color::Red).centered(),
+                rows[FIELDS.len() + 2],
+            );
+        } else if let Some(warning) = &self.warning {
+            frame.render_widget(warning.clone().centered(), rows[FIELDS.len() + 2]);
+        }
+        let help =
+            Paragraph::new("Tab: next field  Enter: set author  Ctrl+r: reset author  Esc: cancel")
+                .fg(Color::DarkGray)
+                .centered();
+        frame.render_widget(help, rows[FIELDS.len() + 4]);
+    }
+}
@@ -1,3 +1,11 @@
+/*! The command popup runs a jj command typed after `:`.
+
+`{change_id}`, `{commit_id}` and `{file}` in the arguments are replaced
+with the change and file selected in the tab, e.g.
+`jj file annotate -r {change_id} {file}`. The output is shown in a
+popup, and the tab is refreshed.
+*/
+
 use anyhow::Context;
 use anyhow::Result;
 use ratatui::crossterm::event::Event;
@@ -20,19 +28,24 @@ use shell_words::split;
 
 use crate::ComponentInputResult;
 use crate::commander::new_commander;
+use crate::commander::substitute_placeholders;
 use crate::ui::Component;
 use crate::ui::ComponentAction;
+use crate::ui::Selection;
 use crate::ui::dialog::MessagePopup;
 use crate::ui::utils::centered_rect_line_height;
 
 pub struct CommandPopup<'a> {
     command_textarea: TextArea<'a>,
+    /// Selection of the tab the popup was opened from
+    selection: Selection,
 }
 
 impl CommandPopup<'_> {
-    pub fn new() -> Self {
+    pub fn new(selection: Selection) -> Self {
         Self {
             command_textarea: TextArea::new(vec![]),
+            selection,
         }
     }
 }
@@ -59,15 +72,17 @@ impl Component for CommandPopup<'_> {
 
         f.render_widget(&self.command_textarea, popup_chunks[0]);
 
-        let help = Paragraph::new(vec!["Enter: run | Escape: cancel".into()])
-            .fg(Color::DarkGray)
-            .alignment(Alignment::Center)
-            .block(
-                Block::default()
-                    .borders(Borders::TOP)
-                    .border_type(BorderType::Rounded)
-                    .border_style(Style::default().fg(Color::DarkGray)),
-            );
+        let help = Paragraph::new(vec![
+            "Enter: run | Escape: cancel | {change_id} {commit_id} {file}: selection".into(),
+        ])
+        .fg(Color::DarkGray)
+        .alignment(Alignment::Center)
+        .block(
+            Block::default()
+                .borders(Borders::TOP)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(Color::DarkGray)),
+        );
 
         f.render_widget(help, popup_chunks[1]);
         Ok(())
@@ -93,6 +108,13 @@ impl Component for CommandPopup<'_> {
 
                     let res: Result<String> = split(command_input)
                         .context("Failed to split command input")
+                        .and_then(|args| {
+                            substitute_placeholders(
+                                args,
+                                self.selection.head.as_ref(),
+                                self.selection.file.as_deref(),
+                            )
+                        })
                         .and_then(|command| {
                             Ok(new_commander().execute_jj_command(command, true, false)?)
                         });
@@ -0,0 +1,234 @@
+/*! The conflict popup shows the conflicts of a file side by side, the
+left side, the base and the right side, to resolve them one region at a
+time by taking a side, and write the file with `jj resolve`.
+
+Only simple conflicts, with two sides, can be resolved this way.
+
+ The UI looks like this
+ ~~~
+          Conflict 1 of 2: taking left · 1 unresolved
+    ╭ Left (side #1) ─╮╭ Base ───────────╮╭ Right (side #2) ╮
+    │ b1              ││ b               ││ b2              │
+    ╰─────────────────╯╰─────────────────╯╰─────────────────╯
+
+    j/k: conflict  h/l/b: take left/right/both  u: undo  w: write  Esc: close
+~~~
+*/
+
+use anyhow::Result;
+use ratatui::Frame;
+use ratatui::crossterm::event::Event;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::crossterm::event::KeyEventKind;
+use ratatui::layout::Constraint;
+use ratatui::layout::Layout;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Block;
+use ratatui::widgets::Clear;
+use ratatui::widgets::Paragraph;
+
+use crate::ComponentInputResult;
+use crate::commander::log::Head;
+use crate::commander::new_commander;
+use crate::ui::Component;
+use crate::ui::ComponentAction;
+use crate::ui::dialog::MessagePopup;
+use crate::ui::styles::create_popup_block;
+use crate::ui::utils::ConflictChoice;
+use crate::ui::utils::ConflictChunk;
+use crate::ui::utils::centered_rect;
+use crate::ui::utils::resolve_conflicts;
+
+/// A popup for resolving the conflicts of a file
+pub struct ConflictPopup {
+    head: Head,
+    path: String,
+    chunks: Vec<ConflictChunk>,
+    /// How each conflict is resolved, None while it is not
+    choices: Vec<Option<ConflictChoice>>,
+    selected: usize,
+    scroll: u16,
+    /// Why the file can't be written, if w was pressed
+    error: Option<&'static str>,
+}
+
+impl ConflictPopup {
+    /// Popup for the conflicts of `path` in `head`, from the chunks of
+    /// [parse_conflicts](crate::ui::utils::parse_conflicts)
+    pub fn new(head: Head, path: String, chunks: Vec<ConflictChunk>) -> Self {
+        let conflicts = chunks
+            .iter()
+            .filter(|chunk| matches!(chunk, ConflictChunk::Conflict { .. }))
+            .count();
+        Self {
+            head,
+            path,
+            chunks,
+            choices: vec![None; conflicts],
+            selected: 0,
+            scroll: 0,
+            error: None,
+        }
+    }
+
+    /// Sides and base of the selected conflict
+    fn selected_conflict(&self) -> Option<(&str, &str, &str)> {
+        self.chunks
+            .iter()
+            .filter_map(|chunk| match chunk {
+                ConflictChunk::Conflict { left, base, right } => {
+                    Some((left.as_str(), base.as_str(), right.as_str()))
+                }
+                ConflictChunk::Resolved(_) => None,
+            })
+            .nth(self.selected)
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index.min(self.choices.len().saturating_sub(1));
+        self.scroll = 0;
+    }
+
+    fn choose(&mut self, choice: Option<ConflictChoice>) {
+        if let Some(selected) = self.choices.get_mut(self.selected) {
+            *selected = choice;
+        }
+        self.error = None;
+        // Move on to the next conflict to resolve
+        if choice.is_some() && self.selected + 1 < self.choices.len() {
+            self.select(self.selected + 1);
+        }
+    }
+
+    /// Write the resolved file with `jj resolve`. None if a conflict is
+    /// not resolved yet.
+    fn write(&mut self) -> Option<ComponentAction> {
+        let Some(content) = resolve_conflicts(&self.chunks, &self.choices) else {
+            self.error = Some("Pick a side for every conflict first");
+            return None;
+        };
+        Some(
+            match new_commander().run_resolve_with(&self.head.commit_id, &self.path, &content) {
+                Ok(()) => ComponentAction::Multiple(vec![
+                    ComponentAction::SetPopup(None),
+                    ComponentAction::RefreshTab(),
+                ]),
+                Err(err) => ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                    "Can't resolve conflicts",
+                    format!("{err:#}"),
+                )))),
+            },
+        )
+    }
+
+    fn status(&self) -> String {
+        let taking = match self.choices.get(self.selected).copied().flatten() {
+            Some(ConflictChoice::Left) => "taking left",
+            Some(ConflictChoice::Right) => "taking right",
+            Some(ConflictChoice::Both) => "taking both",
+            None => "no side taken",
+        };
+        let unresolved = self
+            .choices
+            .iter()
+            .filter(|choice| choice.is_none())
+            .count();
+        format!(
+            "Conflict {} of {}: {taking} · {unresolved} unresolved",
+            self.selected + 1,
+            self.choices.len()
+        )
+    }
+}
+
+impl Component for ConflictPopup {
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let area = centered_rect(area, 90, 85);
+        let title = format!("Conflicts of {}", self.path);
+        let block = create_popup_block(&title);
+        f.render_widget(Clear, area);
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        let [status_area, panes_area, error_area, help_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+        f.render_widget(Line::from(self.status()).bold().centered(), status_area);
+
+        let choice = self.choices.get(self.selected).copied().flatten();
+        let (left_taken, right_taken) = match choice {
+            Some(ConflictChoice::Left) => (true, false),
+            Some(ConflictChoice::Right) => (false, true),
+            Some(ConflictChoice::Both) => (true, true),
+            None => (false, false),
+        };
+        let (left, base, right) = self.selected_conflict().unwrap_or_default();
+        let panes = [
+            ("Left (side #1)", left, left_taken),
+            ("Base", base, false),
+            ("Right (side #2)", right, right_taken),
+        ];
+        let areas = Layout::horizontal([Constraint::Ratio(1, 3); 3]).split(panes_area);
+        for ((title, text, taken), area) in panes.into_iter().zip(areas.iter()) {
+            let color = if taken { Color::Green } else { Color::DarkGray };
+            let pane = Paragraph::new(text).scroll((self.scroll, 0)).block(
+                Block::bordered()
+                    .title(format!(" {title} "))
+                    .border_style(Style::default().fg(color)),
+            );
+            f.render_widget(pane, *area);
+        }
+
+        if let Some(error) = self.error {
+            f.render_widget(Line::from(error).fg(Color::Red).centered(), error_area);
+        }
+        f.render_widget(
+            Paragraph::new(
+                "j/k: conflict  J/K: scroll  h/l/b: take left/right/both  u: undo  w: write  Esc: close",
+            )
+            .fg(Color::DarkGray)
+            .centered(),
+            help_area,
+        );
+        Ok(())
+    }
+
+    fn input(&mut self, event: Event) -> Result<ComponentInputResult> {
+        let Event::Key(key) = event else {
+            return Ok(ComponentInputResult::Handled);
+        };
+        if key.kind != KeyEventKind::Press {
+            return Ok(ComponentInputResult::Handled);
+        }
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                return Ok(ComponentInputResult::HandledAction(
+                    ComponentAction::SetPopup(None),
+                ));
+            }
+            KeyCode::Char('j') | KeyCode::Down => self.select(self.selected + 1),
+            KeyCode::Char('k') | KeyCode::Up => self.select(self.selected.saturating_sub(1)),
+            KeyCode::Char('J') => self.scroll = self.scroll.saturating_add(1),
+            KeyCode::Char('K') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Char('h') | KeyCode::Left => self.choose(Some(ConflictChoice::Left)),
+            KeyCode::Char('l') | KeyCode::Right => self.choose(Some(ConflictChoice::Right)),
+            KeyCode::Char('b') => self.choose(Some(ConflictChoice::Both)),
+            KeyCode::Char('u') => self.choose(None),
+            KeyCode::Char('w') => {
+                if let Some(action) = self.write() {
+                    return Ok(ComponentInputResult::HandledAction(action));
+                }
+            }
+            _ => (),
+        }
+        Ok(ComponentInputResult::Handled)
+    }
+}
@@ -0,0 +1,102 @@
+/*! The custom command popup lists the commands of
+`blazingjj.custom-commands`, to run one with its key.
+
+ The UI looks like this
+ ~~~
+    t  cargo test
+    l  jj log -r {change_id}
+
+    Key: run command  Esc: cancel
+~~~
+The popup opens with `!`, so `!t` runs the command with key `t`.
+*/
+
+use ratatui::Frame;
+use ratatui::crossterm::event::Event;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::crossterm::event::KeyEventKind;
+use ratatui::layout::Constraint;
+use ratatui::layout::Layout;
+use ratatui::style::Color;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Clear;
+use ratatui::widgets::Paragraph;
+
+use crate::env::CustomCommand;
+use crate::ui::styles::create_popup_block;
+use crate::ui::utils::centered_rect_fixed;
+
+/// What the log tab should do after an input to the popup
+pub enum CustomCommandAction {
+    /// Keep the popup open
+    None,
+    /// Close the popup without running a command
+    Cancel,
+    /// Close the popup and run this command
+    Run(CustomCommand),
+}
+
+/// A transient popup for picking a custom command by its key
+pub struct CustomCommandPopup {
+    /// Commands with their keys, sorted by key
+    commands: Vec<(String, CustomCommand)>,
+}
+
+impl CustomCommandPopup {
+    pub fn new(commands: Vec<(String, CustomCommand)>) -> Self {
+        Self { commands }
+    }
+
+    /// Process the input event, and tell if the popup should close
+    pub fn handle_input(&mut self, event: Event) -> CustomCommandAction {
+        let Event::Key(key) = event else {
+            return CustomCommandAction::None;
+        };
+        if key.kind != KeyEventKind::Press {
+            return CustomCommandAction::None;
+        }
+        match key.code {
+            KeyCode::Esc => CustomCommandAction::Cancel,
+            KeyCode::Char(char) => self
+                .commands
+                .iter()
+                .find(|(key, _)| *key == char.to_string())
+                .map_or(CustomCommandAction::None, |(_, command)| {
+                    CustomCommandAction::Run(command.clone())
+                }),
+            _ => CustomCommandAction::None,
+        }
+    }
+
+    /// Render the popup in the middle of the frame
+    pub fn render_widget(&self, frame: &mut Frame) {
+        let height = (self.commands.len() as u16 + 4).min(frame.area().height);
+        let area = centered_rect_fixed(frame.area(), 60, height);
+        let block = create_popup_block("Custom commands");
+        frame.render_widget(Clear, area);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [list_area, help_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(inner);
+        let lines: Vec<Line> = self
+            .commands
+            .iter()
+            .map(|(key, command)| {
+                Line::from(vec![
+                    Span::from(format!("{key}  ")).cyan().bold(),
+                    Span::from(command.name()),
+                ])
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), list_area);
+        frame.render_widget(
+            Paragraph::new("Key: run command  Esc: cancel")
+                .fg(Color::DarkGray)
+                .centered(),
+            help_area,
+        );
+    }
+}
@@ -0,0 +1,213 @@
+/*! The evolog popup lists the versions of a change from its evolution
+log (`jj evolog`), with what changed from the selected version to the
+current one, to restore files of the change from an older version.
+
+ The UI looks like this
+ ~~~
+    3ba7e7fd 2 minutes ago feat: a (snapshot working copy)
+    9f1c2d0a 5 minutes ago feat: a (describe commit 1b2c3d4e5f6a)
+    ─────────────────────────────────────────────────
+    Modified regular file src/main.rs:
+       1    1: fn main() {
+       2     :     println!("Hello");
+            2:     println!("Hello, world!");
+
+    j/k: select  J/K: scroll  r: restore from version  Esc: close
+~~~
+*/
+
+use std::collections::HashMap;
+
+use ansi_to_tui::IntoText;
+use ratatui::Frame;
+use ratatui::crossterm::event::Event;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::crossterm::event::KeyEventKind;
+use ratatui::layout::Constraint;
+use ratatui::layout::Layout;
+use ratatui::style::Color;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Text;
+use ratatui::widgets::Block;
+use ratatui::widgets::Borders;
+use ratatui::widgets::Clear;
+use ratatui::widgets::List;
+use ratatui::widgets::ListState;
+use ratatui::widgets::Paragraph;
+
+use crate::commander::ids::CommitId;
+use crate::commander::log::Head;
+use crate::commander::new_commander;
+use crate::env::DiffFormat;
+use crate::ui::styles::create_popup_block;
+use crate::ui::utils::centered_rect;
+
+/// Rows of the list of versions at most
+const MAX_LIST_HEIGHT: u16 = 8;
+
+/// What the log tab should do after an input to the popup
+pub enum EvologAction {
+    /// Keep the popup open
+    None,
+    /// Close the popup
+    Cancel,
+    /// Close the popup and pick the files to restore from this version
+    Restore(Head),
+}
+
+/// A transient popup for browsing the versions of a change
+pub struct EvologPopup {
+    /// Versions of the change with their summaries, the current one first
+    versions: Vec<(Head, String)>,
+    diff_format: DiffFormat,
+    list_state: ListState,
+    /// Changes from each version to the current one, computed when the
+    /// version is first selected
+    diffs: HashMap<CommitId, Text<'static>>,
+    scroll: u16,
+    /// Why the selected version can't be restored, if r was pressed
+    error: Option<&'static str>,
+}
+
+impl EvologPopup {
+    pub fn new(versions: Vec<(Head, String)>, diff_format: DiffFormat) -> Self {
+        let mut popup = Self {
+            versions,
+            // Width dependent formats are rendered for the details panel
+            diff_format: if diff_format.uses_width() {
+                DiffFormat::Git
+            } else {
+                diff_format
+            },
+            list_state: ListState::default().with_selected(Some(0)),
+            diffs: HashMap::new(),
+            scroll: 0,
+            error: None,
+        };
+        popup.select(0);
+        popup
+    }
+
+    /// Select a version and compute its changes, if it is not cached
+    fn select(&mut self, index: usize) {
+        let index = index.min(self.versions.len().saturating_sub(1));
+        self.list_state.select(Some(index));
+        self.scroll = 0;
+        self.error = None;
+        let (Some((current, _)), Some((version, _))) =
+            (self.versions.first(), self.versions.get(index))
+        else {
+            return;
+        };
+        if index == 0 || self.diffs.contains_key(&version.commit_id) {
+            return;
+        }
+        let diff = new_commander()
+            .get_commits_diff(
+                &version.commit_id,
+                &current.commit_id,
+                &self.diff_format,
+                None,
+                &[],
+                true,
+            )
+            .map(|diff| {
+                if diff.is_empty() {
+                    "The files are the same as in the current version".to_owned()
+                } else {
+                    diff
+                }
+            })
+            .unwrap_or_else(|err| err.to_string());
+        let text = diff.into_text().unwrap_or_else(|_| Text::raw(diff.clone()));
+        self.diffs.insert(version.commit_id.clone(), text);
+    }
+
+    /// Process the input event, and tell if the popup should close
+    pub fn handle_input(&mut self, event: Event) -> EvologAction {
+        let Event::Key(key) = event else {
+            return EvologAction::None;
+        };
+        if key.kind != KeyEventKind::Press {
+            return EvologAction::None;
+        }
+        let selected = self.list_state.selected().unwrap_or_default();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return EvologAction::Cancel,
+            KeyCode::Char('j') | KeyCode::Down => self.select(selected + 1),
+            KeyCode::Char('k') | KeyCode::Up => self.select(selected.saturating_sub(1)),
+            KeyCode::Char('J') => self.scroll = self.scroll.saturating_add(1),
+            KeyCode::Char('K') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Char('r') if selected == 0 => {
+                self.error = Some("Select an older version to restore from");
+            }
+            KeyCode::Char('r') => {
+                if let Some((version, _)) = self.versions.get(selected) {
+                    return EvologAction::Restore(version.clone());
+                }
+            }
+            _ => (),
+        }
+        EvologAction::None
+    }
+
+    /// Render the popup in the middle of the frame
+    pub fn render_widget(&mut self, frame: &mut Frame) {
+        let area = centered_rect(frame.area(), 80, 80);
+        let title = match self.versions.first() {
+            Some((current, _)) => format!("Evolution of {}", current.change_id.short()),
+            None => "Evolution".to_owned(),
+        };
+        let block = create_popup_block(&title);
+        frame.render_widget(Clear, area);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let list_height = (self.versions.len() as u16).clamp(1, MAX_LIST_HEIGHT);
+        let [list_area, diff_area, error_area, help_area] = Layout::vertical([
+            Constraint::Length(list_height),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+
+        let items = self
+            .versions
+            .iter()
+            .map(|(_, summary)| Line::from(summary.as_str()));
+        let list = List::new(items).highlight_style(Style::default().reversed());
+        frame.render_stateful_widget(list, list_area, &mut self.list_state);
+
+        let selected = self.list_state.selected().unwrap_or_default();
+        let diff_block = Block::new()
+            .borders(Borders::TOP)
+            .border_style(Style::default().fg(Color::DarkGray));
+        let diff = match self.versions.get(selected) {
+            Some((version, _)) if selected > 0 => Paragraph::new(
+                self.diffs
+                    .get(&version.commit_id)
+                    .cloned()
+                    .unwrap_or_default(),
+            )
+            .scroll((self.scroll, 0)),
+            _ => {
+                Paragraph::new("The current version, select an older one to see what changed since")
+                    .fg(Color::DarkGray)
+            }
+        };
+        frame.render_widget(diff.block(diff_block), diff_area);
+
+        if let Some(error) = self.error {
+            frame.render_widget(Line::from(error).fg(Color::Red).centered(), error_area);
+        }
+        frame.render_widget(
+            Paragraph::new("j/k: select  J/K: scroll  r: restore from version  Esc: close")
+                .fg(Color::DarkGray)
+                .centered(),
+            help_area,
+        );
+    }
+}
@@ -11,6 +11,7 @@ use std::time::Instant;
 use anyhow::Result;
 use ratatui::Frame;
 use ratatui::crossterm::event::Event;
+use ratatui::layout::Alignment;
 use ratatui::layout::Rect;
 use ratatui::style::Color;
 use ratatui::style::Style;
@@ -35,6 +36,8 @@ pub struct LoaderPopup {
     result_rx: Receiver<OperationResult>,
     throbber_state: ThrobberState,
     last_animation_update: Instant,
+    /// Alignment of the output of the operation
+    text_align: Option<Alignment>,
 }
 
 impl LoaderPopup {
@@ -58,8 +61,14 @@ impl LoaderPopup {
             result_rx: rx,
             throbber_state: ThrobberState::default(),
             last_animation_update: Instant::now(),
+            text_align: None,
         }
     }
+
+    pub fn text_align(mut self, align: Alignment) -> Self {
+        self.text_align = Some(align);
+        self
+    }
 }
 
 impl Component for LoaderPopup {
@@ -78,13 +87,17 @@ impl Component for LoaderPopup {
         };
 
         let action = match result {
-            Ok(output) if !output.is_empty() => ComponentAction::Multiple(vec![
-                ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
-                    format!("{} message", self.operation_name),
-                    output,
-                )))),
-                ComponentAction::RefreshTab(),
-            ]),
+            Ok(output) if !output.is_empty() => {
+                let mut message =
+                    MessagePopup::new(format!("{} message", self.operation_name), output);
+                if let Some(align) = self.text_align {
+                    message = message.text_align(align);
+                }
+                ComponentAction::Multiple(vec![
+                    ComponentAction::SetPopup(Some(Box::new(message))),
+                    ComponentAction::RefreshTab(),
+                ])
+            }
             Ok(_) => ComponentAction::Multiple(vec![
                 ComponentAction::SetPopup(None),
                 ComponentAction::RefreshTab(),
@@ -0,0 +1,207 @@
+/*! The log filter popup narrows the log with a small form, so the
+user does not need to know the revset functions.
+
+ The UI looks like this
+ ~~~
+    Revset       mine()
+    Author       alice
+    Description  fix
+    After        2 weeks ago
+    Before
+
+    Tab: next field  Enter: apply  Esc: cancel
+~~~
+Each filled in field becomes a revset function, e.g. `author("alice")`
+or `committer_date(after:"2 weeks ago")`, and they are combined with
+the revset. An empty revset filters all changes.
+*/
+
+use ratatui::Frame;
+use ratatui::crossterm::event::Event;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::crossterm::event::KeyEventKind;
+use ratatui::layout::Constraint;
+use ratatui::layout::Layout;
+use ratatui::style::Color;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Clear;
+use ratatui::widgets::Paragraph;
+use ratatui_textarea::TextArea;
+
+use crate::ui::styles::create_popup_block;
+use crate::ui::utils::centered_rect_fixed;
+
+/// Fields of the form, in the order they are shown
+const FIELDS: [&str; 5] = ["Revset", "Author", "Description", "After", "Before"];
+
+/// What the log tab should do after an input to the popup
+pub enum LogFilterAction {
+    /// Keep the popup open
+    None,
+    /// Close the popup without changing the log
+    Cancel,
+    /// Close the popup and show this revset, None for the default log
+    Apply(Option<String>),
+}
+
+/// A transient popup for filtering the log by author and date
+pub struct LogFilterPopup<'a> {
+    /// Text field of each of the [FIELDS]
+    fields: Vec<TextArea<'a>>,
+    /// Index of the field being edited
+    focus: usize,
+}
+
+impl LogFilterPopup<'_> {
+    /// Create the form, with the current revset of the log to filter
+    pub fn new(revset: Option<&str>) -> Self {
+        let fields = FIELDS
+            .iter()
+            .enumerate()
+            .map(|(index, _)| {
+                let mut field = TextArea::default();
+                if index == 0
+                    && let Some(revset) = revset
+                {
+                    field.insert_str(revset);
+                }
+                field
+            })
+            .collect();
+        Self { fields, focus: 0 }
+    }
+
+    /// Text of each field
+    fn values(&self) -> Vec<String> {
+        self.fields
+            .iter()
+            .map(|field| field.lines().join(" "))
+            .collect()
+    }
+
+    /// Process the input event, and tell if the popup should close
+    pub fn handle_input(&mut self, event: Event) -> LogFilterAction {
+        let Event::Key(key) = event else {
+            return LogFilterAction::None;
+        };
+        if key.kind != KeyEventKind::Press {
+            return LogFilterAction::None;
+        }
+        match key.code {
+            KeyCode::Esc => return LogFilterAction::Cancel,
+            KeyCode::Enter => return LogFilterAction::Apply(build_revset(&self.values())),
+            KeyCode::Tab | KeyCode::Down => self.focus = (self.focus + 1) % FIELDS.len(),
+            KeyCode::BackTab | KeyCode::Up => {
+                self.focus = (self.focus + FIELDS.len() - 1) % FIELDS.len()
+            }
+            _ => {
+                self.fields[self.focus].input(event);
+            }
+        }
+        LogFilterAction::None
+    }
+
+    /// Render the popup in the middle of the frame
+    pub fn render_widget(&mut self, frame: &mut Frame) {
+        let area = centered_rect_fixed(frame.area(), 60, FIELDS.len() as u16 + 5);
+        let block = create_popup_block("Filter log");
+        frame.render_widget(Clear, area);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let mut constraints = vec![Constraint::Length(1); FIELDS.len()];
+        constraints.extend([Constraint::Length(1), Constraint::Length(1)]);
+        let rows = Layout::vertical(constraints).split(inner);
+        for (index, label) in FIELDS.iter().enumerate() {
+            let [label_area, field_area] =
+                Layout::horizontal([Constraint::Length(13), Constraint::Fill(1)])
+                    .areas(rows[index]);
+            let focused = index == self.focus;
+            let label = Line::from(*label);
+            frame.render_widget(
+                if focused { label.cyan().bold() } else { label },
+                label_area,
+            );
+
+            let field = &mut self.fields[index];
+            field.set_cursor_line_style(Style::default());
+            field.set_cursor_style(if focused {
+                Style::default().reversed()
+            } else {
+                Style::default()
+            });
+            frame.render_widget(&*field, field_area);
+        }
+
+        let help = Paragraph::new("Tab: next field  Enter: apply  Esc: cancel")
+            .fg(Color::DarkGray)
+            .centered();
+        frame.render_widget(help, rows[FIELDS.len() + 1]);
+    }
+}
+
+/// Quote a value as a revset string literal
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Build the revset of the form from the values of the [FIELDS].
+/// Empty fields are left out, and None is returned if all are empty.
+fn build_revset(values: &[String]) -> Option<String> {
+    let value = |index: usize| {
+        values
+            .get(index)
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    };
+    let mut parts = vec![];
+    if let Some(revset) = value(0) {
+        parts.push(format!("({revset})"));
+    }
+    if let Some(author) = value(1) {
+        parts.push(format!("author({})", quote(author)));
+    }
+    if let Some(description) = value(2) {
+        parts.push(format!("description({})", quote(description)));
+    }
+    if let Some(after) = value(3) {
+        parts.push(format!("committer_date(after:{})", quote(after)));
+    }
+    if let Some(before) = value(4) {
+        parts.push(format!("committer_date(before:{})", quote(before)));
+    }
+    match parts.as_slice() {
+        [] => None,
+        // A revset alone is kept as typed
+        [_] if value(0).is_some() => value(0).map(str::to_owned),
+        _ => Some(parts.join(" & ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(values: [&str; 5]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn build_filters() {
+        assert_eq!(build_revset(&values(["", "", "", "", ""])), None);
+        assert_eq!(
+            build_revset(&values(["mine()", "", "", "", ""])),
+            Some("mine()".to_owned())
+        );
+        assert_eq!(
+            build_revset(&values(["::@", "alice", "", "2 weeks ago", ""])),
+            Some(r#"(::@) & author("alice") & committer_date(after:"2 weeks ago")"#.to_owned())
+        );
+        assert_eq!(
+            build_revset(&values(["", "", "say \"hi\"", "", "2024-01-01"])),
+            Some(r#"description("say \"hi\"") & committer_date(before:"2024-01-01")"#.to_owned())
+        );
+    }
+}
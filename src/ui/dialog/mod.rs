@@ -7,16 +7,50 @@ Once launched, a dialog will receive all input events from the App,
 until it is closed.
 */
 
+mod abandon;
+mod author;
 mod bookmark_set;
 mod command;
+mod conflict;
+mod custom_command;
+mod evolog;
 mod help;
 mod loader;
+mod log_filter;
 mod message;
+mod picker;
+mod pull_request;
+mod push;
 mod rebase;
+mod restore;
+mod sparse;
+mod split;
 
+pub use abandon::AbandonAction;
+pub use abandon::AbandonPopup;
+pub use author::AuthorAction;
+pub use author::AuthorPopup;
 pub use bookmark_set::BookmarkSetPopup;
 pub use command::CommandPopup;
+pub use conflict::ConflictPopup;
+pub use custom_command::CustomCommandAction;
+pub use custom_command::CustomCommandPopup;
+pub use evolog::EvologAction;
+pub use evolog::EvologPopup;
 pub use help::HelpPopup;
 pub use loader::LoaderPopup;
+pub use log_filter::LogFilterAction;
+pub use log_filter::LogFilterPopup;
 pub use message::MessagePopup;
+pub use picker::PickerAction;
+pub use picker::PickerPopup;
+pub use pull_request::PullRequestAction;
+pub use pull_request::PullRequestPopup;
+pub use push::PushAction;
+pub use push::PushPopup;
 pub use rebase::RebasePopup;
+pub use restore::RestoreAction;
+pub use restore::RestorePopup;
+pub use sparse::SparsePopup;
+pub use split::SplitAction;
+pub use split::SplitPopup;
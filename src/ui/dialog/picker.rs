@@ -0,0 +1,200 @@
+/*! The picker popup lists some entries, so one can be picked instead
+of typed.
+
+It is used by the log tab to pick a recent revset, which replaces the
+text of the revset popup, where it can be edited before it is applied,
+to pick a bookmark to go to, and to pick a trailer for a description.
+
+With a filter, typing narrows the list down to the entries that fuzzy
+match the typed text, best matches first.
+*/
+
+use ratatui::Frame;
+use ratatui::crossterm::event::Event;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::crossterm::event::KeyEventKind;
+use ratatui::crossterm::event::KeyModifiers;
+use ratatui::layout::Constraint;
+use ratatui::layout::Layout;
+use ratatui::style::Color;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Clear;
+use ratatui::widgets::List;
+use ratatui::widgets::ListState;
+use ratatui::widgets::Paragraph;
+
+use crate::env::get_env;
+use crate::ui::styles::create_popup_block;
+use crate::ui::utils::centered_rect;
+use crate::ui::utils::fuzzy_match;
+
+/// What the log tab should do after an input to the popup
+pub enum PickerAction {
+    /// Keep the popup open
+    None,
+    /// Close the popup without picking an entry
+    Cancel,
+    /// Close the popup and use this entry
+    Select(String),
+}
+
+/// A transient popup for picking an entry of a list
+pub struct PickerPopup {
+    title: &'static str,
+    /// Shown instead of the list when it is empty
+    empty_text: &'static str,
+    /// What Enter does, for the help line
+    select_text: &'static str,
+    entries: Vec<String>,
+    /// Typed text the entries are filtered with, None without a filter
+    filter: Option<String>,
+    /// Indexes of the entries matching the filter, best matches first
+    matches: Vec<usize>,
+    state: ListState,
+}
+
+impl PickerPopup {
+    pub fn new(
+        title: &'static str,
+        empty_text: &'static str,
+        select_text: &'static str,
+        entries: Vec<String>,
+    ) -> Self {
+        let state = ListState::default().with_selected((!entries.is_empty()).then_some(0));
+        Self {
+            title,
+            empty_text,
+            select_text,
+            matches: (0..entries.len()).collect(),
+            entries,
+            filter: None,
+            state,
+        }
+    }
+
+    /// Filter the entries with the typed text, instead of selecting
+    /// with j/k
+    pub fn with_filter(mut self) -> Self {
+        self.filter = Some(String::new());
+        self
+    }
+
+    /// Update the matching entries after the filter changed
+    fn update_matches(&mut self) {
+        let filter = self.filter.as_deref().unwrap_or_default();
+        let mut scored: Vec<(usize, usize)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| Some((fuzzy_match(filter, entry)?, index)))
+            .collect();
+        scored.sort();
+        self.matches = scored.into_iter().map(|(_, index)| index).collect();
+        self.state.select((!self.matches.is_empty()).then_some(0));
+    }
+
+    /// Process the input event, and tell if the popup should close
+    pub fn handle_input(&mut self, event: Event) -> PickerAction {
+        let Event::Key(key) = event else {
+            return PickerAction::None;
+        };
+        if key.kind != KeyEventKind::Press {
+            return PickerAction::None;
+        }
+        if let Some(filter) = &mut self.filter {
+            match key.code {
+                KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.state.select_next();
+                    return PickerAction::None;
+                }
+                KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.state.select_previous();
+                    return PickerAction::None;
+                }
+                KeyCode::Char(char) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    filter.push(char);
+                    self.update_matches();
+                    return PickerAction::None;
+                }
+                KeyCode::Backspace => {
+                    filter.pop();
+                    self.update_matches();
+                    return PickerAction::None;
+                }
+                KeyCode::Char(_) => return PickerAction::None,
+                _ => (),
+            }
+        }
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return PickerAction::Cancel,
+            KeyCode::Enter => {
+                return match self
+                    .state
+                    .selected()
+                    .and_then(|index| self.matches.get(index))
+                    .and_then(|index| self.entries.get(*index))
+                {
+                    Some(entry) => PickerAction::Select(entry.clone()),
+                    None => PickerAction::Cancel,
+                };
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.state.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => self.state.select_previous(),
+            KeyCode::Home | KeyCode::Char('g') => self.state.select_first(),
+            KeyCode::End | KeyCode::Char('G') => self.state.select_last(),
+            _ => (),
+        }
+        PickerAction::None
+    }
+
+    /// Render the popup in the middle of the frame
+    pub fn render_widget(&mut self, frame: &mut Frame) {
+        let area = centered_rect(frame.area(), 60, 50);
+        let block = create_popup_block(self.title);
+        frame.render_widget(Clear, area);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [filter_area, list_area, help_area] = Layout::vertical([
+            Constraint::Length(self.filter.is_some() as u16),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+        if let Some(filter) = &self.filter {
+            frame.render_widget(
+                Line::from(vec!["> ".fg(Color::DarkGray), filter.as_str().into()]),
+                filter_area,
+            );
+        }
+        if self.entries.is_empty() {
+            frame.render_widget(
+                Paragraph::new(self.empty_text).fg(Color::DarkGray),
+                list_area,
+            );
+        } else {
+            let entries = self
+                .matches
+                .iter()
+                .map(|index| self.entries[*index].as_str());
+            let list = List::new(entries)
+                .highlight_style(Style::default().bg(get_env().jj_config.highlight_color()));
+            frame.render_stateful_widget(list, list_area, &mut self.state);
+        }
+
+        let select_keys = if self.filter.is_some() {
+            "↑/↓"
+        } else {
+            "j/k"
+        };
+        let help = Paragraph::new(format!(
+            "{select_keys}: select  Enter: {}  Esc: cancel",
+            self.select_text
+        ))
+        .fg(Color::DarkGray)
+        .centered();
+        frame.render_widget(help, help_area);
+    }
+}
@@ -0,0 +1,122 @@
+/*! The pull request popup creates a pull request from a pushed bookmark,
+with the title and body prefilled from the description of its change.
+
+ The UI looks like this
+ ~~~
+    Add the pull request popup
+
+    Create a pull request from a bookmark, with the
+    CLI of the forge.
+
+    Ctrl+s: create  Esc: cancel
+~~~
+The first line is the title, the following ones the body.
+*/
+
+use ratatui::Frame;
+use ratatui::crossterm::event::Event;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::crossterm::event::KeyEventKind;
+use ratatui::crossterm::event::KeyModifiers;
+use ratatui::layout::Constraint;
+use ratatui::layout::Layout;
+use ratatui::style::Color;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Clear;
+use ratatui::widgets::Paragraph;
+use ratatui_textarea::TextArea;
+
+use crate::ui::styles::create_popup_block;
+use crate::ui::utils::centered_rect;
+
+/// What the bookmarks tab should do after an input to the popup
+pub enum PullRequestAction {
+    /// Keep the popup open
+    None,
+    /// Close the popup without creating the pull request
+    Cancel,
+    /// Close the popup and create the pull request
+    Create { title: String, body: String },
+}
+
+/// A transient popup for editing the title and body of a pull request
+pub struct PullRequestPopup<'a> {
+    /// Bookmark the pull request is created from
+    pub bookmark: String,
+    /// What the forge calls pull requests, e.g. merge request
+    name: &'static str,
+    textarea: TextArea<'a>,
+    /// Why the pull request can't be created, if Ctrl+s was pressed
+    error: Option<&'static str>,
+}
+
+impl PullRequestPopup<'_> {
+    pub fn new(bookmark: String, name: &'static str, description: &str) -> Self {
+        let lines: Vec<String> = description.lines().map(str::to_owned).collect();
+        Self {
+            bookmark,
+            name,
+            textarea: if lines.is_empty() {
+                TextArea::default()
+            } else {
+                TextArea::new(lines)
+            },
+            error: None,
+        }
+    }
+
+    /// Process the input event, and tell if the popup should close
+    pub fn handle_input(&mut self, event: Event) -> PullRequestAction {
+        if let Event::Key(key) = event {
+            if key.kind != KeyEventKind::Press {
+                return PullRequestAction::None;
+            }
+            match key.code {
+                KeyCode::Esc => return PullRequestAction::Cancel,
+                KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    let message = self.textarea.lines().join("\n");
+                    let (title, body) = message.split_once('\n').unwrap_or((&message, ""));
+                    if title.trim().is_empty() {
+                        self.error = Some("Write the title on the first line");
+                        return PullRequestAction::None;
+                    }
+                    return PullRequestAction::Create {
+                        title: title.trim().to_owned(),
+                        body: body.trim().to_owned(),
+                    };
+                }
+                _ => (),
+            }
+        }
+        self.textarea.input(event);
+        PullRequestAction::None
+    }
+
+    /// Render the popup in the middle of the frame
+    pub fn render_widget(&self, frame: &mut Frame) {
+        let area = centered_rect(frame.area(), 60, 50);
+        let title = format!("Create {} from {}", self.name, self.bookmark);
+        let block = create_popup_block(&title);
+        frame.render_widget(Clear, area);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [textarea_area, error_area, help_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+        frame.render_widget(&self.textarea, textarea_area);
+        if let Some(error) = self.error {
+            frame.render_widget(Line::from(error).fg(Color::Red).centered(), error_area);
+        }
+        frame.render_widget(
+            Paragraph::new("First line: title  Ctrl+s: create  Esc: cancel")
+                .fg(Color::DarkGray)
+                .centered(),
+            help_area,
+        );
+    }
+}
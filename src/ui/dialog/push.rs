@@ -0,0 +1,150 @@
+/*! The push popup previews a push with `jj git push --dry-run` before
+pushing.
+
+ The UI looks like this
+ ~~~
+    Push the bookmarks of change qpvuntsm?
+
+    Changes to push to origin:
+      Move forward bookmark main from 3ba7e7fd to 90fe0a96
+      Add bookmark push-qpvuntsm to 90fe0a96
+
+    Enter: push  j/k: scroll  Esc: cancel
+~~~
+New bookmarks are green, bookmarks moved backwards or sideways yellow
+and deleted bookmarks red.
+*/
+
+use ratatui::Frame;
+use ratatui::crossterm::event::Event;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::crossterm::event::KeyEventKind;
+use ratatui::layout::Constraint;
+use ratatui::layout::Layout;
+use ratatui::style::Color;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Clear;
+use ratatui::widgets::Paragraph;
+
+use crate::commander::jj::PushTarget;
+use crate::ui::styles::create_popup_block;
+use crate::ui::utils::centered_rect_fixed;
+
+/// What the log tab should do after an input to the popup
+pub enum PushAction {
+    /// Keep the popup open
+    None,
+    /// Close the popup without pushing
+    Cancel,
+    /// Close the popup and push
+    Push,
+}
+
+/// A transient popup confirming a push
+pub struct PushPopup {
+    pub target: PushTarget,
+    /// Push new bookmarks, `--allow-new`
+    pub allow_new: bool,
+    question: String,
+    /// Lines of `jj git push --dry-run`, e.g.
+    /// `Add bookmark push-qpvuntsm to 90fe0a96`
+    changes: Vec<String>,
+    scroll: u16,
+}
+
+impl PushPopup {
+    pub fn new(
+        target: PushTarget,
+        allow_new: bool,
+        question: String,
+        changes: Vec<String>,
+    ) -> Self {
+        Self {
+            target,
+            allow_new,
+            question,
+            changes,
+            scroll: 0,
+        }
+    }
+
+    /// Process the input event, and tell if the popup should close
+    pub fn handle_input(&mut self, event: Event) -> PushAction {
+        let Event::Key(key) = event else {
+            return PushAction::None;
+        };
+        if key.kind != KeyEventKind::Press {
+            return PushAction::None;
+        }
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('n') => return PushAction::Cancel,
+            KeyCode::Enter | KeyCode::Char('y') => return PushAction::Push,
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.scroll = (self.scroll + 1).min(self.changes.len().saturating_sub(1) as u16)
+            }
+            KeyCode::Char('k') | KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            _ => (),
+        }
+        PushAction::None
+    }
+
+    /// Render the popup in the middle of the frame
+    pub fn render_widget(&self, frame: &mut Frame) {
+        let warning_height = if self.allow_new { 2 } else { 0 };
+        let height =
+            (self.changes.len() as u16 + warning_height + 6).clamp(8, frame.area().height * 2 / 3);
+        let area = centered_rect_fixed(frame.area(), 80, height);
+        let block = create_popup_block("Push");
+        frame.render_widget(Clear, area);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [question_area, changes_area, warning_area, help_area] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Fill(1),
+            Constraint::Length(warning_height),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+
+        frame.render_widget(Line::from(self.question.as_str()).bold(), question_area);
+        let changes: Vec<Line> = self
+            .changes
+            .iter()
+            .map(|change| change_line(change))
+            .collect();
+        frame.render_widget(
+            Paragraph::new(changes).scroll((self.scroll, 0)),
+            changes_area,
+        );
+        if self.allow_new {
+            let warning =
+                Line::from("New bookmarks are pushed too (--allow-new)").fg(Color::Yellow);
+            frame.render_widget(Paragraph::new(vec![Line::default(), warning]), warning_area);
+        }
+        frame.render_widget(
+            Paragraph::new("Enter: push  j/k: scroll  Esc: cancel")
+                .fg(Color::DarkGray)
+                .centered(),
+            help_area,
+        );
+    }
+}
+
+/// Line of the dry run, colored by what happens to the bookmark
+fn change_line(change: &str) -> Line<'_> {
+    let line = Line::from(change);
+    let trimmed = change.trim_start();
+    if trimmed.starts_with("Add ") {
+        line.green()
+    } else if trimmed.starts_with("Delete ") {
+        line.red()
+    } else if trimmed.starts_with("Move backward ") || trimmed.starts_with("Move sideways ") {
+        line.yellow()
+    } else if trimmed.starts_with("Changes to push") {
+        line.bold()
+    } else {
+        line
+    }
+}
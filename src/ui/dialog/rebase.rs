@@ -11,13 +11,18 @@
     (*) -d rebase onto @ as new branch
     ( ) -A rebase after @
     ( ) -B rebase before @
+    jj rebase -r zsztoxlv -d umrpslui
+    Rewrites 1 change:
+      zsztoxlv 5d1c2a3b feat: rebase me
 
     Esc: Cancel    Enter: Rebase
 ~~~
 It has keyboard shortcuts s, b, r, d, shift+a, shift+b for selecting
 a radiobutton, and shortcuts Enter, Esc, q for closing the popup.
 
-
+When changes are marked in the log, they are the source instead of @.
+A change picked in the log as source comes before both. The command
+that will run is shown above the help, with the changes it rewrites.
 */
 
 use anyhow::Result;
@@ -41,6 +46,7 @@ use ratatui::widgets::Paragraph;
 use ratatui::widgets::StatefulWidget;
 
 use crate::ComponentInputResult;
+use crate::commander::ids::CommitId;
 use crate::commander::log::Head;
 use crate::commander::new_commander;
 use crate::keybinds::rebase_popup::CutOption;
@@ -48,6 +54,7 @@ use crate::keybinds::rebase_popup::PasteOption;
 use crate::keybinds::rebase_popup::PopupAction;
 use crate::ui::Component;
 use crate::ui::utils::centered_rect_fixed;
+use crate::ui::utils::rewrites_preview;
 
 type Keybinds = crate::keybinds::rebase_popup::Keybinds;
 
@@ -57,9 +64,18 @@ pub struct RebasePopup {
 
     pub source_rev: Head,
     pub target_rev: Head,
+    /// Marked changes to rebase instead of the source change
+    pub marked_sources: Vec<CommitId>,
+    /// The source is @, rather than a change picked in the log
+    pub source_is_working_copy: bool,
+    /// The rebase was run, rather than cancelled
+    pub rebased: bool,
 
     pub source_mode: CutOption,
     pub target_mode: PasteOption,
+    /// Preview of the changes the rebase rewrites, made again when the
+    /// configuration changes
+    rewrites: Vec<Line<'static>>,
 }
 
 impl RebasePopup {
@@ -68,15 +84,49 @@ impl RebasePopup {
             keybinds: Keybinds::default(),
             source_rev,
             target_rev,
+            marked_sources: vec![],
+            source_is_working_copy: true,
+            rebased: false,
             source_mode: CutOption::SingleRevision,
             target_mode: PasteOption::NewBranch,
+            rewrites: vec![],
         }
     }
 
+    /// Preview the changes the rebase rewrites. Called once the sources
+    /// are set, as the preview runs the rebase.
+    pub fn with_preview(mut self) -> Self {
+        self.refresh_preview();
+        self
+    }
+
+    /// Preview the changes the rebase is currently configured to rewrite
+    fn refresh_preview(&mut self) {
+        let [src_mode, src_rev, tgt_mode, tgt_rev] = self.command_args(false);
+        self.rewrites = rewrites_preview(|commander| {
+            commander.run_rebase(&src_mode, &src_rev, &tgt_mode, &tgt_rev)?;
+            Ok(())
+        });
+    }
+
+    /// Rebase the marked changes instead of the source change
+    pub fn with_marked_sources(mut self, marked_sources: Vec<CommitId>) -> Self {
+        self.marked_sources = marked_sources;
+        self
+    }
+
+    /// Rebase a change picked in the log instead of @
+    pub fn with_picked_source(mut self, source_rev: Head) -> Self {
+        self.source_rev = source_rev;
+        self.source_is_working_copy = false;
+        self
+    }
+
     /// Collect all the rendering code that would have been in
     /// log_tab.rs/draw
     pub fn render_widget(&mut self, frame: &mut Frame) {
-        let area = centered_rect_fixed(frame.area(), 32, 12);
+        let height = 14 + self.rewrites.len() as u16;
+        let area = centered_rect_fixed(frame.area(), 64, height);
         self.draw(frame, area)
             .expect("Expected drawing without failues");
     }
@@ -90,10 +140,27 @@ impl RebasePopup {
         PopupAction::None
     }
 
-    /// Run the command that the popup is currently configured to do
-    fn run_command(&self) -> Result<()> {
-        let src_rev = self.source_rev.commit_id.as_str();
-        let tgt_rev = self.target_rev.commit_id.as_str();
+    /// Arguments of the rebase the popup is currently configured to
+    /// do: source mode, source, target mode and target. With `short`
+    /// the commit ids are shortened for display.
+    fn command_args(&self, short: bool) -> [String; 4] {
+        let id = |commit_id: &CommitId| -> String {
+            if short {
+                commit_id.as_str().chars().take(8).collect()
+            } else {
+                commit_id.as_str().to_owned()
+            }
+        };
+        let src_rev = if self.marked_sources.is_empty() {
+            id(&self.source_rev.commit_id)
+        } else {
+            self.marked_sources
+                .iter()
+                .map(id)
+                .collect::<Vec<_>>()
+                .join("|")
+        };
+        let tgt_rev = id(&self.target_rev.commit_id);
         let src_mode = match self.source_mode {
             CutOption::IncludeDescendants => "-s",
             CutOption::IncludeBranch => "-b",
@@ -104,7 +171,13 @@ impl RebasePopup {
             PasteOption::InsertAfter => "-A",
             PasteOption::InsertBefore => "-B",
         };
-        new_commander().run_rebase(src_mode, src_rev, tgt_mode, tgt_rev)?;
+        [src_mode.to_owned(), src_rev, tgt_mode.to_owned(), tgt_rev]
+    }
+
+    /// Run the command that the popup is currently configured to do
+    fn run_command(&self) -> Result<()> {
+        let [src_mode, src_rev, tgt_mode, tgt_rev] = self.command_args(false);
+        new_commander().run_rebase(&src_mode, &src_rev, &tgt_mode, &tgt_rev)?;
         Ok(())
     }
 
@@ -118,11 +191,22 @@ impl RebasePopup {
         match self.match_event(event) {
             PopupAction::Ok => {
                 self.run_command()?;
+                self.rebased = true;
                 return Ok(true);
             }
             PopupAction::Cancel => return Ok(true),
-            PopupAction::SetSourceMode(m) => self.source_mode = m,
-            PopupAction::SetTargetMode(m) => self.target_mode = m,
+            PopupAction::SetSourceMode(m) => {
+                if self.source_mode != m {
+                    self.source_mode = m;
+                    self.refresh_preview();
+                }
+            }
+            PopupAction::SetTargetMode(m) => {
+                if self.target_mode != m {
+                    self.target_mode = m;
+                    self.refresh_preview();
+                }
+            }
             PopupAction::None => (),
         }
         Ok(false)
@@ -153,6 +237,8 @@ impl Component for RebasePopup {
                     Constraint::Min(3),    // buttons for source mode
                     Constraint::Length(1), // title "Target"
                     Constraint::Min(3),    // buttons for target mode
+                    Constraint::Length(1), // command preview
+                    Constraint::Length(self.rewrites.len() as u16),
                     Constraint::Length(2), // help text
                 ]
                 .as_ref(),
@@ -172,12 +258,14 @@ impl Component for RebasePopup {
             CutOption::IncludeBranch => 1,
             CutOption::SingleRevision => 2,
         };
-        frame.render_widget(
-            Paragraph::new(Span::raw(format!(
-                "Source @ {src_change_id} {src_commit_id}"
-            ))),
-            chunks[0],
-        );
+        let source = if !self.marked_sources.is_empty() {
+            format!("Source {} marked changes", self.marked_sources.len())
+        } else if self.source_is_working_copy {
+            format!("Source @ {src_change_id} {src_commit_id}")
+        } else {
+            format!("Source {src_change_id} {src_commit_id}")
+        };
+        frame.render_widget(Paragraph::new(Span::raw(source)), chunks[0]);
         frame.render_stateful_widget(RadioButton::new(src_options), chunks[1], &mut src_select);
 
         // Radio buttons for target
@@ -199,13 +287,25 @@ impl Component for RebasePopup {
         );
         frame.render_stateful_widget(RadioButton::new(tgt_options), chunks[3], &mut tgt_select);
 
+        // Preview of the command
+        let preview = format!("jj rebase {}", self.command_args(true).join(" "));
+        frame.render_widget(
+            Paragraph::new(Span::styled(preview, Style::default().fg(Color::DarkGray))),
+            chunks[4],
+        );
+
+        // Changes the rebase rewrites
+        if !self.rewrites.is_empty() {
+            frame.render_widget(Paragraph::new(Text::from(self.rewrites.clone())), chunks[5]);
+        }
+
         // Help on terminating dialog
         frame.render_widget(
             Paragraph::new(Text::from(vec![
                 Line::raw(""),
                 Line::raw("Esc: Cancel    Enter: Rebase"),
             ])),
-            chunks[4],
+            chunks[6],
         );
 
         Ok(())
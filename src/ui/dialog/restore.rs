@@ -0,0 +1,149 @@
+/*! The restore popup restores files of a change from another change.
+
+ The UI looks like this
+ ~~~
+    [x] M src/main.rs
+    [ ] A src/restore.rs
+    [x] D README.md
+
+    Space: toggle  a: all  Enter: restore  Esc: cancel
+~~~
+The files are the ones that differ between the two changes. The checked
+files of the destination get the content they have in the source.
+*/
+
+use ratatui::Frame;
+use ratatui::crossterm::event::Event;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::crossterm::event::KeyEventKind;
+use ratatui::layout::Constraint;
+use ratatui::layout::Layout;
+use ratatui::style::Color;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Clear;
+use ratatui::widgets::List;
+use ratatui::widgets::ListState;
+use ratatui::widgets::Paragraph;
+
+use crate::commander::files::File;
+use crate::commander::log::Head;
+use crate::ui::styles::create_popup_block;
+use crate::ui::utils::centered_rect_fixed;
+
+/// What the log tab should do after an input to the popup
+pub enum RestoreAction {
+    /// Keep the popup open
+    None,
+    /// Close the popup without restoring
+    Cancel,
+    /// Close the popup and restore the paths
+    Restore { paths: Vec<String> },
+}
+
+/// A transient popup for picking the files to restore
+pub struct RestorePopup {
+    /// Change to restore the files from
+    pub source: Head,
+    /// Change whose files are restored
+    pub destination: Head,
+    /// Files that differ between the changes, checked if restored
+    files: Vec<(File, bool)>,
+    list_state: ListState,
+    /// Why the files can't be restored, if Enter was pressed
+    error: Option<&'static str>,
+}
+
+impl RestorePopup {
+    pub fn new(source: Head, destination: Head, files: Vec<File>) -> Self {
+        Self {
+            source,
+            destination,
+            files: files.into_iter().map(|file| (file, false)).collect(),
+            list_state: ListState::default().with_selected(Some(0)),
+            error: None,
+        }
+    }
+
+    /// Process the input event, and tell if the popup should close
+    pub fn handle_input(&mut self, event: Event) -> RestoreAction {
+        let Event::Key(key) = event else {
+            return RestoreAction::None;
+        };
+        if key.kind != KeyEventKind::Press {
+            return RestoreAction::None;
+        }
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return RestoreAction::Cancel,
+            KeyCode::Char('j') | KeyCode::Down => self.list_state.select_next(),
+            KeyCode::Char('k') | KeyCode::Up => self.list_state.select_previous(),
+            KeyCode::Char(' ') => {
+                if let Some(index) = self.list_state.selected()
+                    && let Some((_, checked)) = self.files.get_mut(index)
+                {
+                    *checked = !*checked;
+                }
+            }
+            KeyCode::Char('a') => {
+                let check = !self.files.iter().all(|(_, checked)| *checked);
+                for (_, checked) in self.files.iter_mut() {
+                    *checked = check;
+                }
+            }
+            KeyCode::Enter => {
+                let paths: Vec<String> = self
+                    .files
+                    .iter()
+                    .filter(|(_, checked)| *checked)
+                    .flat_map(|(file, _)| file.paths())
+                    .collect();
+                if !paths.is_empty() {
+                    return RestoreAction::Restore { paths };
+                }
+                self.error = Some("Check the files to restore");
+            }
+            _ => (),
+        }
+        RestoreAction::None
+    }
+
+    /// Render the popup in the middle of the frame
+    pub fn render_widget(&mut self, frame: &mut Frame) {
+        let height = (self.files.len() as u16 + 5).clamp(9, frame.area().height * 2 / 3);
+        let area = centered_rect_fixed(frame.area(), 74, height);
+        let title = format!(
+            "Restore from {} into {}",
+            self.source.change_id.short(),
+            self.destination.change_id.short()
+        );
+        let block = create_popup_block(&title);
+        frame.render_widget(Clear, area);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [list_area, error_area, help_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+
+        let items = self.files.iter().map(|(file, checked)| {
+            let check = if *checked { "[x] " } else { "[ ] " };
+            Line::from(format!("{check}{}", file.line))
+        });
+        let list = List::new(items).highlight_style(Style::default().reversed());
+        frame.render_stateful_widget(list, list_area, &mut self.list_state);
+
+        if let Some(error) = self.error {
+            frame.render_widget(Line::from(error).fg(Color::Red).centered(), error_area);
+        }
+        frame.render_widget(
+            Paragraph::new("Space: toggle  a: all  Enter: restore  Esc: cancel")
+                .fg(Color::DarkGray)
+                .centered(),
+            help_area,
+        );
+    }
+}
@@ -0,0 +1,288 @@
+/*! The sparse popup edits the sparse patterns of the working copy
+(`jj sparse`), the directories and files it has. Patterns are checked
+when added, and applying patterns that remove files from the working copy
+tells which first, to be confirmed.
+
+ The UI looks like this
+ ~~~
+    src
+    + docs
+
+    2 files would be removed from the working copy: README.md, Cargo.toml
+
+    j/k: select  a: add  d: remove  w: apply  Esc: close
+~~~
+*/
+
+use anyhow::Result;
+use ratatui::Frame;
+use ratatui::crossterm::event::Event;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::crossterm::event::KeyEventKind;
+use ratatui::layout::Constraint;
+use ratatui::layout::Layout;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Block;
+use ratatui::widgets::Clear;
+use ratatui::widgets::List;
+use ratatui::widgets::ListState;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::Wrap;
+use ratatui_textarea::TextArea;
+
+use crate::ComponentInputResult;
+use crate::commander::CommandError;
+use crate::commander::new_commander;
+use crate::ui::Component;
+use crate::ui::ComponentAction;
+use crate::ui::dialog::MessagePopup;
+use crate::ui::styles::create_popup_block;
+use crate::ui::utils::centered_rect;
+use crate::ui::utils::sparse_removed_files;
+use crate::ui::utils::validate_sparse_pattern;
+
+/// Removed files listed in the warning at most
+const MAX_LISTED_FILES: usize = 5;
+
+/// A popup for editing the sparse patterns of the working copy
+pub struct SparsePopup<'a> {
+    /// Patterns of the working copy
+    current: Vec<String>,
+    /// Patterns as edited
+    patterns: Vec<String>,
+    /// Files of the working copy change, to find the removed ones
+    files: Vec<String>,
+    list_state: ListState,
+    /// Pattern being added
+    adding: Option<TextArea<'a>>,
+    /// Applying was asked once, and removes files
+    confirming: bool,
+    error: Option<&'static str>,
+}
+
+impl SparsePopup<'_> {
+    pub fn new() -> Result<Self, CommandError> {
+        let commander = new_commander();
+        let current = commander.get_sparse_patterns()?;
+        let head = commander
+            .get_current_head()
+            .map_err(|err| CommandError::Status(format!("{err:#}"), None))?;
+        let files = commander.get_file_list(&head.commit_id)?;
+        Ok(Self {
+            patterns: current.clone(),
+            current,
+            files,
+            list_state: ListState::default().with_selected(Some(0)),
+            adding: None,
+            confirming: false,
+            error: None,
+        })
+    }
+
+    fn removed_files(&self) -> Vec<&str> {
+        sparse_removed_files(&self.files, &self.current, &self.patterns)
+    }
+
+    fn select(&mut self, index: usize) {
+        self.list_state
+            .select(Some(index.min(self.patterns.len().saturating_sub(1))));
+    }
+
+    fn edited(&mut self) {
+        self.confirming = false;
+        self.error = None;
+    }
+
+    /// Add the pattern being typed, if it is valid
+    fn add_pattern(&mut self) {
+        let Some(adding) = self.adding.as_ref() else {
+            return;
+        };
+        match validate_sparse_pattern(&adding.lines().join(""), &self.patterns) {
+            Ok(pattern) => {
+                self.patterns.push(pattern);
+                self.adding = None;
+                self.edited();
+                self.select(self.patterns.len() - 1);
+            }
+            Err(err) => self.error = Some(err),
+        }
+    }
+
+    /// Apply the patterns, once confirmed if they remove files
+    fn apply(&mut self) -> Option<ComponentAction> {
+        if self.patterns == self.current {
+            self.error = Some("The patterns are the same as the working copy's");
+            return None;
+        }
+        if !self.confirming && !self.removed_files().is_empty() {
+            self.confirming = true;
+            return None;
+        }
+        Some(match new_commander().set_sparse_patterns(&self.patterns) {
+            Ok(messages) if !messages.trim().is_empty() => ComponentAction::Multiple(vec![
+                ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                    "Sparse patterns",
+                    messages,
+                )))),
+                ComponentAction::RefreshTab(),
+            ]),
+            Ok(_) => ComponentAction::Multiple(vec![
+                ComponentAction::SetPopup(None),
+                ComponentAction::RefreshTab(),
+            ]),
+            Err(err) => ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                "Can't set sparse patterns",
+                err.to_string(),
+            )))),
+        })
+    }
+
+    /// What applying the patterns removes, and the confirmation
+    fn warning(&self) -> Vec<Line<'static>> {
+        let removed = self.removed_files();
+        if removed.is_empty() {
+            return vec![];
+        }
+        let mut listed = removed
+            .iter()
+            .take(MAX_LISTED_FILES)
+            .copied()
+            .collect::<Vec<_>>()
+            .join(", ");
+        if removed.len() > MAX_LISTED_FILES {
+            listed.push_str(&format!(" and {} more", removed.len() - MAX_LISTED_FILES));
+        }
+        let mut lines = vec![
+            Line::from(format!(
+                "{} {} would be removed from the working copy: {listed}",
+                removed.len(),
+                if removed.len() == 1 { "file" } else { "files" },
+            ))
+            .fg(Color::Yellow),
+        ];
+        if self.confirming {
+            lines.push(Line::from("Press w again to apply and remove them").fg(Color::Red));
+        }
+        lines
+    }
+}
+
+impl Component for SparsePopup<'_> {
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let area = centered_rect(area, 60, 60);
+        let block = create_popup_block("Sparse patterns");
+        f.render_widget(Clear, area);
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        let warning = self.warning();
+        let [list_area, input_area, warning_area, error_area, help_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(if self.adding.is_some() { 3 } else { 0 }),
+            Constraint::Length(warning.len() as u16 * 2),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+
+        let items = self.patterns.iter().map(|pattern| {
+            if self.current.contains(pattern) {
+                Line::from(format!("  {pattern}"))
+            } else {
+                Line::from(format!("+ {pattern}")).fg(Color::Green)
+            }
+        });
+        let list = List::new(items).highlight_style(Style::default().reversed());
+        if self.patterns.is_empty() {
+            f.render_widget(
+                Line::from("No patterns, the working copy has no files").fg(Color::DarkGray),
+                list_area,
+            );
+        } else {
+            f.render_stateful_widget(list, list_area, &mut self.list_state);
+        }
+
+        if let Some(adding) = self.adding.as_mut() {
+            adding.set_block(Block::bordered().title(" Add pattern, e.g. src/ui "));
+            f.render_widget(&*adding, input_area);
+        }
+        f.render_widget(
+            Paragraph::new(warning).wrap(Wrap { trim: false }),
+            warning_area,
+        );
+        if let Some(error) = self.error {
+            f.render_widget(Line::from(error).fg(Color::Red).centered(), error_area);
+        }
+        let help = if self.adding.is_some() {
+            "Enter: add  Esc: cancel"
+        } else {
+            "j/k: select  a: add  d: remove  w: apply  Esc: close"
+        };
+        f.render_widget(
+            Paragraph::new(help).fg(Color::DarkGray).centered(),
+            help_area,
+        );
+        Ok(())
+    }
+
+    fn input(&mut self, event: Event) -> Result<ComponentInputResult> {
+        if let Some(adding) = self.adding.as_mut() {
+            if let Event::Key(key) = event
+                && key.kind == KeyEventKind::Press
+            {
+                match key.code {
+                    KeyCode::Enter => {
+                        self.add_pattern();
+                        return Ok(ComponentInputResult::Handled);
+                    }
+                    KeyCode::Esc => {
+                        self.adding = None;
+                        self.error = None;
+                        return Ok(ComponentInputResult::Handled);
+                    }
+                    _ => (),
+                }
+            }
+            adding.input(event);
+            return Ok(ComponentInputResult::Handled);
+        }
+
+        let Event::Key(key) = event else {
+            return Ok(ComponentInputResult::Handled);
+        };
+        if key.kind != KeyEventKind::Press {
+            return Ok(ComponentInputResult::Handled);
+        }
+        let selected = self.list_state.selected().unwrap_or_default();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                return Ok(ComponentInputResult::HandledAction(
+                    ComponentAction::SetPopup(None),
+                ));
+            }
+            KeyCode::Char('j') | KeyCode::Down => self.select(selected + 1),
+            KeyCode::Char('k') | KeyCode::Up => self.select(selected.saturating_sub(1)),
+            KeyCode::Char('a') => {
+                self.adding = Some(TextArea::default());
+                self.error = None;
+            }
+            KeyCode::Char('d') if selected < self.patterns.len() => {
+                self.patterns.remove(selected);
+                self.edited();
+                self.select(selected);
+            }
+            KeyCode::Char('w') => {
+                if let Some(action) = self.apply() {
+                    return Ok(ComponentInputResult::HandledAction(action));
+                }
+            }
+            _ => (),
+        }
+        Ok(ComponentInputResult::Handled)
+    }
+}
@@ -0,0 +1,216 @@
+/*! The split popup splits a change in two by its files.
+
+ The UI looks like this
+ ~~~
+    [x] M src/main.rs
+    [ ] A src/split.rs
+    [x] M README.md
+
+    Space: toggle  a: all  Enter: next  Esc: cancel
+~~~
+The checked files go into the first change, the others into the
+second. Enter then asks for the description of the first and of the
+second change in turn, both starting with the description of the
+change. Ctrl+s accepts a description, Esc goes back a step.
+*/
+
+use ratatui::Frame;
+use ratatui::crossterm::event::Event;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::crossterm::event::KeyEventKind;
+use ratatui::crossterm::event::KeyModifiers;
+use ratatui::layout::Constraint;
+use ratatui::layout::Layout;
+use ratatui::style::Color;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Clear;
+use ratatui::widgets::List;
+use ratatui::widgets::ListState;
+use ratatui::widgets::Paragraph;
+use ratatui_textarea::TextArea;
+
+use crate::commander::files::File;
+use crate::ui::styles::create_popup_block;
+use crate::ui::utils::centered_rect_fixed;
+
+/// What the log tab should do after an input to the popup
+pub enum SplitAction {
+    /// Keep the popup open
+    None,
+    /// Close the popup without splitting
+    Cancel,
+    /// Close the popup and split the paths into the first change
+    Split {
+        paths: Vec<String>,
+        first_message: String,
+        second_message: String,
+    },
+}
+
+/// Step of the split
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SplitStep {
+    Files,
+    /// Description of the first (0) or second (1) change
+    Description(usize),
+}
+
+/// A transient popup for splitting a change by its files
+pub struct SplitPopup<'a> {
+    /// Files of the change, checked if they go into the first change
+    files: Vec<(File, bool)>,
+    list_state: ListState,
+    step: SplitStep,
+    /// Descriptions of the first and second change
+    descriptions: [TextArea<'a>; 2],
+    /// Why the files can't be split as checked, if Enter was pressed
+    error: Option<&'static str>,
+}
+
+impl SplitPopup<'_> {
+    /// Create the popup with the files of the change and its
+    /// description
+    pub fn new(files: Vec<File>, description: &str) -> Self {
+        let description = || match description {
+            "" => TextArea::default(),
+            description => TextArea::new(description.lines().map(String::from).collect()),
+        };
+        Self {
+            files: files.into_iter().map(|file| (file, false)).collect(),
+            list_state: ListState::default().with_selected(Some(0)),
+            step: SplitStep::Files,
+            descriptions: [description(), description()],
+            error: None,
+        }
+    }
+
+    /// Paths of the checked files
+    fn checked_paths(&self) -> Vec<String> {
+        self.files
+            .iter()
+            .filter(|(_, checked)| *checked)
+            .flat_map(|(file, _)| file.paths())
+            .collect()
+    }
+
+    /// Process the input event, and tell if the popup should close
+    pub fn handle_input(&mut self, event: Event) -> SplitAction {
+        let Event::Key(key) = event else {
+            return SplitAction::None;
+        };
+        if key.kind != KeyEventKind::Press {
+            return SplitAction::None;
+        }
+        match self.step {
+            SplitStep::Files => match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => return SplitAction::Cancel,
+                KeyCode::Char('j') | KeyCode::Down => self.list_state.select_next(),
+                KeyCode::Char('k') | KeyCode::Up => self.list_state.select_previous(),
+                KeyCode::Char(' ') => {
+                    if let Some(index) = self.list_state.selected()
+                        && let Some((_, checked)) = self.files.get_mut(index)
+                    {
+                        *checked = !*checked;
+                    }
+                }
+                KeyCode::Char('a') => {
+                    let check = !self.files.iter().all(|(_, checked)| *checked);
+                    for (_, checked) in self.files.iter_mut() {
+                        *checked = check;
+                    }
+                }
+                KeyCode::Enter => {
+                    let checked = self.files.iter().filter(|(_, checked)| *checked).count();
+                    self.error = if checked == 0 {
+                        Some("Check the files of the first change")
+                    } else if checked == self.files.len() {
+                        Some("Leave some files for the second change")
+                    } else {
+                        self.step = SplitStep::Description(0);
+                        None
+                    };
+                }
+                _ => (),
+            },
+            SplitStep::Description(index) => match key.code {
+                KeyCode::Esc => {
+                    self.step = match index {
+                        0 => SplitStep::Files,
+                        _ => SplitStep::Description(index - 1),
+                    }
+                }
+                KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    if index == 0 {
+                        self.step = SplitStep::Description(1);
+                    } else {
+                        let [first, second] = &self.descriptions;
+                        return SplitAction::Split {
+                            paths: self.checked_paths(),
+                            first_message: first.lines().join("\n"),
+                            second_message: second.lines().join("\n"),
+                        };
+                    }
+                }
+                _ => {
+                    self.descriptions[index].input(event);
+                }
+            },
+        }
+        SplitAction::None
+    }
+
+    /// Render the popup in the middle of the frame
+    pub fn render_widget(&mut self, frame: &mut Frame) {
+        let height = (self.files.len() as u16 + 5).clamp(9, frame.area().height * 2 / 3);
+        let area = centered_rect_fixed(frame.area(), 74, height);
+        let title = match self.step {
+            SplitStep::Files => "Split: files of the first change",
+            SplitStep::Description(0) => "Split: description of the first change",
+            SplitStep::Description(_) => "Split: description of the second change",
+        };
+        let block = create_popup_block(title);
+        frame.render_widget(Clear, area);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [content_area, error_area, help_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+
+        let help = match self.step {
+            SplitStep::Files => {
+                let items = self.files.iter().map(|(file, checked)| {
+                    let check = if *checked { "[x] " } else { "[ ] " };
+                    Line::from(format!("{check}{}", file.line))
+                });
+                let list = List::new(items).highlight_style(Style::default().reversed());
+                frame.render_stateful_widget(list, content_area, &mut self.list_state);
+                "Space: toggle  a: all  Enter: next  Esc: cancel"
+            }
+            SplitStep::Description(index) => {
+                let description = &mut self.descriptions[index];
+                description.set_cursor_line_style(Style::default());
+                frame.render_widget(&*description, content_area);
+                if index == 0 {
+                    "Ctrl+s: next  Esc: back"
+                } else {
+                    "Ctrl+s: split  Esc: back"
+                }
+            }
+        };
+        if let Some(error) = self.error
+            && self.step == SplitStep::Files
+        {
+            frame.render_widget(Line::from(error).fg(Color::Red).centered(), error_area);
+        }
+        frame.render_widget(
+            Paragraph::new(help).fg(Color::DarkGray).centered(),
+            help_area,
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::vec;
 
 use ansi_to_tui::IntoText;
@@ -12,6 +14,7 @@ use tracing::instrument;
 use crate::ComponentInputResult;
 use crate::commander::CommandError;
 use crate::commander::files::Conflict;
+use crate::commander::files::DiffType;
 use crate::commander::files::File;
 use crate::commander::log::Head;
 use crate::commander::new_commander;
@@ -20,27 +23,60 @@ use crate::env::JjConfig;
 use crate::env::get_env;
 use crate::ui::Component;
 use crate::ui::ComponentAction;
+use crate::ui::EditorRequest;
+use crate::ui::Selection;
+use crate::ui::dialog::ConflictPopup;
 use crate::ui::dialog::HelpPopup;
 use crate::ui::dialog::MessagePopup;
+use crate::ui::dialog::SparsePopup;
 use crate::ui::panel::DetailsPanel;
 use crate::ui::panel::TextContent;
+use crate::ui::utils::ConflictChunk;
+use crate::ui::utils::FileTree;
 use crate::ui::utils::PaneDivider;
+use crate::ui::utils::TreeRowKind;
+use crate::ui::utils::copy_to_clipboard;
+use crate::ui::utils::format_diff_output;
+use crate::ui::utils::highlight_file;
+use crate::ui::utils::parse_conflicts;
 use crate::ui::utils::tabs_to_spaces;
 
 /// Files tab. Shows files in selected change in main panel and selected file diff in details panel
 pub struct FilesTab {
     head: Head,
     is_current_head: bool,
+    /// Status view of the working copy: follows @, and lists the
+    /// untracked files after the changed ones
+    status: bool,
 
     files_output: Result<Vec<File>, CommandError>,
     conflicts_output: Vec<Conflict>,
+    /// Show all files of the change as a tree, instead of the changed
+    /// files
+    tree_view: bool,
+    /// Files of the change in the tree view
+    file_tree: Option<Result<FileTree, CommandError>>,
     files_list_state: ListState,
     files_height: u16,
+    /// Files marked for the file actions, by path
+    marked: BTreeMap<String, File>,
 
     pub file: Option<File>,
     diff_panel: DetailsPanel,
     diff_output: Result<Option<String>, CommandError>,
     diff_format: DiffFormat,
+    /// Panel width used to render diff_output
+    diff_width: usize,
+    /// Show the diff even if it is longer than the configured limit
+    diff_loaded: bool,
+    /// Number of changed lines of each file of the change, if diffs
+    /// that are too long are not loaded before they are asked for
+    diff_sizes: HashMap<String, usize>,
+    /// Number of changed lines of the current file, when its diff is
+    /// too long to load before it is asked for
+    unloaded_lines: Option<usize>,
+    /// Show the content of the file in the change instead of its diff
+    show_content: bool,
 
     config: JjConfig,
     pane_divider: PaneDivider,
@@ -61,29 +97,76 @@ fn get_current_file_index(
     None
 }
 
+/// Changed files of the change, then in the status view the untracked
+/// files of the working copy
+fn get_files(head: &Head, status: bool) -> Result<Vec<File>, CommandError> {
+    let mut files = new_commander().get_files(head)?;
+    if status {
+        files.extend(new_commander().get_untracked_files()?);
+    }
+    Ok(files)
+}
+
+/// Number of changed lines of each file of the change, unless all diffs
+/// are loaded regardless of their length
+fn get_diff_sizes(head: &Head) -> HashMap<String, usize> {
+    if get_env().jj_config.diff_max_lines() == 0 {
+        return HashMap::new();
+    }
+    new_commander()
+        .get_diff_file_sizes(&["-r", head.commit_id.as_str()], true)
+        .map(|sizes| sizes.into_iter().collect())
+        .unwrap_or_default()
+}
+
+/// Popup listing the ignored files of the working copy, with the
+/// pattern ignoring each
+fn ignored_files_popup() -> MessagePopup<'static> {
+    match new_commander().get_ignored_files() {
+        Ok(files) if files.is_empty() => {
+            MessagePopup::new("Ignored files", "No files of the working copy are ignored")
+        }
+        Ok(files) => {
+            let width = files
+                .iter()
+                .map(|file| file.path.chars().count())
+                .max()
+                .unwrap_or_default();
+            let lines: Vec<String> = files
+                .iter()
+                .map(|file| format!("{:width$}  {}", file.path, file.source))
+                .collect();
+            MessagePopup::new("Ignored files", lines.join("\n"))
+        }
+        Err(err) => MessagePopup::new("Can't list ignored files", format!("{err:#}")),
+    }
+}
+
 impl FilesTab {
-    #[instrument(level = "info", name = "Initializing files tab", parent = None, skip())]
     pub fn new(head: &Head) -> Result<Self> {
+        Self::with_view(head, false)
+    }
+
+    /// Status view of the working copy
+    pub fn new_status() -> Result<Self> {
+        Self::with_view(&new_commander().get_current_head()?, true)
+    }
+
+    #[instrument(level = "info", name = "Initializing files tab", parent = None, skip())]
+    fn with_view(head: &Head, status: bool) -> Result<Self> {
         let head = head.clone();
         let is_current_head = head == new_commander().get_current_head()?;
 
         let diff_format = get_env().jj_config.diff_format();
 
-        let files_output = new_commander().get_files(&head);
+        let files_output = get_files(&head, status);
+        let diff_sizes = get_diff_sizes(&head);
         let conflicts_output = new_commander().get_conflicts(&head.commit_id)?;
         let current_file = files_output
             .as_ref()
             .ok()
             .and_then(|files_output| files_output.first())
             .map(|file| file.to_owned());
-        let diff_output = current_file
-            .as_ref()
-            .map(|current_change| {
-                new_commander().get_file_diff(&head, current_change, &diff_format, true)
-            })
-            .map_or(Ok(None), |r| {
-                r.map(|diff| diff.map(|diff| tabs_to_spaces(&diff)))
-            });
 
         let files_list_state = ListState::default().with_selected(get_current_file_index(
             current_file.as_ref(),
@@ -93,30 +176,46 @@ impl FilesTab {
         let config = get_env().jj_config.clone();
         let pane_divider = PaneDivider::new(config.layout_percent());
 
-        Ok(Self {
+        let mut files_tab = Self {
             head,
             is_current_head,
+            status,
 
             files_output,
+            tree_view: !status,
+            file_tree: None,
             file: current_file,
             files_list_state,
             files_height: 0,
+            marked: BTreeMap::new(),
 
             conflicts_output,
 
-            diff_output,
+            diff_output: Ok(None),
             diff_format,
+            diff_width: 0,
+            diff_loaded: false,
+            diff_sizes,
+            unloaded_lines: None,
+            show_content: false,
             diff_panel: DetailsPanel::new(),
 
             config,
             pane_divider,
-        })
+        };
+        files_tab.refresh_tree();
+        files_tab.select_tree_file();
+        files_tab.refresh_diff()?;
+        Ok(files_tab)
     }
 
     pub fn set_head(&mut self, head: &Head) -> Result<()> {
         self.head = head.clone();
         self.is_current_head = self.head == new_commander().get_current_head()?;
 
+        // Directories expanded in another change are not kept
+        self.file_tree = None;
+        self.marked.clear();
         self.refresh_files()?;
         self.file = self
             .files_output
@@ -124,6 +223,7 @@ impl FilesTab {
             .ok()
             .and_then(|files_output| files_output.first())
             .map(|file| file.to_owned());
+        self.select_tree_file();
         self.refresh_diff()?;
 
         Ok(())
@@ -134,15 +234,127 @@ impl FilesTab {
     }
 
     pub fn refresh_files(&mut self) -> Result<()> {
-        self.files_output = new_commander().get_files(&self.head);
+        self.files_output = get_files(&self.head, self.status);
+        self.diff_sizes = get_diff_sizes(&self.head);
         self.conflicts_output = new_commander().get_conflicts(&self.head.commit_id)?;
+        self.refresh_tree();
         Ok(())
     }
 
+    /// Get the files of the change for the tree view, keeping the
+    /// expanded directories and the selection of the previous tree
+    fn refresh_tree(&mut self) {
+        if !self.tree_view {
+            self.file_tree = None;
+            return;
+        }
+        let previous = self.file_tree.take().and_then(Result::ok);
+        let changed = self.files_output.as_deref().unwrap_or_default();
+        self.file_tree = Some(
+            new_commander()
+                .get_file_list(&self.head.commit_id)
+                .map(|paths| {
+                    let mut tree = FileTree::new(paths, changed, &self.conflicts_output);
+                    if let Some(previous) = previous.as_ref() {
+                        tree.keep_state(previous);
+                    }
+                    tree
+                }),
+        );
+    }
+
+    /// Select the current file in the tree view, or make the selected
+    /// row the current file if it is not in the tree. Returns true if the
+    /// current file changed.
+    fn select_tree_file(&mut self) -> bool {
+        let Some(Ok(tree)) = self.file_tree.as_mut() else {
+            return false;
+        };
+        if let Some(file) = self.file.as_ref() {
+            tree.select_file(file);
+        }
+        let file = tree.selected_file().cloned();
+        let changed = file != self.file;
+        self.file = file;
+        changed
+    }
+
+    /// Change the tree view, and show the diff of the file it selects.
+    /// Returns false without the tree view.
+    fn update_tree(&mut self, update: impl FnOnce(&mut FileTree)) -> Result<bool> {
+        let Some(Ok(tree)) = self.file_tree.as_mut() else {
+            return Ok(false);
+        };
+        update(tree);
+        let file = tree.selected_file().cloned();
+        if file != self.file {
+            self.file = file;
+            self.refresh_diff()?;
+        }
+        Ok(true)
+    }
+
+    /// Whether a directory is selected in the tree view
+    fn is_directory_selected(&self) -> bool {
+        matches!(
+            self.file_tree.as_ref(),
+            Some(Ok(tree)) if tree
+                .selected_row()
+                .is_some_and(|row| matches!(row.kind, TreeRowKind::Directory { .. }))
+        )
+    }
+
+    /// Switch between the tree of all files and the list of changed files
+    fn toggle_tree_view(&mut self) -> Result<()> {
+        self.tree_view = !self.tree_view;
+        self.refresh_tree();
+        if self.tree_view {
+            self.select_tree_file();
+        } else if self.get_current_file_index().is_none() {
+            // Unchanged files are not in the list
+            self.file = self
+                .files_output
+                .as_ref()
+                .ok()
+                .and_then(|files_output| files_output.first())
+                .cloned();
+        }
+        self.refresh_diff()
+    }
+
     pub fn refresh_diff(&mut self) -> Result<()> {
+        self.diff_loaded = false;
+        self.load_diff();
+        self.diff_panel.scroll_to(0);
+        Ok(())
+    }
+
+    /// Get the diff of the current file, rendered for the current panel width
+    fn load_diff(&mut self) {
         let mut commander = new_commander();
         let inner_width = self.diff_panel.columns() as usize;
         commander.limit_width(inner_width);
+        if self.show_content && !self.is_untracked() {
+            self.diff_output = self
+                .file
+                .as_ref()
+                .and_then(|file| file.paths().pop())
+                .map(|path| {
+                    commander
+                        .get_file_content(&self.head.commit_id, &path)
+                        .map(|content| self.format_content(&path, &content))
+                })
+                .transpose();
+            self.diff_width = inner_width;
+            return;
+        }
+        // A diff that is too long is only loaded when asked for
+        self.unloaded_lines = self.long_diff_lines();
+        if self.unloaded_lines.is_some() {
+            self.diff_output = Ok(None);
+            self.diff_width = inner_width;
+            return;
+        }
         self.diff_output = self
             .file
             .as_ref()
@@ -150,18 +362,315 @@ impl FilesTab {
                 commander.get_file_diff(&self.head, current_file, &self.diff_format, true)
             })
             .map_or(Ok(None), |r| {
-                r.map(|diff| diff.map(|diff| tabs_to_spaces(&diff)))
+                r.map(|diff| {
+                    diff.map(|diff| format_diff_output(&diff, &self.diff_format, inner_width))
+                })
             });
-        self.diff_panel.scroll_to(0);
+        self.diff_width = inner_width;
+    }
+
+    /// Content of a file as shown in the diff panel, highlighted if
+    /// syntax highlighting is on
+    fn format_content(&self, path: &str, content: &str) -> String {
+        let content = tabs_to_spaces(content);
+        if self.config.syntax_highlighting() {
+            highlight_file(path, &content)
+        } else {
+            content
+        }
+    }
+
+    /// Number of changed lines of the current file, if its diff is too
+    /// long to load before it is asked for
+    fn long_diff_lines(&self) -> Option<usize> {
+        let max_lines = self.config.diff_max_lines();
+        let lines = *self.diff_sizes.get(self.file.as_ref()?.path.as_ref()?)?;
+        (!self.diff_loaded && max_lines > 0 && lines > max_lines).then_some(lines)
+    }
+
+    /// Placeholder for a diff too long to show before it is asked for:
+    /// its first line and how to load the rest
+    fn truncated_diff(&self, diff: &str) -> Option<Text<'static>> {
+        let max_lines = self.config.diff_max_lines();
+        let line_count = diff.lines().count();
+        if self.diff_loaded || max_lines == 0 || line_count <= max_lines {
+            return None;
+        }
+        let mut text = diff.lines().next().unwrap_or_default().into_text().ok()?;
+        let what = if self.show_content { "file" } else { "diff" };
+        text.push_line(
+            Line::raw(format!(
+                "▸ {what} truncated, {} lines, press Enter to load",
+                line_count - 1
+            ))
+            .fg(Color::DarkGray),
+        );
+        Some(text)
+    }
+
+    /// Diff of the current file as shown in the diff panel
+    fn diff_text(&self) -> Result<Text<'static>> {
+        if let Some(lines) = self.unloaded_lines {
+            return Ok(Text::from(
+                Line::raw(format!(
+                    "▸ diff not loaded, {lines} lines changed, press Enter to load"
+                ))
+                .fg(Color::DarkGray),
+            ));
+        }
+        Ok(match self.diff_output.as_ref() {
+            Ok(Some(content)) if self.show_content && content.is_empty() => Text::from(
+                Line::from(" The file is empty")
+                    .fg(Color::DarkGray)
+                    .italic(),
+            ),
+            Ok(Some(diff_content)) if diff_content.trim().is_empty() => Text::from(
+                Line::from(" No changes to the file in this change")
+                    .fg(Color::DarkGray)
+                    .italic(),
+            ),
+            Ok(Some(diff_content)) => match self.truncated_diff(diff_content) {
+                Some(placeholder) => placeholder,
+                None => diff_content.into_text()?,
+            },
+            Ok(None) if self.is_untracked() => Text::from(
+                Line::from(" Untracked file, press x to track it")
+                    .fg(Color::DarkGray)
+                    .italic(),
+            ),
+            Ok(None) => Text::default(),
+            Err(err) => err.into_text("Error getting diff")?,
+        })
+    }
+
+    /// File and line at the copy cursor or the top of the diff panel.
+    /// Diff formats without file headers use the current file.
+    fn diff_file(&self) -> Result<Option<EditorRequest>> {
+        let content = TextContent::from(self.diff_text()?);
+        let path = self
+            .diff_panel
+            .current_file(&content)
+            .or_else(|| self.file.as_ref().and_then(|file| file.path.clone()));
+        let line = self.diff_panel.current_file_line(&content);
+        Ok(path.map(|path| EditorRequest { path, line }))
+    }
+
+    /// Draw the diff of the current file
+    fn draw_diff(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let diff_content = self.diff_text()?;
+        self.diff_panel
+            .render_context::<TextContent>(diff_content)
+            .title(if self.show_content {
+                " Content "
+            } else {
+                " Diff "
+            })
+            .git_diff(!self.show_content && self.diff_format == DiffFormat::Git)
+            .draw(f, area);
         Ok(())
     }
 
-    pub fn untrack_file(&mut self) -> Result<()> {
+    /// Lines of the tree view: the status marker of the file, then the
+    /// directories and files indented by depth
+    fn tree_lines(&self, tree: &FileTree) -> Vec<Line<'static>> {
+        if tree.rows().is_empty() {
+            return vec![
+                Line::from(" No files in change")
+                    .fg(Color::DarkGray)
+                    .italic(),
+            ];
+        }
+        tree.rows()
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let indent = "  ".repeat(row.depth);
+                let line = match &row.kind {
+                    TreeRowKind::Directory {
+                        expanded, changes, ..
+                    } => {
+                        let arrow = if *expanded { "▾" } else { "▸" };
+                        let mut line = Line::from(vec![
+                            Span::raw(format!("   {indent}{arrow} ")),
+                            Span::raw(format!("{}/", row.name)).fg(Color::Blue).bold(),
+                        ]);
+                        if *changes > 0 {
+                            line.push_span(Span::raw(format!(" ({changes})")).fg(Color::DarkGray));
+                        }
+                        line
+                    }
+                    TreeRowKind::File { file, conflict } => {
+                        let line = Line::raw(format!(
+                            "{}{} {indent}  {}",
+                            if self.is_marked(file) { "●" } else { " " },
+                            match (&file.diff_type, conflict) {
+                                (_, true) => "C",
+                                (Some(diff_type), false) => diff_type.marker(),
+                                (None, false) => " ",
+                            },
+                            row.name
+                        ));
+                        match (&file.diff_type, conflict) {
+                            (_, true) => line.fg(Color::Red),
+                            (Some(diff_type), false) => line.fg(diff_type.color()),
+                            (None, false) => line,
+                        }
+                    }
+                };
+                if i == tree.selected() {
+                    line.bg(self.config.highlight_color())
+                } else {
+                    line
+                }
+            })
+            .collect()
+    }
+
+    /// The change of the tab as it is now, @ in the status view
+    fn latest_head(&self) -> Result<Head> {
+        if self.status {
+            new_commander().get_current_head()
+        } else {
+            new_commander().get_head_latest(&self.head)
+        }
+    }
+
+    fn is_untracked(&self) -> bool {
         self.file
             .as_ref()
-            .map(|current_file| new_commander().untrack_file(current_file))
-            .transpose()?;
-        Ok(())
+            .is_some_and(|file| file.diff_type == Some(DiffType::Untracked))
+    }
+
+    /// Popup for resolving the conflicts of the selected file, or for
+    /// telling why they can't be resolved in the app
+    fn resolve_conflicts(&self) -> Result<Option<Box<dyn Component>>> {
+        let Some(path) = self.file.as_ref().and_then(|file| file.paths().pop()) else {
+            return Ok(None);
+        };
+        if !self
+            .conflicts_output
+            .iter()
+            .any(|conflict| conflict.path == path)
+        {
+            return Ok(Some(Box::new(MessagePopup::new(
+                "No conflicts",
+                format!("{path} has no conflicts in this change"),
+            ))));
+        }
+        let content = new_commander().get_conflicted_file(&self.head.commit_id, &path)?;
+        Ok(Some(match parse_conflicts(&content) {
+            Some(chunks)
+                if chunks
+                    .iter()
+                    .any(|chunk| matches!(chunk, ConflictChunk::Conflict { .. })) =>
+            {
+                Box::new(ConflictPopup::new(self.head.clone(), path, chunks))
+            }
+            _ => Box::new(MessagePopup::new(
+                "Can't resolve conflicts here",
+                format!(
+                    "The conflicts of {path} have more than two sides, resolve them with jj resolve"
+                ),
+            )),
+        }))
+    }
+
+    /// Mark or unmark the selected file for the file actions, then select
+    /// the next one
+    fn toggle_mark(&mut self) -> Result<()> {
+        if self.is_directory_selected() {
+            return Ok(());
+        }
+        if let Some(file) = self.file.as_ref()
+            && let Some(path) = file.paths().pop()
+            && self.marked.remove(&path).is_none()
+        {
+            self.marked.insert(path, file.clone());
+        }
+        self.scroll_files(1)
+    }
+
+    fn is_marked(&self, file: &File) -> bool {
+        file.paths()
+            .pop()
+            .is_some_and(|path| self.marked.contains_key(&path))
+    }
+
+    /// Files the file actions apply to: the marked files, or else the
+    /// selected one
+    fn target_files(&self) -> Vec<File> {
+        if !self.marked.is_empty() {
+            return self.marked.values().cloned().collect();
+        }
+        match self.file.as_ref() {
+            Some(file) if !self.is_directory_selected() => vec![file.clone()],
+            _ => vec![],
+        }
+    }
+
+    /// Track the untracked target files, and untrack the others
+    fn toggle_tracking(&mut self) -> Result<Option<ComponentAction>> {
+        let (untracked, tracked): (Vec<File>, Vec<File>) = self
+            .target_files()
+            .into_iter()
+            .partition(|file| file.diff_type == Some(DiffType::Untracked));
+        let paths = |files: Vec<File>| -> Vec<String> {
+            files
+                .into_iter()
+                .filter_map(|file| file.paths().pop())
+                .collect()
+        };
+        let (untracked, tracked) = (paths(untracked), paths(tracked));
+        let commander = new_commander();
+        if !untracked.is_empty()
+            && let Err(err) = commander.track_files(&untracked)
+        {
+            return Ok(Some(ComponentAction::SetPopup(Some(Box::new(
+                MessagePopup::new("Can't track file", format!("{err:#}")),
+            )))));
+        }
+        // this works even for deleted files because jj doesn't return error in that case
+        if !tracked.is_empty() && commander.untrack_files(&tracked).is_err() {
+            return Ok(Some(ComponentAction::SetPopup(Some(Box::new(
+                MessagePopup::new("Can't untrack file", "Make sure that file is ignored"),
+            )))));
+        }
+        self.set_head(&commander.get_current_head()?)?;
+        Ok(None)
+    }
+
+    /// Make the target files executable or not, in the change of the tab
+    fn chmod(&mut self, executable: bool) -> Result<Option<ComponentAction>> {
+        let paths: Vec<String> = self
+            .target_files()
+            .into_iter()
+            .filter(|file| {
+                !matches!(
+                    file.diff_type,
+                    Some(DiffType::Untracked | DiffType::Deleted)
+                )
+            })
+            .filter_map(|file| file.paths().pop())
+            .collect();
+        if paths.is_empty() {
+            return Ok(None);
+        }
+        if let Err(err) = new_commander().chmod_files(&self.head.commit_id, &paths, executable) {
+            return Ok(Some(ComponentAction::SetPopup(Some(Box::new(
+                MessagePopup::new("Can't change the executable bit", format!("{err:#}")),
+            )))));
+        }
+        self.marked.clear();
+        self.reload()?;
+        Ok(None)
+    }
+
+    /// Show the change of the tab as it is now
+    fn reload(&mut self) -> Result<()> {
+        self.head = self.latest_head()?;
+        self.refresh_files()?;
+        self.select_tree_file();
+        self.refresh_diff()
     }
 
     pub fn restore_file(&mut self) -> Result<()> {
@@ -173,6 +682,9 @@ impl FilesTab {
     }
 
     fn scroll_files(&mut self, scroll: isize) -> Result<()> {
+        if self.update_tree(|tree| tree.select(tree.selected().saturating_add_signed(scroll)))? {
+            return Ok(());
+        }
         if let Ok(files) = self.files_output.as_ref() {
             let current_file_index = self.get_current_file_index();
             let next_file = match current_file_index {
@@ -194,10 +706,18 @@ impl FilesTab {
 }
 
 impl Component for FilesTab {
+    fn selection(&mut self) -> Selection {
+        Selection {
+            head: Some(self.head.clone()),
+            file: self.file.as_ref().and_then(|file| file.path.clone()),
+        }
+    }
+
     fn focus(&mut self) -> Result<()> {
         self.is_current_head = self.head == new_commander().get_current_head()?;
-        self.head = new_commander().get_head_latest(&self.head)?;
+        self.head = self.latest_head()?;
         self.refresh_files()?;
+        self.select_tree_file();
         self.refresh_diff()?;
         Ok(())
     }
@@ -211,10 +731,18 @@ impl Component for FilesTab {
 
         // Draw files
         {
-            let current_file_index = self.get_current_file_index();
+            let mut current_file_index = self.get_current_file_index();
 
-            let mut lines: Vec<Line> = match self.files_output.as_ref() {
-                Ok(files_output) => {
+            let mut lines: Vec<Line> = match (self.file_tree.as_ref(), self.files_output.as_ref()) {
+                (Some(Ok(tree)), _) => {
+                    current_file_index = Some(tree.selected());
+                    self.tree_lines(tree)
+                }
+                (Some(Err(err)), _) => {
+                    current_file_index = None;
+                    err.into_text("Error getting files")?.lines
+                }
+                (None, Ok(files_output)) => {
                     let files_lines = files_output
                         .iter()
                         .enumerate()
@@ -226,8 +754,11 @@ impl Component for FilesTab {
                                 .map(|line| {
                                     let mut line = line.to_owned();
 
-                                    // Add padding at start
-                                    line.spans.insert(0, Span::from(" "));
+                                    // Add padding at start, or the mark
+                                    line.spans.insert(
+                                        0,
+                                        Span::from(if self.is_marked(file) { "●" } else { " " }),
+                                    );
 
                                     if let Some(diff_type) = file.diff_type.as_ref() {
                                         line.spans = line
@@ -257,15 +788,19 @@ impl Component for FilesTab {
 
                     if files_lines.is_empty() {
                         vec![
-                            Line::from(" No changed files in change")
-                                .fg(Color::DarkGray)
-                                .italic(),
+                            Line::from(if self.status {
+                                " No changes in the working copy"
+                            } else {
+                                " No changed files in change"
+                            })
+                            .fg(Color::DarkGray)
+                            .italic(),
                         ]
                     } else {
                         files_lines
                     }
                 }
-                Err(err) => err.into_text("Error getting files")?.lines,
+                (None, Err(err)) => err.into_text("Error getting files")?.lines,
             };
 
             let title_change = if self.is_current_head {
@@ -274,7 +809,8 @@ impl Component for FilesTab {
                 self.head.change_id.as_string()
             };
 
-            if !self.conflicts_output.is_empty() {
+            // The tree view marks the conflicted files
+            if !self.tree_view && !self.conflicts_output.is_empty() {
                 lines.push(Line::default());
 
                 for conflict in &self.conflicts_output {
@@ -285,7 +821,11 @@ impl Component for FilesTab {
             let files = List::new(lines)
                 .block(
                     Block::bordered()
-                        .title(" Files for ".to_owned() + &title_change + " ")
+                        .title(if self.status {
+                            format!(" Status of {title_change} ")
+                        } else {
+                            format!(" Files for {title_change} ")
+                        })
                         .border_type(BorderType::Rounded),
                 )
                 .scroll_padding(3);
@@ -312,17 +852,12 @@ impl Component for FilesTab {
             }
         }
 
-        // Draw diff
-        {
-            let diff_content = match self.diff_output.as_ref() {
-                Ok(Some(diff_content)) => diff_content.into_text()?,
-                Ok(None) => Text::default(),
-                Err(err) => err.into_text("Error getting diff")?,
-            };
-            self.diff_panel
-                .render_context::<TextContent>(diff_content)
-                .title(" Diff ")
-                .draw(f, chunks[1]);
+        // Draw diff. Width dependent formats are rendered again
+        // when the panel changes size.
+        self.draw_diff(f, chunks[1])?;
+        if self.diff_format.uses_width() && self.diff_width != self.diff_panel.columns() as usize {
+            self.load_diff();
+            self.draw_diff(f, chunks[1])?;
         }
 
         Ok(())
@@ -351,17 +886,24 @@ impl Component for FilesTab {
                     self.diff_format = self.diff_format.get_next(self.config.diff_tool());
                     self.refresh_diff()?;
                 }
+                KeyCode::Char(' ') => self.toggle_mark()?,
                 KeyCode::Char('x') => {
-                    // this works even for deleted files because jj doesn't return error in that case
-                    if self.untrack_file().is_err() {
-                        return Ok(ComponentInputResult::HandledAction(
-                            ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
-                                "Can't untrack file",
-                                "Make sure that file is ignored",
-                            )))),
-                        ));
+                    if let Some(action) = self.toggle_tracking()? {
+                        return Ok(ComponentInputResult::HandledAction(action));
                     }
-                    self.set_head(&new_commander().get_current_head()?)?;
+                }
+                KeyCode::Char(key @ ('e' | 'E')) => {
+                    if let Some(action) = self.chmod(key == 'e')? {
+                        return Ok(ComponentInputResult::HandledAction(action));
+                    }
+                }
+                KeyCode::Char('r') if self.is_untracked() => {
+                    return Ok(ComponentInputResult::HandledAction(
+                        ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                            "Can't restore file",
+                            "Untracked files are not in the change, track them with x first",
+                        )))),
+                    ));
                 }
                 KeyCode::Char('r') => {
                     if let Err(err) = self.restore_file() {
@@ -374,11 +916,74 @@ impl Component for FilesTab {
                     }
                     self.set_head(&new_commander().get_current_head()?)?;
                 }
-                KeyCode::Char('R') | KeyCode::F(5) => {
-                    self.head = new_commander().get_head_latest(&self.head)?;
-                    self.refresh_files()?;
+                KeyCode::Char('c') => {
+                    self.show_content = !self.show_content;
                     self.refresh_diff()?;
                 }
+                KeyCode::Char('I') => {
+                    return Ok(ComponentInputResult::HandledAction(
+                        ComponentAction::SetPopup(Some(Box::new(ignored_files_popup()))),
+                    ));
+                }
+                KeyCode::Char('S') => {
+                    let popup: Box<dyn Component> = match SparsePopup::new() {
+                        Ok(popup) => Box::new(popup),
+                        Err(err) => Box::new(MessagePopup::new(
+                            "Can't get sparse patterns",
+                            err.to_string(),
+                        )),
+                    };
+                    return Ok(ComponentInputResult::HandledAction(
+                        ComponentAction::SetPopup(Some(popup)),
+                    ));
+                }
+                KeyCode::Char('a') => {
+                    if let Some(path) = self.file.as_ref().and_then(|file| file.paths().pop()) {
+                        return Ok(ComponentInputResult::HandledAction(
+                            ComponentAction::ViewAnnotate(self.head.clone(), path),
+                        ));
+                    }
+                }
+                KeyCode::Char('m') => {
+                    if let Some(popup) = self.resolve_conflicts()? {
+                        return Ok(ComponentInputResult::HandledAction(
+                            ComponentAction::SetPopup(Some(popup)),
+                        ));
+                    }
+                }
+                KeyCode::Enter if self.is_directory_selected() => {
+                    self.update_tree(FileTree::toggle_selected)?;
+                }
+                KeyCode::Enter => {
+                    self.diff_loaded = true;
+                    if self.unloaded_lines.is_some() {
+                        self.load_diff();
+                    }
+                }
+                KeyCode::Right => {
+                    if !self.update_tree(|tree| tree.set_selected_expanded(true))? {
+                        return Ok(ComponentInputResult::NotHandled);
+                    }
+                }
+                KeyCode::Left => {
+                    if !self.update_tree(|tree| tree.set_selected_expanded(false))? {
+                        return Ok(ComponentInputResult::NotHandled);
+                    }
+                }
+                KeyCode::Char('t') if !self.status => self.toggle_tree_view()?,
+                KeyCode::Char('C') => {
+                    if let Some(file) = self.diff_file()? {
+                        copy_to_clipboard(&file.path);
+                    }
+                }
+                KeyCode::Char('o') => {
+                    if let Some(file) = self.diff_file()? {
+                        return Ok(ComponentInputResult::HandledAction(
+                            ComponentAction::OpenEditor(file),
+                        ));
+                    }
+                }
+                KeyCode::Char('R') | KeyCode::F(5) => self.reload()?,
                 KeyCode::Char('@') => {
                     let head = &new_commander().get_current_head()?;
                     self.set_head(head)?;
@@ -389,9 +994,39 @@ impl Component for FilesTab {
                             vec![
                                 ("j/k".to_owned(), "scroll down/up".to_owned()),
                                 ("J/K".to_owned(), "scroll down by ½ page".to_owned()),
-                                ("x".to_owned(), "untrack file".to_owned()),
+                                ("Space".to_owned(), "mark file".to_owned()),
+                                (
+                                    "x".to_owned(),
+                                    "untrack file, or track an untracked one".to_owned(),
+                                ),
+                                (
+                                    "e/E".to_owned(),
+                                    "make file executable/not executable".to_owned(),
+                                ),
                                 ("r".to_owned(), "restore file".to_owned()),
+                                ("a".to_owned(), "annotate file".to_owned()),
+                                (
+                                    "I".to_owned(),
+                                    "list ignored files of the working copy".to_owned(),
+                                ),
+                                (
+                                    "S".to_owned(),
+                                    "edit sparse patterns of the working copy".to_owned(),
+                                ),
+                                (
+                                    "c".to_owned(),
+                                    "toggle file content at the change, or diff".to_owned(),
+                                ),
+                                (
+                                    "m".to_owned(),
+                                    "resolve conflicts of file side by side".to_owned(),
+                                ),
                                 ("@".to_owned(), "view current change files".to_owned()),
+                                ("t".to_owned(), "toggle file tree".to_owned()),
+                                (
+                                    "Enter/→/←".to_owned(),
+                                    "expand/collapse directory".to_owned(),
+                                ),
                             ],
                             vec![
                                 ("Ctrl+e/Ctrl+y".to_owned(), "scroll down/up".to_owned()),
@@ -403,8 +1038,23 @@ impl Component for FilesTab {
                                     "Ctrl+f/Ctrl+b".to_owned(),
                                     "scroll down/up by page".to_owned(),
                                 ),
+                                ("g/G".to_owned(), "go to top/bottom".to_owned()),
                                 ("w".to_owned(), "toggle diff format".to_owned()),
                                 ("W".to_owned(), "toggle wrapping".to_owned()),
+                                ("L".to_owned(), "toggle line numbers".to_owned()),
+                                ("/".to_owned(), "search".to_owned()),
+                                ("n/N".to_owned(), "next/previous match".to_owned()),
+                                ("]f/[f".to_owned(), "next/previous file".to_owned()),
+                                ("]h/[h".to_owned(), "next/previous hunk".to_owned()),
+                                ("]c/[c".to_owned(), "next/previous conflict".to_owned()),
+                                ("Ctrl+g".to_owned(), "list files".to_owned()),
+                                ("v".to_owned(), "copy mode".to_owned()),
+                                ("Enter".to_owned(), "load truncated diff".to_owned()),
+                                ("C".to_owned(), "yank file path".to_owned()),
+                                (
+                                    "o".to_owned(),
+                                    "open file in $EDITOR at the line".to_owned(),
+                                ),
                             ],
                         )))),
                     ));
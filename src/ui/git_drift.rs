@@ -0,0 +1,78 @@
+/*! The git drift check finds, in a repository colocated with git, how
+the HEAD and branches of git differ from the ones jj imported last, e.g.
+after `git commit` or `git checkout`.
+
+The log tab runs it in the background every few seconds and shows the
+differences above the log, until they are imported with `jj git import`,
+by refreshing, or overwritten with `jj git export`.
+*/
+
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::TryRecvError;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::Result;
+use tracing::warn;
+
+use crate::commander::new_commander;
+
+/// How often git is checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Timer of the background checks
+pub struct GitDriftCheck {
+    next_check: Instant,
+    /// Result of the check running in the background
+    running: Option<Receiver<Result<Vec<String>>>>,
+}
+
+impl GitDriftCheck {
+    /// Check every few seconds, starting after the first interval
+    pub fn new() -> Self {
+        Self {
+            next_check: Instant::now() + CHECK_INTERVAL,
+            running: None,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /// Time until the next check is started
+    pub fn time_until_check(&self) -> Duration {
+        self.next_check.saturating_duration_since(Instant::now())
+    }
+
+    /// Start a check when it is due, and return the differences found by
+    /// a check that finished, e.g. `branch main moved`
+    pub fn update(&mut self) -> Option<Vec<String>> {
+        if let Some(running) = &self.running {
+            let result = match running.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => Ok(vec![]),
+            };
+            self.running = None;
+            return Some(result.unwrap_or_else(|err| {
+                warn!("Checking git for changes failed: {err}");
+                vec![]
+            }));
+        }
+
+        if Instant::now() >= self.next_check {
+            let (tx, rx) = mpsc::channel();
+            thread::spawn(move || {
+                let mut commander = new_commander();
+                commander.background = true;
+                tx.send(commander.get_git_drift())
+            });
+            self.running = Some(rx);
+            self.next_check = Instant::now() + CHECK_INTERVAL;
+        }
+        None
+    }
+}
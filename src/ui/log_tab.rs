@@ -1,16 +1,22 @@
 #![expect(clippy::borrow_interior_mutable_const)]
 
 use std::cmp::max;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::time::Duration;
+use std::time::Instant;
 
 use anyhow::Result;
-use ratatui::crossterm::clipboard::CopyToClipboard;
 use ratatui::crossterm::event::Event;
+use ratatui::crossterm::event::KeyCode;
 use ratatui::crossterm::event::KeyEventKind;
-use ratatui::crossterm::execute;
+use ratatui::crossterm::event::KeyModifiers;
 use ratatui::prelude::*;
 use ratatui::widgets::*;
 use ratatui_textarea::CursorMove;
 use ratatui_textarea::TextArea;
+use throbber_widgets_tui::Throbber;
+use throbber_widgets_tui::ThrobberState;
 use tracing::instrument;
 use tui_confirm_dialog::ButtonLabel;
 use tui_confirm_dialog::ConfirmDialog;
@@ -18,9 +24,25 @@ use tui_confirm_dialog::ConfirmDialogState;
 use tui_confirm_dialog::Listener;
 
 use crate::ComponentInputResult;
+use crate::commander::CommandError;
+use crate::commander::Commander;
+use crate::commander::at_operation;
+use crate::commander::files::File;
+use crate::commander::ids::ChangeId;
 use crate::commander::ids::CommitId;
+use crate::commander::jj::FetchFrom;
+use crate::commander::jj::Operation;
+use crate::commander::jj::PushTarget;
+use crate::commander::jj::add_trailer;
+use crate::commander::jj::combine_descriptions;
+use crate::commander::log::ChangeSize;
 use crate::commander::log::Head;
 use crate::commander::new_commander;
+use crate::commander::set_at_operation;
+use crate::commander::set_snapshot_paused;
+use crate::commander::snapshot_paused;
+use crate::commander::worker::CommandJob;
+use crate::env::CustomCommand;
 use crate::env::DiffFormat;
 use crate::env::JjConfig;
 use crate::env::get_env;
@@ -28,31 +50,267 @@ use crate::keybinds::LogTabEvent;
 use crate::keybinds::LogTabKeybinds;
 use crate::ui::Component;
 use crate::ui::ComponentAction;
+use crate::ui::EditorRequest;
+use crate::ui::Selection;
+use crate::ui::ci_status::CiStatus;
 use crate::ui::commit_show_cache::CommitShowCache;
 use crate::ui::commit_show_cache::CommitShowKey;
 use crate::ui::commit_show_cache::CommitShowValue;
+use crate::ui::commit_show_cache::ScrollMemory;
+use crate::ui::dialog::AbandonAction;
+use crate::ui::dialog::AbandonPopup;
+use crate::ui::dialog::AuthorAction;
+use crate::ui::dialog::AuthorPopup;
 use crate::ui::dialog::BookmarkSetPopup;
+use crate::ui::dialog::CustomCommandAction;
+use crate::ui::dialog::CustomCommandPopup;
+use crate::ui::dialog::EvologAction;
+use crate::ui::dialog::EvologPopup;
 use crate::ui::dialog::HelpPopup;
 use crate::ui::dialog::LoaderPopup;
+use crate::ui::dialog::LogFilterAction;
+use crate::ui::dialog::LogFilterPopup;
 use crate::ui::dialog::MessagePopup;
+use crate::ui::dialog::PickerAction;
+use crate::ui::dialog::PickerPopup;
+use crate::ui::dialog::PushAction;
+use crate::ui::dialog::PushPopup;
 use crate::ui::dialog::RebasePopup;
+use crate::ui::dialog::RestoreAction;
+use crate::ui::dialog::RestorePopup;
+use crate::ui::dialog::SplitAction;
+use crate::ui::dialog::SplitPopup;
+use crate::ui::git_drift::GitDriftCheck;
+use crate::ui::maintenance::MaintenanceTask;
 use crate::ui::panel::DetailsPanel;
+use crate::ui::panel::DetailsPanelEvent;
 use crate::ui::panel::LargeStringContent;
 use crate::ui::panel::LogPanel;
+use crate::ui::panel::LogSearchInput;
+use crate::ui::utils::FileFolds;
+use crate::ui::utils::LargeString;
 use crate::ui::utils::PaneDivider;
+use crate::ui::utils::RevsetHistory;
 use crate::ui::utils::centered_rect_fixed;
 use crate::ui::utils::centered_rect_line_height;
-use crate::ui::utils::tabs_to_spaces;
+use crate::ui::utils::copy_to_clipboard;
+use crate::ui::utils::diff_file_header;
+use crate::ui::utils::find_forge;
+use crate::ui::utils::format_diff_output;
+use crate::ui::utils::insert_file_headers;
+use crate::ui::utils::open_in_browser;
+use crate::ui::utils::rewrites_preview;
 
 const NEW_POPUP_ID: u16 = 1;
 const EDIT_POPUP_ID: u16 = 2;
-const ABANDON_POPUP_ID: u16 = 3;
 const SQUASH_POPUP_ID: u16 = 4;
+const DESCRIBE_POPUP_ID: u16 = 5;
+const SQUASH_CHANGE_POPUP_ID: u16 = 6;
+/// Confirms to keep the result of a command that was run to preview it
+const PREVIEW_POPUP_ID: u16 = 7;
+/// Confirms to move a bookmark backwards or sideways
+const MOVE_BOOKMARK_POPUP_ID: u16 = 8;
+/// Confirms to prune the operation log
+const PRUNE_OPERATIONS_POPUP_ID: u16 = 9;
+
+/// Entry of the remote picker to fetch from all remotes
+const ALL_REMOTES_ENTRY: &str = "(all remotes)";
+
+/// Operations listed in the picker of the operation to view the
+/// repository at
+const OPERATION_PICKER_LIMIT: usize = 100;
+
+/// Entries of the operation picker with their operations, None for the
+/// present
+type OperationEntries = Vec<(String, Option<Operation>)>;
+
+/// Entries of the file picker with their files, None for all files
+type FileEntries = Vec<(String, Option<File>)>;
+
+/// Picker of the operations to diff, with their entries and the
+/// operation to diff from once it is picked
+type OperationDiffPicker = (PickerPopup, Vec<(String, Operation)>, Option<Operation>);
+
+/// Lines of the combined description shown when confirming a squash
+const SQUASH_DESCRIPTION_LINES: usize = 6;
+
+/// Number of context lines jj shows, unless configured otherwise
+const JJ_DEFAULT_CONTEXT: usize = 3;
+
+/// Time between the frames of the spinner shown while loading
+const THROBBER_STEP: Duration = Duration::from_millis(100);
+
+/// Pause in typing a revset before it is checked
+const REVSET_CHECK_DELAY: Duration = Duration::from_millis(300);
+
+/// Changes counted when checking a revset, more are shown as "1000+"
+const REVSET_CHECK_LIMIT: usize = 1000;
+
+/// Ancestors of @ whose authors are offered as Co-authored-by trailers
+const RECENT_AUTHORS_LIMIT: usize = 200;
+
+/// Changes to describe one after another, oldest first, like the
+/// reword of `git rebase -i`
+struct Reword {
+    heads: Vec<Head>,
+    /// The change being described
+    index: usize,
+}
+
+/// Files of a change whose diff is loaded for the details panel
+enum ShownFiles {
+    /// All files, except those with a long diff not loaded by the user
+    All {
+        /// Lines changed by the change, if known from the log
+        size: Option<ChangeSize>,
+        /// Files with a long diff loaded by the user
+        loaded: HashSet<String>,
+    },
+    /// Only the files picked by the user
+    Only(Vec<String>),
+}
+
+/// Check of the revset being typed, so errors are shown before it is
+/// applied
+struct RevsetCheck {
+    revset: String,
+    /// When the revset was changed, None once it has been checked
+    changed_at: Option<Instant>,
+    /// Number of changes of the revset, or the error of jj
+    result: Option<Result<usize, String>>,
+}
+
+impl RevsetCheck {
+    fn new(revset: String) -> Self {
+        Self {
+            revset,
+            changed_at: Some(Instant::now()),
+            result: None,
+        }
+    }
+
+    /// Check the revset again after the next pause in typing
+    fn set_revset(&mut self, revset: String) {
+        if revset != self.revset {
+            self.revset = revset;
+            self.changed_at = Some(Instant::now());
+        }
+    }
+
+    /// Run the check if typing paused long enough
+    fn update(&mut self) {
+        if self
+            .changed_at
+            .is_none_or(|changed_at| changed_at.elapsed() < REVSET_CHECK_DELAY)
+        {
+            return;
+        }
+        self.changed_at = None;
+        self.result = if self.revset.trim().is_empty() {
+            None
+        } else {
+            Some(
+                new_commander()
+                    .count_revset(&self.revset, REVSET_CHECK_LIMIT)
+                    .map_err(|err| err.to_string()),
+            )
+        };
+    }
+
+    /// Line telling if the revset is valid and how many changes it has
+    fn status_line(&self) -> Line<'static> {
+        match &self.result {
+            _ if self.changed_at.is_some() => Line::from("checking…").fg(Color::DarkGray),
+            None => Line::from("default log revset").fg(Color::DarkGray),
+            Some(Ok(0)) => Line::from("no changes").fg(Color::Yellow),
+            Some(Ok(1)) => Line::from("1 change").fg(Color::Green),
+            Some(Ok(count)) if *count >= REVSET_CHECK_LIMIT => {
+                Line::from(format!("{REVSET_CHECK_LIMIT}+ changes")).fg(Color::Green)
+            }
+            Some(Ok(count)) => Line::from(format!("{count} changes")).fg(Color::Green),
+            Some(Err(err)) => {
+                let message = err
+                    .lines()
+                    .find(|line| !line.trim().is_empty())
+                    .unwrap_or_default();
+                Line::from(message.trim_start_matches("Error: ").to_owned()).fg(Color::Red)
+            }
+        }
+    }
+}
+
+/// Squash of a change into its parent or a picked destination, waiting
+/// to be confirmed
+struct PendingSquash {
+    source: Head,
+    destination: Head,
+    /// Combined description of the destination and source
+    message: String,
+    into_parent: bool,
+}
+
+/// A command previewed in a confirmation dialog, run once it is
+/// confirmed
+enum PreviewedCommand {
+    /// `jj absorb --from` the change
+    Absorb(CommitId),
+    /// `jj parallelize` the changes
+    Parallelize(Vec<CommitId>),
+    /// `jj simplify-parents` of the change
+    SimplifyParents(CommitId),
+}
 
 /// Log tab. Shows `jj log` in main panel and shows selected change details of in details panel.
 pub struct LogTab<'a> {
     /// The revset filter to apply to jj log
     log_revset_textarea: Option<TextArea<'a>>,
+    revset_check: Option<RevsetCheck>,
+    /// Checks for changes made with git, in a colocated repository
+    git_drift_check: Option<GitDriftCheck>,
+    /// Polls the checks of the pushed changes, if
+    /// `blazingjj.ci-status-minutes` is set
+    ci_status: Option<CiStatus>,
+    /// Revsets shown before, recalled in the revset popup
+    revset_history: RevsetHistory,
+    revset_history_popup: Option<PickerPopup>,
+    /// Trailers to add to the description being edited
+    trailer_picker: Option<PickerPopup>,
+    bookmark_picker: Option<PickerPopup>,
+    /// Local bookmarks, to pick the one to move to the selected change
+    move_bookmark_picker: Option<PickerPopup>,
+    /// Bookmark to move, if moving it backwards or sideways is confirmed
+    pending_bookmark_move: Option<String>,
+    push_popup: Option<PushPopup>,
+    /// Remotes to fetch from
+    remote_picker: Option<PickerPopup>,
+    /// Tasks of the maintenance menu
+    maintenance_picker: Option<PickerPopup>,
+    /// Pages of the selected change on the forge, with their entries in
+    /// the picker
+    forge_picker: Option<(PickerPopup, Vec<(String, String)>)>,
+    /// Changed files of the selected change, to show one alone
+    file_picker: Option<(PickerPopup, FileEntries)>,
+    /// Operations to view the repository at
+    operation_picker: Option<(PickerPopup, OperationEntries)>,
+    /// Operations to diff, `jj op diff`
+    operation_diff_picker: Option<OperationDiffPicker>,
+    /// Copies of a divergent change, with their summaries shown in the
+    /// picker, to pick the one to keep
+    divergence_picker: Option<(PickerPopup, Vec<(Head, String)>)>,
+    /// Pinned changes, with their entries in the picker
+    pin_picker: Option<(PickerPopup, Vec<(Head, String)>)>,
+    /// Change being split, with the popup picking its files
+    split_popup: Option<(SplitPopup<'a>, Head)>,
+    abandon_popup: Option<AbandonPopup>,
+    restore_popup: Option<RestorePopup>,
+    /// Versions of the selected change from its evolution log
+    evolog_popup: Option<EvologPopup>,
+    custom_command_popup: Option<CustomCommandPopup>,
+    log_paths_textarea: Option<TextArea<'a>>,
+    /// Change id, commit id or bookmark to select in the log
+    go_to_textarea: Option<TextArea<'a>>,
+    /// Name of the git tag to create at the selected change
+    tag_textarea: Option<TextArea<'a>>,
 
     /// The list of changes shown to the left
     log_panel: LogPanel<'a>,
@@ -66,12 +324,45 @@ pub struct LogTab<'a> {
     /// Cached change content
     commit_show_cache: CommitShowCache,
 
+    /// Change content being loaded in the background, when it is not
+    /// cached
+    head_load: Option<(CommitShowKey, CommandJob<CommitShowValue>)>,
+
+    /// The loading of the selected change content was cancelled
+    head_load_cancelled: bool,
+
+    /// Folds of the selected change content before the details panel
+    /// was resized, for the content loaded with the new width
+    resized_folds: Option<(CommitShowKey, FileFolds)>,
+
+    /// Diff of a file of the selected change content being loaded in
+    /// the background, by file index, when it was left out for its
+    /// length
+    file_load: Option<(CommitShowKey, usize, CommandJob<String>)>,
+
+    /// Files with a long diff loaded by the user, by change
+    loaded_files: HashMap<ChangeId, HashSet<String>>,
+
+    /// Scroll position of recently shown change content
+    scroll_memory: ScrollMemory,
+
     /// The currently selected change. It is a copy of `self.log_panel.head`,
     /// so if these differ, we need to update `self.head`
     head: Head,
 
     diff_format: DiffFormat,
 
+    /// Changes shown with another diff format than `diff_format`,
+    /// flipped between git and color words by the user
+    diff_format_overrides: HashMap<ChangeId, DiffFormat>,
+
+    /// Number of context lines in the diff. None for the jj default.
+    diff_context: Option<usize>,
+
+    /// Changes shown with the diff of one of their files only, picked
+    /// by the user
+    shown_files: HashMap<ChangeId, File>,
+
     popup: ConfirmDialogState,
     popup_tx: std::sync::mpsc::Sender<Listener>,
     popup_rx: std::sync::mpsc::Receiver<Listener>,
@@ -80,14 +371,34 @@ pub struct LogTab<'a> {
     bookmark_set_popup_rx: std::sync::mpsc::Receiver<bool>,
 
     describe_textarea: Option<TextArea<'a>>,
+    /// Changes described one after another, while rewording
+    reword: Option<Reword>,
     describe_after_new: bool,
+    /// The describe editor is for the message of jj commit
+    committing: bool,
+    /// Insert the next new change before (true) or after (false) the
+    /// selected change, instead of on top of it
+    insert_new_before: Option<bool>,
 
     rebase_popup: Option<RebasePopup>,
 
+    log_filter_popup: Option<LogFilterPopup<'a>>,
+    author_popup: Option<AuthorPopup<'a>>,
+
+    /// Revset to go back to when the conflicted changes are shown
+    revset_before_conflicts: Option<Option<String>>,
+    /// Revset to go back to when the recently hidden changes are shown
+    revset_before_hidden: Option<Option<String>>,
+
     squash_ignore_immutable: bool,
+    pending_squash: Option<PendingSquash>,
     squash_target: Option<Head>,
+    /// Command to run if its preview is confirmed
+    previewed_command: Option<PreviewedCommand>,
 
     edit_ignore_immutable: bool,
+    abandon_ignore_immutable: bool,
+    describe_ignore_immutable: bool,
 
     config: JjConfig,
     pane_divider: PaneDivider,
@@ -117,9 +428,14 @@ The main functions are:
   right panel
   (called by sync_head_output)
 
-* [compute_head_content](LogTab::compute_head_content) - Call `jj show` and
-  wrap the output as a ShowCacheValue
+* [load_head_output](LogTab::load_head_output) - Load content of the
+  right panel in the background, when it is not cached. The panel shows
+  a spinner until [update](LogTab::update) receives it.
   (called by refresh_head_output)
+
+* [compute_head_content](LogTab::compute_head_content) - Call `jj show` (or `jj diff` to compare) and
+  wrap the output as a ShowCacheValue
+  (called by load_head_output, on a worker thread)
 */
 impl<'a> LogTab<'a> {
     #[instrument(level = "info", name = "Initializing log tab", parent = None, skip())]
@@ -129,12 +445,24 @@ impl<'a> LogTab<'a> {
         let head = new_commander().get_current_head()?;
 
         const NO_WIDTH: usize = 0;
-        let head_key = CommitShowKey::new(head.clone(), diff_format.clone(), NO_WIDTH);
+        let head_key = CommitShowKey::new(head.clone(), diff_format.clone(), None, NO_WIDTH);
 
         let mut commit_show_cache = CommitShowCache::new();
 
         let _new_content = commit_show_cache.get_or_insert(&head_key, || {
-            Self::compute_head_content(NO_WIDTH, &head, &diff_format)
+            Self::compute_head_content(
+                &mut new_commander(),
+                NO_WIDTH,
+                &head,
+                None,
+                &diff_format,
+                None,
+                &ShownFiles::All {
+                    size: None,
+                    loaded: HashSet::new(),
+                },
+            )
+            .unwrap_or_else(|err| CommitShowValue::new(head_key.clone(), err.to_string()))
         });
 
         let (popup_tx, popup_rx) = std::sync::mpsc::channel();
@@ -150,6 +478,32 @@ impl<'a> LogTab<'a> {
 
         Ok(Self {
             log_revset_textarea: None,
+            revset_check: None,
+            git_drift_check: new_commander().is_colocated().then(GitDriftCheck::new),
+            ci_status: get_env().jj_config.ci_status_interval().map(CiStatus::new),
+            revset_history: RevsetHistory::load(&get_env().root),
+            revset_history_popup: None,
+            trailer_picker: None,
+            bookmark_picker: None,
+            move_bookmark_picker: None,
+            pending_bookmark_move: None,
+            push_popup: None,
+            remote_picker: None,
+            maintenance_picker: None,
+            forge_picker: None,
+            file_picker: None,
+            operation_picker: None,
+            operation_diff_picker: None,
+            divergence_picker: None,
+            pin_picker: None,
+            split_popup: None,
+            custom_command_popup: None,
+            restore_popup: None,
+            evolog_popup: None,
+            abandon_popup: None,
+            log_paths_textarea: None,
+            go_to_textarea: None,
+            tag_textarea: None,
 
             log_panel: LogPanel::new()?,
 
@@ -158,8 +512,17 @@ impl<'a> LogTab<'a> {
             head_key,
 
             commit_show_cache,
+            head_load: None,
+            head_load_cancelled: false,
+            resized_folds: None,
+            file_load: None,
+            loaded_files: HashMap::new(),
+            scroll_memory: ScrollMemory::new(),
 
             diff_format,
+            diff_format_overrides: HashMap::new(),
+            diff_context: None,
+            shown_files: HashMap::new(),
 
             popup: ConfirmDialogState::default(),
             popup_tx,
@@ -169,14 +532,26 @@ impl<'a> LogTab<'a> {
             bookmark_set_popup_rx,
 
             describe_textarea: None,
+            reword: None,
             describe_after_new: false,
+            committing: false,
+            insert_new_before: None,
 
             rebase_popup: None,
 
+            log_filter_popup: None,
+            author_popup: None,
+            revset_before_conflicts: None,
+            revset_before_hidden: None,
+
             squash_ignore_immutable: false,
+            pending_squash: None,
+            previewed_command: None,
             squash_target: None,
 
             edit_ignore_immutable: false,
+            abandon_ignore_immutable: false,
+            describe_ignore_immutable: false,
 
             config,
             pane_divider,
@@ -184,12 +559,375 @@ impl<'a> LogTab<'a> {
         })
     }
 
+    /// A page of the log is about to be loaded
+    pub fn is_loading(&self) -> bool {
+        self.log_panel.is_loading()
+    }
+
+    /// Show a revset typed by the user, and remember it in the history
+    fn apply_log_revset(&mut self, log_revset: Option<String>) {
+        if let Some(log_revset) = &log_revset {
+            self.revset_history.push(log_revset);
+        }
+        self.revset_before_conflicts = None;
+        self.revset_before_hidden = None;
+        self.log_panel.set_log_revset(log_revset);
+        self.update_cache_active_commits();
+        self.sync_head_output();
+    }
+
+    /// Show only the changes with conflicts, or go back to the revset
+    /// shown before
+    fn toggle_conflicts(&mut self) {
+        let log_revset = match self.revset_before_conflicts.take() {
+            Some(log_revset) => log_revset,
+            None => {
+                self.revset_before_conflicts = Some(self.log_panel.log_revset.clone());
+                Some("conflicts()".to_owned())
+            }
+        };
+        self.log_panel.set_log_revset(log_revset);
+        self.update_cache_active_commits();
+        self.sync_head_output();
+    }
+
+    /// Add the changes hidden by the last operation to the shown revset,
+    /// e.g. abandoned changes or the old versions of rewritten ones, or
+    /// go back to the revset shown before
+    fn toggle_hidden(&mut self) -> Result<()> {
+        let log_revset = match self.revset_before_hidden.take() {
+            Some(log_revset) => log_revset,
+            None => {
+                let base = match &self.log_panel.log_revset {
+                    Some(log_revset) => log_revset.clone(),
+                    None => new_commander().get_default_log_revset()?,
+                };
+                self.revset_before_hidden = Some(self.log_panel.log_revset.clone());
+                Some(format!("({base}) | (at_operation(@-, mutable()) ~ all())"))
+            }
+        };
+        self.log_panel.set_log_revset(log_revset);
+        self.update_cache_active_commits();
+        self.sync_head_output();
+        Ok(())
+    }
+
+    /// Replace the text of the revset popup, e.g. with a revset of the
+    /// history
+    fn set_revset_text(&mut self, revset: &str) {
+        let Some(log_revset_textarea) = self.log_revset_textarea.as_mut() else {
+            return;
+        };
+        *log_revset_textarea = match revset {
+            "" => TextArea::default(),
+            revset => TextArea::new(revset.lines().map(String::from).collect()),
+        };
+        log_revset_textarea.move_cursor(CursorMove::End);
+        if let Some(revset_check) = self.revset_check.as_mut() {
+            revset_check.set_revset(revset.to_owned());
+        }
+    }
+
+    /// A revset being typed is waiting to be checked, or the content
+    /// of the selected change or a page of the log is loading
+    pub fn is_ticking(&self) -> bool {
+        self.revset_check
+            .as_ref()
+            .is_some_and(|revset_check| revset_check.changed_at.is_some())
+            || self.head_load.is_some()
+            || self.log_panel.is_loading_page()
+            || self.ci_status.as_ref().is_some_and(CiStatus::is_running)
+            || self
+                .git_drift_check
+                .as_ref()
+                .is_some_and(GitDriftCheck::is_running)
+    }
+
+    /// Time until the checks of the pushed changes are polled, or git is
+    /// checked for changes, again
+    pub fn time_until_background_check(&self) -> Option<Duration> {
+        let ci_poll = self.ci_status.as_ref().map(CiStatus::time_until_poll);
+        let git_check = self
+            .git_drift_check
+            .as_ref()
+            .map(GitDriftCheck::time_until_check);
+        ci_poll.into_iter().chain(git_check).min()
+    }
+
+    /// Line above the log with the changes made with git, and the keys
+    /// to import or export them
+    fn git_drift_line(&self, drift: &[String]) -> Option<Line<'static>> {
+        if drift.is_empty() {
+            return None;
+        }
+        let mut spans = vec![
+            Span::raw(format!(" git changed outside jj: {}", drift.join(", "))).fg(Color::Yellow),
+        ];
+        for (action, label) in [
+            (LogTabEvent::GitImport, "import"),
+            (LogTabEvent::GitExport, "export"),
+        ] {
+            if let Some(shortcut) = self.keybinds.shortcut(action) {
+                spans.push(Span::raw(format!("  {shortcut}: {label}")).fg(Color::DarkGray));
+            }
+        }
+        Some(Line::from(spans))
+    }
+
+    /// List the check runs of the selected change in a popup
+    fn show_checks(&self) -> ComponentInputResult {
+        let message = match &self.ci_status {
+            None => {
+                "Set blazingjj.ci-status-minutes to poll the checks of pushed changes".to_owned()
+            }
+            Some(ci_status) => match ci_status.checks(&self.head.commit_id) {
+                None | Some([]) => "No checks for this change".to_owned(),
+                Some(runs) => runs
+                    .iter()
+                    .map(|run| format!("{} {}", run.state.icon(), run.name))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            },
+        };
+        let title = format!("Checks of {}", self.head.change_id.short());
+        ComponentInputResult::HandledAction(ComponentAction::SetPopup(Some(Box::new(
+            MessagePopup::new(title, message),
+        ))))
+    }
+
+    /// Pick the files of the selected change to restore from an older
+    /// version of it
+    fn restore_from_version(&mut self, version: Head) -> ComponentInputResult {
+        let files = new_commander()
+            .get_files_between(version.commit_id.as_str(), self.head.commit_id.as_str());
+        let message = match files {
+            Ok(files) if !files.is_empty() => {
+                self.restore_popup = Some(RestorePopup::new(version, self.head.clone(), files));
+                return ComponentInputResult::Handled;
+            }
+            Ok(_) => "The files are the same as in the current version".to_owned(),
+            Err(err) => err.to_string(),
+        };
+        ComponentInputResult::HandledAction(ComponentAction::SetPopup(Some(Box::new(
+            MessagePopup::new("Restore", message),
+        ))))
+    }
+
+    /// Pick an operation of the operation log to view the repository at,
+    /// or the last one to return to the present
+    fn pick_operation(&mut self) -> Result<()> {
+        let viewed = at_operation();
+        let entries: OperationEntries = operation_entries()?
+            .into_iter()
+            .enumerate()
+            .map(|(index, (mut entry, operation))| {
+                if index == 0 {
+                    entry.push_str("  (present)");
+                } else if viewed.as_ref() == Some(&operation) {
+                    entry.push_str("  (viewed)");
+                }
+                (entry, (index > 0).then_some(operation))
+            })
+            .collect();
+        self.operation_picker = Some((
+            PickerPopup::new(
+                "View at operation",
+                "No operations",
+                "view",
+                entries.iter().map(|(entry, _)| entry.clone()).collect(),
+            )
+            .with_filter(),
+            entries,
+        ));
+        Ok(())
+    }
+
+    /// Pick the operation to diff from, or the one to diff to once the
+    /// first one is picked
+    fn pick_operation_diff(&mut self, from: Option<Operation>) -> Result<()> {
+        let entries = operation_entries()?;
+        let title = if from.is_some() {
+            "Diff to operation"
+        } else {
+            "Diff from operation"
+        };
+        self.operation_diff_picker = Some((
+            PickerPopup::new(
+                title,
+                "No operations",
+                "pick",
+                entries.iter().map(|(entry, _)| entry.clone()).collect(),
+            )
+            .with_filter(),
+            entries,
+            from,
+        ));
+        Ok(())
+    }
+
+    /// Key to view the repository at another operation, for the banner
+    /// shown while it is viewed at an old one
+    pub fn view_at_operation_shortcut(&self) -> Option<String> {
+        self.keybinds.shortcut(LogTabEvent::ViewAtOperation)
+    }
+
     /// Set cursor and update log panel and diff panel
     pub fn set_head(&mut self, head: Head) {
         self.log_panel.set_head(head);
         self.refresh_log_output();
     }
 
+    /// Select a revision in the log, loading more of the log or adding
+    /// the revision to the revset until it is shown. Errors are shown
+    /// in a popup.
+    fn go_to_revision(&mut self, revision: &str) -> ComponentInputResult {
+        let result = new_commander()
+            .get_revision_head(revision)
+            .and_then(|head| self.go_to_head(head));
+        match result {
+            Ok(()) => ComponentInputResult::Handled,
+            Err(err) => ComponentInputResult::HandledAction(ComponentAction::SetPopup(Some(
+                Box::new(MessagePopup::new("Go to change", err.to_string())),
+            ))),
+        }
+    }
+
+    /// Select a change, adding it to the revset if it is not shown
+    pub fn go_to_head(&mut self, head: Head) -> Result<()> {
+        let result = self.log_panel.go_to_head(head);
+        self.update_cache_active_commits();
+        self.sync_head_output();
+        result
+    }
+
+    /// Move a bookmark to the selected change. Moving it backwards or
+    /// sideways is confirmed first, other errors are shown in a popup.
+    fn move_bookmark(&mut self, name: String, allow_backwards: bool) -> Option<ComponentAction> {
+        let result = new_commander().move_bookmark(&name, &self.head.commit_id, allow_backwards);
+        match result {
+            Ok(()) => {
+                self.refresh_log_output();
+                None
+            }
+            Err(err) if err.to_string().contains("backwards or sideways") => {
+                self.popup = ConfirmDialogState::new(
+                    MOVE_BOOKMARK_POPUP_ID,
+                    Span::styled(" Move bookmark ", Style::new().bold().cyan()),
+                    Text::from(vec![
+                        Line::from(format!(
+                            "The selected change is not a descendant of bookmark {name}."
+                        )),
+                        Line::from("Are you sure you want to move it backwards or sideways?"),
+                        Line::from(format!("Change: {}", self.head.change_id.as_str())),
+                    ]),
+                );
+                self.popup
+                    .with_yes_button(ButtonLabel::YES.clone())
+                    .with_no_button(ButtonLabel::NO.clone())
+                    .with_listener(Some(self.popup_tx.clone()))
+                    .open();
+                self.pending_bookmark_move = Some(name);
+                None
+            }
+            Err(err) => Some(ComponentAction::SetPopup(Some(Box::new(
+                MessagePopup::new("Move bookmark", err.to_string()),
+            )))),
+        }
+    }
+
+    /// Open a page of the selected change on the forge of the remote,
+    /// or pick one if there are several
+    fn open_on_forge(&mut self) -> Result<ComponentInputResult> {
+        let pages = self.forge_pages()?;
+        if let [(_, url)] = pages.as_slice() {
+            return Ok(open_url(url));
+        }
+        let entries = pages.iter().map(|(entry, _)| entry.clone()).collect();
+        self.forge_picker = Some((
+            PickerPopup::new("Open in browser", "", "open", entries),
+            pages,
+        ));
+        Ok(ComponentInputResult::Handled)
+    }
+
+    /// Pages of the selected change on the forge of `origin`, or of the
+    /// first remote: the commit, the file in the details panel at the
+    /// commit and the branches of the bookmarks pushed to the remote
+    fn forge_pages(&mut self) -> Result<Vec<(String, String)>> {
+        let (remote, forge) = find_forge(None)?;
+
+        let commit_id = self.head.commit_id.clone();
+        let mut pages = vec![(
+            format!("Change {}", self.head.change_id.short()),
+            forge.commit_url(commit_id.as_str()),
+        )];
+        if let Some(file) = self.head_file() {
+            pages.push((
+                format!("File {}", file.path),
+                forge.file_url(commit_id.as_str(), &file.path, file.line),
+            ));
+        }
+        for bookmark in new_commander().get_remote_bookmarks_at(&commit_id, &remote)? {
+            pages.push((format!("Bookmark {bookmark}"), forge.branch_url(&bookmark)));
+        }
+        Ok(pages)
+    }
+
+    /// Confirm a push, previewing it with `jj git push --dry-run`.
+    /// Errors, or nothing to push, are shown in a popup instead.
+    fn confirm_push(&mut self, target: PushTarget, allow_new: bool) -> ComponentInputResult {
+        let messages = match new_commander().git_push_dry_run(&target, allow_new) {
+            Ok(messages) => messages,
+            Err(err) => {
+                return ComponentInputResult::HandledAction(ComponentAction::SetPopup(Some(
+                    Box::new(MessagePopup::new("Push", err.to_string())),
+                )));
+            }
+        };
+        if !messages.contains("Changes to push") {
+            return ComponentInputResult::HandledAction(ComponentAction::SetPopup(Some(Box::new(
+                MessagePopup::new("Push", "Nothing to push"),
+            ))));
+        }
+        let changes = messages
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with("Dry-run requested"))
+            .map(str::to_owned)
+            .collect();
+
+        let question = match &target {
+            PushTarget::Bookmarks(_) => format!(
+                "Push the bookmarks of change {}?",
+                self.head.change_id.short()
+            ),
+            PushTarget::Change(change_id) => format!("Push change {}?", change_id.short()),
+            PushTarget::All => "Push all bookmarks?".to_owned(),
+        };
+        self.push_popup = Some(PushPopup::new(target, allow_new, question, changes));
+        ComponentInputResult::Handled
+    }
+
+    /// Show the repository as it is now, after another process changed
+    /// it, keeping the selected change if it still exists
+    pub fn reload(&mut self) -> Result<()> {
+        self.mark_cache_as_dirty();
+        let latest_head = new_commander().get_head_latest(&self.head)?;
+        self.set_head(latest_head);
+        self.follow_working_copy()
+    }
+
+    /// Select the working copy change if the log follows it, e.g. after
+    /// an operation moved it
+    fn follow_working_copy(&mut self) -> Result<()> {
+        if self.log_panel.follow_working_copy {
+            let head = new_commander().get_current_head()?;
+            self.log_panel.go_to_head(head)?;
+            self.update_cache_active_commits();
+            self.sync_head_output();
+        }
+        Ok(())
+    }
+
     /// Update the log panel and diff panel. This will also refresh
     /// the diff cache.
     fn refresh_log_output(&mut self) {
@@ -211,22 +949,419 @@ impl<'a> LogTab<'a> {
         // generate different output for some keys. We probably need
         // a forced cache clear function.
 
-        // TODO use shared function to build key, so width can be cleared if not needed
         let inner_width = self.head_panel.columns() as usize;
-        let key = CommitShowKey::new(self.head.clone(), self.diff_format.clone(), inner_width);
-        let _new_content = self.commit_show_cache.get_or_insert(&key, || {
-            Self::compute_head_content(inner_width, &self.head, &self.diff_format)
-        });
+        let key = self.head_key(inner_width);
+        if self.commit_show_cache.has_exact_match(&key) {
+            // Cancels the loading of another change
+            self.head_load = None;
+        } else {
+            self.load_head_output(&key, inner_width);
+        }
 
         let content_changed = self.head_key != key;
+        let resized = key.is_resize_of(&self.head_key);
 
         // Only update if content actually changed to prevent scroll jumping
         if content_changed {
+            if resized {
+                // The same files are shown, so the folds still apply
+                let folds = self
+                    .commit_show_cache
+                    .get(&self.head_key)
+                    .map(|value| value.folds().clone());
+                if let Some(folds) = folds {
+                    if self.commit_show_cache.has_exact_match(&key)
+                        && let Some(value) = self.commit_show_cache.get_mut(&key)
+                    {
+                        value.set_folds(folds);
+                    } else {
+                        // Applied once the content is loaded
+                        self.resized_folds = Some((key.clone(), folds));
+                    }
+                }
+                // The old width will not be used again
+                self.commit_show_cache.remove(&self.head_key);
+            } else {
+                // Continue where the user left the content last time
+                self.scroll_memory
+                    .insert(&self.head_key, self.head_panel.scroll_position());
+                self.head_panel.restore_scroll(self.scroll_memory.get(&key));
+            }
             self.head_key = key;
-            self.head_panel.scroll_to(0);
         }
     }
 
+    /// Start loading the content of the selected change on a worker
+    /// thread, unless it is being loaded already. Loading another change
+    /// is cancelled.
+    fn load_head_output(&mut self, key: &CommitShowKey, inner_width: usize) {
+        if self
+            .head_load
+            .as_ref()
+            .is_some_and(|(loading, _)| loading == key)
+        {
+            return;
+        }
+        let head = self.head.clone();
+        let compare_base = Self::compare_base(&self.log_panel, &self.head).cloned();
+        let diff_format = self.head_diff_format().clone();
+        let diff_context = self.diff_context;
+        let paths = self.shown_file_paths();
+        let files = if paths.is_empty() {
+            ShownFiles::All {
+                size: self.log_panel.change_size(&head),
+                loaded: self
+                    .loaded_files
+                    .get(&head.change_id)
+                    .cloned()
+                    .unwrap_or_default(),
+            }
+        } else {
+            ShownFiles::Only(paths)
+        };
+        let job = CommandJob::spawn(new_commander(), move |commander| {
+            Self::compute_head_content(
+                commander,
+                inner_width,
+                &head,
+                compare_base.as_ref(),
+                &diff_format,
+                diff_context,
+                &files,
+            )
+        });
+        self.head_load = Some((key.clone(), job));
+        self.head_load_cancelled = false;
+    }
+
+    /// Put the loaded content of the selected change in the cache, once
+    /// it is loaded
+    fn receive_head_output(&mut self) {
+        let Some(result) = self
+            .head_load
+            .as_ref()
+            .and_then(|(_, job)| job.try_result())
+        else {
+            return;
+        };
+        let Some((key, _)) = self.head_load.take() else {
+            return;
+        };
+        match result {
+            Ok(mut value) => {
+                if let Some((_, folds)) = self
+                    .resized_folds
+                    .take_if(|(resized_key, _)| *resized_key == key)
+                {
+                    value.set_folds(folds);
+                }
+                self.commit_show_cache.insert_document(value)
+            }
+            Err(CommandError::Cancelled) => self.head_load_cancelled = true,
+            Err(err) => self
+                .commit_show_cache
+                .insert_document(CommitShowValue::new(key, err.to_string())),
+        }
+    }
+
+    /// Start loading the diff of a file the user unfolded, if it was
+    /// left out of the selected change content for its length
+    fn load_requested_file(&mut self) {
+        let Some(content) = self.commit_show_cache.get_mut(&self.head_key) else {
+            return;
+        };
+        let (large_string, folds) = content.value_with_folds();
+        let Some(index) = folds.take_load_request() else {
+            return;
+        };
+        let Some(path) = large_string
+            .diff_files()
+            .get(index)
+            .map(|file| file.path.clone())
+        else {
+            return;
+        };
+        self.loaded_files
+            .entry(self.head.change_id.clone())
+            .or_default()
+            .insert(path.clone());
+        let head = self.head.clone();
+        let compare_base = Self::compare_base(&self.log_panel, &self.head).cloned();
+        let diff_format = self.head_diff_format().clone();
+        let diff_context = self.diff_context;
+        let inner_width = self.head_panel.columns() as usize;
+        let job = CommandJob::spawn(new_commander(), move |commander| {
+            commander.limit_width(inner_width);
+            let paths = [path];
+            match compare_base {
+                Some(base) => commander.get_commits_diff(
+                    &base.commit_id,
+                    &head.commit_id,
+                    &diff_format,
+                    diff_context,
+                    &paths,
+                    true,
+                ),
+                None => commander.get_commit_diff(
+                    &head.commit_id,
+                    &diff_format,
+                    diff_context,
+                    &paths,
+                    true,
+                ),
+            }
+            .map(|text| format_diff_output(&text, &diff_format, inner_width))
+        });
+        self.file_load = Some((self.head_key.clone(), index, job));
+    }
+
+    /// Put the loaded diff of a file in the content, once it is loaded
+    fn receive_file_diff(&mut self) {
+        let Some(result) = self
+            .file_load
+            .as_ref()
+            .and_then(|(_, _, job)| job.try_result())
+        else {
+            return;
+        };
+        let Some((key, index, _)) = self.file_load.take() else {
+            return;
+        };
+        let diff = match result {
+            // The loaded diff starts with the header that is already shown
+            Ok(text) => text
+                .split_once('\n')
+                .map_or(String::new(), |(_, diff)| diff.to_owned()),
+            Err(CommandError::Cancelled) => return,
+            Err(err) => err.to_string(),
+        };
+        if self.commit_show_cache.has_exact_match(&key)
+            && let Some(content) = self.commit_show_cache.get_mut(&key)
+        {
+            content.load_file(index, &diff);
+        }
+    }
+
+    /// Stop loading the content of the selected change
+    fn cancel_head_load(&mut self) {
+        if let Some((_, job)) = self.head_load.as_ref() {
+            job.cancel();
+        }
+    }
+
+    /// Width dependent diff formats must be rendered again when the
+    /// details panel changes size. Returns true if content was updated.
+    fn refresh_head_output_on_resize(&mut self) -> bool {
+        if !self.head_diff_format().uses_width() {
+            return false;
+        }
+        let key = self.head_key(self.head_panel.columns() as usize);
+        if !key.is_resize_of(&self.head_key) {
+            return false;
+        }
+        self.refresh_head_output();
+        true
+    }
+
+    /// Key of the selected change content, as rendered with `width`
+    fn head_key(&self, width: usize) -> CommitShowKey {
+        let base = Self::compare_base(&self.log_panel, &self.head);
+        CommitShowKey::new(
+            self.head.clone(),
+            self.head_diff_format().clone(),
+            self.diff_context,
+            width,
+        )
+        .compared_to(base.map(|base| base.commit_id.clone()))
+        .for_paths(self.shown_file_paths())
+    }
+
+    /// Paths of the file the details of the selected change are limited
+    /// to, none for the whole change
+    fn shown_file_paths(&self) -> Vec<String> {
+        self.shown_files
+            .get(&self.head.change_id)
+            .map(File::paths)
+            .unwrap_or_default()
+    }
+
+    /// Pick a file of the selected change to show alone in the details
+    /// panel, or all files again. The files come from the summary of
+    /// the diff, so a huge change can be reviewed file by file without
+    /// rendering all of it.
+    fn pick_shown_file(&mut self) -> Result<()> {
+        let commander = new_commander();
+        let files = match Self::compare_base(&self.log_panel, &self.head) {
+            Some(base) => {
+                commander.get_files_between(base.commit_id.as_str(), self.head.commit_id.as_str())
+            }
+            None => commander.get_files(&self.head),
+        }?;
+        let shown = self.shown_files.get(&self.head.change_id);
+        let entries: FileEntries = std::iter::once(("(all files)".to_owned(), None))
+            .chain(
+                files
+                    .into_iter()
+                    .map(|file| (file.line.clone(), Some(file))),
+            )
+            .map(|(mut entry, file)| {
+                if file.as_ref() == shown {
+                    entry.push_str("  (shown)");
+                }
+                (entry, file)
+            })
+            .collect();
+        self.file_picker = Some((
+            PickerPopup::new(
+                "Show file",
+                "No changed files",
+                "show",
+                entries.iter().map(|(entry, _)| entry.clone()).collect(),
+            )
+            .with_filter(),
+            entries,
+        ));
+        Ok(())
+    }
+
+    /// Show only a file of the selected change in the details panel, or
+    /// the whole change
+    fn show_file(&mut self, file: Option<File>) {
+        match file {
+            Some(file) => self.shown_files.insert(self.head.change_id.clone(), file),
+            None => self.shown_files.remove(&self.head.change_id),
+        };
+        self.refresh_head_output();
+    }
+
+    /// Change the selected change is compared to, unless it is the
+    /// selected change itself
+    fn compare_base<'b>(log_panel: &'b LogPanel, head: &Head) -> Option<&'b Head> {
+        log_panel.compare_base.as_ref().filter(|base| *base != head)
+    }
+
+    /// Diff format of the selected change
+    fn head_diff_format(&self) -> &DiffFormat {
+        self.diff_format_overrides
+            .get(&self.head.change_id)
+            .unwrap_or(&self.diff_format)
+    }
+
+    /// Flip the diff of the selected change between git and color
+    /// words, without changing the format of other changes
+    fn toggle_unified_diff(&mut self) {
+        let format = match self.head_diff_format() {
+            DiffFormat::Git => DiffFormat::ColorWords,
+            _ => DiffFormat::Git,
+        };
+        if format == self.diff_format {
+            self.diff_format_overrides.remove(&self.head.change_id);
+        } else {
+            self.diff_format_overrides
+                .insert(self.head.change_id.clone(), format);
+        }
+        self.refresh_head_output();
+    }
+
+    /// Change the number of context lines of the diff by `delta`
+    fn change_diff_context(&mut self, delta: isize) {
+        let context = self.diff_context.unwrap_or(JJ_DEFAULT_CONTEXT);
+        self.diff_context = Some(context.saturating_add_signed(delta));
+        self.refresh_head_output();
+    }
+
+    /// File and line at the copy cursor or the top of the details panel
+    fn head_file(&mut self) -> Option<EditorRequest> {
+        let content = self.commit_show_cache.get_mut(&self.head_key)?;
+        let (large_string, folds) = content.value_with_folds();
+        let content = LargeStringContent::with_folds(large_string, folds);
+        let path = self.head_panel.current_file(&content)?;
+        let line = self.head_panel.current_file_line(&content);
+        Some(EditorRequest { path, line })
+    }
+
+    /// Draw the details panel with the content of the selected change
+    fn draw_head_panel(&mut self, f: &mut ratatui::prelude::Frame<'_>, area: Rect) {
+        let mut subject = match Self::compare_base(&self.log_panel, &self.head) {
+            Some(base) => format!("Compare {} → {}", base.change_id, self.head.change_id),
+            None => format!("Details for {}", self.head.change_id),
+        };
+        if let Some(path) = self
+            .shown_files
+            .get(&self.head.change_id)
+            .and_then(|file| file.path.as_ref())
+        {
+            subject.push_str(&format!(": {path}"));
+        }
+        let diff_format = self.head_diff_format().clone();
+        let title = match self.diff_context {
+            Some(context) if diff_format.uses_context() => {
+                format!(" {subject} (context {context}) ")
+            }
+            _ => format!(" {subject} "),
+        };
+        let has_content = match self.commit_show_cache.get_mut(&self.head_key) {
+            Some(content) => {
+                let (large_string, folds) = content.value_with_folds();
+                self.head_panel
+                    .render_context::<LargeStringContent>(LargeStringContent::with_folds(
+                        large_string,
+                        folds,
+                    ))
+                    .title(title)
+                    .git_diff(diff_format == DiffFormat::Git)
+                    .draw(f, area);
+                true
+            }
+            None => {
+                let empty = LargeString::new(String::new());
+                let mut folds = FileFolds::default();
+                self.head_panel
+                    .render_context::<LargeStringContent>(LargeStringContent::with_folds(
+                        &empty, &mut folds,
+                    ))
+                    .title(title)
+                    .draw(f, area);
+                false
+            }
+        };
+        self.draw_head_load(f, area, has_content);
+    }
+
+    /// Draw a spinner while the content of the selected change loads, at
+    /// the bottom of the details panel if older content is shown
+    fn draw_head_load(&self, f: &mut ratatui::prelude::Frame<'_>, area: Rect, has_content: bool) {
+        let line = match self.head_load.as_ref() {
+            Some((_, job)) => {
+                let elapsed = job.elapsed();
+                let mut state = ThrobberState::default();
+                // A step of 0 would pick a random frame
+                let steps = (elapsed.as_millis() / THROBBER_STEP.as_millis() % 100) as i8;
+                if steps > 0 {
+                    state.calc_step(steps);
+                }
+                let label = match elapsed.as_secs() {
+                    0 => "Loading… ".to_owned(),
+                    seconds => format!("Loading… {seconds}s "),
+                };
+                let mut line = Throbber::default().label(label).to_line(&state);
+                line.push_span(Span::from("(Esc: cancel) ").fg(Color::DarkGray));
+                line
+            }
+            None if self.head_load_cancelled && !has_content => {
+                Line::from(" Loading cancelled, refresh to load it again ").fg(Color::DarkGray)
+            }
+            None => return,
+        };
+        let width = (line.width() as u16).min(area.width.saturating_sub(4));
+        let y = if has_content {
+            area.bottom().saturating_sub(1)
+        } else {
+            area.y + 1
+        };
+        f.render_widget(line, Rect::new(area.x + 2, y, width, 1));
+    }
+
     //
     // Cache related
     //
@@ -241,37 +1376,169 @@ impl<'a> LogTab<'a> {
     /// the changes there as active. For non-active changes, keep at most
     /// one commit.
     fn update_cache_active_commits(&mut self) {
-        let key = CommitShowKey::new(
-            self.head.clone(),
-            self.diff_format.clone(),
-            self.head_panel.columns() as usize,
-        );
+        let key = self.head_key(self.head_panel.columns() as usize);
         let active_heads = self.log_panel.log_heads();
         self.commit_show_cache.set_active(active_heads, &key);
     }
 
     /// Extract head content from commander.get_commit_show
     /// Wraps it in a cache value before returning it.
+    /// Fails only if the command was cancelled, other errors are shown as
+    /// the content.
     fn compute_head_content(
+        commander: &mut Commander,
         inner_width: usize,
         head: &Head,
+        compare_base: Option<&Head>,
         diff_format: &DiffFormat,
-    ) -> CommitShowValue {
-        // Call jj show
+        diff_context: Option<usize>,
+        files: &ShownFiles,
+    ) -> Result<CommitShowValue, CommandError> {
+        // Files with a long diff are left out, without even being loaded
+        let max_lines = get_env().jj_config.diff_max_lines();
+        let (sizes, unloaded) = match files {
+            ShownFiles::All { size, loaded } => {
+                match Self::diff_file_sizes(commander, head, compare_base, diff_format, *size) {
+                    Err(CommandError::Cancelled) => return Err(CommandError::Cancelled),
+                    result => {
+                        let sizes = result.unwrap_or_default();
+                        let unloaded = sizes
+                            .iter()
+                            .filter(|(path, lines)| *lines > max_lines && !loaded.contains(path))
+                            .cloned()
+                            .collect();
+                        (sizes, unloaded)
+                    }
+                }
+            }
+            ShownFiles::Only(_) => (vec![], vec![]),
+        };
+        let excluded: Vec<String> = unloaded.iter().map(|(path, _)| path.clone()).collect();
+        let paths = match files {
+            ShownFiles::Only(paths) => paths.as_slice(),
+            ShownFiles::All { .. } => &[],
+        };
+
+        // Call jj show, or jj diff when comparing to a base or showing
+        // some files
         let commit_id = &head.commit_id;
-        let mut commander = new_commander();
         commander.limit_width(inner_width);
-        let head_output = commander
-            .get_commit_show(commit_id, diff_format, true)
-            .map(|text| tabs_to_spaces(&text));
+        let head_output = match compare_base {
+            Some(base) if !excluded.is_empty() => commander.get_commits_diff_excluding(
+                &base.commit_id,
+                commit_id,
+                diff_format,
+                diff_context,
+                &excluded,
+                true,
+            ),
+            Some(base) => commander.get_commits_diff(
+                &base.commit_id,
+                commit_id,
+                diff_format,
+                diff_context,
+                paths,
+                true,
+            ),
+            None if !paths.is_empty() => {
+                commander.get_commit_diff(commit_id, diff_format, diff_context, paths, true)
+            }
+            None if !excluded.is_empty() => commander.get_commit_show_excluding(
+                commit_id,
+                diff_format,
+                diff_context,
+                &excluded,
+                true,
+            ),
+            None => commander.get_commit_show(commit_id, diff_format, diff_context, true),
+        }
+        .map(|text| format_diff_output(&text, diff_format, inner_width));
         // Format output as string
-        let output = match head_output {
-            Ok(head_output) => head_output,
-            Err(err) => err.to_string(),
+        let (output, unloaded) = match head_output {
+            Ok(head_output) if unloaded.is_empty() => (head_output, unloaded),
+            Ok(head_output) => (
+                Self::insert_unloaded_headers(
+                    commander,
+                    &head_output,
+                    head,
+                    compare_base,
+                    diff_format,
+                    &sizes,
+                    &unloaded,
+                ),
+                unloaded,
+            ),
+            Err(CommandError::Cancelled) => return Err(CommandError::Cancelled),
+            Err(err) => (err.to_string(), vec![]),
         };
         // Build value used by cache and return it
-        let key = CommitShowKey::new(head.clone(), diff_format.clone(), inner_width);
-        CommitShowValue::new(key, output)
+        let key = CommitShowKey::new(head.clone(), diff_format.clone(), diff_context, inner_width)
+            .compared_to(compare_base.map(|base| base.commit_id.clone()))
+            .for_paths(paths.to_vec());
+        Ok(CommitShowValue::with_unloaded(key, output, &unloaded))
+    }
+
+    /// Files of the change with their number of changed lines, in the
+    /// order of the diff, to find the ones longer than `diff_max_lines`.
+    /// A change that is known to be small from its size in the log is
+    /// not looked at.
+    fn diff_file_sizes(
+        commander: &mut Commander,
+        head: &Head,
+        compare_base: Option<&Head>,
+        diff_format: &DiffFormat,
+        size: Option<ChangeSize>,
+    ) -> Result<Vec<(String, usize)>, CommandError> {
+        let max_lines = get_env().jj_config.diff_max_lines();
+        let small = compare_base.is_none()
+            && size.is_some_and(|size| size.insertions + size.deletions <= max_lines);
+        if max_lines == 0 || small || !diff_format.has_file_headers() {
+            return Ok(vec![]);
+        }
+        let revisions = match compare_base {
+            Some(base) => vec![
+                "--from",
+                base.commit_id.as_str(),
+                "--to",
+                head.commit_id.as_str(),
+            ],
+            None => vec!["-r", head.commit_id.as_str()],
+        };
+        commander.get_diff_file_sizes(&revisions, true)
+    }
+
+    /// Add the headers of the files left out of the diff where jj shows
+    /// them, from the order of `sizes`, so they can be unfolded to load
+    /// them
+    fn insert_unloaded_headers(
+        commander: &Commander,
+        output: &str,
+        head: &Head,
+        compare_base: Option<&Head>,
+        diff_format: &DiffFormat,
+        sizes: &[(String, usize)],
+        unloaded: &[(String, usize)],
+    ) -> String {
+        // Only the color words header tells if a file was added or removed
+        let files = match compare_base {
+            _ if *diff_format != DiffFormat::ColorWords => vec![],
+            Some(base) => commander
+                .get_files_between(base.commit_id.as_str(), head.commit_id.as_str())
+                .unwrap_or_default(),
+            None => commander.get_files(head).unwrap_or_default(),
+        };
+        let headers: Vec<(String, String)> = unloaded
+            .iter()
+            .map(|(path, _)| {
+                let diff_type = files
+                    .iter()
+                    .find(|file| file.path.as_ref() == Some(path))
+                    .and_then(|file| file.diff_type.as_ref());
+                (path.clone(), diff_file_header(path, diff_type, diff_format))
+            })
+            .collect();
+        let order: Vec<String> = sizes.iter().map(|(path, _)| path.clone()).collect();
+        insert_file_headers(output, &order, &headers)
     }
 }
 
@@ -289,9 +1556,33 @@ each other in code:
 * `execute_<action>` - Perform some action after the dialog closed.
 */
 impl<'a> LogTab<'a> {
-    fn handle_new(&mut self, describe: bool) -> Result<ComponentInputResult> {
-        let mark_count = self.log_panel.marked_heads.len();
-        let text = if mark_count > 0 {
+    fn handle_new(
+        &mut self,
+        describe: bool,
+        insert_before: Option<bool>,
+    ) -> Result<ComponentInputResult> {
+        let mark_count = self.log_panel.mark_count();
+        let text = if let Some(before) = insert_before {
+            Text::from(vec![
+                Line::from(format!(
+                    "Are you sure you want to insert a new change {}?",
+                    if before { "before" } else { "after" }
+                )),
+                Line::from(format!("Change: {}", self.head.change_id.as_str())),
+            ])
+            .fg(Color::default())
+        } else if mark_count > 1 {
+            let mut lines = vec![Line::from(format!(
+                "Are you sure you want to create a merge of {mark_count} marked changes?"
+            ))];
+            lines.extend(
+                self.log_panel
+                    .marked_log_heads()
+                    .iter()
+                    .map(|head| Line::from(format!("Parent: {}", head.change_id.as_str()))),
+            );
+            Text::from(lines).fg(Color::default())
+        } else if mark_count > 0 {
             Text::from(vec![Line::from(format!(
                 "Are you sure you want to create a new change with {mark_count} marked parents?"
             ))])
@@ -303,9 +1594,14 @@ impl<'a> LogTab<'a> {
             ])
             .fg(Color::default())
         };
+        let title = if insert_before.is_none() && mark_count > 1 {
+            " Merge "
+        } else {
+            " New "
+        };
         self.popup = ConfirmDialogState::new(
             NEW_POPUP_ID,
-            Span::styled(" New ", Style::new().bold().cyan()),
+            Span::styled(title, Style::new().bold().cyan()),
             text,
         );
         self.popup
@@ -314,69 +1610,580 @@ impl<'a> LogTab<'a> {
             .with_listener(Some(self.popup_tx.clone()))
             .open();
         self.describe_after_new = describe;
+        self.insert_new_before = insert_before;
         Ok(ComponentInputResult::Handled)
     }
 
     // Execute new command, after self.popup returned
     fn execute_new(&mut self) -> Result<Option<ComponentAction>> {
-        let commit_ids = self.log_panel.extract_and_clear_head_marks();
-        if commit_ids.is_empty() {
-            new_commander().run_new([self.head.commit_id.as_str()])?;
+        if let Some(before) = self.insert_new_before.take() {
+            new_commander().run_new_insert(self.head.commit_id.as_str(), before)?;
         } else {
-            new_commander().run_new(commit_ids.iter().map(CommitId::as_str))?;
+            let commit_ids = self.log_panel.extract_and_clear_head_marks();
+            if commit_ids.is_empty() {
+                new_commander().run_new([self.head.commit_id.as_str()])?;
+            } else {
+                new_commander().run_new(commit_ids.iter().map(CommitId::as_str))?;
+            }
         }
         self.set_head(new_commander().get_current_head()?);
         if self.describe_after_new {
             self.describe_after_new = false;
-            let textarea = TextArea::default();
-            self.describe_textarea = Some(textarea);
+            self.describe_textarea = Some(self.description_textarea(""));
+            self.describe_ignore_immutable = false;
         }
         Ok(Some(ComponentAction::ChangeHead(self.head.clone())))
     }
 
-    fn handle_abandon(&mut self) -> Result<ComponentInputResult> {
-        // Cannot abandon immutable changes
-        if self.head.immutable {
+    /// Show where the hunks of the change would go, from a simulation
+    /// of the absorb, and ask to absorb it
+    fn handle_absorb(&mut self) -> Result<ComponentInputResult> {
+        let commit_id = self.head.commit_id.clone();
+        let destinations = new_commander().simulate(|simulation| {
+            let operation = simulation.get_operation_id()?;
+            simulation
+                .run_absorb(commit_id.as_str())?
+                .into_iter()
+                .map(|destination| {
+                    let change_id = destination.split_whitespace().next().unwrap_or_default();
+                    let files = simulation.get_absorbed_files(&operation, change_id)?;
+                    Ok((destination, files))
+                })
+                .collect::<Result<Vec<_>>>()
+        })?;
+        if destinations.is_empty() {
+            return Ok(ComponentInputResult::HandledAction(
+                ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                    "Absorb",
+                    "Nothing to absorb: no hunk of the change has a single mutable destination",
+                )))),
+            ));
+        }
+
+        let mut lines = vec![
+            Line::from(format!("Absorbs {} into:", self.head.change_id.short())),
+            Line::default(),
+        ];
+        for (destination, files) in destinations {
+            lines.push(Line::from(destination).bold());
+            for file in files {
+                lines.push(Line::from(format!("  {file}")));
+            }
+        }
+        self.previewed_command = Some(PreviewedCommand::Absorb(commit_id));
+        self.open_preview(" Absorb ", lines);
+        Ok(ComponentInputResult::Handled)
+    }
+
+    /// Sign the marked or selected changes, or drop their signatures,
+    /// and show the backend and the output of jj
+    fn handle_sign(&mut self, unsign: bool) -> Result<ComponentInputResult> {
+        let title = if unsign { "Unsign" } else { "Sign" };
+        let backend = self.config.signing_backend().map(str::to_owned);
+        if !unsign && backend.is_none() {
+            return Ok(ComponentInputResult::HandledAction(
+                ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                    title,
+                    "No signing backend is configured, set `signing.backend` to gpg, ssh or gpgsm",
+                )))),
+            ));
+        }
+
+        let mut commit_ids = self.log_panel.marked_commit_ids();
+        if commit_ids.is_empty() {
+            commit_ids.push(self.head.commit_id.clone());
+        }
+        let output = match new_commander().run_sign(&commit_ids, unsign) {
+            Ok(output) => output,
+            Err(err) => {
+                return Ok(ComponentInputResult::HandledAction(
+                    ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                        format!("{title} error"),
+                        format!("{err:?}"),
+                    )))),
+                ));
+            }
+        };
+        // The signed changes and their descendants were rewritten
+        self.log_panel.extract_and_clear_head_marks();
+        self.mark_cache_as_dirty();
+        let head = new_commander().get_head_latest(&self.head)?;
+        self.set_head(head);
+
+        let mut message = format!(
+            "Signing backend: {}\n{output}",
+            backend.as_deref().unwrap_or("none")
+        );
+        if !self.config.log_show_signatures() {
+            message.push_str("\n\nSet blazingjj.log-show-signatures to see signatures in the log");
+        }
+        Ok(ComponentInputResult::HandledAction(
+            ComponentAction::Multiple(vec![
+                ComponentAction::ChangeHead(self.head.clone()),
+                ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(title, message)))),
+            ]),
+        ))
+    }
+
+    /// Show the parents the marked changes would have, from a
+    /// simulation of parallelizing them, and ask to parallelize them
+    fn handle_parallelize(&mut self) -> Result<ComponentInputResult> {
+        let heads = self.log_panel.marked_log_heads();
+        if heads.len() < 2 {
+            return Ok(ComponentInputResult::HandledAction(
+                ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                    "Parallelize",
+                    "Mark the connected changes to make siblings with space or visual mode",
+                )))),
+            ));
+        }
+
+        let commander = new_commander();
+        let parents_before = heads
+            .iter()
+            .map(|head| commander.get_parents(head.commit_id.as_str()))
+            .collect::<Result<Vec<_>>>()?;
+        let commit_ids: Vec<CommitId> = heads.iter().map(|head| head.commit_id.clone()).collect();
+        let parents_after = commander.simulate(|simulation| {
+            simulation.run_parallelize(&commit_ids)?;
+            heads
+                .iter()
+                .map(|head| simulation.get_parents(head.change_id.as_str()))
+                .collect::<Result<Vec<_>>>()
+        })?;
+
+        let mut lines = vec![
+            Line::from(format!("Parallelizes {} changes:", heads.len())),
+            Line::default(),
+        ];
+        for ((head, before), after) in heads.iter().zip(parents_before).zip(parents_after) {
+            lines.push(parents_line(head, &before, &after));
+        }
+        self.previewed_command = Some(PreviewedCommand::Parallelize(commit_ids));
+        self.open_preview(" Parallelize ", lines);
+        Ok(ComponentInputResult::Handled)
+    }
+
+    /// Show the redundant parents of the change, from a simulation of
+    /// removing them, and ask to remove them
+    fn handle_simplify_parents(&mut self) -> Result<ComponentInputResult> {
+        let commander = new_commander();
+        let commit_id = self.head.commit_id.clone();
+        let before = commander.get_parents(commit_id.as_str())?;
+        let after = commander.simulate(|simulation| {
+            simulation.run_simplify_parents(commit_id.as_str())?;
+            simulation.get_parents(self.head.change_id.as_str())
+        })?;
+        if before == after {
             return Ok(ComponentInputResult::HandledAction(
                 ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
-                    "Abandon",
-                    "The change cannot be abandoned because it is immutable.",
+                    "Simplify parents",
+                    "No parent of the change is an ancestor of its other parents",
                 )))),
             ));
         }
 
+        let lines = vec![
+            Line::from("Removes the redundant parents:"),
+            Line::default(),
+            parents_line(&self.head, &before, &after),
+        ];
+        self.previewed_command = Some(PreviewedCommand::SimplifyParents(commit_id));
+        self.open_preview(" Simplify parents ", lines);
+        Ok(ComponentInputResult::Handled)
+    }
+
+    /// Ask to run the previewed command, with the lines of its preview
+    fn open_preview(&mut self, title: &'static str, mut lines: Vec<Line<'static>>) {
+        lines.push(Line::default());
+        lines.push(Line::from("Run it? No leaves the repository as it is.").fg(Color::Yellow));
+
+        self.popup = ConfirmDialogState::new(
+            PREVIEW_POPUP_ID,
+            Span::styled(title, Style::new().bold().cyan()),
+            Text::from(lines).fg(Color::default()),
+        );
+        self.popup
+            .with_yes_button(ButtonLabel::YES.clone())
+            .with_no_button(ButtonLabel::NO.clone())
+            .with_listener(Some(self.popup_tx.clone()))
+            .open();
+    }
+
+    /// Run a previewed command that was confirmed
+    fn run_previewed_command(&mut self) -> Result<()> {
+        match self.previewed_command.take() {
+            Some(PreviewedCommand::Absorb(commit_id)) => {
+                new_commander().run_absorb(commit_id.as_str())?;
+            }
+            Some(PreviewedCommand::Parallelize(commit_ids)) => {
+                new_commander().run_parallelize(&commit_ids)?;
+            }
+            Some(PreviewedCommand::SimplifyParents(commit_id)) => {
+                new_commander().run_simplify_parents(commit_id.as_str())?;
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    fn handle_abandon(&mut self) -> Result<ComponentInputResult> {
         // Ask for confirmation by launching a popup
-        let mark_count = self.log_panel.marked_heads.len();
-        let text = if mark_count > 0 {
-            Text::from(vec![Line::from(format!(
-                "Are you sure you want to abandon {} marked changes?",
-                mark_count
-            ))])
-            .fg(Color::default())
+        let mark_count = self.log_panel.mark_count();
+        let (mut lines, immutable, commit_ids) = if mark_count > 0 {
+            (
+                vec![Line::from(format!(
+                    "Are you sure you want to abandon {} marked changes?",
+                    mark_count
+                ))],
+                self.log_panel.has_immutable_marks(),
+                self.log_panel.marked_commit_ids(),
+            )
         } else {
-            Text::from(vec![
-                Line::from("Are you sure you want to abandon this change?"),
-                Line::from(format!("Change: {}", self.head.change_id.as_str())),
-            ])
-            .fg(Color::default())
+            (
+                vec![
+                    Line::from("Are you sure you want to abandon this change?"),
+                    Line::from(format!("Change: {}", self.head.change_id.as_str())),
+                ],
+                self.head.immutable,
+                vec![self.head.commit_id.clone()],
+            )
+        };
+        lines.push(match new_commander().count_descendants(&commit_ids)? {
+            0 => Line::from("No descendants to rebase"),
+            1 => Line::from("1 descendant will be rebased onto the parents"),
+            count => Line::from(format!(
+                "{count} descendants will be rebased onto the parents"
+            )),
+        });
+        lines.extend(rewrites_preview(|commander| {
+            commander.run_abandon(&commit_ids, immutable, false)
+        }));
+        // Immutable changes are abandoned with --ignore-immutable
+        if immutable {
+            lines.push(immutable_warning());
+        }
+        self.abandon_ignore_immutable = immutable;
+        self.abandon_popup = Some(AbandonPopup::new(lines));
+        Ok(ComponentInputResult::Handled)
+    }
+
+    /// Edit the description of the selected change, with
+    /// --ignore-immutable if the change is immutable
+    fn open_describe(&mut self, ignore_immutable: bool) -> Result<()> {
+        let description = new_commander().get_commit_description(&self.head.commit_id)?;
+        self.describe_textarea = Some(self.description_textarea(&description));
+        self.describe_ignore_immutable = ignore_immutable;
+        Ok(())
+    }
+
+    /// Select the working copy change and edit the message to commit
+    /// it with
+    fn open_commit(&mut self) -> Result<ComponentInputResult> {
+        self.set_head(new_commander().get_current_head()?);
+        self.open_describe(false)?;
+        self.committing = true;
+        Ok(ComponentInputResult::HandledAction(
+            ComponentAction::ChangeHead(self.head.clone()),
+        ))
+    }
+
+    /// Start describing the mutable marked changes, or the mutable
+    /// ancestors of the selected change, one after another
+    fn start_reword(&mut self) -> Result<ComponentInputResult> {
+        let commit_ids = self.log_panel.extract_and_clear_head_marks();
+        let revset = if commit_ids.is_empty() {
+            format!("::{} & mutable()", self.head.commit_id)
+        } else {
+            format!(
+                "({}) & mutable()",
+                commit_ids
+                    .iter()
+                    .map(CommitId::as_str)
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            )
+        };
+        let mut heads = new_commander().get_heads(&revset)?;
+        if heads.is_empty() {
+            return Ok(ComponentInputResult::HandledAction(
+                ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                    "Reword",
+                    "There are no mutable changes to reword",
+                )))),
+            ));
+        }
+        heads.reverse();
+        self.reword = Some(Reword { heads, index: 0 });
+        self.open_reword_step()?;
+        Ok(ComponentInputResult::HandledAction(
+            ComponentAction::ChangeHead(self.head.clone()),
+        ))
+    }
+
+    /// Select the change to reword next and edit its description, or
+    /// stop rewording after the last one
+    fn open_reword_step(&mut self) -> Result<()> {
+        let Some(reword) = &self.reword else {
+            return Ok(());
         };
+        let Some(head) = reword.heads.get(reword.index) else {
+            self.reword = None;
+            return Ok(());
+        };
+        // Rewording an earlier change rewrote this one
+        let head = new_commander().get_head_latest(head)?;
+        self.set_head(head);
+        self.open_describe(false)
+    }
+
+    /// Text area to edit a description. An empty description starts
+    /// with the `blazingjj.describe-template`, if any.
+    fn description_textarea(&self, description: &str) -> TextArea<'a> {
+        match (description.trim(), self.config.describe_template()) {
+            ("", Some(template)) => TextArea::new(template.lines().map(String::from).collect()),
+            _ => {
+                let mut textarea =
+                    TextArea::new(description.split("\n").map(String::from).collect());
+                textarea.move_cursor(CursorMove::End);
+                textarea
+            }
+        }
+    }
+
+    /// Open the picker for a trailer to add to the description: the
+    /// user's Signed-off-by and Co-authored-by of recent authors
+    fn open_trailer_picker(&mut self) -> Result<()> {
+        let user = self.config.user_signature();
+        let mut trailers: Vec<String> = user
+            .iter()
+            .map(|user| format!("Signed-off-by: {user}"))
+            .collect();
+        trailers.extend(
+            new_commander()
+                .get_recent_authors(RECENT_AUTHORS_LIMIT)?
+                .into_iter()
+                .filter(|author| Some(author) != user.as_ref())
+                .map(|author| format!("Co-authored-by: {author}")),
+        );
+        self.trailer_picker = Some(PickerPopup::new(
+            "Add trailer",
+            "Set user.name and user.email to sign off",
+            "add trailer",
+            trailers,
+        ));
+        Ok(())
+    }
+
+    /// Show the copies of the selected divergent change in the log and
+    /// open the picker for the copy to keep
+    fn open_divergence_picker(&mut self) -> Result<ComponentInputResult> {
+        if !self.head.divergent {
+            return Ok(ComponentInputResult::HandledAction(
+                ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                    "Resolve divergence",
+                    "The selected change is not divergent",
+                )))),
+            ));
+        }
+        let commits = new_commander().get_change_commits(&self.head.change_id)?;
+        self.log_panel.reveal_change(&self.head.change_id)?;
+        let entries = commits.iter().map(|(_, summary)| summary.clone()).collect();
+        self.divergence_picker = Some((
+            PickerPopup::new(
+                "Divergent change",
+                "The change has no visible commits",
+                "keep, abandon the others",
+                entries,
+            ),
+            commits,
+        ));
+        Ok(ComponentInputResult::Handled)
+    }
+
+    /// Resolve a divergent change by abandoning all its copies except
+    /// the one to keep, which is selected afterwards
+    fn resolve_divergence(&mut self, keep: &Head, commits: &[(Head, String)]) -> Result<()> {
+        let abandon: Vec<CommitId> = commits
+            .iter()
+            .filter(|(head, _)| head != keep)
+            .map(|(head, _)| head.commit_id.clone())
+            .collect();
+        new_commander().run_abandon(&abandon, false, false)?;
+        self.log_panel.refresh_log_output();
+        let kept = new_commander().get_head_latest(keep)?;
+        self.set_head(kept);
+        self.follow_working_copy()?;
+        Ok(())
+    }
+
+    /// Ask to confirm squashing a change, showing the description the
+    /// destination gets
+    fn confirm_squash_change(
+        &mut self,
+        source: Head,
+        destination: Head,
+        into_parent: bool,
+    ) -> Result<()> {
+        let message = combine_descriptions(
+            &new_commander().get_commit_description(&destination.commit_id)?,
+            &new_commander().get_commit_description(&source.commit_id)?,
+        );
+        let mut lines = vec![
+            Line::from(format!(
+                "Squash {} into {}{}?",
+                source.change_id.short(),
+                if into_parent { "its parent " } else { "" },
+                destination.change_id.short()
+            )),
+            Line::from("Resulting description:"),
+        ];
+        if message.is_empty() {
+            lines.push(Line::from("  (no description set)").fg(Color::DarkGray));
+        }
+        lines.extend(
+            message
+                .lines()
+                .take(SQUASH_DESCRIPTION_LINES)
+                .map(|line| Line::from(format!("  {line}")).fg(Color::Cyan)),
+        );
+        if message.lines().count() > SQUASH_DESCRIPTION_LINES {
+            lines.push(Line::from("  …").fg(Color::Cyan));
+        }
+        let ignore_immutable = source.immutable || destination.immutable;
+        lines.extend(rewrites_preview(|commander| {
+            commander.run_squash_change(
+                source.commit_id.as_str(),
+                (!into_parent).then_some(destination.commit_id.as_str()),
+                &message,
+                ignore_immutable,
+            )
+        }));
+        if ignore_immutable {
+            lines.push(immutable_warning());
+        }
         self.popup = ConfirmDialogState::new(
-            ABANDON_POPUP_ID,
-            Span::styled(" Abandon ", Style::new().bold().cyan()),
-            text,
+            SQUASH_CHANGE_POPUP_ID,
+            Span::styled(" Squash ", Style::new().bold().cyan()),
+            Text::from(lines).fg(Color::default()),
         );
         self.popup
             .with_yes_button(ButtonLabel::YES.clone())
             .with_no_button(ButtonLabel::NO.clone())
             .with_listener(Some(self.popup_tx.clone()))
             .open();
+        self.pending_squash = Some(PendingSquash {
+            source,
+            destination,
+            message,
+            into_parent,
+        });
+        Ok(())
+    }
+
+    /// Squash a change after the confirmation, and select the
+    /// destination
+    fn execute_squash_change(&mut self, squash: PendingSquash) -> Result<Option<ComponentAction>> {
+        let destination = (!squash.into_parent).then_some(squash.destination.commit_id.as_str());
+        new_commander().run_squash_change(
+            squash.source.commit_id.as_str(),
+            destination,
+            &squash.message,
+            squash.source.immutable || squash.destination.immutable,
+        )?;
+        self.set_head(new_commander().get_head_latest(&squash.destination)?);
+        self.follow_working_copy()?;
+        Ok(Some(ComponentAction::ChangeHead(self.head.clone())))
+    }
+
+    /// Open the popup picking the files to split off the selected
+    /// change
+    fn open_split(&mut self) -> Result<ComponentInputResult> {
+        let files: Vec<File> = new_commander()
+            .get_files(&self.head)?
+            .into_iter()
+            .filter(|file| file.path.is_some())
+            .collect();
+        if files.len() < 2 {
+            return Ok(ComponentInputResult::HandledAction(
+                ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                    "Split",
+                    "A change needs at least two changed files to be split by files",
+                )))),
+            ));
+        }
+        let description = new_commander().get_commit_description(&self.head.commit_id)?;
+        self.split_popup = Some((SplitPopup::new(files, &description), self.head.clone()));
         Ok(ComponentInputResult::Handled)
     }
 
-    // Execute abandon command, after self.popup returned
-    fn execute_abandon(&mut self) -> Result<Option<ComponentAction>> {
+    /// Open the popup listing the custom commands, to run one with its
+    /// key
+    fn open_custom_commands(&mut self) -> ComponentInputResult {
+        let commands = self.config.custom_commands();
+        if commands.is_empty() {
+            return ComponentInputResult::HandledAction(ComponentAction::SetPopup(Some(Box::new(
+                MessagePopup::new(
+                    "Custom commands",
+                    "No custom commands are configured, add them to blazingjj.custom-commands",
+                ),
+            ))));
+        }
+        let commands = commands
+            .iter()
+            .map(|(key, command)| (key.clone(), command.clone()))
+            .collect();
+        self.custom_command_popup = Some(CustomCommandPopup::new(commands));
+        ComponentInputResult::Handled
+    }
+
+    /// Run a custom command on the selected change and file in the
+    /// background, and show its output. The log is reloaded, in case the
+    /// command changed the repository.
+    fn run_custom_command(&mut self, command: &CustomCommand) -> ComponentInputResult {
+        let file = self.head_file().map(|request| request.path);
+        let args = command.command.clone();
+        let head = self.head.clone();
+        let loader = LoaderPopup::new(command.name(), move || {
+            new_commander().run_custom_command(&args, &head, file.as_deref())
+        })
+        .text_align(Alignment::Left);
+        ComponentInputResult::HandledAction(ComponentAction::SetPopup(Some(Box::new(loader))))
+    }
+
+    /// Select the copy made by jj duplicate
+    fn select_duplicate(&mut self) -> Result<ComponentInputResult> {
+        self.set_head(new_commander().get_created_head()?);
+        Ok(ComponentInputResult::HandledAction(
+            ComponentAction::ChangeHead(self.head.clone()),
+        ))
+    }
+
+    /// Split a change, and select the change that kept its change id
+    fn execute_split(
+        &mut self,
+        head: &Head,
+        paths: &[String],
+        first_message: &str,
+        second_message: &str,
+    ) -> Result<()> {
+        new_commander().run_split(head, paths, first_message, second_message)?;
+        self.log_panel.refresh_log_output();
+        self.set_head(new_commander().get_head_latest(head)?);
+        self.follow_working_copy()
+    }
+
+    // Execute edit command, directly or after self.popup returned
+    fn execute_edit(&mut self) -> Result<ComponentAction> {
+        new_commander().run_edit(self.head.commit_id.as_str(), self.edit_ignore_immutable)?;
+        self.refresh_log_output();
+        Ok(ComponentAction::ChangeHead(self.head.clone()))
+    }
+
+    // Execute abandon command, after self.abandon_popup returned
+    fn execute_abandon(&mut self, retain_bookmarks: bool) -> Result<Option<ComponentAction>> {
         // If none marked, mark current head
-        if self.log_panel.marked_heads.is_empty() {
+        if self.log_panel.mark_count() == 0 {
             self.log_panel.toggle_head_mark();
         }
         // Move selection to parent until it is no longer inside the marked commits
@@ -387,13 +2194,18 @@ impl<'a> LogTab<'a> {
         }
         // Abandon marked commmits
         let commit_id_list = self.log_panel.extract_and_clear_head_marks();
-        new_commander().run_abandon(&commit_id_list)?;
+        new_commander().run_abandon(
+            &commit_id_list,
+            self.abandon_ignore_immutable,
+            retain_bookmarks,
+        )?;
         // Update selection to latest version, in case abandon triggered a rebase.
         let new_selection = new_commander().get_head_latest(&selection)?;
         // Update log panel and diff panel
-        self.set_head(new_selection.clone());
+        self.set_head(new_selection);
+        self.follow_working_copy()?;
         // If selection was moved, tell the application
-        if new_selection != old_selection {
+        if self.head != old_selection {
             Ok(Some(ComponentAction::ChangeHead(self.head.clone())))
         } else {
             Ok(None)
@@ -408,38 +2220,172 @@ impl<'a> LogTab<'a> {
             | LogTabEvent::ScrollUpHalf
             | LogTabEvent::ScrollToBottom
             | LogTabEvent::ScrollToTop
-            | LogTabEvent::ToggleHeadMark => {
+            | LogTabEvent::ToggleHeadMark
+            | LogTabEvent::ToggleVisualMode => {
                 self.log_panel.handle_event(log_tab_event)?;
                 self.sync_head_output();
             }
             LogTabEvent::FocusCurrent => {
-                self.set_head(new_commander().get_current_head()?);
+                self.log_panel.refresh_log_output();
+                return Ok(self.go_to_revision("@"));
+            }
+            LogTabEvent::GoToTrunk => return Ok(self.go_to_revision("trunk()")),
+            LogTabEvent::GoToBookmark => {
+                let commander = new_commander();
+                let mut refs: Vec<String> = commander
+                    .get_bookmarks_list(false)?
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect();
+                refs.extend(commander.get_tags_list()?);
+                self.bookmark_picker = Some(PickerPopup::new(
+                    "Go to bookmark or tag",
+                    "There are no bookmarks or tags",
+                    "go to",
+                    refs,
+                ));
+            }
+            LogTabEvent::CreateTag => {
+                if !new_commander().is_colocated() {
+                    return Ok(ComponentInputResult::HandledAction(
+                        ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                            "Create tag",
+                            "Tags can only be created in repositories colocated with git",
+                        )))),
+                    ));
+                }
+                self.tag_textarea = Some(TextArea::default());
+            }
+            LogTabEvent::MoveBookmark => {
+                let bookmarks = new_commander()
+                    .get_bookmarks_list(false)?
+                    .into_iter()
+                    .filter(|bookmark| bookmark.remote.is_none())
+                    .map(|bookmark| bookmark.name)
+                    .collect();
+                self.move_bookmark_picker = Some(
+                    PickerPopup::new(
+                        "Move bookmark to selected change",
+                        "There are no local bookmarks",
+                        "move bookmark",
+                        bookmarks,
+                    )
+                    .with_filter(),
+                );
+            }
+            LogTabEvent::ResolveDivergence => return self.open_divergence_picker(),
+            LogTabEvent::TogglePin => self.log_panel.toggle_pin(),
+            LogTabEvent::GoToPin => {
+                let pins: Vec<(Head, String)> = self
+                    .log_panel
+                    .pinned_commits()
+                    .iter()
+                    .map(|(head, summary)| {
+                        (
+                            head.clone(),
+                            format!("{} {summary}", head.change_id.short()),
+                        )
+                    })
+                    .collect();
+                let entries = pins.iter().map(|(_, entry)| entry.clone()).collect();
+                self.pin_picker = Some((
+                    PickerPopup::new(
+                        "Pinned changes",
+                        "There are no pinned changes",
+                        "go to change",
+                        entries,
+                    ),
+                    pins,
+                ));
             }
             LogTabEvent::ToggleDiffFormat => {
+                self.diff_format_overrides.remove(&self.head.change_id);
                 self.diff_format = self.diff_format.get_next(self.config.diff_tool());
                 self.refresh_head_output();
             }
+            LogTabEvent::ToggleUnifiedDiff => self.toggle_unified_diff(),
+            LogTabEvent::ToggleCompareBase => {
+                self.log_panel.toggle_compare_base();
+                self.refresh_head_output();
+            }
+            LogTabEvent::IncreaseContext => self.change_diff_context(1),
+            LogTabEvent::DecreaseContext => self.change_diff_context(-1),
             LogTabEvent::Refresh => {
                 self.mark_cache_as_dirty();
                 self.refresh_log_output();
+                self.follow_working_copy()?;
+            }
+            LogTabEvent::ToggleFollow => {
+                self.log_panel.follow_working_copy = !self.log_panel.follow_working_copy;
+                self.follow_working_copy()?;
             }
 
             LogTabEvent::Duplicate => {
-                let _ = new_commander().run_duplicate(&self.head.change_id.to_string());
-                self.refresh_log_output();
+                let commit_ids = self.log_panel.extract_and_clear_head_marks();
+                if commit_ids.is_empty() {
+                    new_commander().run_duplicate([self.head.commit_id.as_str()])?;
+                } else {
+                    new_commander().run_duplicate(commit_ids.iter().map(CommitId::as_str))?;
+                }
+                return self.select_duplicate();
+            }
+            LogTabEvent::DuplicateOnto { insert_after } => {
+                match self.log_panel.duplicate_sources.take() {
+                    None => {
+                        let mut commit_ids = self.log_panel.extract_and_clear_head_marks();
+                        if commit_ids.is_empty() {
+                            commit_ids.push(self.head.commit_id.clone());
+                        }
+                        self.log_panel.duplicate_sources = Some(commit_ids);
+                    }
+                    Some(commit_ids) => {
+                        new_commander().run_duplicate_onto(
+                            &commit_ids,
+                            self.head.commit_id.as_str(),
+                            insert_after,
+                        )?;
+                        return self.select_duplicate();
+                    }
+                }
             }
 
             LogTabEvent::CreateNew { describe } => {
-                return self.handle_new(describe);
+                return self.handle_new(describe, None);
+            }
+            LogTabEvent::InsertNew { before, describe } => {
+                return self.handle_new(describe, Some(before));
             }
             LogTabEvent::Rebase => {
+                // Keep the visual selection marked, so the target can be
+                // selected next
+                if self.log_panel.is_visual_mode() {
+                    self.log_panel.mark_visual_heads();
+                    return Ok(ComponentInputResult::Handled);
+                }
                 let source_change = new_commander().get_current_head()?;
                 let target_change = &self.head;
-                self.rebase_popup = Some(RebasePopup::new(
-                    source_change.clone(),
-                    target_change.clone(),
-                ));
+                let mut rebase_popup =
+                    RebasePopup::new(source_change.clone(), target_change.clone());
+                if let Some(rebase_source) = self.log_panel.rebase_source.take() {
+                    rebase_popup = rebase_popup.with_picked_source(rebase_source);
+                } else if self.log_panel.mark_count() > 0 {
+                    rebase_popup =
+                        rebase_popup.with_marked_sources(self.log_panel.marked_commit_ids());
+                }
+                self.rebase_popup = Some(rebase_popup.with_preview());
             }
+            LogTabEvent::RebaseChange => match self.log_panel.rebase_source.take() {
+                None => self.log_panel.rebase_source = Some(self.head.clone()),
+                // Pressed again on the source, stop picking
+                Some(source) if source == self.head => (),
+                Some(source) => {
+                    self.rebase_popup = Some(
+                        RebasePopup::new(source.clone(), self.head.clone())
+                            .with_picked_source(source)
+                            .with_preview(),
+                    );
+                }
+            },
             LogTabEvent::Squash { ignore_immutable } => {
                 let current_head = new_commander().get_current_head()?;
                 let target = if self.head.change_id == current_head.change_id {
@@ -462,26 +2408,21 @@ impl<'a> LogTab<'a> {
                     self.head.clone()
                 };
 
-                if target.immutable && !ignore_immutable {
-                    return Ok(ComponentInputResult::HandledAction(
-                        ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
-                            "Squash",
-                            "Cannot squash onto immutable change",
-                        )))),
-                    ));
-                }
-
                 let description = if self.squash_target.is_some() {
                     "Are you sure you want to squash @ into its parent?"
                 } else {
                     "Are you sure you want to squash @ into this change?"
                 };
+                let squash_ignore_immutable = ignore_immutable || target.immutable;
                 let mut lines = vec![
                     Line::from(description),
                     Line::from(format!("Squash into {}", target.change_id.as_str())),
                 ];
-                if ignore_immutable {
-                    lines.push(Line::from("This change is immutable."));
+                lines.extend(rewrites_preview(|commander| {
+                    commander.run_squash(target.commit_id.as_str(), squash_ignore_immutable)
+                }));
+                if target.immutable {
+                    lines.push(immutable_warning());
                 }
                 self.popup = ConfirmDialogState::new(
                     SQUASH_POPUP_ID,
@@ -493,24 +2434,61 @@ impl<'a> LogTab<'a> {
                     .with_no_button(ButtonLabel::NO.clone())
                     .with_listener(Some(self.popup_tx.clone()))
                     .open();
-                self.squash_ignore_immutable = ignore_immutable;
+                self.squash_ignore_immutable = squash_ignore_immutable;
+            }
+            LogTabEvent::Split => return self.open_split(),
+            LogTabEvent::SplitHunks => {
+                return Ok(ComponentInputResult::HandledAction(
+                    ComponentAction::RunInteractive(interactive_args(
+                        "split",
+                        ["-r", self.head.commit_id.as_str()],
+                    )),
+                ));
+            }
+            LogTabEvent::SquashHunks => {
+                // Into the destination picked after I, or into the parent
+                let args = match self.log_panel.squash_source.take() {
+                    Some(source) => interactive_args(
+                        "squash",
+                        [
+                            "--from",
+                            source.commit_id.as_str(),
+                            "--into",
+                            self.head.commit_id.as_str(),
+                        ],
+                    ),
+                    None => interactive_args("squash", ["-r", self.head.commit_id.as_str()]),
+                };
+                return Ok(ComponentInputResult::HandledAction(
+                    ComponentAction::RunInteractive(args),
+                ));
             }
+            LogTabEvent::SquashIntoParent => {
+                let parent = new_commander().get_commit_parent(&self.head.commit_id)?;
+                self.confirm_squash_change(self.head.clone(), parent, true)?;
+            }
+            LogTabEvent::SquashInto => match self.log_panel.squash_source.take() {
+                None => self.log_panel.squash_source = Some(self.head.clone()),
+                // Pressed again on the source, stop picking
+                Some(source) if source == self.head => (),
+                Some(source) => self.confirm_squash_change(source, self.head.clone(), false)?,
+            },
             LogTabEvent::EditChange { ignore_immutable } => {
-                if self.head.immutable && !ignore_immutable {
-                    return Ok(ComponentInputResult::HandledAction(
-                        ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
-                            " Edit ",
-                            "The change cannot be edited because it is immutable.",
-                        )))),
-                    ));
-                }
-
+                self.edit_ignore_immutable = ignore_immutable || self.head.immutable;
+                // Only changes that are risky to rewrite need confirmation
                 let mut lines = vec![
                     Line::from("Are you sure you want to edit an existing change?"),
                     Line::from(format!("Change: {}", self.head.change_id.as_str())),
                 ];
-                if ignore_immutable {
-                    lines.push(Line::from("This change is immutable."))
+                if self.head.immutable {
+                    lines.push(immutable_warning())
+                } else if new_commander().check_revision_pushed(self.head.commit_id.as_str())? {
+                    lines.push(
+                        Line::from("This change was pushed, editing it rewrites pushed history.")
+                            .fg(Color::Yellow),
+                    )
+                } else {
+                    return Ok(ComponentInputResult::HandledAction(self.execute_edit()?));
                 }
                 self.popup = ConfirmDialogState::new(
                     EDIT_POPUP_ID,
@@ -522,37 +2500,100 @@ impl<'a> LogTab<'a> {
                     .with_no_button(ButtonLabel::NO.clone())
                     .with_listener(Some(self.popup_tx.clone()))
                     .open();
-                self.edit_ignore_immutable = ignore_immutable;
             }
             LogTabEvent::Abandon => {
                 return self.handle_abandon();
             }
-            LogTabEvent::Absorb => {
-                new_commander().run_absorb(self.head.commit_id.as_str())?;
+            LogTabEvent::MoveChangeUp | LogTabEvent::MoveChangeDown => {
+                let up = log_tab_event == LogTabEvent::MoveChangeUp;
+                new_commander().run_move_change(&self.head.commit_id, up)?;
                 self.set_head(new_commander().get_head_latest(&self.head)?);
                 return Ok(ComponentInputResult::HandledAction(
                     ComponentAction::ChangeHead(self.head.clone()),
                 ));
             }
+            LogTabEvent::Absorb => {
+                return self.handle_absorb();
+            }
+            LogTabEvent::Sign { unsign } => {
+                return self.handle_sign(unsign);
+            }
+            LogTabEvent::Fix => {
+                let commit_ids = self.log_panel.marked_commit_ids();
+                let revset = match commit_ids.is_empty() {
+                    true => self.head.commit_id.as_str().to_owned(),
+                    false => commit_ids
+                        .iter()
+                        .map(CommitId::as_str)
+                        .collect::<Vec<_>>()
+                        .join(" | "),
+                };
+                let output = match new_commander().run_fix(&revset, self.config.fix_command()) {
+                    Ok(output) => output,
+                    Err(err) => {
+                        return Ok(ComponentInputResult::HandledAction(
+                            ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                                "Fix error",
+                                format!("{err:?}"),
+                            )))),
+                        ));
+                    }
+                };
+                // The fixed changes and their descendants were rewritten
+                self.log_panel.extract_and_clear_head_marks();
+                self.mark_cache_as_dirty();
+                let head = new_commander().get_head_latest(&self.head)?;
+                self.set_head(head);
+                let mut actions = vec![ComponentAction::ChangeHead(self.head.clone())];
+                if !output.is_empty() {
+                    actions.push(ComponentAction::SetPopup(Some(Box::new(
+                        MessagePopup::new("Fix message", output),
+                    ))));
+                }
+                return Ok(ComponentInputResult::HandledAction(
+                    ComponentAction::Multiple(actions),
+                ));
+            }
+            LogTabEvent::Parallelize => {
+                return self.handle_parallelize();
+            }
+            LogTabEvent::SimplifyParents => {
+                return self.handle_simplify_parents();
+            }
+            LogTabEvent::Commit => {
+                return self.open_commit();
+            }
+            LogTabEvent::CustomCommand => {
+                return Ok(self.open_custom_commands());
+            }
+            LogTabEvent::Reword => {
+                return self.start_reword();
+            }
+            LogTabEvent::EditAuthor => {
+                let author = new_commander().get_commit_author(&self.head.commit_id)?;
+                let warning = self.head.immutable.then(immutable_warning);
+                self.author_popup = Some(AuthorPopup::new(author, warning));
+            }
             LogTabEvent::Describe => {
                 if self.head.immutable {
-                    return Ok(ComponentInputResult::HandledAction(
-                        ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
-                            "Describe",
-                            "The change cannot be described because it is immutable.",
-                        )))),
-                    ));
-                } else {
-                    let mut textarea = TextArea::new(
-                        new_commander()
-                            .get_commit_description(&self.head.commit_id)?
-                            .split("\n")
-                            .map(|line| line.to_string())
-                            .collect(),
+                    // Confirm before opening the description
+                    self.popup = ConfirmDialogState::new(
+                        DESCRIBE_POPUP_ID,
+                        Span::styled(" Describe ", Style::new().bold().cyan()),
+                        Text::from(vec![
+                            Line::from("Are you sure you want to describe an immutable change?"),
+                            Line::from(format!("Change: {}", self.head.change_id.as_str())),
+                            immutable_warning(),
+                        ])
+                        .fg(Color::default()),
                     );
-                    textarea.move_cursor(CursorMove::End);
-                    self.describe_textarea = Some(textarea);
-                    return Ok(ComponentInputResult::Handled);
+                    self.popup
+                        .with_yes_button(ButtonLabel::YES.clone())
+                        .with_no_button(ButtonLabel::NO.clone())
+                        .with_listener(Some(self.popup_tx.clone()))
+                        .open();
+                } else {
+                    self.open_describe(false)?;
                 }
             }
             LogTabEvent::EditRevset => {
@@ -566,11 +2607,66 @@ impl<'a> LogTab<'a> {
                         .collect(),
                 );
                 textarea.move_cursor(CursorMove::End);
+                self.revset_check = Some(RevsetCheck::new(textarea.lines().join("\n")));
+                self.revset_history.reset();
                 self.log_revset_textarea = Some(textarea);
                 return Ok(ComponentInputResult::Handled);
             }
-            LogTabEvent::SetBookmark => {
-                return Ok(ComponentInputResult::HandledAction(
+            LogTabEvent::FilterPaths => {
+                let mut textarea =
+                    TextArea::new(vec![shell_words::join(&self.log_panel.log_paths)]);
+                textarea.move_cursor(CursorMove::End);
+                self.log_paths_textarea = Some(textarea);
+                return Ok(ComponentInputResult::Handled);
+            }
+            LogTabEvent::ClearPathFilter => {
+                self.log_panel.set_log_paths(vec![]);
+                self.update_cache_active_commits();
+                self.sync_head_output();
+            }
+            LogTabEvent::ToggleTimestamps => self.log_panel.toggle_relative_timestamps(),
+            LogTabEvent::ToggleRelated => self.log_panel.toggle_highlight_related(),
+            LogTabEvent::ToggleConflicts => self.toggle_conflicts(),
+            LogTabEvent::ToggleHidden => self.toggle_hidden()?,
+            LogTabEvent::ToggleSnapshot => {
+                let paused = !snapshot_paused();
+                set_snapshot_paused(paused);
+                if !paused {
+                    // Show the files edited while paused
+                    self.reload()?;
+                }
+            }
+            LogTabEvent::ExpandElided => {
+                let result = self.log_panel.expand_elided();
+                self.update_cache_active_commits();
+                self.sync_head_output();
+                let message = match result {
+                    Ok(true) => return Ok(ComponentInputResult::Handled),
+                    Ok(false) => "No elided revisions below the selected change".to_owned(),
+                    Err(err) => err.to_string(),
+                };
+                return Ok(ComponentInputResult::HandledAction(
+                    ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                        "Expand elided revisions",
+                        message,
+                    )))),
+                ));
+            }
+            LogTabEvent::SearchLog => self.log_panel.start_search(),
+            LogTabEvent::SearchDetails => {
+                self.head_panel.handle_event(DetailsPanelEvent::StartSearch)
+            }
+            LogTabEvent::GoToChange => {
+                self.go_to_textarea = Some(TextArea::default());
+                return Ok(ComponentInputResult::Handled);
+            }
+            LogTabEvent::FilterLog => {
+                self.log_filter_popup =
+                    Some(LogFilterPopup::new(self.log_panel.log_revset.as_deref()));
+                return Ok(ComponentInputResult::Handled);
+            }
+            LogTabEvent::SetBookmark => {
+                return Ok(ComponentInputResult::HandledAction(
                     ComponentAction::SetPopup(Some(Box::new(BookmarkSetPopup::new(
                         self.config.clone(),
                         Some(self.head.change_id.clone()),
@@ -585,44 +2681,165 @@ impl<'a> LogTab<'a> {
                 ));
             }
             LogTabEvent::CopyChangeId => {
-                // Copy change ID to clipboard using crossterm
-                let change_id = self.head.change_id.as_str();
-                let _ = execute!(
-                    std::io::stdout(),
-                    CopyToClipboard::to_clipboard_from(change_id)
-                );
+                copy_to_clipboard(self.head.change_id.as_str());
             }
             LogTabEvent::CopyRev => {
-                // Copy revision (commit ID) to clipboard using crossterm
-                let commit_id = self.head.commit_id.as_str();
-                let _ = execute!(
-                    std::io::stdout(),
-                    CopyToClipboard::to_clipboard_from(commit_id)
-                );
+                // Copy revision (commit ID)
+                copy_to_clipboard(self.head.commit_id.as_str());
+            }
+            LogTabEvent::RestoreFrom => match self.log_panel.restore_destination.take() {
+                None => self.log_panel.restore_destination = Some(self.head.clone()),
+                // Pressed again on the destination, stop picking
+                Some(destination) if destination == self.head => (),
+                Some(destination) => {
+                    let files = new_commander().get_files_between(
+                        self.head.commit_id.as_str(),
+                        destination.commit_id.as_str(),
+                    )?;
+                    if files.is_empty() {
+                        return Ok(ComponentInputResult::HandledAction(
+                            ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                                "Restore",
+                                "The files of both changes are the same",
+                            )))),
+                        ));
+                    }
+                    self.restore_popup =
+                        Some(RestorePopup::new(self.head.clone(), destination, files));
+                }
+            },
+            LogTabEvent::ShowFile => {
+                if let Err(err) = self.pick_shown_file() {
+                    return Ok(ComponentInputResult::HandledAction(
+                        ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                            "Show file",
+                            format!("{err:#}"),
+                        )))),
+                    ));
+                }
+            }
+            LogTabEvent::ShowEvolog => match new_commander().get_evolog(&self.head.commit_id) {
+                Ok(versions) => {
+                    self.evolog_popup =
+                        Some(EvologPopup::new(versions, self.head_diff_format().clone()));
+                }
+                Err(err) => {
+                    return Ok(ComponentInputResult::HandledAction(
+                        ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                            "Evolution log",
+                            err.to_string(),
+                        )))),
+                    ));
+                }
+            },
+            LogTabEvent::DiscardFile => {
+                if let Some(file) = self.head_file() {
+                    new_commander().run_discard_file(self.head.commit_id.as_str(), &file.path)?;
+                    self.set_head(new_commander().get_head_latest(&self.head)?);
+                    return Ok(ComponentInputResult::HandledAction(
+                        ComponentAction::ChangeHead(self.head.clone()),
+                    ));
+                }
+            }
+            LogTabEvent::CopyFilePath => {
+                if let Some(file) = self.head_file() {
+                    copy_to_clipboard(&file.path);
+                }
+            }
+            LogTabEvent::OpenFile => {
+                if let Some(file) = self.head_file() {
+                    return Ok(ComponentInputResult::HandledAction(
+                        ComponentAction::OpenEditor(file),
+                    ));
+                }
             }
             LogTabEvent::Push {
                 all_bookmarks,
                 allow_new,
             } => {
-                let commit_id = self.head.commit_id.clone();
-
-                let loader = LoaderPopup::new("Pushing".to_string(), move || {
-                    new_commander().git_push(all_bookmarks, allow_new, &commit_id)
+                let target = if all_bookmarks {
+                    PushTarget::All
+                } else {
+                    PushTarget::Bookmarks(self.head.commit_id.clone())
+                };
+                return Ok(self.confirm_push(target, allow_new));
+            }
+            LogTabEvent::OpenInBrowser => {
+                return Ok(match self.open_on_forge() {
+                    Ok(result) => result,
+                    Err(err) => {
+                        ComponentInputResult::HandledAction(ComponentAction::SetPopup(Some(
+                            Box::new(MessagePopup::new("Open in browser", err.to_string())),
+                        )))
+                    }
+                });
+            }
+            LogTabEvent::ShowChecks => return Ok(self.show_checks()),
+            LogTabEvent::DiffOperations => {
+                if let Err(err) = self.pick_operation_diff(None) {
+                    return Ok(ComponentInputResult::HandledAction(
+                        ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                            "Operation diff",
+                            err.to_string(),
+                        )))),
+                    ));
+                }
+            }
+            LogTabEvent::ViewAtOperation => {
+                if let Err(err) = self.pick_operation() {
+                    return Ok(ComponentInputResult::HandledAction(
+                        ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                            "View at operation",
+                            err.to_string(),
+                        )))),
+                    ));
+                }
+            }
+            LogTabEvent::GitImport => {
+                let loader = LoaderPopup::new("Importing from git".to_string(), || {
+                    new_commander().git_import()
                 });
-
                 return Ok(ComponentInputResult::HandledAction(
                     ComponentAction::SetPopup(Some(Box::new(loader))),
                 ));
             }
-            LogTabEvent::Fetch { all_remotes } => {
-                let loader = LoaderPopup::new("Fetching".to_string(), move || {
-                    new_commander().git_fetch(all_remotes)
+            LogTabEvent::GitExport => {
+                let loader = LoaderPopup::new("Exporting to git".to_string(), || {
+                    new_commander().git_export()
                 });
-
                 return Ok(ComponentInputResult::HandledAction(
                     ComponentAction::SetPopup(Some(Box::new(loader))),
                 ));
             }
+            LogTabEvent::PushChange => {
+                let target = PushTarget::Change(self.head.change_id.clone());
+                return Ok(self.confirm_push(target, false));
+            }
+            LogTabEvent::Fetch { all_remotes: true } => return Ok(fetch(FetchFrom::AllRemotes)),
+            LogTabEvent::Fetch { all_remotes: false } => {
+                let mut remotes = new_commander().get_git_remotes()?;
+                if remotes.len() == 1 {
+                    let remote = remotes.pop().unwrap_or_default();
+                    return Ok(fetch(FetchFrom::Remote(remote)));
+                }
+                if !remotes.is_empty() {
+                    remotes.push(ALL_REMOTES_ENTRY.to_owned());
+                }
+                self.remote_picker = Some(PickerPopup::new(
+                    "Fetch from remote",
+                    "There are no remotes",
+                    "fetch",
+                    remotes,
+                ));
+            }
+            LogTabEvent::Maintenance => {
+                self.maintenance_picker = Some(PickerPopup::new(
+                    "Maintenance",
+                    "",
+                    "run",
+                    MaintenanceTask::ALL.map(MaintenanceTask::entry).to_vec(),
+                ));
+            }
             LogTabEvent::OpenHelp => {
                 return Ok(ComponentInputResult::HandledAction(
                     ComponentAction::SetPopup(Some(Box::new(HelpPopup::new(
@@ -637,12 +2854,41 @@ impl<'a> LogTab<'a> {
                                 "Ctrl+f/Ctrl+b".to_owned(),
                                 "scroll down/up by page".to_owned(),
                             ),
+                            ("g/G".to_owned(), "go to top/bottom".to_owned()),
                             ("w".to_owned(), "toggle diff format".to_owned()),
                             ("W".to_owned(), "toggle wrapping".to_owned()),
+                            ("L".to_owned(), "toggle line numbers".to_owned()),
+                            ("\\".to_owned(), "search".to_owned()),
+                            ("n/N".to_owned(), "next/previous match".to_owned()),
+                            ("]f/[f".to_owned(), "next/previous file".to_owned()),
+                            ("]h/[h".to_owned(), "next/previous hunk".to_owned()),
+                            ("]c/[c".to_owned(), "next/previous conflict".to_owned()),
+                            ("Ctrl+g".to_owned(), "list files".to_owned()),
+                            ("v".to_owned(), "copy mode".to_owned()),
+                            ("za/zA".to_owned(), "fold file/all files".to_owned()),
                         ],
                     )))),
                 ));
             }
+            LogTabEvent::Cancel if self.head_load.is_some() || self.log_panel.is_loading_page() => {
+                self.cancel_head_load();
+                self.log_panel.cancel_page_load();
+            }
+            LogTabEvent::Cancel if self.log_panel.is_visual_mode() => {
+                self.log_panel.toggle_visual_mode();
+            }
+            LogTabEvent::Cancel if self.log_panel.squash_source.is_some() => {
+                self.log_panel.squash_source = None;
+            }
+            LogTabEvent::Cancel if self.log_panel.rebase_source.is_some() => {
+                self.log_panel.rebase_source = None;
+            }
+            LogTabEvent::Cancel if self.log_panel.duplicate_sources.is_some() => {
+                self.log_panel.duplicate_sources = None;
+            }
+            LogTabEvent::Cancel if self.log_panel.restore_destination.is_some() => {
+                self.log_panel.restore_destination = None;
+            }
             LogTabEvent::Save
             | LogTabEvent::Cancel
             | LogTabEvent::ClosePopup
@@ -653,29 +2899,71 @@ impl<'a> LogTab<'a> {
 }
 
 impl Component for LogTab<'_> {
+    fn selection(&mut self) -> Selection {
+        Selection {
+            head: Some(self.head.clone()),
+            file: self.head_file().map(|request| request.path),
+        }
+    }
+
     fn focus(&mut self) -> Result<()> {
         let latest_head = new_commander().get_head_latest(&self.head)?;
         self.set_head(latest_head);
-        Ok(())
+        self.follow_working_copy()
     }
 
     fn update(&mut self) -> Result<Option<ComponentAction>> {
-        // Check for popup action
-        if let Ok(res) = self.popup_rx.try_recv()
-            && res.1.unwrap_or(false)
+        if self.log_panel.load_next_page() {
+            self.update_cache_active_commits();
+        }
+
+        self.receive_head_output();
+        self.load_requested_file();
+        self.receive_file_diff();
+
+        if let Some(revset_check) = self.revset_check.as_mut() {
+            revset_check.update();
+        }
+
+        if let Some(drift) = self
+            .git_drift_check
+            .as_mut()
+            .and_then(GitDriftCheck::update)
+        {
+            let line = self.git_drift_line(&drift);
+            self.log_panel.set_git_drift(line);
+        }
+
+        if let Some(ci_status) = self.ci_status.as_mut()
+            && ci_status.update(|| self.log_panel.commit_ids())
         {
+            self.log_panel.set_ci_states(ci_status.states());
+        }
+
+        // Check for popup action
+        if let Ok(res) = self.popup_rx.try_recv() {
+            let confirmed = res.1.unwrap_or(false);
             match res.0 {
+                PREVIEW_POPUP_ID if confirmed => {
+                    self.run_previewed_command()?;
+                    // The marked commits were rewritten
+                    self.log_panel.extract_and_clear_head_marks();
+                    let head = new_commander().get_head_latest(&self.head);
+                    self.set_head(head.or_else(|_| new_commander().get_current_head())?);
+                    return Ok(Some(ComponentAction::ChangeHead(self.head.clone())));
+                }
+                PREVIEW_POPUP_ID => {
+                    self.previewed_command = None;
+                }
+                _ if !confirmed => {}
                 NEW_POPUP_ID => {
                     return self.execute_new();
                 }
                 EDIT_POPUP_ID => {
-                    new_commander()
-                        .run_edit(self.head.commit_id.as_str(), self.edit_ignore_immutable)?;
-                    self.refresh_log_output();
-                    return Ok(Some(ComponentAction::ChangeHead(self.head.clone())));
+                    return self.execute_edit().map(Some);
                 }
-                ABANDON_POPUP_ID => {
-                    return self.execute_abandon();
+                DESCRIBE_POPUP_ID => {
+                    self.open_describe(true)?;
                 }
                 SQUASH_POPUP_ID => {
                     let target_id = self
@@ -687,6 +2975,21 @@ impl Component for LogTab<'_> {
                     self.set_head(new_commander().get_current_head()?);
                     return Ok(Some(ComponentAction::ChangeHead(self.head.clone())));
                 }
+                SQUASH_CHANGE_POPUP_ID => {
+                    if let Some(squash) = self.pending_squash.take() {
+                        return self.execute_squash_change(squash);
+                    }
+                }
+                MOVE_BOOKMARK_POPUP_ID => {
+                    if let Some(name) = self.pending_bookmark_move.take() {
+                        return Ok(self.move_bookmark(name, true));
+                    }
+                }
+                PRUNE_OPERATIONS_POPUP_ID => {
+                    return Ok(Some(ComponentAction::SetPopup(Some(Box::new(
+                        MaintenanceTask::PruneOperations.loader(),
+                    )))));
+                }
                 _ => {}
             }
         }
@@ -709,11 +3012,9 @@ impl Component for LogTab<'_> {
         self.log_panel.draw(f, chunks[0])?;
 
         // Draw change details
-        if let Some(content) = self.commit_show_cache.get(&self.head_key) {
-            self.head_panel
-                .render_context::<LargeStringContent>(content.value())
-                .title(format!(" Details for {} ", self.head.change_id))
-                .draw(f, chunks[1])
+        self.draw_head_panel(f, chunks[1]);
+        if self.refresh_head_output_on_resize() {
+            self.draw_head_panel(f, chunks[1]);
         }
 
         // Draw popup
@@ -732,9 +3033,19 @@ impl Component for LogTab<'_> {
 
         // Draw describe textarea
         {
+            let title = match &self.reword {
+                Some(reword) => format!(
+                    " Reword {}/{}: {} ",
+                    reword.index + 1,
+                    reword.heads.len(),
+                    self.head.change_id.short()
+                ),
+                None if self.committing => " Commit ".to_owned(),
+                None => " Describe ".to_owned(),
+            };
             if let Some(describe_textarea) = self.describe_textarea.as_mut() {
                 let block = Block::bordered()
-                    .title(Span::styled(" Describe ", Style::new().bold().cyan()))
+                    .title(Span::styled(title, Style::new().bold().cyan()))
                     .title_alignment(Alignment::Center)
                     .border_type(BorderType::Rounded)
                     .border_style(Style::default().fg(Color::Green));
@@ -757,51 +3068,132 @@ impl Component for LogTab<'_> {
 
                 f.render_widget(&*describe_textarea, popup_chunks[0]);
 
-                let help = Paragraph::new(vec!["Ctrl+s: save | Escape: cancel".into()])
-                    .fg(Color::DarkGray)
-                    .alignment(Alignment::Center)
-                    .block(
-                        Block::default()
-                            .borders(Borders::TOP)
-                            .border_type(BorderType::Rounded)
-                            .border_style(Style::default().fg(Color::DarkGray)),
-                    );
+                let help = Paragraph::new(vec![
+                    "Ctrl+s: save | Ctrl+t: add trailer | Escape: cancel".into(),
+                ])
+                .fg(Color::DarkGray)
+                .alignment(Alignment::Center)
+                .block(
+                    Block::default()
+                        .borders(Borders::TOP)
+                        .border_type(BorderType::Rounded)
+                        .border_style(Style::default().fg(Color::DarkGray)),
+                );
 
                 f.render_widget(help, popup_chunks[1]);
             }
         }
 
         // Draw revset textarea
-        {
-            if let Some(log_revset_textarea) = self.log_revset_textarea.as_mut() {
-                let block = Block::bordered()
-                    .title(Span::styled(" Revset ", Style::new().bold().cyan()))
-                    .title_alignment(Alignment::Center)
-                    .border_type(BorderType::Rounded)
-                    .border_style(Style::default().fg(Color::Green));
-                let area = centered_rect_line_height(area, 30, 7);
-                f.render_widget(Clear, area);
-                f.render_widget(&block, area);
+        if let Some(log_revset_textarea) = self.log_revset_textarea.as_ref() {
+            let status = self.revset_check.as_ref().map(RevsetCheck::status_line);
+            draw_textarea_popup(f, area, " Revset ", log_revset_textarea, status);
+        }
 
-                let popup_chunks = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([Constraint::Fill(1), Constraint::Length(2)])
-                    .split(block.inner(area));
+        // Draw revset history popup
+        if let Some(trailer_picker) = self.trailer_picker.as_mut() {
+            trailer_picker.render_widget(f);
+        }
+        if let Some(revset_history_popup) = self.revset_history_popup.as_mut() {
+            revset_history_popup.render_widget(f);
+        }
 
-                f.render_widget(&*log_revset_textarea, popup_chunks[0]);
+        // Draw bookmark picker
+        if let Some(bookmark_picker) = self.bookmark_picker.as_mut() {
+            bookmark_picker.render_widget(f);
+        }
 
-                let help = Paragraph::new(vec!["Ctrl+s: save | Escape: cancel".into()])
-                    .fg(Color::DarkGray)
-                    .alignment(Alignment::Center)
-                    .block(
-                        Block::default()
-                            .borders(Borders::TOP)
-                            .border_type(BorderType::Rounded)
-                            .border_style(Style::default().fg(Color::DarkGray)),
-                    );
+        if let Some(move_bookmark_picker) = self.move_bookmark_picker.as_mut() {
+            move_bookmark_picker.render_widget(f);
+        }
 
-                f.render_widget(help, popup_chunks[1]);
-            }
+        if let Some(remote_picker) = self.remote_picker.as_mut() {
+            remote_picker.render_widget(f);
+        }
+
+        if let Some(maintenance_picker) = self.maintenance_picker.as_mut() {
+            maintenance_picker.render_widget(f);
+        }
+
+        if let Some((forge_picker, _)) = self.forge_picker.as_mut() {
+            forge_picker.render_widget(f);
+        }
+
+        if let Some((file_picker, _)) = self.file_picker.as_mut() {
+            file_picker.render_widget(f);
+        }
+        if let Some((operation_picker, _)) = self.operation_picker.as_mut() {
+            operation_picker.render_widget(f);
+        }
+
+        if let Some((operation_diff_picker, _, _)) = self.operation_diff_picker.as_mut() {
+            operation_diff_picker.render_widget(f);
+        }
+
+        if let Some(push_popup) = self.push_popup.as_ref() {
+            push_popup.render_widget(f);
+        }
+
+        // Draw divergence picker
+        if let Some((divergence_picker, _)) = self.divergence_picker.as_mut() {
+            divergence_picker.render_widget(f);
+        }
+
+        // Draw split popup
+        if let Some((split_popup, _)) = self.split_popup.as_mut() {
+            split_popup.render_widget(f);
+        }
+
+        // Draw custom command popup
+        if let Some(custom_command_popup) = self.custom_command_popup.as_ref() {
+            custom_command_popup.render_widget(f);
+        }
+
+        // Draw restore popup
+        if let Some(evolog_popup) = self.evolog_popup.as_mut() {
+            evolog_popup.render_widget(f);
+        }
+
+        if let Some(restore_popup) = self.restore_popup.as_mut() {
+            restore_popup.render_widget(f);
+        }
+
+        // Draw abandon popup
+        if let Some(abandon_popup) = self.abandon_popup.as_ref() {
+            abandon_popup.render_widget(f);
+        }
+
+        // Draw pin picker
+        if let Some((pin_picker, _)) = self.pin_picker.as_mut() {
+            pin_picker.render_widget(f);
+        }
+
+        // Draw paths textarea
+        if let Some(log_paths_textarea) = self.log_paths_textarea.as_ref() {
+            draw_textarea_popup(
+                f,
+                area,
+                " Only changes touching paths ",
+                log_paths_textarea,
+                None,
+            );
+        }
+
+        // Draw go to textarea
+        if let Some(go_to_textarea) = self.go_to_textarea.as_ref() {
+            draw_textarea_popup(
+                f,
+                area,
+                " Go to change ID, commit ID or bookmark ",
+                go_to_textarea,
+                None,
+            );
+        }
+
+        // Draw tag textarea
+        if let Some(tag_textarea) = self.tag_textarea.as_ref() {
+            let title = format!(" Create tag at {} ", self.head.change_id.short());
+            draw_textarea_popup(f, area, &title, tag_textarea, None);
         }
 
         // Draw rebase popup
@@ -811,25 +3203,97 @@ impl Component for LogTab<'_> {
             }
         }
 
+        // Draw log filter popup
+        if let Some(log_filter_popup) = &mut self.log_filter_popup {
+            log_filter_popup.render_widget(f);
+        }
+
+        // Draw author popup
+        if let Some(author_popup) = &mut self.author_popup {
+            author_popup.render_widget(f);
+        }
+
         Ok(())
     }
 
     fn input(&mut self, event: Event) -> Result<ComponentInputResult> {
+        if let Some(trailer_picker) = self.trailer_picker.as_mut() {
+            match trailer_picker.handle_input(event) {
+                PickerAction::None => (),
+                PickerAction::Cancel => self.trailer_picker = None,
+                PickerAction::Select(trailer) => {
+                    self.trailer_picker = None;
+                    if let Some(describe_textarea) = self.describe_textarea.as_mut() {
+                        let description =
+                            add_trailer(&describe_textarea.lines().join("\n"), &trailer);
+                        *describe_textarea =
+                            TextArea::new(description.lines().map(String::from).collect());
+                        describe_textarea.move_cursor(CursorMove::Bottom);
+                        describe_textarea.move_cursor(CursorMove::End);
+                    }
+                }
+            }
+            return Ok(ComponentInputResult::Handled);
+        }
+
         if let Some(describe_textarea) = self.describe_textarea.as_mut() {
             if let Event::Key(key) = event {
+                if key.code == KeyCode::Char('t') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                    self.open_trailer_picker()?;
+                    return Ok(ComponentInputResult::Handled);
+                }
                 match self.keybinds.match_event(key) {
                     LogTabEvent::Save => {
-                        // TODO: Handle error
-                        new_commander().run_describe(
-                            self.head.commit_id.as_str(),
-                            &describe_textarea.lines().join("\n"),
-                        )?;
+                        let message = describe_textarea.lines().join("\n");
+                        let result = if self.committing {
+                            new_commander().run_commit(&message)
+                        } else {
+                            new_commander().run_describe(
+                                self.head.commit_id.as_str(),
+                                &message,
+                                self.describe_ignore_immutable,
+                            )
+                        };
+                        // Keep the editor open on errors, so the draft is not lost
+                        if let Err(err) = result {
+                            let title = if self.committing {
+                                "Commit"
+                            } else {
+                                "Describe"
+                            };
+                            return Ok(ComponentInputResult::HandledAction(
+                                ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                                    title,
+                                    err.to_string(),
+                                )))),
+                            ));
+                        }
+                        if self.committing {
+                            // Select the new empty working copy change
+                            self.committing = false;
+                            self.describe_textarea = None;
+                            self.set_head(new_commander().get_current_head()?);
+                            return Ok(ComponentInputResult::HandledAction(
+                                ComponentAction::ChangeHead(self.head.clone()),
+                            ));
+                        }
                         self.set_head(new_commander().get_head_latest(&self.head)?);
                         self.describe_textarea = None;
+                        if let Some(reword) = self.reword.as_mut() {
+                            reword.index += 1;
+                            self.open_reword_step()?;
+                            return Ok(ComponentInputResult::HandledAction(
+                                ComponentAction::ChangeHead(self.head.clone()),
+                            ));
+                        }
                         return Ok(ComponentInputResult::Handled);
                     }
                     LogTabEvent::Cancel => {
+                        // Stop rewording, the changes described so far keep
+                        // their new descriptions
                         self.describe_textarea = None;
+                        self.reword = None;
+                        self.committing = false;
                         return Ok(ComponentInputResult::Handled);
                     }
                     _ => (),
@@ -839,28 +3303,529 @@ impl Component for LogTab<'_> {
             return Ok(ComponentInputResult::Handled);
         }
 
+        if let Some(revset_history_popup) = self.revset_history_popup.as_mut() {
+            match revset_history_popup.handle_input(event) {
+                PickerAction::None => (),
+                PickerAction::Cancel => self.revset_history_popup = None,
+                PickerAction::Select(revset) => {
+                    self.revset_history_popup = None;
+                    self.set_revset_text(&revset);
+                }
+            }
+            return Ok(ComponentInputResult::Handled);
+        }
+
         if let Some(log_revset_textarea) = self.log_revset_textarea.as_mut() {
             if let Event::Key(key) = event {
+                let row = log_revset_textarea.cursor().0;
+                let last_row = log_revset_textarea.lines().len().saturating_sub(1);
+                match key.code {
+                    // Step through the history from the first/last line
+                    KeyCode::Up if row == 0 => {
+                        let text = log_revset_textarea.lines().join("\n");
+                        if let Some(revset) = self.revset_history.older(&text) {
+                            let revset = revset.to_owned();
+                            self.set_revset_text(&revset);
+                        }
+                        return Ok(ComponentInputResult::Handled);
+                    }
+                    KeyCode::Down if row == last_row => {
+                        if let Some(revset) = self.revset_history.newer() {
+                            let revset = revset.to_owned();
+                            self.set_revset_text(&revset);
+                        }
+                        return Ok(ComponentInputResult::Handled);
+                    }
+                    KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        self.revset_history_popup = Some(PickerPopup::new(
+                            "Recent revsets",
+                            "No revsets were used yet",
+                            "edit revset",
+                            self.revset_history.entries().to_vec(),
+                        ));
+                        return Ok(ComponentInputResult::Handled);
+                    }
+                    _ => (),
+                }
                 match self.keybinds.match_event(key) {
                     LogTabEvent::Save => {
                         let log_revset = log_revset_textarea.lines().join("\n");
-                        self.log_panel.log_revset = if log_revset.trim().is_empty() {
+                        self.apply_log_revset(if log_revset.trim().is_empty() {
                             None
                         } else {
                             Some(log_revset)
-                        };
-                        self.refresh_log_output();
+                        });
                         self.log_revset_textarea = None;
+                        self.revset_check = None;
                         return Ok(ComponentInputResult::Handled);
                     }
                     LogTabEvent::Cancel => {
                         self.log_revset_textarea = None;
+                        self.revset_check = None;
                         return Ok(ComponentInputResult::Handled);
                     }
                     _ => (),
                 }
             }
             log_revset_textarea.input(event);
+            if let Some(revset_check) = self.revset_check.as_mut() {
+                revset_check.set_revset(log_revset_textarea.lines().join("\n"));
+            }
+            return Ok(ComponentInputResult::Handled);
+        }
+
+        if let Some(log_paths_textarea) = self.log_paths_textarea.as_mut() {
+            if let Event::Key(key) = event {
+                match self.keybinds.match_event(key) {
+                    LogTabEvent::Save => {
+                        let log_paths = log_paths_textarea.lines().join(" ");
+                        // Paths with spaces can be quoted like in a shell
+                        let log_paths = shell_words::split(&log_paths).unwrap_or_else(|_| {
+                            log_paths.split_whitespace().map(String::from).collect()
+                        });
+                        self.log_panel.set_log_paths(log_paths);
+                        self.update_cache_active_commits();
+                        self.sync_head_output();
+                        self.log_paths_textarea = None;
+                        return Ok(ComponentInputResult::Handled);
+                    }
+                    LogTabEvent::Cancel => {
+                        self.log_paths_textarea = None;
+                        return Ok(ComponentInputResult::Handled);
+                    }
+                    _ => (),
+                }
+            }
+            log_paths_textarea.input(event);
+            return Ok(ComponentInputResult::Handled);
+        }
+
+        if let Some(go_to_textarea) = self.go_to_textarea.as_mut() {
+            if let Event::Key(key) = event {
+                match self.keybinds.match_event(key) {
+                    LogTabEvent::Save => (),
+                    _ if key.code == KeyCode::Enter => (),
+                    LogTabEvent::Cancel => {
+                        self.go_to_textarea = None;
+                        return Ok(ComponentInputResult::Handled);
+                    }
+                    _ => {
+                        go_to_textarea.input(event);
+                        return Ok(ComponentInputResult::Handled);
+                    }
+                }
+                let revision = go_to_textarea.lines().join("").trim().to_owned();
+                self.go_to_textarea = None;
+                if revision.is_empty() {
+                    return Ok(ComponentInputResult::Handled);
+                }
+                return Ok(self.go_to_revision(&revision));
+            }
+            return Ok(ComponentInputResult::Handled);
+        }
+
+        if let Some(tag_textarea) = self.tag_textarea.as_mut() {
+            if let Event::Key(key) = event {
+                match self.keybinds.match_event(key) {
+                    LogTabEvent::Save => (),
+                    _ if key.code == KeyCode::Enter => (),
+                    LogTabEvent::Cancel => {
+                        self.tag_textarea = None;
+                        return Ok(ComponentInputResult::Handled);
+                    }
+                    _ => {
+                        tag_textarea.input(event);
+                        return Ok(ComponentInputResult::Handled);
+                    }
+                }
+                let name = tag_textarea.lines().join("").trim().to_owned();
+                self.tag_textarea = None;
+                if name.is_empty() {
+                    return Ok(ComponentInputResult::Handled);
+                }
+                if let Err(err) = new_commander().create_git_tag(&name, &self.head.commit_id) {
+                    return Ok(ComponentInputResult::HandledAction(
+                        ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                            "Create tag",
+                            format!("{err:#}"),
+                        )))),
+                    ));
+                }
+                self.refresh_log_output();
+            }
+            return Ok(ComponentInputResult::Handled);
+        }
+
+        if let Some(bookmark_picker) = self.bookmark_picker.as_mut() {
+            match bookmark_picker.handle_input(event) {
+                PickerAction::None => (),
+                PickerAction::Cancel => self.bookmark_picker = None,
+                PickerAction::Select(bookmark) => {
+                    self.bookmark_picker = None;
+                    return Ok(self.go_to_revision(&bookmark));
+                }
+            }
+            return Ok(ComponentInputResult::Handled);
+        }
+
+        if let Some(push_popup) = self.push_popup.as_mut() {
+            match push_popup.handle_input(event) {
+                PushAction::None => (),
+                PushAction::Cancel => self.push_popup = None,
+                PushAction::Push => {
+                    if let Some(PushPopup {
+                        target, allow_new, ..
+                    }) = self.push_popup.take()
+                    {
+                        let loader = LoaderPopup::new("Pushing".to_string(), move || {
+                            new_commander().git_push(&target, allow_new)
+                        });
+                        return Ok(ComponentInputResult::HandledAction(
+                            ComponentAction::SetPopup(Some(Box::new(loader))),
+                        ));
+                    }
+                }
+            }
+            return Ok(ComponentInputResult::Handled);
+        }
+
+        if let Some(maintenance_picker) = self.maintenance_picker.as_mut() {
+            match maintenance_picker.handle_input(event) {
+                PickerAction::None => (),
+                PickerAction::Cancel => self.maintenance_picker = None,
+                PickerAction::Select(entry) => {
+                    self.maintenance_picker = None;
+                    let task = MaintenanceTask::ALL
+                        .into_iter()
+                        .find(|task| task.entry() == entry);
+                    if task == Some(MaintenanceTask::PruneOperations) {
+                        self.popup = ConfirmDialogState::new(
+                            PRUNE_OPERATIONS_POPUP_ID,
+                            Span::styled(" Prune operations ", Style::new().bold().cyan()),
+                            Text::from(vec![
+                                Line::from(format!(
+                                    "Abandon the operations before the last {}?",
+                                    get_env().jj_config.op_log_keep()
+                                )),
+                                Line::from(
+                                    "They can't be undone or restored afterwards, \
+                                     and their changes can be garbage collected.",
+                                )
+                                .fg(Color::Yellow),
+                            ])
+                            .fg(Color::default()),
+                        );
+                        self.popup
+                            .with_yes_button(ButtonLabel::YES.clone())
+                            .with_no_button(ButtonLabel::NO.clone())
+                            .with_listener(Some(self.popup_tx.clone()))
+                            .open();
+                    } else if let Some(task) = task {
+                        return Ok(ComponentInputResult::HandledAction(
+                            ComponentAction::SetPopup(Some(Box::new(task.loader()))),
+                        ));
+                    }
+                }
+            }
+            return Ok(ComponentInputResult::Handled);
+        }
+
+        if let Some(remote_picker) = self.remote_picker.as_mut() {
+            match remote_picker.handle_input(event) {
+                PickerAction::None => (),
+                PickerAction::Cancel => self.remote_picker = None,
+                PickerAction::Select(remote) => {
+                    self.remote_picker = None;
+                    return Ok(fetch(if remote == ALL_REMOTES_ENTRY {
+                        FetchFrom::AllRemotes
+                    } else {
+                        FetchFrom::Remote(remote)
+                    }));
+                }
+            }
+            return Ok(ComponentInputResult::Handled);
+        }
+
+        if let Some((forge_picker, pages)) = self.forge_picker.as_mut() {
+            match forge_picker.handle_input(event) {
+                PickerAction::None => (),
+                PickerAction::Cancel => self.forge_picker = None,
+                PickerAction::Select(entry) => {
+                    let url = pages
+                        .iter()
+                        .find(|(page, _)| *page == entry)
+                        .map(|(_, url)| url.clone());
+                    self.forge_picker = None;
+                    if let Some(url) = url {
+                        return Ok(open_url(&url));
+                    }
+                }
+            }
+            return Ok(ComponentInputResult::Handled);
+        }
+
+        if let Some((file_picker, files)) = self.file_picker.as_mut() {
+            match file_picker.handle_input(event) {
+                PickerAction::None => (),
+                PickerAction::Cancel => self.file_picker = None,
+                PickerAction::Select(entry) => {
+                    let file = files
+                        .iter()
+                        .find(|(file, _)| *file == entry)
+                        .map(|(_, file)| file.clone());
+                    self.file_picker = None;
+                    if let Some(file) = file {
+                        self.show_file(file);
+                    }
+                }
+            }
+            return Ok(ComponentInputResult::Handled);
+        }
+
+        if let Some((operation_picker, operations)) = self.operation_picker.as_mut() {
+            match operation_picker.handle_input(event) {
+                PickerAction::None => (),
+                PickerAction::Cancel => self.operation_picker = None,
+                PickerAction::Select(entry) => {
+                    let operation = operations
+                        .iter()
+                        .find(|(operation, _)| *operation == entry)
+                        .map(|(_, operation)| operation.clone());
+                    self.operation_picker = None;
+                    if let Some(operation) = operation {
+                        set_at_operation(operation);
+                        return Ok(ComponentInputResult::HandledAction(
+                            ComponentAction::RefreshTab(),
+                        ));
+                    }
+                }
+            }
+            return Ok(ComponentInputResult::Handled);
+        }
+
+        if let Some((operation_diff_picker, operations, _)) = self.operation_diff_picker.as_mut() {
+            match operation_diff_picker.handle_input(event) {
+                PickerAction::None => (),
+                PickerAction::Cancel => self.operation_diff_picker = None,
+                PickerAction::Select(entry) => {
+                    let operation = operations
+                        .iter()
+                        .find(|(operation, _)| *operation == entry)
+                        .map(|(_, operation)| operation.clone());
+                    let from = self
+                        .operation_diff_picker
+                        .take()
+                        .and_then(|(_, _, from)| from);
+                    match (from, operation) {
+                        (None, Some(operation)) => self.pick_operation_diff(Some(operation))?,
+                        (Some(from), Some(to)) => return Ok(show_operation_diff(&from, &to)),
+                        _ => (),
+                    }
+                }
+            }
+            return Ok(ComponentInputResult::Handled);
+        }
+
+        if let Some(move_bookmark_picker) = self.move_bookmark_picker.as_mut() {
+            match move_bookmark_picker.handle_input(event) {
+                PickerAction::None => (),
+                PickerAction::Cancel => self.move_bookmark_picker = None,
+                PickerAction::Select(bookmark) => {
+                    self.move_bookmark_picker = None;
+                    if let Some(action) = self.move_bookmark(bookmark, false) {
+                        return Ok(ComponentInputResult::HandledAction(action));
+                    }
+                }
+            }
+            return Ok(ComponentInputResult::Handled);
+        }
+
+        if let Some(abandon_popup) = self.abandon_popup.as_mut() {
+            match abandon_popup.handle_input(event) {
+                AbandonAction::None => (),
+                AbandonAction::Cancel => self.abandon_popup = None,
+                AbandonAction::Abandon { retain_bookmarks } => {
+                    self.abandon_popup = None;
+                    if let Some(action) = self.execute_abandon(retain_bookmarks)? {
+                        return Ok(ComponentInputResult::HandledAction(action));
+                    }
+                }
+            }
+            return Ok(ComponentInputResult::Handled);
+        }
+
+        if let Some((split_popup, head)) = self.split_popup.as_mut() {
+            match split_popup.handle_input(event) {
+                SplitAction::None => (),
+                SplitAction::Cancel => self.split_popup = None,
+                SplitAction::Split {
+                    paths,
+                    first_message,
+                    second_message,
+                } => {
+                    let head = head.clone();
+                    self.split_popup = None;
+                    if let Err(err) =
+                        self.execute_split(&head, &paths, &first_message, &second_message)
+                    {
+                        return Ok(ComponentInputResult::HandledAction(
+                            ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                                "Split",
+                                err.to_string(),
+                            )))),
+                        ));
+                    }
+                    return Ok(ComponentInputResult::HandledAction(
+                        ComponentAction::ChangeHead(self.head.clone()),
+                    ));
+                }
+            }
+            return Ok(ComponentInputResult::Handled);
+        }
+
+        if let Some(custom_command_popup) = self.custom_command_popup.as_mut() {
+            match custom_command_popup.handle_input(event) {
+                CustomCommandAction::None => (),
+                CustomCommandAction::Cancel => self.custom_command_popup = None,
+                CustomCommandAction::Run(command) => {
+                    self.custom_command_popup = None;
+                    return Ok(self.run_custom_command(&command));
+                }
+            }
+            return Ok(ComponentInputResult::Handled);
+        }
+
+        if let Some(evolog_popup) = self.evolog_popup.as_mut() {
+            match evolog_popup.handle_input(event) {
+                EvologAction::None => (),
+                EvologAction::Cancel => self.evolog_popup = None,
+                EvologAction::Restore(version) => {
+                    self.evolog_popup = None;
+                    return Ok(self.restore_from_version(version));
+                }
+            }
+            return Ok(ComponentInputResult::Handled);
+        }
+
+        if let Some(restore_popup) = self.restore_popup.as_mut() {
+            match restore_popup.handle_input(event) {
+                RestoreAction::None => (),
+                RestoreAction::Cancel => self.restore_popup = None,
+                RestoreAction::Restore { paths } => {
+                    let (source, destination) = (&restore_popup.source, &restore_popup.destination);
+                    let result = new_commander().run_restore_from(
+                        source.commit_id.as_str(),
+                        destination.commit_id.as_str(),
+                        &paths,
+                    );
+                    self.restore_popup = None;
+                    if let Err(err) = result {
+                        return Ok(ComponentInputResult::HandledAction(
+                            ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                                "Restore",
+                                err.to_string(),
+                            )))),
+                        ));
+                    }
+                    self.set_head(new_commander().get_head_latest(&self.head)?);
+                    return Ok(ComponentInputResult::HandledAction(
+                        ComponentAction::ChangeHead(self.head.clone()),
+                    ));
+                }
+            }
+            return Ok(ComponentInputResult::Handled);
+        }
+
+        if let Some((pin_picker, pins)) = self.pin_picker.as_mut() {
+            match pin_picker.handle_input(event) {
+                PickerAction::None => (),
+                PickerAction::Cancel => self.pin_picker = None,
+                PickerAction::Select(entry) => {
+                    let commit_id = pins
+                        .iter()
+                        .find(|(_, pin)| *pin == entry)
+                        .map(|(head, _)| head.commit_id.clone());
+                    self.pin_picker = None;
+                    if let Some(commit_id) = commit_id {
+                        return Ok(self.go_to_revision(commit_id.as_str()));
+                    }
+                }
+            }
+            return Ok(ComponentInputResult::Handled);
+        }
+
+        if let Some((divergence_picker, commits)) = self.divergence_picker.as_mut() {
+            match divergence_picker.handle_input(event) {
+                PickerAction::None => (),
+                PickerAction::Cancel => self.divergence_picker = None,
+                PickerAction::Select(summary) => {
+                    let commits = std::mem::take(commits);
+                    self.divergence_picker = None;
+                    let Some((keep, _)) = commits.iter().find(|(_, entry)| *entry == summary)
+                    else {
+                        return Ok(ComponentInputResult::Handled);
+                    };
+                    if let Err(err) = self.resolve_divergence(keep, &commits) {
+                        return Ok(ComponentInputResult::HandledAction(
+                            ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                                "Resolve divergence",
+                                err.to_string(),
+                            )))),
+                        ));
+                    }
+                    return Ok(ComponentInputResult::HandledAction(
+                        ComponentAction::ChangeHead(self.head.clone()),
+                    ));
+                }
+            }
+            return Ok(ComponentInputResult::Handled);
+        }
+
+        if let Some(author_popup) = &mut self.author_popup {
+            let author = match author_popup.handle_input(event) {
+                AuthorAction::None => return Ok(ComponentInputResult::Handled),
+                AuthorAction::Cancel => {
+                    self.author_popup = None;
+                    return Ok(ComponentInputResult::Handled);
+                }
+                AuthorAction::SetAuthor(author) => Some(author),
+                AuthorAction::ResetAuthor => None,
+            };
+            self.author_popup = None;
+            let commander = new_commander();
+            let result = commander
+                .get_commit_description(&self.head.commit_id)
+                .and_then(|message| {
+                    commander.run_set_author(
+                        self.head.commit_id.as_str(),
+                        author.as_deref(),
+                        &message,
+                        self.head.immutable,
+                    )
+                });
+            if let Err(err) = result {
+                return Ok(ComponentInputResult::HandledAction(
+                    ComponentAction::SetPopup(Some(Box::new(MessagePopup::new(
+                        "Author",
+                        err.to_string(),
+                    )))),
+                ));
+            }
+            self.set_head(new_commander().get_head_latest(&self.head)?);
+            return Ok(ComponentInputResult::HandledAction(
+                ComponentAction::ChangeHead(self.head.clone()),
+            ));
+        }
+
+        if let Some(log_filter_popup) = &mut self.log_filter_popup {
+            match log_filter_popup.handle_input(event) {
+                LogFilterAction::None => (),
+                LogFilterAction::Cancel => self.log_filter_popup = None,
+                LogFilterAction::Apply(revset) => {
+                    self.log_filter_popup = None;
+                    self.apply_log_revset(revset);
+                }
+            }
             return Ok(ComponentInputResult::Handled);
         }
 
@@ -880,6 +3845,9 @@ impl Component for LogTab<'_> {
             if handled.ok() == Some(true) {
                 // when handle_input returns true,
                 // the popup should be closed
+                if rebase_popup.rebased {
+                    self.log_panel.extract_and_clear_head_marks();
+                }
                 self.rebase_popup = None;
                 return Ok(ComponentInputResult::HandledAction(
                     ComponentAction::RefreshTab(),
@@ -900,6 +3868,7 @@ impl Component for LogTab<'_> {
                     LogTabEvent::ClosePopup | LogTabEvent::Cancel
                 ) {
                     self.popup = ConfirmDialogState::default();
+                    self.previewed_command = None;
                 } else {
                     self.popup.handle(&key);
                 }
@@ -907,6 +3876,29 @@ impl Component for LogTab<'_> {
                 return Ok(ComponentInputResult::Handled);
             }
 
+            if !self.head_panel.is_capturing_keys() {
+                match self.log_panel.search_input(key) {
+                    LogSearchInput::NotHandled => (),
+                    LogSearchInput::Handled => {
+                        self.sync_head_output();
+                        return Ok(ComponentInputResult::Handled);
+                    }
+                    LogSearchInput::Reloaded => {
+                        self.update_cache_active_commits();
+                        self.sync_head_output();
+                        return Ok(ComponentInputResult::Handled);
+                    }
+                }
+
+                // The log tab has its own search, so the details panel
+                // search is started with a different key
+                if let log_tab_event @ (LogTabEvent::SearchLog | LogTabEvent::SearchDetails) =
+                    self.keybinds.match_event(key)
+                {
+                    return self.handle_event(log_tab_event);
+                }
+            }
+
             if self.head_panel.input(key) {
                 return Ok(ComponentInputResult::Handled);
             }
@@ -928,17 +3920,139 @@ impl Component for LogTab<'_> {
             {
                 return Ok(ComponentInputResult::Handled);
             }
+            // The details panel goes first, so it gets the end of a
+            // scrollbar drag even if the mouse is over the log panel
+            if self.head_panel.input_mouse(mouse_event) {
+                return Ok(ComponentInputResult::Handled);
+            }
             let input_result = self.log_panel.input(event.clone())?;
             if input_result.is_handled() {
                 self.sync_head_output();
                 return Ok(input_result);
             }
-            if self.head_panel.input_mouse(mouse_event) {
-                return Ok(ComponentInputResult::Handled);
-            }
             return Ok(ComponentInputResult::NotHandled);
         }
 
         Ok(ComponentInputResult::Handled)
     }
 }
+
+/// Arguments of a jj command picking the hunks with the builtin diff
+/// editor, e.g. `jj split -i --tool :builtin -r <revision>`
+fn interactive_args<'a>(command: &'a str, args: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    [command, "-i", "--tool", ":builtin"]
+        .into_iter()
+        .chain(args)
+        .map(String::from)
+        .collect()
+}
+
+/// Line of a confirm dialog for a change that is immutable
+fn immutable_warning() -> Line<'static> {
+    Line::from("This change is immutable, it is changed with --ignore-immutable.").fg(Color::Yellow)
+}
+
+/// Fetch in the background. The updated bookmarks are shown in a popup
+/// and the log is refreshed.
+fn fetch(from: FetchFrom) -> ComponentInputResult {
+    let loader = LoaderPopup::new("Fetching".to_string(), move || {
+        new_commander().git_fetch(&from)
+    });
+    ComponentInputResult::HandledAction(ComponentAction::SetPopup(Some(Box::new(loader))))
+}
+
+/// Open a page in the browser. Errors are shown in a popup.
+fn open_url(url: &str) -> ComponentInputResult {
+    match open_in_browser(url) {
+        Ok(()) => ComponentInputResult::Handled,
+        Err(err) => ComponentInputResult::HandledAction(ComponentAction::SetPopup(Some(Box::new(
+            MessagePopup::new("Open in browser", err.to_string()),
+        )))),
+    }
+}
+
+/// Entries of the last operations, e.g. `3ba7e7fd2c5a  3 minutes ago
+/// new empty commit`, with the operations
+fn operation_entries() -> Result<Vec<(String, Operation)>> {
+    Ok(new_commander()
+        .get_operations(OPERATION_PICKER_LIMIT)?
+        .into_iter()
+        .map(|(operation, ago)| {
+            let entry = format!("{}  {ago}  {}", operation.short_id(), operation.description);
+            (entry, operation)
+        })
+        .collect())
+}
+
+/// Show what changed in the repository between two operations in a
+/// popup
+fn show_operation_diff(from: &Operation, to: &Operation) -> ComponentInputResult {
+    let title = format!("Operation diff {} → {}", from.short_id(), to.short_id());
+    let message = match new_commander().get_operation_diff(&from.id, &to.id) {
+        Ok(diff) if diff.trim().is_empty() => "The operations are the same".to_owned(),
+        Ok(diff) => diff,
+        Err(err) => err.to_string(),
+    };
+    ComponentInputResult::HandledAction(ComponentAction::SetPopup(Some(Box::new(
+        MessagePopup::new(title, message),
+    ))))
+}
+
+/// Draw a small popup with a text field to edit, e.g. the revset, and
+/// a status line below it, e.g. to tell if the revset is valid
+fn draw_textarea_popup(
+    f: &mut Frame<'_>,
+    area: Rect,
+    title: &str,
+    textarea: &TextArea,
+    status: Option<Line>,
+) {
+    let block = Block::bordered()
+        .title(Span::styled(title, Style::new().bold().cyan()))
+        .title_alignment(Alignment::Center)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Green));
+    let status_height = if status.is_some() { 2 } else { 0 };
+    let area = centered_rect_line_height(area, 30, 7 + status_height);
+    f.render_widget(Clear, area);
+    f.render_widget(&block, area);
+
+    let popup_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Fill(1),
+            Constraint::Length(status_height),
+            Constraint::Length(2),
+        ])
+        .split(block.inner(area));
+
+    f.render_widget(textarea, popup_chunks[0]);
+    if let Some(status) = status {
+        f.render_widget(
+            Paragraph::new(status).wrap(Wrap { trim: true }),
+            popup_chunks[1],
+        );
+    }
+
+    let help = Paragraph::new(vec!["Ctrl+s: save | Escape: cancel".into()])
+        .fg(Color::DarkGray)
+        .alignment(Alignment::Center)
+        .block(
+            Block::default()
+                .borders(Borders::TOP)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(Color::DarkGray)),
+        );
+
+    f.render_widget(help, popup_chunks[2]);
+}
+
+/// A change with its parents before and after a command, e.g.
+/// `qpvuntsm: parents zsztoxlv, kmkuslsw -> zsztoxlv`
+fn parents_line(head: &Head, before: &[String], after: &[String]) -> Line<'static> {
+    Line::from(vec![
+        Span::from(head.change_id.short().to_owned()).bold(),
+        Span::from(format!(": parents {} -> ", before.join(", "))),
+        Span::from(after.join(", ")).fg(Color::Green),
+    ])
+}
@@ -0,0 +1,98 @@
+/*! Maintenance keeps the repository from growing too large: garbage
+collection removes the objects no operation can reach anymore, and
+pruning abandons the operations older than the last
+`blazingjj.op-log-keep` ones, which makes more objects unreachable.
+
+The log tab lists the tasks in a menu, and runs the picked one in a
+loader popup, which tells what it did. Pruning can't be undone, so it
+is confirmed first.
+*/
+
+use std::path::Path;
+
+use crate::commander::CommandError;
+use crate::commander::new_commander;
+use crate::env::get_env;
+use crate::ui::dialog::LoaderPopup;
+use crate::ui::utils::format_size;
+use crate::ui::utils::repo_size;
+
+/// A task of the maintenance menu
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MaintenanceTask {
+    CollectGarbage,
+    PruneOperations,
+}
+
+impl MaintenanceTask {
+    pub const ALL: [MaintenanceTask; 2] = [
+        MaintenanceTask::CollectGarbage,
+        MaintenanceTask::PruneOperations,
+    ];
+
+    /// Entry of the task in the menu
+    pub fn entry(self) -> String {
+        match self {
+            MaintenanceTask::CollectGarbage => {
+                "Collect garbage, removing unreachable objects (jj util gc)".to_owned()
+            }
+            MaintenanceTask::PruneOperations => format!(
+                "Prune the operation log to the last {} operations (jj op abandon)",
+                get_env().jj_config.op_log_keep()
+            ),
+        }
+    }
+
+    /// Name of the task in the loader popup, e.g. `Collecting garbage`
+    pub fn progress_name(self) -> &'static str {
+        match self {
+            MaintenanceTask::CollectGarbage => "Collecting garbage",
+            MaintenanceTask::PruneOperations => "Pruning the operation log",
+        }
+    }
+
+    /// Loader popup running the task in the background
+    pub fn loader(self) -> LoaderPopup {
+        LoaderPopup::new(self.progress_name().to_owned(), move || self.run())
+    }
+
+    /// Run the task, and tell what it did
+    pub fn run(self) -> Result<String, CommandError> {
+        match self {
+            MaintenanceTask::CollectGarbage => collect_garbage(),
+            MaintenanceTask::PruneOperations => prune_operations(get_env().jj_config.op_log_keep()),
+        }
+    }
+}
+
+/// Run `jj util gc`, and tell how much space it reclaimed
+fn collect_garbage() -> Result<String, CommandError> {
+    let root = Path::new(&get_env().root);
+    let before = repo_size(root);
+    let messages = new_commander().run_gc()?;
+    let after = repo_size(root);
+    Ok(format!(
+        "{messages}Reclaimed {} ({} → {})",
+        format_size(before.saturating_sub(after)),
+        format_size(before),
+        format_size(after)
+    ))
+}
+
+/// Abandon the operations before the last `keep` ones
+fn prune_operations(keep: usize) -> Result<String, CommandError> {
+    let commander = new_commander();
+    // One more to find the first operation to abandon, and the root
+    // operation, which can't be abandoned
+    let operations = commander
+        .get_operations(keep + 2)
+        .map_err(|err| CommandError::Status(format!("{err:#}"), None))?;
+    match operations.get(keep) {
+        Some((operation, _)) if operations.len() == keep + 2 => {
+            commander.run_op_abandon_until(&operation.id)
+        }
+        _ => Ok(format!(
+            "There are no more than {keep} operations, nothing to prune"
+        )),
+    }
+}
@@ -1,10 +1,18 @@
+pub mod annotate_tab;
+pub mod auto_fetch;
 pub mod bookmarks_tab;
+pub mod ci_status;
 pub mod commit_show_cache;
 pub mod dialog;
 pub mod files_tab;
+pub mod git_drift;
 pub mod log_tab;
+pub mod maintenance;
+pub mod op_watch;
 pub mod panel;
 pub mod styles;
+pub mod toast;
+pub mod undo;
 pub mod utils;
 
 use anyhow::Result;
@@ -24,16 +32,42 @@ use tracing::instrument;
 use crate::ComponentInputResult;
 use crate::app::App;
 use crate::app::Tab;
+use crate::commander::at_operation;
 use crate::commander::log::Head;
+use crate::commander::snapshot_paused;
 use crate::env::get_env;
+use crate::ui::log_tab::LogTab;
+
+/// A file to open in the user's editor, relative to the repository root
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditorRequest {
+    pub path: String,
+    pub line: Option<usize>,
+}
+
+/// The change and file selected in a tab, for the placeholders of the
+/// command popup
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    pub head: Option<Head>,
+    pub file: Option<String>,
+}
 
 pub enum ComponentAction {
     ViewFiles(Head),
     ViewLog(Head),
+    /// Select a change in the log tab, even if it is not in the revset
+    GoToChange(Head),
+    /// Annotate a file of a change in the annotate tab
+    ViewAnnotate(Head, String),
     ChangeHead(Head),
     SetPopup(Option<Box<dyn Component>>),
     Multiple(Vec<ComponentAction>),
     RefreshTab(),
+    OpenEditor(EditorRequest),
+    /// Hand the terminal over to a jj command, e.g. `jj split -i` with
+    /// the builtin diff editor, and refresh the tab afterwards
+    RunInteractive(Vec<String>),
 }
 
 pub trait Component {
@@ -49,6 +83,11 @@ pub trait Component {
     fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()>;
 
     fn input(&mut self, event: Event) -> Result<ComponentInputResult>;
+
+    /// The change and file the component has selected, if any
+    fn selection(&mut self) -> Selection {
+        Selection::default()
+    }
 }
 
 #[instrument(level = "trace", name = "draw", skip(f, app))]
@@ -86,15 +125,42 @@ pub fn ui(f: &mut Frame, app: &mut App) -> Result<()> {
 
         f.render_widget(tabs, header_chunks[0]);
     }
-    {
+    if let Some(operation) = at_operation() {
+        // Banner telling that the repository is not shown as it is now
+        let shortcut = app
+            .log
+            .as_ref()
+            .and_then(LogTab::view_at_operation_shortcut);
+        let mut spans = vec![
+            Span::from(format!("{} ", operation.short_id())).bold(),
+            Span::from(operation.description),
+        ];
+        if let Some(shortcut) = shortcut {
+            spans.push(
+                Span::from(format!(" | {shortcut}: back to the present")).fg(Color::DarkGray),
+            );
+        }
+        let mut block = Block::bordered()
+            .title(" Viewing an old operation, read-only ".bold())
+            .border_type(BorderType::Rounded)
+            .fg(Color::Red);
+        if snapshot_paused() {
+            block = block.title(Line::from(" snapshots paused ").yellow().right_aligned());
+        }
+        let banner = Paragraph::new(Line::from(spans)).block(block);
+
+        f.render_widget(banner, header_chunks[1]);
+    } else {
+        let mut block = Block::bordered()
+            .title(" blazingjj ")
+            .border_type(BorderType::Rounded)
+            .fg(Color::default());
+        if snapshot_paused() {
+            block = block.title(Line::from(" snapshots paused ").yellow().right_aligned());
+        }
         let tabs = Paragraph::new("q: quit | ?: help | R: refresh | 1/2/3: change tab")
             .fg(Color::DarkGray)
-            .block(
-                Block::bordered()
-                    .title(" blazingjj ")
-                    .border_type(BorderType::Rounded)
-                    .fg(Color::default()),
-            );
+            .block(block);
 
         f.render_widget(tabs, header_chunks[1]);
     }
@@ -107,6 +173,18 @@ pub fn ui(f: &mut Frame, app: &mut App) -> Result<()> {
         popup.draw(f, f.area())?;
     }
 
+    if let Some(toast) = app.toast.as_ref() {
+        toast.draw(f);
+    } else if let Some(undo_toast) = app.undo_toast.as_ref() {
+        undo_toast.draw(f);
+    } else if let Some(reload_toast) = app.reload_toast.as_ref() {
+        reload_toast.draw(f);
+    }
+    if let Some(fetch_toast) = app.fetch_toast.as_ref() {
+        let below = app.toast.is_some() || app.undo_toast.is_some() || app.reload_toast.is_some();
+        fetch_toast.draw(f, below);
+    }
+
     {
         let paragraph = Paragraph::new(format!("{}ms", app.stats.start_time.elapsed().as_millis()))
             .alignment(Alignment::Right);
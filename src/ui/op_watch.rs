@@ -0,0 +1,115 @@
+/*! The operation watch notices operations created by other processes,
+e.g. `jj` in another terminal or an editor integration, by checking the
+operation heads in `.jj/repo/op_heads/heads` every second.
+
+The app syncs the watch after each of its own jj commands, even the ones
+that only read the repository and may snapshot the working copy, so only
+the operations of other processes are reported, and then reloads the tabs instead of
+showing stale changes until a manual refresh.
+*/
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::ui::utils::repo_dir;
+
+/// How often the operation heads are checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Timer of the checks, with the operation heads seen last
+pub struct OperationWatch {
+    heads_dir: PathBuf,
+    heads: BTreeSet<String>,
+    next_check: Instant,
+}
+
+impl OperationWatch {
+    /// Watch the repository of the workspace at `root`. None if its
+    /// operation heads can't be found, e.g. with another backend.
+    pub fn new(root: &Path) -> Option<Self> {
+        let heads_dir = repo_dir(root)?.join("op_heads").join("heads");
+        if !heads_dir.is_dir() {
+            return None;
+        }
+        let mut watch = Self {
+            heads_dir,
+            heads: BTreeSet::new(),
+            next_check: Instant::now() + CHECK_INTERVAL,
+        };
+        watch.sync();
+        Some(watch)
+    }
+
+    /// Time until the next check
+    pub fn time_until_check(&self) -> Duration {
+        self.next_check.saturating_duration_since(Instant::now())
+    }
+
+    /// Remember the current operation heads, e.g. after a command of the
+    /// app, so they are not reported
+    pub fn sync(&mut self) {
+        self.heads = self.read_heads();
+    }
+
+    /// Check the operation heads when it is due, and tell if they changed
+    /// since the last check or sync
+    pub fn update(&mut self) -> bool {
+        if Instant::now() < self.next_check {
+            return false;
+        }
+        self.next_check = Instant::now() + CHECK_INTERVAL;
+        let heads = self.read_heads();
+        // While jj writes an operation, the heads can be empty
+        if heads.is_empty() || heads == self.heads {
+            return false;
+        }
+        self.heads = heads;
+        true
+    }
+
+    fn read_heads(&self) -> BTreeSet<String> {
+        let Ok(entries) = fs::read_dir(&self.heads_dir) else {
+            return BTreeSet::new();
+        };
+        entries
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn operation_heads() -> std::io::Result<()> {
+        let directory = TempDir::new()?;
+        assert!(OperationWatch::new(directory.path()).is_none());
+
+        let heads_dir = directory.path().join(".jj/repo/op_heads/heads");
+        fs::create_dir_all(&heads_dir)?;
+        fs::write(heads_dir.join("aaa"), "")?;
+        let mut watch = OperationWatch::new(directory.path()).expect("heads are found");
+        watch.next_check = Instant::now();
+        assert!(!watch.update());
+
+        fs::remove_file(heads_dir.join("aaa"))?;
+        fs::write(heads_dir.join("bbb"), "")?;
+        watch.next_check = Instant::now();
+        assert!(watch.update());
+        assert!(!watch.update());
+
+        fs::write(heads_dir.join("ccc"), "")?;
+        watch.sync();
+        watch.next_check = Instant::now();
+        assert!(!watch.update());
+
+        Ok(())
+    }
+}
@@ -9,20 +9,36 @@ To make this effcicient there are two ways to provide content.
 * TextContent - for small texts rendered as a Ratatui Paragraph.
 * LargeStringContent - to render only the visible subset.
 
+The panel also supports incremental search, navigation between the
+files, hunks and conflicts of a diff, folding of files and copying of lines. Since the panel does not own its
+content, these only record what to do, and the action is resolved the
+next time the panel is drawn.
+
 */
 
+use std::ops::Range;
+use std::ops::RangeInclusive;
+
 use ratatui::crossterm::event::KeyCode;
 use ratatui::crossterm::event::KeyEvent;
 use ratatui::crossterm::event::KeyModifiers;
+use ratatui::crossterm::event::MouseButton;
 use ratatui::crossterm::event::MouseEvent;
 use ratatui::crossterm::event::MouseEventKind;
 use ratatui::layout::Margin;
 use ratatui::layout::Position;
 use ratatui::layout::Rect;
+use ratatui::style::Color;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
 use ratatui::text::Line;
+use ratatui::text::Span;
 use ratatui::text::Text;
 use ratatui::widgets::Block;
 use ratatui::widgets::BorderType;
+use ratatui::widgets::Clear;
+use ratatui::widgets::List;
+use ratatui::widgets::ListState;
 use ratatui::widgets::Padding;
 use ratatui::widgets::Paragraph;
 use ratatui::widgets::Scrollbar;
@@ -31,7 +47,27 @@ use ratatui::widgets::ScrollbarState;
 use ratatui::widgets::Wrap;
 use tracing::trace;
 
+use crate::env::get_env;
+use crate::ui::styles::create_popup_block;
+use crate::ui::utils::DiffFile;
+use crate::ui::utils::DiffLineNumber;
+use crate::ui::utils::FileFolds;
+use crate::ui::utils::FoldedView;
 use crate::ui::utils::LargeString;
+use crate::ui::utils::SearchPattern;
+use crate::ui::utils::centered_rect;
+use crate::ui::utils::conflict_regions;
+use crate::ui::utils::copy_to_clipboard;
+use crate::ui::utils::diff_file_path;
+use crate::ui::utils::diff_line_numbers;
+use crate::ui::utils::is_hunk_header;
+use crate::ui::utils::line_number_gutter;
+use crate::ui::utils::line_number_width;
+use crate::ui::utils::new_file_line;
+use crate::ui::utils::section_end;
+
+/// Background of the lines of a conflict
+const CONFLICT_COLOR: Color = Color::Rgb(80, 40, 20);
 
 /// Details panel used for the right side of each tab.
 /// This handles scrolling and wrapping.
@@ -40,18 +76,150 @@ pub struct DetailsPanel {
     panel_rect: Rect,
     /// Area used for rendering content of panel
     content_rect: Rect,
+    /// Area of the scrollbar, including arrows. Empty if not shown.
+    scrollbar_rect: Rect,
+    /// Row of the thumb grabbed by the mouse, while dragging the scrollbar
+    scrollbar_drag: Option<u16>,
     /// First line of content that is visible
-    scroll: u16,
+    scroll: usize,
     /// Total number of lines in content, including extra lines for wrapped lines.
-    lines: u16,
+    lines: usize,
     /// Wrap long lines of content into multiple lines
     wrap: bool,
+    /// Show line numbers of git diffs, if the user wants them
+    line_numbers: bool,
+    /// Line numbers are shown in the current frame. Only true if
+    /// wanted by the user, and the content is a git diff.
+    show_line_numbers: bool,
+    /// Active search, if any
+    search: Option<PanelSearch>,
+    /// First key of a two key sequence, `[`, `]` or `z`
+    pending_prefix: Option<char>,
+    /// Fold change to perform at next draw
+    pending_fold: Option<FoldAction>,
+    /// File, hunk or conflict jump to perform at next draw
+    pending_jump: Option<SectionJump>,
+    /// Open list of files to jump to, if any
+    file_list: Option<FileList>,
+    /// Active copy mode, if any
+    copy: Option<CopyMode>,
+}
+
+/// State of copy mode, where the user selects lines to copy
+struct CopyMode {
+    /// Visible line of the cursor. None until the content is drawn,
+    /// then it starts at the top line.
+    cursor: Option<usize>,
+    /// Visible line where the selection started, if any
+    mark: Option<usize>,
+    /// Copy the selection at next draw
+    pending_copy: bool,
+}
+
+/// State of an incremental search in the panel content
+struct PanelSearch {
+    /// The text typed by the user
+    query: String,
+    /// Compiled query. None if the query is empty.
+    pattern: Option<SearchPattern>,
+    /// True while the query is being typed
+    editing: bool,
+    /// Scroll position when the search was started
+    origin: usize,
+    /// Jump to perform at next draw, when content is available
+    pending: Option<SearchJump>,
+    /// The last jump did not find any match
+    not_found: bool,
+}
+
+/// Where a search should move the scroll position
+#[derive(Clone, Copy)]
+enum SearchJump {
+    /// First match at or after the line where the search started
+    FromOrigin,
+    /// First match after the top line
+    Next,
+    /// Last match before the top line
+    Previous,
+}
+
+/// Where a file, hunk or conflict jump should move the scroll position
+#[derive(Clone, Copy)]
+enum SectionJump {
+    /// First file header after the top line
+    NextFile,
+    /// Last file header before the top line
+    PreviousFile,
+    /// First hunk header after the top line
+    NextHunk,
+    /// Last hunk header before the top line
+    PreviousHunk,
+    /// Start of the first conflict after the top line
+    NextConflict,
+    /// Start of the last conflict before the top line
+    PreviousConflict,
+}
+
+/// How a fold command changes the folded files
+#[derive(Clone, Copy)]
+pub enum FoldAction {
+    /// Fold or unfold the file at the top line
+    Toggle,
+    /// Fold all files, or unfold all if all are folded
+    ToggleAll,
+}
+
+/// State of the list of files shown on top of the content
+struct FileList {
+    /// Files of the content at last draw
+    files: Vec<DiffFile>,
+    state: ListState,
 }
 
 /// Content of the detail panel must be able to render as a paragraph
 pub trait DetailContent<'a> {
     /// Render content as a paragraph, and update panel total lines
     fn render_as_paragraph(&self, panel: &mut DetailsPanel, area: Rect) -> Paragraph<'_>;
+
+    /// Find the scroll position of the first line matching the pattern,
+    /// scanning from scroll position `start` and wrapping around the end.
+    fn find_match(
+        &self,
+        panel: &DetailsPanel,
+        pattern: &SearchPattern,
+        start: usize,
+        forward: bool,
+    ) -> Option<usize>;
+
+    /// Find the file sections of a diff. The line of each
+    /// [DiffFile] is the scroll position of the file header.
+    fn diff_files(&self, panel: &DetailsPanel) -> Vec<DiffFile>;
+
+    /// Find the scroll positions of the hunk headers of a git diff
+    fn diff_hunks(&self, panel: &DetailsPanel) -> Vec<usize>;
+
+    /// Find the scroll positions of the starts of the conflicts shown in a diff
+    fn diff_conflicts(&self, panel: &DetailsPanel) -> Vec<usize>;
+
+    /// Change which files are folded. Returns the new scroll position,
+    /// or None if the content can't be folded.
+    fn fold(&mut self, panel: &DetailsPanel, action: FoldAction) -> Option<usize>;
+
+    /// Number of visible lines. A wrapped line is a single line.
+    fn line_count(&self, panel: &DetailsPanel) -> usize;
+
+    /// Scroll position of a visible line. For the line after the last,
+    /// it is the total number of scroll positions.
+    fn line_row(&self, panel: &DetailsPanel, line: usize) -> usize;
+
+    /// Visible line shown at a scroll position
+    fn row_line(&self, panel: &DetailsPanel, row: usize) -> usize;
+
+    /// Text of a range of visible lines, without any styling
+    fn copy_lines(&self, panel: &DetailsPanel, lines: RangeInclusive<usize>) -> String;
+
+    /// Line number in the new file of a visible line of a diff
+    fn file_line(&self, panel: &DetailsPanel, line: usize) -> Option<usize>;
 }
 
 /// Content is preformatted ratatui Text
@@ -62,6 +230,8 @@ pub struct TextContent<'a> {
 /// Content is a large string that can quickly fetch a range of lines
 pub struct LargeStringContent<'a> {
     large_string: &'a LargeString,
+    /// Folded files. Without it, the content can't be folded.
+    folds: Option<&'a mut FileFolds>,
 }
 
 /// Transient object holding render data
@@ -72,6 +242,8 @@ where
     panel: &'a mut DetailsPanel,
     title: Option<Line<'a>>,
     content: Content,
+    /// Content is a git diff, so line numbers can be shown
+    git_diff: bool,
 }
 
 /// Commands that can be handled by the details panel
@@ -82,7 +254,24 @@ pub enum DetailsPanelEvent {
     ScrollUpHalfPage,
     ScrollDownPage,
     ScrollUpPage,
+    ScrollToTop,
+    ScrollToBottom,
     ToggleWrap,
+    ToggleLineNumbers,
+    StartSearch,
+    SearchNext,
+    SearchPrevious,
+    ClearSearch,
+    ToggleFold,
+    ToggleAllFolds,
+    NextFile,
+    PreviousFile,
+    NextHunk,
+    PreviousHunk,
+    NextConflict,
+    PreviousConflict,
+    OpenFileList,
+    StartCopy,
 }
 
 //
@@ -91,7 +280,80 @@ pub enum DetailsPanelEvent {
 
 impl<'a> From<&'a LargeString> for LargeStringContent<'a> {
     fn from(large_string: &'a LargeString) -> Self {
-        Self { large_string }
+        Self {
+            large_string,
+            folds: None,
+        }
+    }
+}
+
+impl<'a> LargeStringContent<'a> {
+    /// Content where the user can fold files
+    pub fn with_folds(large_string: &'a LargeString, folds: &'a mut FileFolds) -> Self {
+        Self {
+            large_string,
+            folds: Some(folds),
+        }
+    }
+
+    /// Visible lines of the large string
+    fn folded_view(&self) -> FoldedView {
+        let folds = match self.folds.as_deref() {
+            Some(folds) => folds,
+            None => &FileFolds::default(),
+        };
+        FoldedView::new(
+            self.large_string.lines(),
+            self.large_string.diff_files(),
+            folds,
+        )
+    }
+
+    /// Render the visible lines from `top_line`, marking folded files.
+    /// Also returns the content line of each rendered line.
+    fn render_folded(
+        &self,
+        view: &FoldedView,
+        top_line: usize,
+        line_count: usize,
+    ) -> (Text<'_>, Vec<usize>) {
+        let files = self.large_string.diff_files();
+        let mut text = Text::default();
+        let mut content_lines = vec![];
+        for range in view.content_ranges(top_line..top_line + line_count) {
+            let mut part = self.large_string.render(range.start, range.len());
+            content_lines.extend(range.clone().take(part.lines.len()));
+            // A range ending at a folded file header is followed by the fold
+            let last_line = range.end - 1;
+            let folded_file = files.iter().enumerate().find(|(index, file)| {
+                file.line == last_line
+                    && self
+                        .folds
+                        .as_deref()
+                        .is_some_and(|folds| folds.is_folded(*index))
+            });
+            if let (Some((index, _)), Some(line)) = (folded_file, part.lines.last_mut()) {
+                let end = section_end(files, index, self.large_string.lines());
+                let hidden = end - last_line - 1;
+                let max_lines = get_env().jj_config.diff_max_lines();
+                let unloaded = self
+                    .folds
+                    .as_deref()
+                    .and_then(|folds| folds.unloaded_lines(index));
+                let note = if let Some(info) = self.large_string.binary_files().get(&index) {
+                    format!(" ▸ {info}")
+                } else if let Some(lines) = unloaded {
+                    format!(" ▸ diff not loaded, {lines} lines changed, press za to load")
+                } else if max_lines > 0 && hidden > max_lines {
+                    format!(" ▸ diff truncated, {hidden} lines, press za to load")
+                } else {
+                    format!(" ▸ {hidden} lines folded")
+                };
+                line.spans.push(Span::raw(note).fg(Color::DarkGray));
+            }
+            text.lines.append(&mut part.lines);
+        }
+        (text, content_lines)
     }
 }
 
@@ -103,31 +365,422 @@ impl<'a, T: Into<Text<'a>>> From<T> for TextContent<'a> {
     }
 }
 
+/// Scroll positions of the visible lines of a large string. When
+/// wrapped, a line takes up more than one scroll position (row).
+struct RowMap {
+    /// First row of each visible line, and the total number of rows
+    /// at the end. None if each line is a single row.
+    starts: Option<Vec<usize>>,
+}
+
+impl RowMap {
+    /// First row of a visible line
+    fn row(&self, visible_line: usize) -> usize {
+        match &self.starts {
+            Some(starts) => starts[visible_line.min(starts.len() - 1)],
+            None => visible_line,
+        }
+    }
+
+    /// Visible line shown at a row, and the row offset within that line
+    fn visible_line(&self, row: usize) -> (usize, usize) {
+        match &self.starts {
+            Some(starts) => {
+                let line = starts
+                    .partition_point(|&start| start <= row)
+                    .saturating_sub(1)
+                    .min(starts.len().saturating_sub(2));
+                (line, row.saturating_sub(starts[line]))
+            }
+            None => (row, 0),
+        }
+    }
+
+    /// Total number of rows
+    fn len(&self, view: &FoldedView) -> usize {
+        match &self.starts {
+            Some(starts) => starts.last().copied().unwrap_or(0),
+            None => view.len(),
+        }
+    }
+}
+
+impl LargeStringContent<'_> {
+    /// Map between visible lines and scroll positions
+    fn row_map(&self, panel: &DetailsPanel, view: &FoldedView) -> RowMap {
+        let mut width = panel.columns();
+        if panel.show_line_numbers {
+            // The gutter takes up two numbers and 4 extra characters
+            let number_width = line_number_width(self.large_string.line_numbers());
+            width = width.saturating_sub((number_width * 2 + 4) as u16);
+        }
+        if !panel.wrap || width == 0 {
+            return RowMap { starts: None };
+        }
+        let row_counts = self.large_string.row_counts(width);
+        let mut starts = Vec::with_capacity(view.len() + 1);
+        let mut row = 0;
+        starts.push(row);
+        for line in view.content_lines() {
+            row += row_counts[line] as usize;
+            starts.push(row);
+        }
+        RowMap {
+            starts: Some(starts),
+        }
+    }
+}
+
 impl<'a> DetailContent<'a> for LargeStringContent<'a> {
     fn render_as_paragraph(&self, panel: &mut DetailsPanel, area: Rect) -> Paragraph<'_> {
         panel.content_rect = area;
+        let view = self.folded_view();
+        let rows = self.row_map(panel, &view);
         // Update total length. This is used by the scroll bar
-        panel.lines = self.large_string.lines() as u16;
-        // Extract visible part of content
-        let top_line = panel.scroll as usize;
+        panel.lines = rows.len(&view);
+        // Extract visible part of content. The top line may be
+        // partially scrolled out of view when wrapped.
+        let (top_line, top_offset) = rows.visible_line(panel.scroll);
         let line_count = area.height as usize;
-        let content_text = self.large_string.render(top_line, line_count);
-        Paragraph::new(content_text)
+        let (mut content_text, content_lines) = self.render_folded(&view, top_line, line_count);
+        let conflicts = self.large_string.conflicts();
+        for (line, content_line) in content_text.lines.iter_mut().zip(&content_lines) {
+            if conflicts
+                .iter()
+                .any(|conflict| conflict.contains(content_line))
+            {
+                highlight_conflict(line);
+            }
+        }
+        if let Some(pattern) = panel.search_pattern() {
+            content_text.lines = content_text
+                .lines
+                .into_iter()
+                .map(|line| pattern.highlight_line(line))
+                .collect();
+        }
+        if let Some(selection) = panel.copy_selection() {
+            for (index, line) in content_text.lines.iter_mut().enumerate() {
+                if selection.contains(&(top_line + index)) {
+                    highlight_selected(line);
+                }
+            }
+        }
+        if panel.show_line_numbers {
+            let numbers = self.large_string.line_numbers();
+            let width = line_number_width(numbers);
+            for (line, content_line) in content_text.lines.iter_mut().zip(content_lines) {
+                add_line_number(line, numbers.get(content_line), width);
+            }
+        }
+        Paragraph::new(content_text).scroll((top_offset as u16, 0))
+    }
+
+    fn find_match(
+        &self,
+        panel: &DetailsPanel,
+        pattern: &SearchPattern,
+        start: usize,
+        forward: bool,
+    ) -> Option<usize> {
+        let view = self.folded_view();
+        let rows = self.row_map(panel, &view);
+        let (mut start_line, offset) = rows.visible_line(start);
+        // Start is inside a wrapped line, so that line is already passed
+        if forward && offset > 0 {
+            start_line += 1;
+        }
+        self.large_string
+            .find(pattern, view.to_content(start_line), forward)
+            .map(|line| rows.row(view.to_visible(line)))
+    }
+
+    fn diff_files(&self, panel: &DetailsPanel) -> Vec<DiffFile> {
+        let view = self.folded_view();
+        let rows = self.row_map(panel, &view);
+        self.large_string
+            .diff_files()
+            .iter()
+            .map(|file| DiffFile {
+                line: rows.row(view.to_visible(file.line)),
+                path: file.path.clone(),
+            })
+            .collect()
+    }
+
+    fn diff_hunks(&self, panel: &DetailsPanel) -> Vec<usize> {
+        let view = self.folded_view();
+        let rows = self.row_map(panel, &view);
+        self.large_string
+            .hunks()
+            .iter()
+            .filter_map(|&line| {
+                // Hunks inside a folded file are hidden
+                let visible = view.to_visible(line);
+                (view.to_content(visible) == line).then(|| rows.row(visible))
+            })
+            .collect()
+    }
+
+    fn diff_conflicts(&self, panel: &DetailsPanel) -> Vec<usize> {
+        let view = self.folded_view();
+        let rows = self.row_map(panel, &view);
+        self.large_string
+            .conflicts()
+            .iter()
+            .filter_map(|conflict| {
+                // Conflicts inside a folded file are hidden
+                let visible = view.to_visible(conflict.start);
+                (view.to_content(visible) == conflict.start).then(|| rows.row(visible))
+            })
+            .collect()
+    }
+
+    fn fold(&mut self, panel: &DetailsPanel, action: FoldAction) -> Option<usize> {
+        let view = self.folded_view();
+        let rows = self.row_map(panel, &view);
+        let top_line = view.to_content(rows.visible_line(panel.scroll).0);
+        let files = self.large_string.diff_files();
+        // The file shown at the top line
+        let current = files.iter().rposition(|file| file.line <= top_line);
+        let folds = self.folds.as_deref_mut()?;
+        match action {
+            FoldAction::Toggle => folds.toggle(current?),
+            FoldAction::ToggleAll => folds.toggle_all(files.len()),
+        }
+        // Keep the current file header at the top
+        let anchor = current.map_or(top_line, |index| files[index].line);
+        let view = self.folded_view();
+        let rows = self.row_map(panel, &view);
+        Some(rows.row(view.to_visible(anchor)))
+    }
+
+    fn line_count(&self, _panel: &DetailsPanel) -> usize {
+        self.folded_view().len()
+    }
+
+    fn line_row(&self, panel: &DetailsPanel, line: usize) -> usize {
+        let view = self.folded_view();
+        match self.row_map(panel, &view).starts {
+            Some(starts) => starts[line.min(starts.len() - 1)],
+            None => line.min(view.len()),
+        }
+    }
+
+    fn row_line(&self, panel: &DetailsPanel, row: usize) -> usize {
+        let view = self.folded_view();
+        self.row_map(panel, &view).visible_line(row).0
+    }
+
+    fn copy_lines(&self, _panel: &DetailsPanel, lines: RangeInclusive<usize>) -> String {
+        let view = self.folded_view();
+        view.content_ranges(*lines.start()..*lines.end() + 1)
+            .into_iter()
+            .map(|range| self.large_string.plain_text(range))
+            .collect()
+    }
+
+    fn file_line(&self, _panel: &DetailsPanel, line: usize) -> Option<usize> {
+        let view = self.folded_view();
+        new_file_line(self.large_string.line_numbers(), view.to_content(line))
     }
 }
 
 impl<'a> DetailContent<'a> for TextContent<'a> {
     fn render_as_paragraph(&self, panel: &mut DetailsPanel, area: Rect) -> Paragraph<'_> {
-        let content_text = &self.text;
-        let mut paragraph = Paragraph::new(content_text.clone());
+        let mut content_text = self.text.clone();
+        for conflict in self.conflicts() {
+            for line in content_text.lines.get_mut(conflict).unwrap_or_default() {
+                highlight_conflict(line);
+            }
+        }
+        if let Some(pattern) = panel.search_pattern() {
+            content_text.lines = content_text
+                .lines
+                .into_iter()
+                .map(|line| pattern.highlight_line(line))
+                .collect();
+        }
+        if let Some(selection) = panel.copy_selection() {
+            for (index, line) in content_text.lines.iter_mut().enumerate() {
+                if selection.contains(&index) {
+                    highlight_selected(line);
+                }
+            }
+        }
+        if panel.show_line_numbers {
+            let numbers = diff_line_numbers(self.text.lines.iter().map(line_text));
+            let width = line_number_width(&numbers);
+            for (line, number) in content_text.lines.iter_mut().zip(numbers.iter()) {
+                add_line_number(line, Some(number), width);
+            }
+        }
+        let mut paragraph = Paragraph::new(content_text);
 
         panel.content_rect = area;
-        panel.lines = paragraph.line_count(area.width) as u16;
+        panel.lines = paragraph.line_count(area.width);
 
-        paragraph = paragraph.scroll((panel.scroll.min(panel.lines.saturating_sub(1)), 0));
+        let scroll = panel.scroll.min(panel.lines.saturating_sub(1));
+        paragraph = paragraph.scroll((u16::try_from(scroll).unwrap_or(u16::MAX), 0));
 
         paragraph
     }
+
+    fn find_match(
+        &self,
+        panel: &DetailsPanel,
+        pattern: &SearchPattern,
+        start: usize,
+        forward: bool,
+    ) -> Option<usize> {
+        // Find scroll position of every matching line
+        let positions: Vec<usize> = self
+            .line_positions(panel)
+            .into_iter()
+            .zip(self.text.lines.iter())
+            .filter(|(_, line)| pattern.is_match(&line_text(line)))
+            .map(|(position, _)| position)
+            .collect();
+
+        if forward {
+            positions
+                .iter()
+                .find(|&&position| position >= start)
+                .or(positions.first())
+                .copied()
+        } else {
+            positions
+                .iter()
+                .rev()
+                .find(|&&position| position <= start)
+                .or(positions.last())
+                .copied()
+        }
+    }
+
+    fn diff_files(&self, panel: &DetailsPanel) -> Vec<DiffFile> {
+        self.line_positions(panel)
+            .into_iter()
+            .zip(self.text.lines.iter())
+            .filter_map(|(position, line)| {
+                diff_file_path(&line_text(line)).map(|path| DiffFile {
+                    line: position,
+                    path,
+                })
+            })
+            .collect()
+    }
+
+    fn diff_hunks(&self, panel: &DetailsPanel) -> Vec<usize> {
+        self.line_positions(panel)
+            .into_iter()
+            .zip(self.text.lines.iter())
+            .filter(|(_, line)| is_hunk_header(&line_text(line)))
+            .map(|(position, _)| position)
+            .collect()
+    }
+
+    fn diff_conflicts(&self, panel: &DetailsPanel) -> Vec<usize> {
+        let positions = self.line_positions(panel);
+        self.conflicts()
+            .into_iter()
+            .map(|conflict| positions[conflict.start])
+            .collect()
+    }
+
+    fn fold(&mut self, _panel: &DetailsPanel, _action: FoldAction) -> Option<usize> {
+        None
+    }
+
+    fn line_count(&self, _panel: &DetailsPanel) -> usize {
+        self.text.lines.len()
+    }
+
+    fn line_row(&self, panel: &DetailsPanel, line: usize) -> usize {
+        let positions = self.line_positions(panel);
+        positions[line.min(positions.len() - 1)]
+    }
+
+    fn row_line(&self, panel: &DetailsPanel, row: usize) -> usize {
+        let positions = self.line_positions(panel);
+        let line = positions.partition_point(|&position| position <= row);
+        line.saturating_sub(1)
+            .min(self.text.lines.len().saturating_sub(1))
+    }
+
+    fn copy_lines(&self, _panel: &DetailsPanel, lines: RangeInclusive<usize>) -> String {
+        self.text
+            .lines
+            .get(lines)
+            .unwrap_or_default()
+            .iter()
+            .map(|line| line_text(line) + "\n")
+            .collect()
+    }
+
+    fn file_line(&self, _panel: &DetailsPanel, line: usize) -> Option<usize> {
+        let numbers = diff_line_numbers(self.text.lines.iter().map(line_text));
+        new_file_line(&numbers, line)
+    }
+}
+
+impl TextContent<'_> {
+    /// Line ranges of the conflicts shown in the text
+    fn conflicts(&self) -> Vec<Range<usize>> {
+        let lines: Vec<String> = self.text.lines.iter().map(line_text).collect();
+        conflict_regions(&lines, &diff_line_numbers(&lines))
+    }
+
+    /// Scroll position of the start of each line, and the total number
+    /// of scroll positions at the end.
+    /// A wrapped line takes up more than one scroll position.
+    fn line_positions(&self, panel: &DetailsPanel) -> Vec<usize> {
+        let width = panel.columns();
+        let mut positions = Vec::with_capacity(self.text.lines.len() + 1);
+        let mut position: usize = 0;
+        for line in self.text.lines.iter() {
+            positions.push(position);
+            let height = if panel.wrap {
+                Paragraph::new(line.clone())
+                    .wrap(Wrap { trim: false })
+                    .line_count(width)
+                    .max(1)
+            } else {
+                1
+            };
+            position = position.saturating_add(height);
+        }
+        positions.push(position);
+        positions
+    }
+}
+
+/// Put the line number gutter in front of a line
+fn add_line_number(line: &mut Line, number: Option<&DiffLineNumber>, width: usize) {
+    let gutter = line_number_gutter(number.unwrap_or(&DiffLineNumber::default()), width);
+    line.spans.insert(0, Span::raw(gutter).fg(Color::DarkGray));
+}
+
+/// Mark a line as part of a conflict
+fn highlight_conflict(line: &mut Line) {
+    line.style = line.style.patch(Style::default().bg(CONFLICT_COLOR));
+}
+
+/// Mark a line as selected for copying
+fn highlight_selected(line: &mut Line) {
+    let style = Style::default().bg(get_env().jj_config.highlight_color());
+    line.style = line.style.patch(style);
+    for span in line.spans.iter_mut() {
+        span.style = span.style.patch(style);
+    }
+}
+
+/// The text of a line, without styling
+fn line_text(line: &Line) -> String {
+    line.spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect()
 }
 
 impl<'a, Content> DetailsPanelRenderContext<'a, Content>
@@ -139,6 +792,7 @@ where
             panel,
             title: None,
             content,
+            git_diff: false,
         }
     }
     /// Set the title on the frame that surrounds the content
@@ -150,9 +804,16 @@ where
         self
     }
 
+    /// Tell if the content is a git diff, where line numbers can be shown
+    pub fn git_diff(&mut self, git_diff: bool) -> &mut Self {
+        self.git_diff = git_diff;
+        self
+    }
+
     pub fn draw(&mut self, f: &mut ratatui::prelude::Frame<'_>, area: ratatui::prelude::Rect) {
         // Remember last rendered rect for mouse event handling
         self.panel.panel_rect = area;
+        self.panel.show_line_numbers = self.panel.line_numbers && self.git_diff;
 
         // Define border block
         let mut border = Block::bordered()
@@ -162,10 +823,37 @@ where
         if let Some(title) = &self.title {
             border = border.title_top(title.clone());
         }
+        // Show copy mode or search prompt at the bottom
+        if let Some(copy_line) = self.panel.copy_line() {
+            border = border.title_bottom(copy_line);
+        } else if let Some(search_line) = self.panel.search_line() {
+            border = border.title_bottom(search_line);
+        }
 
         // Create content widget that uses border
         let paragraph_area = border.inner(area);
+        self.panel.content_rect = paragraph_area;
+        self.panel.resolve_fold(&mut self.content);
         let content = &self.content;
+        self.panel.resolve_search(content);
+        self.panel.resolve_jump(content);
+        self.panel.resolve_copy(content);
+        // Show position among the hunks and conflicts of a diff
+        let hunks = content.diff_hunks(self.panel);
+        if !hunks.is_empty() {
+            let current = hunks.partition_point(|&hunk| hunk <= self.panel.scroll);
+            border = border
+                .title_top(Line::from(format!(" hunk {current}/{} ", hunks.len())).right_aligned());
+        }
+        let conflicts = content.diff_conflicts(self.panel);
+        if !conflicts.is_empty() {
+            let current = conflicts.partition_point(|&conflict| conflict <= self.panel.scroll);
+            border = border.title_top(
+                Line::from(format!(" conflict {current}/{} ", conflicts.len()))
+                    .fg(Color::Red)
+                    .right_aligned(),
+            );
+        }
         let mut paragraph = content
             .render_as_paragraph(self.panel, paragraph_area)
             .block(border);
@@ -178,20 +866,42 @@ where
         f.render_widget(paragraph, area);
 
         // render scrollbar on top of border
-        if self.panel.lines > paragraph_area.height {
+        self.panel.scrollbar_rect = Rect::ZERO;
+        if self.panel.lines > paragraph_area.height as usize {
             let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight);
 
             let mut scrollbar_state =
-                ScrollbarState::new(self.panel.lines.into()).position(self.panel.scroll.into());
-
-            f.render_stateful_widget(
-                scrollbar,
-                area.inner(Margin {
-                    vertical: 1,
-                    horizontal: 0,
-                }),
-                &mut scrollbar_state,
-            );
+                ScrollbarState::new(self.panel.lines).position(self.panel.scroll);
+
+            let scrollbar_area = area.inner(Margin {
+                vertical: 1,
+                horizontal: 0,
+            });
+            f.render_stateful_widget(scrollbar, scrollbar_area, &mut scrollbar_state);
+            self.panel.scrollbar_rect = Rect {
+                x: scrollbar_area.right().saturating_sub(1),
+                width: 1,
+                ..scrollbar_area
+            };
+        }
+
+        // render list of files on top of content
+        if self.panel.file_list.is_some() {
+            let files = content.diff_files(self.panel);
+            let Some(file_list) = self.panel.file_list.as_mut() else {
+                return;
+            };
+            file_list.files = files;
+            let list_area = centered_rect(area, 80, 60);
+            let list = List::new(file_list.files.iter().map(|file| file.path.as_str()))
+                .block(create_popup_block("Files"))
+                .scroll_padding(3)
+                .highlight_style(Style::default().bg(get_env().jj_config.highlight_color()));
+            if file_list.state.selected().is_none() && !file_list.files.is_empty() {
+                file_list.state.select_first();
+            }
+            f.render_widget(Clear, list_area);
+            f.render_stateful_widget(list, list_area, &mut file_list.state);
         }
     }
 }
@@ -201,9 +911,19 @@ impl DetailsPanel {
         Self {
             panel_rect: Rect::ZERO,
             content_rect: Rect::ZERO,
+            scrollbar_rect: Rect::ZERO,
+            scrollbar_drag: None,
             scroll: 0,
             lines: 0,
             wrap: true,
+            line_numbers: get_env().jj_config.diff_line_numbers(),
+            show_line_numbers: false,
+            search: None,
+            pending_prefix: None,
+            pending_fold: None,
+            pending_jump: None,
+            file_list: None,
+            copy: None,
         }
     }
 
@@ -231,12 +951,49 @@ impl DetailsPanel {
         self.content_rect.height
     }
 
-    pub fn scroll_to(&mut self, line_no: u16) {
+    /// Current scroll position, the first visible line of content
+    pub fn scroll_position(&self) -> usize {
+        self.scroll
+    }
+
+    /// Path of the diff file section at the copy mode cursor, or else
+    /// at the top of the panel
+    pub fn current_file<'a>(&self, content: &impl DetailContent<'a>) -> Option<String> {
+        let position = match self.copy.as_ref().and_then(|copy| copy.cursor) {
+            Some(cursor) => content.line_row(self, cursor),
+            None => self.scroll,
+        };
+        content
+            .diff_files(self)
+            .into_iter()
+            .take_while(|file| file.line <= position)
+            .last()
+            .map(|file| file.path)
+    }
+
+    /// Line number in the new file at the copy mode cursor, or else at
+    /// the top of the panel. None outside of the lines of a diff.
+    pub fn current_file_line<'a>(&self, content: &impl DetailContent<'a>) -> Option<usize> {
+        let line = match self.copy.as_ref().and_then(|copy| copy.cursor) {
+            Some(cursor) => cursor,
+            None => content.row_line(self, self.scroll),
+        };
+        content.file_line(self, line)
+    }
+
+    /// Restore a scroll position saved for content about to be drawn.
+    /// Unlike [scroll_to](Self::scroll_to) it is not limited by the
+    /// length of the content drawn last.
+    pub fn restore_scroll(&mut self, scroll: usize) {
+        self.scroll = scroll;
+    }
+
+    pub fn scroll_to(&mut self, line_no: usize) {
         self.scroll = line_no.min(self.lines.saturating_sub(1))
     }
 
     pub fn scroll(&mut self, scroll: isize) {
-        self.scroll_to(self.scroll.saturating_add_signed(scroll as i16))
+        self.scroll_to(self.scroll.saturating_add_signed(scroll))
     }
 
     pub fn handle_event(&mut self, details_panel_event: DetailsPanelEvent) {
@@ -249,12 +1006,362 @@ impl DetailsPanel {
             }
             DetailsPanelEvent::ScrollDownPage => self.scroll(self.rows() as isize),
             DetailsPanelEvent::ScrollUpPage => self.scroll((self.rows() as isize).saturating_neg()),
+            DetailsPanelEvent::ScrollToTop => self.scroll_to(0),
+            // Show the last page. Lines include the rows of wrapped lines.
+            DetailsPanelEvent::ScrollToBottom => {
+                self.scroll_to(self.lines.saturating_sub(self.rows() as usize))
+            }
             DetailsPanelEvent::ToggleWrap => self.wrap = !self.wrap,
+            DetailsPanelEvent::ToggleLineNumbers => self.line_numbers = !self.line_numbers,
+            DetailsPanelEvent::StartSearch => {
+                self.search = Some(PanelSearch {
+                    query: String::new(),
+                    pattern: None,
+                    editing: true,
+                    origin: self.scroll,
+                    pending: None,
+                    not_found: false,
+                })
+            }
+            DetailsPanelEvent::SearchNext => self.request_search_jump(SearchJump::Next),
+            DetailsPanelEvent::SearchPrevious => self.request_search_jump(SearchJump::Previous),
+            DetailsPanelEvent::ClearSearch => self.search = None,
+            DetailsPanelEvent::ToggleFold => self.pending_fold = Some(FoldAction::Toggle),
+            DetailsPanelEvent::ToggleAllFolds => self.pending_fold = Some(FoldAction::ToggleAll),
+            DetailsPanelEvent::NextFile => self.pending_jump = Some(SectionJump::NextFile),
+            DetailsPanelEvent::PreviousFile => self.pending_jump = Some(SectionJump::PreviousFile),
+            DetailsPanelEvent::NextHunk => self.pending_jump = Some(SectionJump::NextHunk),
+            DetailsPanelEvent::PreviousHunk => self.pending_jump = Some(SectionJump::PreviousHunk),
+            DetailsPanelEvent::NextConflict => self.pending_jump = Some(SectionJump::NextConflict),
+            DetailsPanelEvent::PreviousConflict => {
+                self.pending_jump = Some(SectionJump::PreviousConflict)
+            }
+            DetailsPanelEvent::StartCopy => {
+                self.copy = Some(CopyMode {
+                    cursor: None,
+                    mark: None,
+                    pending_copy: false,
+                })
+            }
+            DetailsPanelEvent::OpenFileList => {
+                self.file_list = Some(FileList {
+                    files: vec![],
+                    state: ListState::default(),
+                })
+            }
+        }
+    }
+
+    //
+    //  File, hunk and conflict navigation
+    //
+
+    /// Perform a pending file, hunk or conflict jump using the content about to be drawn
+    fn resolve_jump<'a>(&mut self, content: &impl DetailContent<'a>) {
+        let Some(jump) = self.pending_jump.take() else {
+            return;
+        };
+        let scroll = self.scroll;
+        let positions: Vec<usize> = match jump {
+            SectionJump::NextFile | SectionJump::PreviousFile => content
+                .diff_files(self)
+                .iter()
+                .map(|file| file.line)
+                .collect(),
+            SectionJump::NextHunk | SectionJump::PreviousHunk => content.diff_hunks(self),
+            SectionJump::NextConflict | SectionJump::PreviousConflict => {
+                content.diff_conflicts(self)
+            }
+        };
+        let found = match jump {
+            SectionJump::NextFile | SectionJump::NextHunk | SectionJump::NextConflict => {
+                positions.iter().find(|&&position| position > scroll)
+            }
+            SectionJump::PreviousFile
+            | SectionJump::PreviousHunk
+            | SectionJump::PreviousConflict => {
+                positions.iter().rev().find(|&&position| position < scroll)
+            }
+        };
+        if let Some(&position) = found {
+            self.scroll = position;
+        }
+    }
+
+    /// Perform a pending fold change on the content about to be drawn
+    fn resolve_fold<'a>(&mut self, content: &mut impl DetailContent<'a>) {
+        let Some(action) = self.pending_fold.take() else {
+            return;
+        };
+        if let Some(scroll) = content.fold(self, action) {
+            self.scroll = scroll;
+        }
+    }
+
+    /// Handle a key while the list of files is open
+    fn file_list_input(&mut self, key: KeyEvent) {
+        let Some(file_list) = self.file_list.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => file_list.state.select_next(),
+            KeyCode::Char('k') | KeyCode::Up => file_list.state.select_previous(),
+            KeyCode::Enter => {
+                let selected = file_list
+                    .state
+                    .selected()
+                    .and_then(|index| file_list.files.get(index));
+                if let Some(file) = selected {
+                    self.scroll = file.line;
+                }
+                self.file_list = None;
+            }
+            KeyCode::Esc | KeyCode::Char('q') => self.file_list = None,
+            _ => (),
+        }
+    }
+
+    //
+    //  Copy mode
+    //
+
+    /// Visible lines selected for copying, if in copy mode
+    fn copy_selection(&self) -> Option<RangeInclusive<usize>> {
+        let copy = self.copy.as_ref()?;
+        let cursor = copy.cursor?;
+        let mark = copy.mark.unwrap_or(cursor);
+        Some(cursor.min(mark)..=cursor.max(mark))
+    }
+
+    /// Handle a key while in copy mode. Returns false for keys that
+    /// copy mode does not use, e.g. to act on the file at the cursor.
+    fn copy_input(&mut self, key: KeyEvent) -> bool {
+        let half_page = (self.rows() as isize / 2).max(1);
+        let Some(copy) = self.copy.as_mut() else {
+            return false;
+        };
+        let delta = match key.code {
+            KeyCode::Char('j') | KeyCode::Down => 1,
+            KeyCode::Char('k') | KeyCode::Up => -1,
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => half_page,
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => -half_page,
+            KeyCode::Char('g') | KeyCode::Home => {
+                copy.cursor = Some(0);
+                return true;
+            }
+            KeyCode::Char('G') | KeyCode::End => {
+                // Limited to the last line at next draw
+                copy.cursor = Some(usize::MAX);
+                return true;
+            }
+            KeyCode::Char('v') | KeyCode::Char(' ') => {
+                copy.mark = match copy.mark {
+                    Some(_) => None,
+                    None => copy.cursor,
+                };
+                return true;
+            }
+            KeyCode::Char('y') | KeyCode::Enter => {
+                copy.pending_copy = true;
+                return true;
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.copy = None;
+                return true;
+            }
+            _ => return false,
+        };
+        copy.cursor = copy
+            .cursor
+            .map(|cursor| cursor.saturating_add_signed(delta));
+        true
+    }
+
+    /// Place the cursor and perform a pending copy using the content
+    /// about to be drawn. The cursor is kept inside the visible area.
+    fn resolve_copy<'a>(&mut self, content: &impl DetailContent<'a>) {
+        let Some(mut copy) = self.copy.take() else {
+            return;
+        };
+        let last_line = content.line_count(self).saturating_sub(1);
+        let cursor = copy
+            .cursor
+            .unwrap_or_else(|| content.row_line(self, self.scroll))
+            .min(last_line);
+        copy.cursor = Some(cursor);
+        copy.mark = copy.mark.map(|mark| mark.min(last_line));
+
+        if copy.pending_copy {
+            let mark = copy.mark.unwrap_or(cursor);
+            let text = content.copy_lines(self, cursor.min(mark)..=cursor.max(mark));
+            copy_to_clipboard(&text);
+            // Copying ends copy mode
+            return;
         }
+
+        let top = content.line_row(self, cursor);
+        let bottom = content.line_row(self, cursor.saturating_add(1));
+        let rows = self.rows() as usize;
+        if top < self.scroll {
+            self.scroll = top;
+        } else if bottom > self.scroll + rows {
+            self.scroll = bottom.saturating_sub(rows).min(top);
+        }
+        self.copy = Some(copy);
+    }
+
+    /// Line describing copy mode, shown at the bottom of the panel
+    fn copy_line(&self) -> Option<Line<'static>> {
+        let copy = self.copy.as_ref()?;
+        let text = match (copy.mark, self.copy_selection()) {
+            (Some(_), Some(selection)) => {
+                format!(" -- COPY -- {} lines, y to copy ", selection.count())
+            }
+            _ => " -- COPY -- v to select, y to copy ".to_owned(),
+        };
+        Some(Line::from(text).fg(Color::Yellow))
+    }
+
+    //
+    //  Search
+    //
+
+    /// The compiled search query, if a search is active
+    fn search_pattern(&self) -> Option<&SearchPattern> {
+        self.search
+            .as_ref()
+            .and_then(|search| search.pattern.as_ref())
+    }
+
+    /// Ask for a jump to be made at next draw
+    fn request_search_jump(&mut self, jump: SearchJump) {
+        if let Some(search) = self.search.as_mut() {
+            search.pending = Some(jump);
+        }
+    }
+
+    /// Handle a key while the search query is being typed
+    fn search_input(&mut self, key: KeyEvent) {
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.scroll = search.origin;
+                self.search = None;
+                return;
+            }
+            KeyCode::Enter => {
+                if search.query.is_empty() {
+                    self.search = None;
+                } else {
+                    search.editing = false;
+                }
+                return;
+            }
+            KeyCode::Backspace => {
+                search.query.pop();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                search.query.push(c);
+            }
+            _ => return,
+        }
+        search.pattern = SearchPattern::new(&search.query);
+        search.pending = Some(SearchJump::FromOrigin);
+    }
+
+    /// Perform a pending search jump using the content about to be drawn
+    fn resolve_search<'a>(&mut self, content: &impl DetailContent<'a>) {
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+        let Some(jump) = search.pending.take() else {
+            return;
+        };
+        let Some(pattern) = search.pattern.clone() else {
+            // Empty query while typing, so go back to where we started
+            search.not_found = false;
+            self.scroll = search.origin;
+            return;
+        };
+        let origin = search.origin;
+
+        let (start, forward) = match jump {
+            SearchJump::FromOrigin => (origin, true),
+            SearchJump::Next => (self.scroll.saturating_add(1), true),
+            SearchJump::Previous => match self.scroll.checked_sub(1) {
+                Some(start) => (start, false),
+                None => (self.lines.saturating_sub(1), false),
+            },
+        };
+        let found = content.find_match(self, &pattern, start, forward);
+
+        if let Some(found) = found {
+            self.scroll = found;
+        } else if let SearchJump::FromOrigin = jump {
+            self.scroll = origin;
+        }
+        if let Some(search) = self.search.as_mut() {
+            search.not_found = found.is_none();
+        }
+    }
+
+    /// Line describing the search, shown at the bottom of the panel
+    fn search_line(&self) -> Option<Line<'static>> {
+        let search = self.search.as_ref()?;
+        let mut spans = vec![Span::raw(format!(" /{}", search.query))];
+        if search.editing {
+            spans.push(Span::raw("_"));
+        }
+        if search.not_found {
+            spans.push(Span::raw(" [no match]").fg(Color::Red));
+        }
+        spans.push(Span::raw(" "));
+        Some(Line::from(spans))
+    }
+
+    /// True while all keys belong to the panel, e.g. while a search
+    /// query is typed
+    pub fn is_capturing_keys(&self) -> bool {
+        self.search.as_ref().is_some_and(|search| search.editing)
+            || self.file_list.is_some()
+            || self.copy.is_some()
+            || self.pending_prefix.is_some()
     }
 
     /// Handle input. Returns bool of if event was handled
     pub fn input(&mut self, key: KeyEvent) -> bool {
+        // While typing a search query, all keys belong to the query
+        if self.search.as_ref().is_some_and(|search| search.editing) {
+            self.search_input(key);
+            return true;
+        }
+        // While the file list is open, all keys belong to the list
+        if self.file_list.is_some() {
+            self.file_list_input(key);
+            return true;
+        }
+        // While in copy mode, the keys of copy mode belong to it
+        if self.copy.is_some() {
+            return self.copy_input(key);
+        }
+        // Second key of `]f`, `[f`, `]h`, `[h`, `]c`, `[c`, `za` and `zA`
+        if let Some(prefix) = self.pending_prefix.take() {
+            match (prefix, key.code) {
+                (']', KeyCode::Char('f')) => self.handle_event(DetailsPanelEvent::NextFile),
+                ('[', KeyCode::Char('f')) => self.handle_event(DetailsPanelEvent::PreviousFile),
+                (']', KeyCode::Char('h')) => self.handle_event(DetailsPanelEvent::NextHunk),
+                ('[', KeyCode::Char('h')) => self.handle_event(DetailsPanelEvent::PreviousHunk),
+                (']', KeyCode::Char('c')) => self.handle_event(DetailsPanelEvent::NextConflict),
+                ('[', KeyCode::Char('c')) => self.handle_event(DetailsPanelEvent::PreviousConflict),
+                ('z', KeyCode::Char('a')) => self.handle_event(DetailsPanelEvent::ToggleFold),
+                ('z', KeyCode::Char('A')) => self.handle_event(DetailsPanelEvent::ToggleAllFolds),
+                _ => (),
+            }
+            return true;
+        }
+
         match key.code {
             KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.handle_event(DetailsPanelEvent::ScrollDown)
@@ -274,15 +1381,120 @@ impl DetailsPanel {
             KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.handle_event(DetailsPanelEvent::ScrollUpPage)
             }
+            KeyCode::Char('g') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.handle_event(DetailsPanelEvent::OpenFileList)
+            }
+            // With Ctrl the other keys are left to the tab, e.g. Ctrl+z or
+            // Ctrl+Home
+            _ if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Char('g') | KeyCode::Home => self.handle_event(DetailsPanelEvent::ScrollToTop),
+            KeyCode::Char('G') | KeyCode::End => {
+                self.handle_event(DetailsPanelEvent::ScrollToBottom)
+            }
             KeyCode::Char('W') => self.handle_event(DetailsPanelEvent::ToggleWrap),
+            KeyCode::Char('L') => self.handle_event(DetailsPanelEvent::ToggleLineNumbers),
+            KeyCode::Char(prefix @ (']' | '[' | 'z')) => self.pending_prefix = Some(prefix),
+            KeyCode::Char('/') => self.handle_event(DetailsPanelEvent::StartSearch),
+            KeyCode::Char('v') => self.handle_event(DetailsPanelEvent::StartCopy),
+            KeyCode::Char('n') if self.search.is_some() => {
+                self.handle_event(DetailsPanelEvent::SearchNext)
+            }
+            KeyCode::Char('N') if self.search.is_some() => {
+                self.handle_event(DetailsPanelEvent::SearchPrevious)
+            }
+            KeyCode::Esc if self.search.is_some() => {
+                self.handle_event(DetailsPanelEvent::ClearSearch)
+            }
             _ => return false,
         };
 
         true
     }
 
+    //
+    //  Scrollbar
+    //
+
+    /// Rows of the scrollbar track, between the arrows, and the
+    /// range of rows covered by the thumb. Same as the Scrollbar widget.
+    fn scrollbar_thumb(&self) -> (Range<u16>, Range<u16>) {
+        let rect = self.scrollbar_rect;
+        let track = rect.top() + 1..rect.bottom().saturating_sub(1).max(rect.top() + 1);
+        let track_length = track.len() as f64;
+        let viewport_length = rect.height as f64;
+        let max_position = self.lines.saturating_sub(1) as f64;
+        let start_position = (self.scroll as f64).clamp(0.0, max_position);
+        let max_viewport_position = max_position + viewport_length;
+        let thumb_start = (start_position * track_length / max_viewport_position)
+            .round()
+            .clamp(0.0, (track_length - 1.0).max(0.0)) as u16;
+        let thumb_end = ((start_position + viewport_length) * track_length / max_viewport_position)
+            .round()
+            .clamp(0.0, track_length) as u16;
+        let thumb_length = thumb_end.saturating_sub(thumb_start).max(1);
+        let thumb_start = track.start + thumb_start;
+        (
+            track.clone(),
+            thumb_start..(thumb_start + thumb_length).min(track.end),
+        )
+    }
+
+    /// Scroll so the top of the thumb is at `row`
+    fn drag_scrollbar_to(&mut self, row: u16) {
+        let (track, _) = self.scrollbar_thumb();
+        if track.is_empty() {
+            return;
+        }
+        let offset = row.clamp(track.start, track.end - 1) - track.start;
+        let max_viewport_position =
+            self.lines.saturating_sub(1) as f64 + self.scrollbar_rect.height as f64;
+        let scroll = offset as f64 * max_viewport_position / track.len() as f64;
+        self.scroll_to(scroll.round() as usize);
+    }
+
+    /// Handle a mouse event on the scrollbar. Returns true if handled.
+    fn scrollbar_mouse(&mut self, mouse: MouseEvent) -> bool {
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.scrollbar_drag = None;
+                if !self.scrollbar_rect.contains(Position {
+                    y: mouse.row,
+                    x: mouse.column,
+                }) {
+                    return false;
+                }
+                let (track, thumb) = self.scrollbar_thumb();
+                if mouse.row < track.start {
+                    self.handle_event(DetailsPanelEvent::ScrollUp);
+                } else if mouse.row >= track.end {
+                    self.handle_event(DetailsPanelEvent::ScrollDown);
+                } else if mouse.row < thumb.start {
+                    self.handle_event(DetailsPanelEvent::ScrollUpPage);
+                } else if mouse.row >= thumb.end {
+                    self.handle_event(DetailsPanelEvent::ScrollDownPage);
+                } else {
+                    self.scrollbar_drag = Some(mouse.row - thumb.start);
+                }
+                true
+            }
+            MouseEventKind::Drag(MouseButton::Left) => {
+                let Some(grab) = self.scrollbar_drag else {
+                    return false;
+                };
+                self.drag_scrollbar_to(mouse.row.saturating_sub(grab));
+                true
+            }
+            MouseEventKind::Up(MouseButton::Left) => self.scrollbar_drag.take().is_some(),
+            _ => false,
+        }
+    }
+
     /// Handle input. Returns bool of if event was handled
     pub fn input_mouse(&mut self, mouse: MouseEvent) -> bool {
+        // A drag continues outside of the panel
+        if self.scrollbar_mouse(mouse) {
+            return true;
+        }
         if !self.panel_rect.contains(Position {
             y: mouse.row,
             x: mouse.column,
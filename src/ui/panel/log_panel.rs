@@ -1,23 +1,48 @@
 /*! The log panel shows the list of changes on the left side of the
-log tab. */
-
+log tab.
+
+On huge repos, the log is loaded a page of changes at a time with
+`jj log --limit`. When the selection gets near the end of the loaded
+changes, the next page is requested. A "loading…" row is drawn at the
+end of the log first, and the log is loaded again with a higher limit
+in the background, so the graph stays intact across pages and the log
+can still be browsed meanwhile.
+
+The loaded log can be searched, e.g. for a change id, a description,
+an author or a bookmark. Matches are highlighted in the log, and the
+selection jumps between the changes with a match. The descriptions of
+the whole repo can be searched by showing the revset
+`description(substring-i:"...")` instead. */
+
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::collections::HashSet;
+use std::ops::Range;
 
 use ansi_to_tui::IntoText;
 use anyhow::Result;
+use chrono::Utc;
 use ratatui::crossterm::event::Event;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::crossterm::event::KeyEvent;
+use ratatui::crossterm::event::KeyModifiers;
 use ratatui::crossterm::event::MouseEvent;
 use ratatui::crossterm::event::MouseEventKind;
 use ratatui::layout::Rect;
 use ratatui::prelude::*;
 use ratatui::text::ToText;
 use ratatui::widgets::*;
+use tracing::error;
 
 use crate::commander::CommandError;
+use crate::commander::files::DiffStat;
+use crate::commander::ids::ChangeId;
 use crate::commander::ids::CommitId;
+use crate::commander::log::ChangeSize;
 use crate::commander::log::Head;
 use crate::commander::log::LogOutput;
 use crate::commander::new_commander;
+use crate::commander::worker::CommandJob;
 use crate::env::JjConfig;
 use crate::env::get_env;
 use crate::keybinds::LogTabEvent;
@@ -25,6 +50,13 @@ use crate::keybinds::LogTabKeybinds;
 use crate::ui::Component;
 use crate::ui::ComponentAction;
 use crate::ui::ComponentInputResult;
+use crate::ui::ci_status::CheckState;
+use crate::ui::utils::Pins;
+use crate::ui::utils::SearchPattern;
+use crate::ui::utils::append_change_sizes;
+use crate::ui::utils::color_graph_lanes;
+use crate::ui::utils::ref_chips;
+use crate::ui::utils::relative_timestamps;
 
 /**
     A panel that displays the output of jj log.
@@ -45,6 +77,25 @@ pub struct LogPanel<'a> {
     /// Output from 'jj log' as provided by command::get_show_log
     log_output: Result<LogOutput, CommandError>,
 
+    /// Changed files and lines of the working copy, shown above the
+    /// log if enabled
+    working_copy_stat: Option<DiffStat>,
+
+    /// Changes made with git that jj didn't import yet, shown above the
+    /// log until the log is loaded again
+    git_drift: Option<Line<'static>>,
+
+    /// Changes pinned for quick access, kept per repository
+    pins: Pins,
+
+    /// Visible commits of the pinned changes, with a summary each
+    pinned_commits: Vec<(Head, String)>,
+
+    /// Row of the pinned changes, if shown, and the columns of each,
+    /// to select one with the mouse
+    pins_area: Option<Rect>,
+    pin_columns: Vec<(Range<u16>, Head)>,
+
     /// Output from 'jj log' converted to Ratatui Text
     log_output_text: Text<'a>,
 
@@ -57,11 +108,67 @@ pub struct LogPanel<'a> {
     /// The revision filter used for the log
     pub log_revset: Option<String>,
 
+    /// The revset extended with a change outside of it that was gone
+    /// to, until the revset is changed
+    revealed_revset: Option<String>,
+
+    /// Only show changes touching these paths, if any
+    pub log_paths: Vec<String>,
+
     /// Currently selected commit
     pub head: Head,
 
     /// Currently marked commits
-    pub marked_heads: HashSet<CommitId>,
+    marked_heads: HashSet<CommitId>,
+
+    /// Change where the visual selection started. The changes from it
+    /// to the selected change count as marked.
+    visual_anchor: Option<Head>,
+
+    /// Change that the selected change is compared to, if any
+    pub compare_base: Option<Head>,
+
+    /// Change to squash into the change picked next, if any
+    pub squash_source: Option<Head>,
+
+    /// Change to rebase onto the change picked next, if any
+    pub rebase_source: Option<Head>,
+
+    /// Changes to duplicate onto the change picked next, if any
+    pub duplicate_sources: Option<Vec<CommitId>>,
+
+    /// Change to restore files into from the change picked next, if any
+    pub restore_destination: Option<Head>,
+
+    /// Select the working copy change whenever the log tab reloads
+    pub follow_working_copy: bool,
+
+    /// Dim the changes that are neither ancestors nor descendants of
+    /// the selected change
+    highlight_related: bool,
+
+    /// Ancestors and descendants in the shown revset of the change
+    /// with the commit id, for highlight_related
+    related_heads: Option<(CommitId, HashSet<CommitId>)>,
+
+    /// Number of changes to load, None to load the whole log
+    log_limit: Option<usize>,
+
+    /// Loading of the next page of the log
+    page_load: PageLoad,
+
+    /// The log with the next page, being loaded in the background with
+    /// its limit
+    next_page: Option<(Option<usize>, CommandJob<LogOutput>)>,
+
+    /// Show timestamps like "3 hours ago"
+    relative_timestamps: bool,
+
+    /// State of the checks of the pushed changes, if polled
+    ci_states: HashMap<CommitId, CheckState>,
+
+    /// Search in the loaded log, if any
+    search: Option<LogSearch>,
 
     /// Area where panel was drawn. This includes the border.
     panel_rect: Rect,
@@ -70,8 +177,58 @@ pub struct LogPanel<'a> {
     config: JjConfig,
 }
 
+/// State of loading the next page of the log
+#[derive(Clone, Copy, PartialEq)]
+enum PageLoad {
+    Idle,
+    /// The selection is near the end of the loaded log
+    Requested,
+    /// The "loading…" row has been drawn, so the page can be loaded
+    Drawn,
+    /// The page is loading in the background
+    Running,
+}
+
+/// A search of the loaded log
+struct LogSearch {
+    /// The text typed by the user
+    query: String,
+    /// Compiled query. None if the query is empty.
+    pattern: Option<SearchPattern>,
+    /// True while the query is being typed
+    editing: bool,
+    /// Selected change when the search was started
+    origin: Head,
+    /// The last jump did not find any match
+    not_found: bool,
+}
+
+/// Which match the selection should move to
+#[derive(Clone, Copy)]
+enum SearchJump {
+    /// First match at or after the change where the search started
+    FromOrigin,
+    /// First match after the selected change
+    Next,
+    /// Last match before the selected change
+    Previous,
+}
+
+/// What a key did to the log search
+pub enum LogSearchInput {
+    /// The key does not belong to the search
+    NotHandled,
+    /// The search or the selected change changed
+    Handled,
+    /// The log was loaded with a different revset
+    Reloaded,
+}
+
 const LEFT_MARGIN_BLANK: char = ' ';
 const LEFT_MARGIN_MARKED: char = '>';
+const LEFT_MARGIN_COMPARE_BASE: char = '=';
+const LEFT_MARGIN_IMMUTABLE: char = '◆';
+const LEFT_MARGIN_CONFLICT: char = '×';
 
 /*
 pub enum LogPanelEvent {
@@ -105,10 +262,89 @@ fn get_head_index(head: &Head, log_output: &Result<LogOutput, CommandError>) ->
     }
 }
 
+/// Convert the log output to ratatui text, optionally with relative
+/// timestamps, and the state of the checks of the pushed changes
+fn log_text<'a>(
+    log_output: &Result<LogOutput, CommandError>,
+    relative: bool,
+    ci_states: &HashMap<CommitId, CheckState>,
+) -> Text<'a> {
+    let Ok(log_output) = log_output.as_ref() else {
+        return Text::default();
+    };
+    let graph = if relative {
+        relative_timestamps(&log_output.graph, Utc::now())
+    } else {
+        Cow::Borrowed(log_output.graph.as_str())
+    };
+    let text = graph
+        .as_bytes()
+        .into_text()
+        .unwrap_or(Text::from("Could not turn text into TUI text (coloring)"));
+    let lane_colors = get_env().jj_config.graph_lane_colors();
+    // Refs are drawn as chips on the first line of a change with any
+    let mut chipped_head = None;
+    let lines = text.lines.into_iter().enumerate().map(|(index, mut line)| {
+        if lane_colors {
+            line = color_graph_lanes(line);
+        }
+        if let Some(head) = log_output.head_at(index)
+            && chipped_head != Some(head)
+            && let Some(refs) = log_output.refs.get(&head.commit_id)
+        {
+            let (chipped_line, found) = ref_chips(line, refs, &log_output.tracking);
+            line = chipped_line;
+            if found {
+                chipped_head = Some(head);
+            }
+        }
+        // The state of the checks follows the first line of a change
+        if let Some(head) = log_output.head_at(index)
+            && (index == 0 || log_output.head_at(index - 1) != Some(head))
+            && let Some(state) = ci_states.get(&head.commit_id)
+        {
+            line.spans.push(Span::raw(" "));
+            line.spans.push(Span::raw(state.icon()).fg(state.color()));
+        }
+        line
+    });
+    if log_output.sizes.is_empty() {
+        return Text::from_iter(lines);
+    }
+    // Sizes are shown on the first line of each change
+    let sizes: Vec<Option<ChangeSize>> = (0..log_output.graph_heads.len())
+        .map(|index| {
+            let head = log_output.head_at(index)?;
+            if index > 0 && log_output.head_at(index - 1) == Some(head) {
+                return None;
+            }
+            log_output.sizes.get(&head.commit_id).copied()
+        })
+        .collect();
+    Text::from(append_change_sizes(lines.collect(), &sizes))
+}
+
+/// Indices into `log_output.heads` of the changes with a line (without
+/// ANSI codes) that matches the pattern, in log order
+fn matching_heads(lines: &[String], log_output: &LogOutput, pattern: &SearchPattern) -> Vec<usize> {
+    let mut matches: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| pattern.is_match(line))
+        .filter_map(|(index, _)| {
+            let head = log_output.head_at(index)?;
+            log_output.heads.iter().position(|other| other == head)
+        })
+        .collect();
+    matches.dedup();
+    matches
+}
+
 impl<'a> LogPanel<'a> {
     pub fn new() -> Result<Self> {
         let log_revset = new_commander().env.default_revset.clone();
-        let log_output = new_commander().get_log(&log_revset);
+        let log_limit = Self::first_page_limit();
+        let log_output = new_commander().get_log(&log_revset, &[], log_limit);
         let head = new_commander().get_current_head()?;
 
         let log_list_state = ListState::default().with_selected(get_head_index(&head, &log_output));
@@ -118,24 +354,48 @@ impl<'a> LogPanel<'a> {
             keybinds.extend_from_config(keybinds_config);
         }
 
-        let log_output_text = match log_output.as_ref() {
-            Ok(log_output) => log_output
-                .graph
-                .into_text()
-                .unwrap_or(Text::from("Could not turn text into TUI text (coloring)")),
-            Err(_) => Text::default(),
-        };
+        let relative_timestamps = get_env().jj_config.log_relative_timestamps();
+        let log_output_text = log_text(&log_output, relative_timestamps, &HashMap::new());
+
+        let pins = Pins::load(&get_env().root);
+        let pinned_commits = load_pinned_commits(&pins);
 
         Ok(Self {
             log_output_text,
             log_output,
+            working_copy_stat: load_working_copy_stat(),
+            git_drift: None,
+            pins,
+            pinned_commits,
+            pins_area: None,
+            pin_columns: vec![],
             log_list_state,
             log_rect: Rect::ZERO,
 
             log_revset,
+            revealed_revset: None,
+            log_paths: vec![],
 
             head,
             marked_heads: HashSet::new(),
+            visual_anchor: None,
+            compare_base: None,
+            squash_source: None,
+            rebase_source: None,
+            duplicate_sources: None,
+            restore_destination: None,
+            follow_working_copy: false,
+            highlight_related: false,
+            related_heads: None,
+
+            log_limit,
+            page_load: PageLoad::Idle,
+            next_page: None,
+
+            relative_timestamps,
+            ci_states: HashMap::new(),
+
+            search: None,
 
             panel_rect: Rect::ZERO,
 
@@ -147,32 +407,142 @@ impl<'a> LogPanel<'a> {
     //  Handle jj log output
     //
 
-    /// Run jj log and store output for display
+    /// Limit of the log when only the first page is loaded
+    fn first_page_limit() -> Option<usize> {
+        Some(get_env().jj_config.log_page_size()).filter(|&size| size > 0)
+    }
+
+    /// Run jj log and store output for display. Pages loaded so far
+    /// are loaded again.
     pub fn refresh_log_output(&mut self) {
-        self.log_output = new_commander().get_log(&self.log_revset);
-        self.log_output_text = match self.log_output.as_ref() {
+        // The page being loaded would be outdated
+        if self.next_page.take().is_some() {
+            self.page_load = PageLoad::Idle;
+        }
+        let log_output =
+            new_commander().get_log(self.shown_revset(), &self.log_paths, self.log_limit);
+        self.set_log_output(log_output);
+    }
+
+    /// Show a loaded log
+    fn set_log_output(&mut self, log_output: Result<LogOutput, CommandError>) {
+        self.log_output = log_output;
+        self.log_output_text =
+            log_text(&self.log_output, self.relative_timestamps, &self.ci_states);
+        self.related_heads = None;
+        self.working_copy_stat = load_working_copy_stat();
+        // Loading the log imported them
+        self.git_drift = None;
+        self.pinned_commits = load_pinned_commits(&self.pins);
+    }
+
+    /// LogTabEvent: Pin the selected change, or unpin it if it is
+    /// pinned
+    pub fn toggle_pin(&mut self) {
+        self.pins.toggle(&self.head.change_id);
+        self.pinned_commits = load_pinned_commits(&self.pins);
+    }
+
+    /// Visible commits of the pinned changes, with a summary each
+    pub fn pinned_commits(&self) -> &[(Head, String)] {
+        &self.pinned_commits
+    }
+
+    /// Show the changes made with git that jj didn't import yet
+    pub fn set_git_drift(&mut self, git_drift: Option<Line<'static>>) {
+        self.git_drift = git_drift;
+    }
+
+    /// Show the state of the checks of the pushed changes, without
+    /// loading the log again
+    pub fn set_ci_states(&mut self, ci_states: HashMap<CommitId, CheckState>) {
+        self.ci_states = ci_states;
+        self.log_output_text =
+            log_text(&self.log_output, self.relative_timestamps, &self.ci_states);
+    }
+
+    /// Commits of the changes in the log, from the top
+    pub fn commit_ids(&self) -> Vec<CommitId> {
+        match &self.log_output {
             Ok(log_output) => log_output
-                .graph
-                .into_text()
-                .unwrap_or(Text::from("Could not turn text into TUI text (coloring)")),
-            Err(_) => Text::default(),
+                .heads
+                .iter()
+                .map(|head| head.commit_id.clone())
+                .collect(),
+            Err(_) => vec![],
+        }
+    }
+
+    /// LogTabEvent: Switch between absolute and relative timestamps,
+    /// without loading the log again
+    pub fn toggle_relative_timestamps(&mut self) {
+        self.relative_timestamps = !self.relative_timestamps;
+        self.log_output_text =
+            log_text(&self.log_output, self.relative_timestamps, &self.ci_states);
+    }
+
+    /// Revset of the changes in the log
+    fn shown_revset(&self) -> &Option<String> {
+        match &self.revealed_revset {
+            Some(_) => &self.revealed_revset,
+            None => &self.log_revset,
+        }
+    }
+
+    /// LogTabEvent: Dim the changes that are not related to the
+    /// selected change, or stop dimming them
+    pub fn toggle_highlight_related(&mut self) {
+        self.highlight_related = !self.highlight_related;
+        self.related_heads = None;
+    }
+
+    /// Find the ancestors and descendants of the selected change, if
+    /// they are highlighted and the selection changed
+    fn update_related_heads(&mut self) {
+        if !self.highlight_related
+            || self
+                .related_heads
+                .as_ref()
+                .is_some_and(|(commit_id, _)| *commit_id == self.head.commit_id)
+        {
+            return;
+        }
+        let commit_id = self.head.commit_id.clone();
+        let related = self.shown_revset_or_default().and_then(|revset| {
+            Ok(new_commander()
+                .get_commit_ids(&format!("({revset}) & (::{commit_id} | {commit_id}::)"))?)
+        });
+        self.related_heads = match related {
+            Ok(related) => Some((commit_id, related.into_iter().collect())),
+            Err(err) => {
+                error!("Error finding the changes related to {commit_id}: {err}");
+                None
+            }
         };
     }
 
     /// Convert log output to a list of formatted lines
     fn output_to_lines(&self, log_output: &LogOutput) -> Vec<Line<'a>> {
         // Add commit mark
+        let visual_heads = self.visual_heads();
         let add_mark = |line: &mut Line, i: usize| {
-            let at_marked_commit = log_output
-                .head_at(i)
-                .is_some_and(|head| self.is_head_marked(head));
-
-            let symbol = if at_marked_commit {
-                LEFT_MARGIN_MARKED
+            let head = log_output.head_at(i);
+            let at_marked_commit = head.is_some_and(|head| {
+                self.marked_heads.contains(&head.commit_id) || visual_heads.contains(head)
+            });
+            let at_compare_base = head.is_some_and(|head| self.compare_base.as_ref() == Some(head));
+
+            let span = if at_compare_base {
+                Span::from(LEFT_MARGIN_COMPARE_BASE.to_string()).fg(Color::Yellow)
+            } else if at_marked_commit {
+                Span::from(LEFT_MARGIN_MARKED.to_string())
+            } else if head.is_some_and(|head| head.conflict) {
+                Span::from(LEFT_MARGIN_CONFLICT.to_string()).fg(Color::Red)
+            } else if head.is_some_and(|head| head.immutable) {
+                Span::from(LEFT_MARGIN_IMMUTABLE.to_string()).fg(Color::DarkGray)
             } else {
-                LEFT_MARGIN_BLANK
+                Span::from(LEFT_MARGIN_BLANK.to_string())
             };
-            let span = Span::from(symbol.to_string());
             line.spans.insert(0, span);
         };
 
@@ -187,12 +557,25 @@ impl<'a> LogPanel<'a> {
             }
         }
 
+        // Changes to dim, if the related changes are highlighted
+        let unrelated = |head: &Head| {
+            self.related_heads
+                .as_ref()
+                .is_some_and(|(_, related)| !related.contains(&head.commit_id))
+        };
+
         self.log_output_text
             .iter()
             .enumerate()
             .map(|(i, line)| {
                 let mut line = line.to_owned();
 
+                if log_output.head_at(i).is_some_and(unrelated) {
+                    for span in line.spans.iter_mut() {
+                        span.style = span.style.fg(Color::DarkGray);
+                    }
+                }
+
                 // Add padding at start
                 add_mark(&mut line, i);
 
@@ -209,11 +592,126 @@ impl<'a> LogPanel<'a> {
     /// Get lines to show in log list
     fn log_lines(&self) -> Vec<Line<'a>> {
         match self.log_output.as_ref() {
-            Ok(log_output) => self.output_to_lines(log_output),
+            Ok(log_output) => {
+                let mut lines = self.output_to_lines(log_output);
+                if let Some(pattern) = self.search_pattern() {
+                    lines = lines
+                        .into_iter()
+                        .map(|line| pattern.highlight_line(line))
+                        .collect();
+                }
+                if log_output.truncated && self.next_page.is_some() {
+                    lines.push(Line::from("  loading… (Esc: cancel)").fg(Color::DarkGray));
+                } else if log_output.truncated {
+                    lines.push(Line::from("  loading…").fg(Color::DarkGray));
+                }
+                lines
+            }
             Err(err) => err.into_text("Error getting log").unwrap().lines,
         }
     }
 
+    //
+    //  Pages of the log
+    //
+
+    /// Show a different revset, starting again with the first page
+    pub fn set_log_revset(&mut self, log_revset: Option<String>) {
+        self.log_revset = log_revset;
+        self.revealed_revset = None;
+        self.reload_first_page();
+    }
+
+    /// Only show changes of the revset touching the paths, or all
+    /// changes of the revset if there are no paths
+    pub fn set_log_paths(&mut self, log_paths: Vec<String>) {
+        self.log_paths = log_paths;
+        self.reload_first_page();
+    }
+
+    /// Load the log again, starting with the first page
+    fn reload_first_page(&mut self) {
+        self.log_limit = Self::first_page_limit();
+        self.page_load = PageLoad::Idle;
+        self.refresh_log_output();
+    }
+
+    /// A page of the log is about to be loaded
+    pub fn is_loading(&self) -> bool {
+        matches!(self.page_load, PageLoad::Requested | PageLoad::Drawn)
+    }
+
+    /// A page of the log is loading in the background
+    pub fn is_loading_page(&self) -> bool {
+        self.next_page.is_some()
+    }
+
+    /// Stop loading the next page. It is requested again when the
+    /// selection moves.
+    pub fn cancel_page_load(&mut self) {
+        if let Some((_, job)) = self.next_page.as_ref() {
+            job.cancel();
+        }
+    }
+
+    /// Ask for the next page when the selection is less than a
+    /// screen away from the end of a truncated log
+    fn request_next_page(&mut self) {
+        let Ok(log_output) = self.log_output.as_ref() else {
+            return;
+        };
+        let remaining = match self.get_current_head_index() {
+            Some(index) => log_output.heads.len().saturating_sub(index + 1),
+            None => 0,
+        };
+        if log_output.truncated
+            && self.page_load == PageLoad::Idle
+            && remaining <= self.visible_heads() as usize
+        {
+            self.page_load = PageLoad::Requested;
+        }
+    }
+
+    /// Limit of the log with the next page
+    fn next_page_limit(&self) -> Option<usize> {
+        let page_size = get_env().jj_config.log_page_size();
+        self.log_limit.map(|limit| limit + page_size)
+    }
+
+    /// Start loading the next page in the background, if requested and
+    /// the "loading…" row has been drawn. Returns true once the log with
+    /// the next page is loaded.
+    pub fn load_next_page(&mut self) -> bool {
+        if self.page_load == PageLoad::Drawn {
+            self.page_load = PageLoad::Running;
+            let limit = self.next_page_limit();
+            let revset = self.shown_revset().clone();
+            let paths = self.log_paths.clone();
+            let job = CommandJob::spawn(new_commander(), move |commander| {
+                commander.get_log(&revset, &paths, limit)
+            });
+            self.next_page = Some((limit, job));
+            return false;
+        }
+        let Some(result) = self
+            .next_page
+            .as_ref()
+            .and_then(|(_, job)| job.try_result())
+        else {
+            return false;
+        };
+        let Some((limit, _)) = self.next_page.take() else {
+            return false;
+        };
+        self.page_load = PageLoad::Idle;
+        if matches!(result, Err(CommandError::Cancelled)) {
+            return false;
+        }
+        self.log_limit = limit;
+        self.set_log_output(result);
+        true
+    }
+
     /// Get a list of all heads in log list
     pub fn log_heads(&self) -> Vec<Head> {
         match self.log_output.as_ref() {
@@ -222,6 +720,16 @@ impl<'a> LogPanel<'a> {
         }
     }
 
+    /// Number of lines changed by a change, if the sizes are shown
+    pub fn change_size(&self, head: &Head) -> Option<ChangeSize> {
+        self.log_output
+            .as_ref()
+            .ok()?
+            .sizes
+            .get(&head.commit_id)
+            .copied()
+    }
+
     //
     //  Selected head and the special head index
     //
@@ -261,6 +769,78 @@ impl<'a> LogPanel<'a> {
         head.clone_into(&mut self.head);
     }
 
+    /// Select a change, which may be outside of the loaded log. Pages
+    /// are loaded until it is found, and a change outside of the
+    /// revset is added to it with `| (<commit id>)`.
+    pub fn go_to_head(&mut self, head: Head) -> Result<()> {
+        if get_head_index(&head, &self.log_output).is_none() {
+            self.reveal_revset(head.commit_id.as_str())?;
+        }
+        while get_head_index(&head, &self.log_output).is_none()
+            && self
+                .log_output
+                .as_ref()
+                .is_ok_and(|log_output| log_output.truncated)
+        {
+            self.log_limit = self.next_page_limit();
+            self.refresh_log_output();
+        }
+        self.set_head(head);
+        Ok(())
+    }
+
+    /// Show all visible commits of a change, e.g. the copies of a
+    /// divergent change, by adding them to the revset
+    pub fn reveal_change(&mut self, change_id: &ChangeId) -> Result<()> {
+        self.reveal_revset(&format!("change_id({})", change_id.as_str()))
+    }
+
+    /// Tell if the graph shows "(elided revisions)" below the selected
+    /// change, i.e. it has ancestors in the revset that are connected
+    /// through changes outside of it
+    fn has_elided_parents(&self) -> bool {
+        let (Some(start), Ok(log_output)) = (self.selected_log_line(), self.log_output.as_ref())
+        else {
+            return false;
+        };
+        let texts = self.log_line_texts();
+        (start..texts.len())
+            .skip_while(|&line| log_output.head_at(line) == Some(&self.head))
+            .take_while(|&line| log_output.head_at(line).is_none())
+            .any(|line| texts[line].contains("(elided revisions)"))
+    }
+
+    /// LogTabEvent: Show the changes elided below the selected change,
+    /// by adding the range to its shown ancestors to the revset. Tells
+    /// if there were elided changes.
+    pub fn expand_elided(&mut self) -> Result<bool> {
+        if !self.has_elided_parents() {
+            return Ok(false);
+        }
+        let base = self.shown_revset_or_default()?;
+        let commit_id = &self.head.commit_id;
+        self.reveal_revset(&format!("(({base}) & ::{commit_id}-)::{commit_id}"))?;
+        Ok(true)
+    }
+
+    /// Shown revset, with the default log revset of jj if none is set
+    fn shown_revset_or_default(&self) -> Result<String> {
+        match self.shown_revset() {
+            Some(revset) => Ok(revset.clone()),
+            None => Ok(new_commander().get_default_log_revset()?),
+        }
+    }
+
+    /// Add changes to the shown revset until the revset is set again.
+    /// The pages loaded so far are kept, so the changes are inserted
+    /// in place.
+    fn reveal_revset(&mut self, revset: &str) -> Result<()> {
+        let base = self.shown_revset_or_default()?;
+        self.revealed_revset = Some(format!("({base}) | ({revset})"));
+        self.refresh_log_output();
+        Ok(())
+    }
+
     /// Move selection relative to the current position.
     /// The scroll is relative to head-index, not line-index.
     /// This will update self.head
@@ -284,6 +864,7 @@ impl<'a> LogPanel<'a> {
         if let Some(next_head) = next_head {
             self.set_head(next_head.clone());
         }
+        self.request_next_page();
         // TODO Notify about change of head
     }
 
@@ -300,20 +881,303 @@ impl<'a> LogPanel<'a> {
         }
     }
 
-    /// Check if a head is marked for batch operation
+    /// Check if a head is marked for batch operation, or is in the
+    /// visual selection
     pub fn is_head_marked(&self, head: &Head) -> bool {
         self.marked_heads.contains(&head.commit_id)
+            || self
+                .visual_heads()
+                .iter()
+                .any(|visual_head| visual_head.commit_id == head.commit_id)
     }
 
     /// LogTabEvent: Toggle mark on the current head
     pub fn toggle_head_mark(&mut self) {
-        let was_marked = self.is_head_marked(&self.head);
+        let was_marked = self.marked_heads.contains(&self.head.commit_id);
         self.set_head_mark(&self.head.clone(), !was_marked);
     }
 
-    /// Extract the list of all marked heads and clear it
+    /// Marked changes, including the visual selection, in the order of
+    /// the log, e.g. for the parents of a merge
+    pub fn marked_commit_ids(&self) -> Vec<CommitId> {
+        let mut commit_ids: Vec<CommitId> = self.marked_heads.iter().cloned().collect();
+        for head in self.visual_heads() {
+            if !self.marked_heads.contains(&head.commit_id) {
+                commit_ids.push(head.commit_id.clone());
+            }
+        }
+        if let Ok(log_output) = &self.log_output {
+            sort_by_log_order(&mut commit_ids, &log_output.heads);
+        }
+        commit_ids
+    }
+
+    /// Marked changes that are in the log, including the visual
+    /// selection, in the order of the log
+    pub fn marked_log_heads(&self) -> Vec<Head> {
+        let commit_ids = self.marked_commit_ids();
+        self.log_output.as_ref().map_or(vec![], |log_output| {
+            log_output
+                .heads
+                .iter()
+                .filter(|head| commit_ids.contains(&head.commit_id))
+                .cloned()
+                .collect()
+        })
+    }
+
+    /// Check if any marked change is immutable
+    pub fn has_immutable_marks(&self) -> bool {
+        let commit_ids = self.marked_commit_ids();
+        self.log_output.as_ref().is_ok_and(|log_output| {
+            log_output
+                .heads
+                .iter()
+                .any(|head| head.immutable && commit_ids.contains(&head.commit_id))
+        })
+    }
+
+    /// Number of marked changes, including the visual selection
+    pub fn mark_count(&self) -> usize {
+        self.marked_commit_ids().len()
+    }
+
+    /// LogTabEvent: Start a visual selection at the current head, or
+    /// end it
+    pub fn toggle_visual_mode(&mut self) {
+        self.visual_anchor = match self.visual_anchor {
+            Some(_) => None,
+            None => Some(self.head.clone()),
+        };
+    }
+
+    pub fn is_visual_mode(&self) -> bool {
+        self.visual_anchor.is_some()
+    }
+
+    /// End the visual selection, keeping its changes marked
+    pub fn mark_visual_heads(&mut self) {
+        self.marked_heads = self.marked_commit_ids().into_iter().collect();
+        self.visual_anchor = None;
+    }
+
+    /// Log entries from the visual anchor to the current head, empty
+    /// outside of visual mode or if the anchor is no longer in the log
+    fn visual_heads(&self) -> &[Head] {
+        let (Some(anchor), Ok(log_output)) =
+            (self.visual_anchor.as_ref(), self.log_output.as_ref())
+        else {
+            return &[];
+        };
+        match (
+            get_head_index(anchor, &self.log_output),
+            self.get_current_head_index(),
+        ) {
+            (Some(start), Some(end)) => &log_output.heads[start.min(end)..=start.max(end)],
+            _ => &[],
+        }
+    }
+
+    /// LogTabEvent: Compare other changes to the current head, or stop
+    /// comparing if it already is the base
+    pub fn toggle_compare_base(&mut self) {
+        if self.compare_base.as_ref() == Some(&self.head) {
+            self.compare_base = None;
+        } else {
+            self.compare_base = Some(self.head.clone());
+        }
+    }
+
+    /// Extract the list of all marked heads and clear it, ending the
+    /// visual selection
     pub fn extract_and_clear_head_marks(&mut self) -> Vec<CommitId> {
-        self.marked_heads.drain().collect()
+        self.mark_visual_heads();
+        let commit_ids = self.marked_commit_ids();
+        self.marked_heads.clear();
+        commit_ids
+    }
+
+    //
+    //  Search
+    //
+
+    /// LogTabEvent: Start typing a query to search the loaded log
+    pub fn start_search(&mut self) {
+        self.search = Some(LogSearch {
+            query: String::new(),
+            pattern: None,
+            editing: true,
+            origin: self.head.clone(),
+            not_found: false,
+        });
+    }
+
+    fn search_pattern(&self) -> Option<&SearchPattern> {
+        self.search
+            .as_ref()
+            .and_then(|search| search.pattern.as_ref())
+    }
+
+    /// Text of the log lines, without ANSI codes
+    fn log_line_texts(&self) -> Vec<String> {
+        self.log_output_text
+            .lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Select a change matching the search, wrapping around at the
+    /// end of the loaded log
+    fn jump_to_match(&mut self, jump: SearchJump) {
+        let (Some(search), Ok(log_output)) = (self.search.as_ref(), self.log_output.as_ref())
+        else {
+            return;
+        };
+        let Some(pattern) = search.pattern.as_ref() else {
+            // Empty query while typing, so go back to where we started
+            let origin = search.origin.clone();
+            self.set_head(origin);
+            return;
+        };
+        let matches = matching_heads(&self.log_line_texts(), log_output, pattern);
+        let current = match jump {
+            SearchJump::FromOrigin => get_head_index(&search.origin, &self.log_output),
+            SearchJump::Next | SearchJump::Previous => self.get_current_head_index(),
+        }
+        .unwrap_or(0);
+        let found = match jump {
+            SearchJump::FromOrigin => matches.iter().find(|&&index| index >= current),
+            SearchJump::Next => matches.iter().find(|&&index| index > current),
+            SearchJump::Previous => matches.iter().rev().find(|&&index| index < current),
+        };
+        let found = match jump {
+            SearchJump::Previous => found.or(matches.last()),
+            _ => found.or(matches.first()),
+        };
+        let head = found
+            .and_then(|&index| log_output.heads.get(index))
+            .cloned();
+
+        if let Some(search) = self.search.as_mut() {
+            search.not_found = head.is_none();
+        }
+        if let Some(head) = head {
+            self.set_head(head);
+            self.request_next_page();
+        }
+    }
+
+    /// Show the changes of the whole repo with the query in their
+    /// description, instead of searching the loaded log
+    fn search_repo_descriptions(&mut self) {
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+        search.editing = false;
+        let query = search.query.replace('\\', "\\\\").replace('"', "\\\"");
+        self.set_log_revset(Some(format!("description(substring-i:\"{query}\")")));
+        if let Some(head) = self.log_heads().into_iter().next()
+            && let Some(search) = self.search.as_mut()
+        {
+            search.origin = head;
+        }
+        self.jump_to_match(SearchJump::FromOrigin);
+    }
+
+    /// Handle a key while searching the log. While the query is typed,
+    /// all keys belong to the search, else only the keys to jump
+    /// between matches and to clear the search.
+    pub fn search_input(&mut self, key: KeyEvent) -> LogSearchInput {
+        let Some(search) = self.search.as_mut() else {
+            return LogSearchInput::NotHandled;
+        };
+        if !search.editing {
+            match key.code {
+                KeyCode::Char('n') => self.jump_to_match(SearchJump::Next),
+                KeyCode::Char('N') => self.jump_to_match(SearchJump::Previous),
+                KeyCode::Esc => self.search = None,
+                _ => return LogSearchInput::NotHandled,
+            }
+            return LogSearchInput::Handled;
+        }
+        match key.code {
+            KeyCode::Esc => {
+                let origin = search.origin.clone();
+                self.search = None;
+                self.set_head(origin);
+                return LogSearchInput::Handled;
+            }
+            KeyCode::Enter => {
+                if search.query.is_empty() {
+                    self.search = None;
+                } else {
+                    search.editing = false;
+                }
+                return LogSearchInput::Handled;
+            }
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if search.query.is_empty() {
+                    return LogSearchInput::Handled;
+                }
+                self.search_repo_descriptions();
+                return LogSearchInput::Reloaded;
+            }
+            KeyCode::Backspace => {
+                search.query.pop();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                search.query.push(c);
+            }
+            _ => return LogSearchInput::Handled,
+        }
+        search.pattern = SearchPattern::new(&search.query);
+        search.not_found = false;
+        self.jump_to_match(SearchJump::FromOrigin);
+        LogSearchInput::Handled
+    }
+
+    /// Line describing the search, shown at the bottom of the panel
+    fn search_line(&self) -> Option<Line<'static>> {
+        let search = self.search.as_ref()?;
+        let mut spans = vec![Span::raw(format!(" /{}", search.query))];
+        if search.editing {
+            spans.push(Span::raw("_"));
+            spans.push(Span::raw("  Ctrl+r: whole repo").fg(Color::DarkGray));
+        }
+        if search.not_found {
+            spans.push(Span::raw(" [no match]").fg(Color::Red));
+        }
+        spans.push(Span::raw(" "));
+        Some(Line::from(spans))
+    }
+
+    /// Row with the short change ids of the pinned changes, the
+    /// selected one highlighted, and the columns of each starting at x
+    fn pins_line(&self, x: u16) -> (Line<'static>, Vec<(Range<u16>, Head)>) {
+        const PREFIX: &str = " pinned:";
+        let mut spans = vec![Span::raw(PREFIX).fg(Color::DarkGray)];
+        let mut columns = vec![];
+        let mut start = x + PREFIX.len() as u16;
+        for (head, _) in &self.pinned_commits {
+            let change_id = head.change_id.short();
+            let mut span = Span::raw(change_id.to_owned()).fg(Color::Magenta);
+            if *head == self.head {
+                span = span.reversed();
+            }
+            spans.push(Span::raw(" "));
+            spans.push(span);
+            start += 1;
+            let end = start + change_id.len() as u16;
+            columns.push((start..end, head.clone()));
+            start = end;
+        }
+        (Line::from(spans), columns)
     }
 
     //
@@ -343,6 +1207,9 @@ impl<'a> LogPanel<'a> {
             LogTabEvent::ToggleHeadMark => {
                 self.toggle_head_mark();
             }
+            LogTabEvent::ToggleVisualMode => {
+                self.toggle_visual_mode();
+            }
             _ => {
                 return Ok(ComponentInputResult::NotHandled);
             }
@@ -363,21 +1230,96 @@ impl Component for LogPanel<'_> {
 
     fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
         self.panel_rect = area;
+        self.update_related_heads();
 
-        let title = match &self.log_revset {
-            Some(log_revset) => &format!(" Log for: {log_revset} "),
-            None => " Log ",
+        let mut title = match self.shown_revset() {
+            Some(log_revset) => format!(" Log for: {log_revset} "),
+            None => " Log ".to_owned(),
         };
+        if !self.log_paths.is_empty() {
+            title.push_str(&format!("touching: {} ", self.log_paths.join(" ")));
+        }
+        if self.follow_working_copy {
+            title.push_str("[following @] ");
+        }
+        if let Some(squash_source) = &self.squash_source {
+            title.push_str(&format!(
+                "[squash {} into: pick destination] ",
+                squash_source.change_id.short()
+            ));
+        }
+        if let Some(rebase_source) = &self.rebase_source {
+            title.push_str(&format!(
+                "[rebase {}: pick destination] ",
+                rebase_source.change_id.short()
+            ));
+        }
+        if let Some(duplicate_sources) = &self.duplicate_sources {
+            title.push_str(&match duplicate_sources.len() {
+                1 => "[duplicate 1 change: pick destination] ".to_owned(),
+                count => format!("[duplicate {count} changes: pick destination] "),
+            });
+        }
+        if let Some(restore_destination) = &self.restore_destination {
+            title.push_str(&format!(
+                "[restore into {}: pick source] ",
+                restore_destination.change_id.short()
+            ));
+        }
+        if self.is_visual_mode() {
+            title.push_str(&format!(
+                "[VISUAL: {} selected] ",
+                self.visual_heads().len()
+            ));
+        }
 
         let log_lines = self.log_lines();
         let log_length: usize = log_lines.len();
-        let log_block = Block::bordered()
+        let mut log_block = Block::bordered()
             .title(title)
             .border_type(BorderType::Rounded);
-        self.log_rect = log_block.inner(area);
+        if let Some(search_line) = self.search_line() {
+            log_block = log_block.title_bottom(search_line);
+        }
+        if self.head.divergent {
+            log_block = log_block.title_bottom(
+                Line::from(" divergent change ")
+                    .fg(Color::Red)
+                    .bold()
+                    .right_aligned(),
+            );
+        }
+        let mut inner = log_block.inner(area);
+        f.render_widget(log_block, area);
+        if let Some(git_drift) = &self.git_drift {
+            let [drift_area, log_area] =
+                Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(inner);
+            f.render_widget(git_drift, drift_area);
+            inner = log_area;
+        }
+        if let Some(working_copy_stat) = self.working_copy_stat {
+            let [summary_area, log_area] =
+                Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(inner);
+            f.render_widget(working_copy_line(working_copy_stat), summary_area);
+            inner = log_area;
+        }
+        self.pins_area = None;
+        if !self.pinned_commits.is_empty() {
+            let [pins_area, log_area] =
+                Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(inner);
+            let (pins_line, pin_columns) = self.pins_line(pins_area.x);
+            f.render_widget(pins_line, pins_area);
+            self.pins_area = Some(pins_area);
+            self.pin_columns = pin_columns;
+            inner = log_area;
+        }
+        self.log_rect = inner;
         self.log_list_state.select(self.selected_log_line());
-        let log = List::new(log_lines).block(log_block).scroll_padding(7);
-        f.render_stateful_widget(log, area, &mut self.log_list_state);
+        let log = List::new(log_lines).scroll_padding(7);
+        f.render_stateful_widget(log, self.log_rect, &mut self.log_list_state);
+        if self.page_load == PageLoad::Requested {
+            self.page_load = PageLoad::Drawn;
+        }
 
         // Show scrollbar if lines don't fit the screen height
         if log_length > self.log_rect.height.into() {
@@ -389,10 +1331,11 @@ impl Component for LogPanel<'_> {
 
             f.render_stateful_widget(
                 scrollbar,
-                area.inner(Margin {
-                    vertical: 1,
-                    horizontal: 0,
-                }),
+                Rect {
+                    y: self.log_rect.y,
+                    height: self.log_rect.height,
+                    ..area
+                },
                 &mut scrollbar_state,
             );
         }
@@ -419,6 +1362,21 @@ impl Component for LogPanel<'_> {
                     return Ok(ComponentInputResult::Handled);
                 }
                 MouseEventKind::Up(_) => {
+                    // Go to a pinned change
+                    if self
+                        .pins_area
+                        .is_some_and(|pins_area| pins_area.contains(mouse_pos))
+                    {
+                        if let Some((_, head)) = self
+                            .pin_columns
+                            .iter()
+                            .find(|(range, _)| range.contains(&mouse_event.column))
+                        {
+                            self.go_to_head(head.clone())?;
+                        }
+                        return Ok(ComponentInputResult::Handled);
+                    }
+
                     // Check all items in list
 
                     // TODO make a function that constructs the log list
@@ -437,6 +1395,7 @@ impl Component for LogPanel<'_> {
                     ) && let Some(head) = self.head_at_log_line(inx)
                     {
                         self.set_head(head);
+                        self.request_next_page();
                         return Ok(ComponentInputResult::Handled);
                     }
                 }
@@ -448,6 +1407,55 @@ impl Component for LogPanel<'_> {
     }
 }
 
+/// Changed files and lines of the working copy, if shown above the log.
+/// Errors are logged, the row is left out then.
+fn load_working_copy_stat() -> Option<DiffStat> {
+    if !get_env().jj_config.log_working_copy_summary() {
+        return None;
+    }
+    new_commander()
+        .get_diff_stat("@")
+        .inspect_err(|err| error!("Error getting working copy diff stat: {err}"))
+        .ok()
+}
+
+/// Visible commits of the pinned changes. Errors are logged, the pins
+/// are left out then.
+fn load_pinned_commits(pins: &Pins) -> Vec<(Head, String)> {
+    new_commander()
+        .get_changes_commits(pins.change_ids())
+        .inspect_err(|err| error!("Error getting pinned changes: {err}"))
+        .unwrap_or_default()
+}
+
+/// Row above the log telling if the working copy has changes
+fn working_copy_line(stat: DiffStat) -> Line<'static> {
+    if stat.is_empty() {
+        return Line::from(" @ no changes in the working copy").fg(Color::DarkGray);
+    }
+    let files = match stat.files {
+        1 => "1 file changed".to_owned(),
+        files => format!("{files} files changed"),
+    };
+    Line::from(vec![
+        Span::raw(format!(" @ {files} ")),
+        Span::raw(format!("+{}", stat.insertions)).fg(Color::Green),
+        Span::raw(" "),
+        Span::raw(format!("-{}", stat.deletions)).fg(Color::Red),
+    ])
+}
+
+/// Sort commits like the log shows them, commits that are not in the
+/// log go last
+fn sort_by_log_order(commit_ids: &mut [CommitId], heads: &[Head]) {
+    commit_ids.sort_by_key(|commit_id| {
+        heads
+            .iter()
+            .position(|head| &head.commit_id == commit_id)
+            .unwrap_or(usize::MAX)
+    });
+}
+
 // Determine which list item a mouse event is related to
 fn list_item_from_mouse_event(
     list: &[ListItem],
@@ -469,3 +1477,75 @@ fn list_item_from_mouse_event(
     }
     Some(item_index)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::commander::ids::ChangeId;
+
+    fn head(id: &str) -> Head {
+        Head {
+            change_id: ChangeId(id.to_owned()),
+            commit_id: CommitId(id.to_owned()),
+            divergent: false,
+            immutable: false,
+            conflict: false,
+        }
+    }
+
+    #[test]
+    fn sort_marks_by_log_order() {
+        let heads = vec![head("aaa"), head("bbb"), head("ccc")];
+        let mut commit_ids = ["zzz", "ccc", "aaa"].map(|id| CommitId(id.to_owned()));
+        sort_by_log_order(&mut commit_ids, &heads);
+        assert_eq!(
+            commit_ids,
+            ["aaa", "ccc", "zzz"].map(|id| CommitId(id.to_owned()))
+        );
+    }
+
+    #[test]
+    fn find_matching_heads() {
+        let heads = vec![head("aaa"), head("bbb"), head("ccc")];
+        let log_output = LogOutput {
+            graph: String::new(),
+            graph_heads: vec![
+                Some(heads[0].clone()),
+                Some(heads[0].clone()),
+                Some(heads[1].clone()),
+                Some(heads[1].clone()),
+                None,
+                Some(heads[2].clone()),
+                Some(heads[2].clone()),
+            ],
+            heads,
+            truncated: false,
+            refs: HashMap::new(),
+            tracking: HashMap::new(),
+            sizes: HashMap::new(),
+        };
+        let lines: Vec<String> = [
+            "@  aaa alice@example.com main",
+            "│  Fix the parser",
+            "○  bbb bob@example.com",
+            "│  Add a parser test",
+            "~",
+            "○  ccc alice@example.com",
+            "   Parser fixes",
+        ]
+        .iter()
+        .map(|line| line.to_string())
+        .collect();
+
+        let pattern = SearchPattern::new("parser").unwrap();
+        assert_eq!(matching_heads(&lines, &log_output, &pattern), [0, 1, 2]);
+        let pattern = SearchPattern::new("Parser").unwrap();
+        assert_eq!(matching_heads(&lines, &log_output, &pattern), [2]);
+        let pattern = SearchPattern::new("alice").unwrap();
+        assert_eq!(matching_heads(&lines, &log_output, &pattern), [0, 2]);
+        let pattern = SearchPattern::new("main").unwrap();
+        assert_eq!(matching_heads(&lines, &log_output, &pattern), [0]);
+    }
+}
@@ -2,6 +2,8 @@ mod details_panel;
 mod log_panel;
 
 pub use details_panel::DetailsPanel;
+pub use details_panel::DetailsPanelEvent;
 pub use details_panel::LargeStringContent;
 pub use details_panel::TextContent;
 pub use log_panel::LogPanel;
+pub use log_panel::LogSearchInput;
@@ -0,0 +1,194 @@
+/*! The operation toast shows the operation of the last command that
+changed the repository for a few seconds, in the bottom right corner.
+
+ The UI looks like this
+ ~~~
+  ╭──────────────────────────────────────────────────╮
+  │ Operation 3ba7e7fd2c5a: new empty commit  U: undo │
+  ╰──────────────────────────────────────────────────╯
+~~~
+`U` undoes that operation with `jj op revert`, even if other operations
+followed it. Without the toast, `U` undoes the last operation, and again
+the one before it, and `.` redoes them. The undo toast tells which
+operation was undone or redone. The reload toast tells which operation of
+another process made the app reload.
+
+The fetch toast shows the remote bookmarks moved by a background fetch
+the same way, above the operation toast.
+*/
+
+use std::time::Duration;
+use std::time::Instant;
+
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Block;
+use ratatui::widgets::BorderType;
+use ratatui::widgets::Clear;
+use ratatui::widgets::Paragraph;
+
+use crate::commander::jj::Operation;
+
+/// How long the toast is shown
+const TOAST_DURATION: Duration = Duration::from_secs(8);
+
+/// A transient toast with the operation of the last command
+pub struct OperationToast {
+    pub operation: Operation,
+    shown_at: Instant,
+}
+
+impl OperationToast {
+    pub fn new(operation: Operation) -> Self {
+        Self {
+            operation,
+            shown_at: Instant::now(),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.shown_at.elapsed() >= TOAST_DURATION
+    }
+
+    /// Render the toast in the bottom right corner of the frame
+    pub fn draw(&self, frame: &mut Frame) {
+        let line = Line::from(vec![
+            Span::from(format!("Operation {}", self.operation.short_id())).bold(),
+            Span::from(format!(": {}  ", self.operation.description)),
+            Span::from("U: undo").fg(Color::DarkGray),
+        ]);
+        draw_toast(frame, line, 0);
+    }
+}
+
+/// A transient toast with the operation that was undone or redone
+pub struct UndoToast {
+    /// e.g. `Undid`
+    verb: &'static str,
+    operation: Operation,
+    shown_at: Instant,
+}
+
+impl UndoToast {
+    pub fn undone(operation: Operation) -> Self {
+        Self::new("Undid", operation)
+    }
+
+    pub fn redone(operation: Operation) -> Self {
+        Self::new("Redid", operation)
+    }
+
+    fn new(verb: &'static str, operation: Operation) -> Self {
+        Self {
+            verb,
+            operation,
+            shown_at: Instant::now(),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.shown_at.elapsed() >= TOAST_DURATION
+    }
+
+    /// Render the toast in the bottom right corner of the frame
+    pub fn draw(&self, frame: &mut Frame) {
+        let line = Line::from(vec![
+            Span::from(format!("{} {}", self.verb, self.operation.short_id())).bold(),
+            Span::from(format!(": {}  ", self.operation.description)),
+            Span::from("U: undo  .: redo").fg(Color::DarkGray),
+        ]);
+        draw_toast(frame, line, 0);
+    }
+}
+
+/// A transient toast with the operation of another process the tabs
+/// were reloaded after
+pub struct ReloadToast {
+    operation: Operation,
+    shown_at: Instant,
+}
+
+impl ReloadToast {
+    pub fn new(operation: Operation) -> Self {
+        Self {
+            operation,
+            shown_at: Instant::now(),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.shown_at.elapsed() >= TOAST_DURATION
+    }
+
+    /// Render the toast in the bottom right corner of the frame
+    pub fn draw(&self, frame: &mut Frame) {
+        let line = Line::from(vec![
+            Span::from(format!("Reloaded after {}", self.operation.short_id())).bold(),
+            Span::from(format!(": {}", self.operation.description)),
+        ]);
+        draw_toast(frame, line, 0);
+    }
+}
+
+/// A transient toast with the remote bookmarks moved by a background
+/// fetch
+pub struct FetchToast {
+    /// Names of the bookmarks, e.g. `main@origin`
+    bookmarks: Vec<String>,
+    shown_at: Instant,
+}
+
+impl FetchToast {
+    /// Create the toast from the updates reported by `jj git fetch`,
+    /// e.g. `bookmark: main@origin [updated] tracked`
+    pub fn new(updates: &[String]) -> Self {
+        let bookmarks = updates
+            .iter()
+            .filter_map(|update| update.split_whitespace().nth(1))
+            .map(str::to_owned)
+            .collect();
+        Self {
+            bookmarks,
+            shown_at: Instant::now(),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.shown_at.elapsed() >= TOAST_DURATION
+    }
+
+    /// Render the toast in the bottom right corner of the frame, above
+    /// the operation toast if there is one
+    pub fn draw(&self, frame: &mut Frame, above_operation: bool) {
+        let line = Line::from(vec![
+            Span::from("Fetched").bold(),
+            Span::from(format!(": {}  ", self.bookmarks.join(", "))),
+            Span::from("R: refresh").fg(Color::DarkGray),
+        ]);
+        draw_toast(frame, line, if above_operation { 3 } else { 0 });
+    }
+}
+
+/// Render a toast in the bottom right corner of the frame, `offset`
+/// rows above the bottom
+fn draw_toast(frame: &mut Frame, line: Line, offset: u16) {
+    let frame_area = frame.area();
+    let width = (line.width() as u16 + 4).min(frame_area.width);
+    let height = 3.min(frame_area.height);
+    let area = Rect {
+        x: frame_area.right() - width,
+        y: (frame_area.bottom() - height).saturating_sub(offset),
+        width,
+        height,
+    };
+    let block = Block::bordered()
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Green));
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(line).block(block), area);
+}
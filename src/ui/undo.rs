@@ -0,0 +1,103 @@
+/*! The undo history undoes operations one after another, and redoes
+them, with `jj op revert`.
+
+Undoing reverts the last operation, then the one before it, and so on.
+Redoing reverts the revert of the last undo. Any other operation in
+between starts over from the last operation, like in an editor. The
+operations made to preview a command are skipped, see
+[skip_simulations].
+*/
+
+use anyhow::Result;
+use anyhow::bail;
+
+use crate::commander::Commander;
+use crate::commander::jj::Operation;
+use crate::commander::jj::skip_simulations;
+
+/// Operations undone since the last operation that was not an undo or
+/// redo
+#[derive(Default)]
+pub struct UndoHistory {
+    /// Operation of the last undo or redo
+    last_operation: Option<String>,
+    /// Operation to undo next, if the last undo or redo is the last
+    /// operation
+    next_undo: Option<String>,
+    /// Reverts of the undone operations, with the undone operations,
+    /// the last undo last
+    redo: Vec<(String, Operation)>,
+}
+
+impl UndoHistory {
+    /// Undo the operation, or by default the last operation or the one
+    /// before the last undone one. Returns the undone operation.
+    pub fn undo(&mut self, commander: &Commander, operation: Option<&str>) -> Result<Operation> {
+        let current = skip_simulations(commander.get_operation_id()?);
+        if self.last_operation.as_ref() != Some(&current) {
+            self.next_undo = None;
+            self.redo.clear();
+        }
+        let target = match operation {
+            Some(operation) => operation.to_owned(),
+            None => self.next_undo.clone().unwrap_or(current),
+        };
+        let (undone, parent) = commander.get_operation_with_parent(&target)?;
+        let Some(parent) = parent else {
+            bail!("Nothing to undo");
+        };
+        commander.run_op_revert(&undone.id)?;
+        let revert = commander.get_operation_id()?;
+        self.redo.push((revert.clone(), undone.clone()));
+        self.last_operation = Some(revert);
+        self.next_undo = Some(skip_simulations(parent));
+        Ok(undone)
+    }
+
+    /// Redo the last undone operation, if nothing happened since.
+    /// Returns the redone operation.
+    pub fn redo(&mut self, commander: &Commander) -> Result<Operation> {
+        let current = skip_simulations(commander.get_operation_id()?);
+        if self.last_operation.as_ref() != Some(&current) {
+            self.redo.clear();
+        }
+        let Some((revert, redone)) = self.redo.pop() else {
+            bail!("Nothing to redo");
+        };
+        commander.run_op_revert(&revert)?;
+        self.last_operation = Some(commander.get_operation_id()?);
+        self.next_undo = Some(redone.id.clone());
+        Ok(redone)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commander::tests::TestRepo;
+
+    #[test]
+    fn undo_after_preview() -> Result<()> {
+        let test_repo = TestRepo::new()?;
+        let commander = &test_repo.commander;
+
+        let head = commander.get_current_head()?;
+        commander.run_new([head.commit_id.as_str()])?;
+        let new_operation = commander.get_operation_id()?;
+        let new_head = commander.get_current_head()?;
+        // A preview of abandoning the new change, which is not confirmed
+        commander.simulate_rewrites(|commander| {
+            commander.run_abandon(std::slice::from_ref(&new_head.commit_id), false, false)
+        })?;
+
+        let mut undo_history = UndoHistory::default();
+        let undone = undo_history.undo(commander, None)?;
+        assert_eq!(undone.id, new_operation);
+        assert_eq!(commander.get_current_head()?.change_id, head.change_id);
+        let redone = undo_history.redo(commander)?;
+        assert_eq!(redone.id, new_operation);
+        assert_eq!(commander.get_current_head()?.change_id, new_head.change_id);
+
+        Ok(())
+    }
+}
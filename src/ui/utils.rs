@@ -1,5 +1,57 @@
+mod binary_info;
+mod change_sizes;
+mod clipboard;
+mod conflicts;
+mod diff_files;
+mod file_tree;
+mod folds;
+mod forge;
+mod fuzzy;
+mod graph_lanes;
 mod large_string;
+mod line_numbers;
+mod pins;
+mod ref_chips;
+mod repo_dir;
+mod revset_history;
+mod rewrites;
+mod search;
+mod side_by_side;
+mod sparse_patterns;
+mod syntax_highlight;
+mod timestamps;
+pub use binary_info::BinaryInfo;
+pub use change_sizes::append_change_sizes;
+pub use clipboard::copy_to_clipboard;
+pub use conflicts::ConflictChoice;
+pub use conflicts::ConflictChunk;
+pub use conflicts::conflict_regions;
+pub use conflicts::parse_conflicts;
+pub use conflicts::resolve_conflicts;
+pub use diff_files::DiffFile;
+pub use diff_files::diff_file_header;
+pub use diff_files::diff_file_path;
+pub use diff_files::insert_file_headers;
+pub use diff_files::is_binary_marker;
+pub use diff_files::section_end;
+pub use file_tree::FileTree;
+pub use file_tree::TreeRowKind;
+pub use folds::FileFolds;
+pub use folds::FoldedView;
+pub use forge::Forge;
+pub use forge::find_forge;
+pub use forge::open_in_browser;
+pub use fuzzy::fuzzy_match;
+pub use graph_lanes::color_graph_lanes;
 pub use large_string::LargeString;
+pub use line_numbers::DiffLineNumber;
+pub use line_numbers::color_words_text;
+pub use line_numbers::diff_line_numbers;
+pub use line_numbers::is_hunk_header;
+pub use line_numbers::line_number_gutter;
+pub use line_numbers::line_number_width;
+pub use line_numbers::new_file_line;
+pub use pins::Pins;
 use ratatui::crossterm::event::MouseButton;
 use ratatui::crossterm::event::MouseEvent;
 use ratatui::crossterm::event::MouseEventKind;
@@ -7,8 +59,25 @@ use ratatui::layout::Constraint;
 use ratatui::layout::Direction;
 use ratatui::layout::Layout;
 use ratatui::layout::Rect;
+pub use ref_chips::ref_chips;
+pub use repo_dir::format_size;
+pub use repo_dir::repo_dir;
+pub use repo_dir::repo_size;
+pub use revset_history::RevsetHistory;
+pub use rewrites::rewrites_preview;
+pub use search::SearchPattern;
+use side_by_side::side_by_side;
+pub use sparse_patterns::sparse_removed_files;
+pub use sparse_patterns::validate_sparse_pattern;
+use syntax_highlight::highlight_diff;
+pub use syntax_highlight::highlight_file;
+pub use timestamps::age_color;
+pub use timestamps::format_ago;
+pub use timestamps::relative_timestamps;
 
+use crate::env::DiffFormat;
 use crate::env::JJLayout;
+use crate::env::get_env;
 
 /// Tracks the split position between two panes and handles drag-to-resize mouse events.
 pub struct PaneDivider {
@@ -234,3 +303,56 @@ pub fn tabs_to_spaces(line: &str) -> String {
     }
     out
 }
+
+/// removes ansi control sequences from a string
+///
+/// this is used where the visible text is needed, e.g.
+/// when searching colored jj output.
+pub fn strip_ansi(line: &str) -> String {
+    enum AnsiState {
+        Neutral,
+        Escape,
+        Csi,
+    }
+
+    let mut out = String::with_capacity(line.len());
+    let mut ansi_state = AnsiState::Neutral;
+    for c in line.chars() {
+        match ansi_state {
+            AnsiState::Neutral => {
+                if c == '\x1b' {
+                    ansi_state = AnsiState::Escape;
+                } else {
+                    out.push(c);
+                }
+            }
+            AnsiState::Escape => {
+                ansi_state = if c == '[' {
+                    AnsiState::Csi
+                } else {
+                    AnsiState::Neutral
+                };
+            }
+            AnsiState::Csi => {
+                if ('\x40'..='\x7f').contains(&c) {
+                    ansi_state = AnsiState::Neutral;
+                }
+            }
+        }
+    }
+    out
+}
+
+/// prepares diff output from jj for the details panel
+///
+/// tabs are converted to spaces, git diffs get syntax highlighting
+/// if enabled, and formats that jj can't produce by itself are
+/// rendered to fit into width columns.
+pub fn format_diff_output(output: &str, diff_format: &DiffFormat, width: usize) -> String {
+    let output = tabs_to_spaces(output);
+    match diff_format {
+        DiffFormat::Git if get_env().jj_config.syntax_highlighting() => highlight_diff(&output),
+        DiffFormat::SideBySide => side_by_side(&output, width),
+        _ => output,
+    }
+}
@@ -0,0 +1,171 @@
+/*! Metadata of a changed binary file.
+
+There is no diff to show for a binary file, but the lines around the
+"Binary files ... differ" marker of the git format still tell how the
+file changed: whether it was added or removed, renamed, its mode and,
+if the diff carries a binary patch, its size. The header of the color
+words format only tells whether it was added or removed.
+*/
+
+use std::fmt;
+
+/// How a binary file changed, as far as the diff tells
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BinaryInfo {
+    pub added: bool,
+    pub removed: bool,
+    /// Path the file was renamed or copied from, with "renamed" or "copied"
+    pub source: Option<(&'static str, String)>,
+    /// Similarity of a renamed or copied file, e.g. "90%"
+    pub similarity: Option<String>,
+    pub old_mode: Option<String>,
+    pub new_mode: Option<String>,
+    pub old_size: Option<usize>,
+    pub new_size: Option<usize>,
+}
+
+impl BinaryInfo {
+    /// Read the metadata from the lines (without ANSI codes) of the
+    /// section of a binary file, starting with its header
+    pub fn parse<S: AsRef<str>>(lines: impl IntoIterator<Item = S>) -> Self {
+        let mut info = BinaryInfo::default();
+        // Sizes of the forward and then the reverse literal of a patch
+        let mut literals = vec![];
+        for line in lines {
+            let line = line.as_ref().trim_end();
+            if let Some(mode) = line.strip_prefix("new file mode ") {
+                info.added = true;
+                info.new_mode = Some(mode.to_owned());
+            } else if let Some(mode) = line.strip_prefix("deleted file mode ") {
+                info.removed = true;
+                info.old_mode = Some(mode.to_owned());
+            } else if let Some(mode) = line.strip_prefix("old mode ") {
+                info.old_mode = Some(mode.to_owned());
+            } else if let Some(mode) = line.strip_prefix("new mode ") {
+                info.new_mode = Some(mode.to_owned());
+            } else if let Some(path) = line.strip_prefix("rename from ") {
+                info.source = Some(("renamed", path.to_owned()));
+            } else if let Some(path) = line.strip_prefix("copy from ") {
+                info.source = Some(("copied", path.to_owned()));
+            } else if let Some(similarity) = line.strip_prefix("similarity index ") {
+                info.similarity = Some(similarity.to_owned());
+            } else if let Some(size) = line.strip_prefix("literal ") {
+                literals.extend(size.parse::<usize>().ok());
+            } else if line.starts_with("Added ") && line.ends_with(':') {
+                info.added = true;
+            } else if line.starts_with("Removed ") && line.ends_with(':') {
+                info.removed = true;
+            }
+        }
+        // The patch of an added file has no reverse literal and
+        // the patch of a removed file has an empty forward literal
+        match (info.added, info.removed, literals.as_slice()) {
+            (true, _, [new, ..]) => info.new_size = Some(*new),
+            (_, true, [_, old, ..]) => info.old_size = Some(*old),
+            (_, _, [new, old, ..]) => {
+                info.new_size = Some(*new);
+                info.old_size = Some(*old);
+            }
+            _ => {}
+        }
+        info
+    }
+}
+
+/// Size in bytes, with a binary unit when large
+fn format_size(size: usize) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if size < 1024 {
+        return format!("{size} B");
+    }
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+/// Show a change from `old` to `new`, or just the value that is known
+fn format_change(old: Option<String>, new: Option<String>) -> Option<String> {
+    match (old, new) {
+        (Some(old), Some(new)) if old != new => Some(format!("{old} → {new}")),
+        (old, new) => new.or(old),
+    }
+}
+
+impl fmt::Display for BinaryInfo {
+    /// Summary like "binary file added, mode 100644, 2.0 KiB"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some((how, path)) = &self.source {
+            write!(f, "binary file {how} from {path}")?;
+            if let Some(similarity) = &self.similarity {
+                write!(f, " ({similarity})")?;
+            }
+        } else if self.added {
+            write!(f, "binary file added")?;
+        } else if self.removed {
+            write!(f, "binary file removed")?;
+        } else {
+            write!(f, "binary file changed")?;
+        }
+        if let Some(mode) = format_change(self.old_mode.clone(), self.new_mode.clone()) {
+            write!(f, ", mode {mode}")?;
+        }
+        let size = format_change(
+            self.old_size.map(format_size),
+            self.new_size.map(format_size),
+        );
+        if let Some(size) = size {
+            write!(f, ", {size}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarize_git_headers() {
+        let info = BinaryInfo::parse([
+            "diff --git a/logo.png b/assets/logo.png",
+            "old mode 100644",
+            "new mode 100755",
+            "similarity index 90%",
+            "rename from logo.png",
+            "rename to assets/logo.png",
+            "Binary files a/logo.png and b/assets/logo.png differ",
+        ]);
+        assert_eq!(
+            info.to_string(),
+            "binary file renamed from logo.png (90%), mode 100644 → 100755"
+        );
+
+        let info = BinaryInfo::parse([
+            "diff --git a/icon.ico b/icon.ico",
+            "new file mode 100644",
+            "index 0000000000..1234567890",
+            "GIT binary patch",
+            "literal 2048",
+        ]);
+        assert_eq!(info.to_string(), "binary file added, mode 100644, 2.0 KiB");
+
+        let info = BinaryInfo::parse([
+            "diff --git a/data.bin b/data.bin",
+            "GIT binary patch",
+            "literal 3000000",
+            "",
+            "literal 512",
+        ]);
+        assert_eq!(info.to_string(), "binary file changed, 512 B → 2.9 MiB");
+    }
+
+    #[test]
+    fn summarize_color_words_header() {
+        let info = BinaryInfo::parse(["Removed regular file logo.png:", "    (binary)"]);
+        assert_eq!(info.to_string(), "binary file removed");
+    }
+}
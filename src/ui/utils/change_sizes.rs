@@ -0,0 +1,90 @@
+/*! Size column of the log.
+
+With `blazingjj.log-change-size`, the first line of each change in the
+log ends with the lines inserted and deleted by the change, like
+`+120 -4`. The sizes are padded to line up in a column after the
+longest of these lines, so large changes stand out while scanning the
+log.
+*/
+
+use ratatui::style::Color;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+
+use crate::commander::log::ChangeSize;
+
+/// Append the sizes to the lines they belong to, `sizes[index]` being
+/// the size for `lines[index]` if any
+pub fn append_change_sizes<'a>(
+    mut lines: Vec<Line<'a>>,
+    sizes: &[Option<ChangeSize>],
+) -> Vec<Line<'a>> {
+    let sized = || {
+        lines
+            .iter()
+            .zip(sizes)
+            .filter_map(|(line, size)| Some((line, (*size)?)))
+    };
+    let column = sized().map(|(line, _)| line.width()).max().unwrap_or(0) + 1;
+    let insertions_width = sized()
+        .map(|(_, size)| size.insertions.to_string().len())
+        .max()
+        .unwrap_or(0)
+        + 1;
+    for (line, size) in lines.iter_mut().zip(sizes) {
+        let Some(size) = size else {
+            continue;
+        };
+        let padding = column - line.width();
+        line.spans.push(Span::raw(" ".repeat(padding)));
+        line.spans.push(
+            Span::raw(format!(
+                "{:>insertions_width$}",
+                format!("+{}", size.insertions)
+            ))
+            .fg(Color::Green),
+        );
+        line.spans.push(Span::raw(" "));
+        line.spans
+            .push(Span::raw(format!("-{}", size.deletions)).fg(Color::Red));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn align_sizes() {
+        let lines = vec![
+            Line::from("@  qpvuntsm alice"),
+            Line::from("│  first"),
+            Line::from("○  zzzzzzzz root()"),
+        ];
+        let sizes = [
+            Some(ChangeSize {
+                insertions: 120,
+                deletions: 4,
+            }),
+            None,
+            Some(ChangeSize {
+                insertions: 3,
+                deletions: 0,
+            }),
+        ];
+        let lines: Vec<String> = append_change_sizes(lines, &sizes)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "@  qpvuntsm alice  +120 -4",
+                "│  first",
+                "○  zzzzzzzz root()   +3 -0",
+            ]
+        );
+    }
+}
@@ -0,0 +1,63 @@
+/*! Copy text to the system clipboard.
+
+The text is always sent to the terminal as an OSC 52 escape sequence,
+which also works over ssh, but not all terminals support it. So the
+text is also given to the first native clipboard program that works.
+*/
+
+use std::env;
+use std::io::Write;
+use std::process::Command;
+use std::process::Stdio;
+
+use ratatui::crossterm::clipboard::CopyToClipboard;
+use ratatui::crossterm::execute;
+use tracing::trace;
+
+/// Native clipboard programs and their arguments, in order of preference
+fn native_programs() -> Vec<(&'static str, &'static [&'static str])> {
+    if cfg!(target_os = "macos") {
+        vec![("pbcopy", &[])]
+    } else if cfg!(windows) {
+        vec![("clip.exe", &[])]
+    } else {
+        let mut programs: Vec<(&'static str, &'static [&'static str])> = vec![];
+        if env::var_os("WAYLAND_DISPLAY").is_some() {
+            programs.push(("wl-copy", &[]));
+        }
+        if env::var_os("DISPLAY").is_some() {
+            programs.push(("xclip", &["-selection", "clipboard"]));
+            programs.push(("xsel", &["--clipboard", "--input"]));
+        }
+        programs
+    }
+}
+
+/// Give the text to a clipboard program. Returns true if it succeeded.
+fn copy_with_program(program: &str, args: &[&str], text: &str) -> bool {
+    let child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    let Ok(mut child) = child else {
+        return false;
+    };
+    let written = child
+        .stdin
+        .take()
+        .is_some_and(|mut stdin| stdin.write_all(text.as_bytes()).is_ok());
+    child.wait().is_ok_and(|status| status.success()) && written
+}
+
+/// Copy text to the clipboard, using both OSC 52 and a native program
+pub fn copy_to_clipboard(text: &str) {
+    let _ = execute!(std::io::stdout(), CopyToClipboard::to_clipboard_from(text));
+    for (program, args) in native_programs() {
+        if copy_with_program(program, args, text) {
+            trace!("Copied {} bytes to clipboard with {program}", text.len());
+            return;
+        }
+    }
+}
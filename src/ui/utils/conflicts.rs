@@ -0,0 +1,295 @@
+/*! Find the conflicts shown in a diff, and parse the conflicts of a
+file.
+
+A commit with conflicts has the conflicts materialized in its files,
+as a region from a `<<<<<<<` marker line to a `>>>>>>>` marker line,
+so they show up as added or unchanged lines of its diff. The markers
+are found after the `+` of a git diff line or the line numbers of a
+color words line. Removed markers, e.g. of a resolved conflict, are
+not part of a conflict.
+
+With `ui.conflict-marker-style=snapshot`, the sides of a conflict are
+listed in full, which [parse_conflicts] splits into the left side, the
+base and the right side, for resolving it one region at a time.
+~~~
+<<<<<<< Conflict 1 of 1
++++++++ Contents of side #1
+left
+------- Contents of base
+base
++++++++ Contents of side #2
+right
+>>>>>>> Conflict 1 of 1 ends
+~~~
+*/
+
+use std::ops::Range;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::ui::utils::DiffLineNumber;
+use crate::ui::utils::color_words_text;
+
+/// Marker starting and ending a conflict, with the label after it
+static START_MARKER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^<{7,}(?: |$)").unwrap());
+static END_MARKER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^>{7,}(?: |$)").unwrap());
+
+/// A part of a file with conflicts, with its lines and their line
+/// endings
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConflictChunk {
+    Resolved(String),
+    Conflict {
+        left: String,
+        base: String,
+        right: String,
+    },
+}
+
+/// How a conflict is resolved
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictChoice {
+    Left,
+    Right,
+    /// The left side, then the right side
+    Both,
+}
+
+/// Text of a line of the new file, without the diff prefix. None for
+/// removed lines.
+fn new_file_text(line: &str, number: DiffLineNumber) -> Option<&str> {
+    match number {
+        DiffLineNumber {
+            old: None,
+            new: None,
+        } => Some(line),
+        DiffLineNumber { new: None, .. } => None,
+        _ => color_words_text(line).or_else(|| line.get(1..)),
+    }
+}
+
+/// Find the line ranges of the conflicts in the lines (without ANSI
+/// codes) of a diff, with their line numbers. A conflict without an
+/// end marker ends before the next conflict or at the end.
+pub fn conflict_regions<S: AsRef<str>>(
+    lines: impl IntoIterator<Item = S>,
+    numbers: &[DiffLineNumber],
+) -> Vec<Range<usize>> {
+    let mut regions = vec![];
+    let mut start = None;
+    let mut count = 0;
+    for (index, line) in lines.into_iter().enumerate() {
+        count = index + 1;
+        let number = numbers.get(index).copied().unwrap_or_default();
+        let Some(text) = new_file_text(line.as_ref(), number) else {
+            continue;
+        };
+        if START_MARKER.is_match(text) {
+            if let Some(start) = start.replace(index) {
+                regions.push(start..index);
+            }
+        } else if END_MARKER.is_match(text)
+            && let Some(start) = start.take()
+        {
+            regions.push(start..index + 1);
+        }
+    }
+    if let Some(start) = start {
+        regions.push(start..count);
+    }
+    regions
+}
+
+/// Parse a file with snapshot style conflicts. None if a conflict is
+/// not simple, i.e. has another number of sides than two.
+pub fn parse_conflicts(text: &str) -> Option<Vec<ConflictChunk>> {
+    let mut chunks = vec![];
+    let mut resolved = String::new();
+    // Length of the markers of the conflict being parsed, which jj makes
+    // longer than any marker-like line of the sides, and its sections,
+    // true for the base
+    let mut conflict: Option<(usize, Vec<(bool, String)>)> = None;
+    for line in text.split_inclusive('\n') {
+        let marker = line.trim_end_matches(['\n', '\r']);
+        match conflict.as_mut() {
+            None if START_MARKER.is_match(marker) => {
+                if !resolved.is_empty() {
+                    chunks.push(ConflictChunk::Resolved(std::mem::take(&mut resolved)));
+                }
+                let len = marker.len() - marker.trim_start_matches('<').len();
+                conflict = Some((len, vec![]));
+            }
+            None => resolved.push_str(line),
+            Some((len, sections)) if is_marker(marker, '+', *len) => {
+                sections.push((false, String::new()));
+            }
+            Some((len, sections)) if is_marker(marker, '-', *len) => {
+                sections.push((true, String::new()));
+            }
+            Some((len, _)) if is_marker(marker, '>', *len) => {
+                let (_, sections) = conflict.take()?;
+                let [(false, left), (true, base), (false, right)] =
+                    <[_; 3]>::try_from(sections).ok()?
+                else {
+                    return None;
+                };
+                chunks.push(ConflictChunk::Conflict { left, base, right });
+            }
+            Some((_, sections)) => sections.last_mut()?.1.push_str(line),
+        }
+    }
+    if conflict.is_some() {
+        return None;
+    }
+    if !resolved.is_empty() {
+        chunks.push(ConflictChunk::Resolved(resolved));
+    }
+    Some(chunks)
+}
+
+/// Whether a line is a conflict marker of `len` times `c`, with a label
+/// or not
+fn is_marker(line: &str, c: char, len: usize) -> bool {
+    let rest = line.trim_start_matches(c);
+    line.len() - rest.len() == len && (rest.is_empty() || rest.starts_with(' '))
+}
+
+/// The file with each conflict resolved by its choice. None if a
+/// conflict has no choice.
+pub fn resolve_conflicts(
+    chunks: &[ConflictChunk],
+    choices: &[Option<ConflictChoice>],
+) -> Option<String> {
+    let mut choices = choices.iter();
+    let mut text = String::new();
+    for chunk in chunks {
+        match chunk {
+            ConflictChunk::Resolved(lines) => text.push_str(lines),
+            ConflictChunk::Conflict { left, right, .. } => match (*choices.next()?)? {
+                ConflictChoice::Left => text.push_str(left),
+                ConflictChoice::Right => text.push_str(right),
+                ConflictChoice::Both => {
+                    text.push_str(left);
+                    text.push_str(right);
+                }
+            },
+        }
+    }
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::utils::diff_line_numbers;
+
+    #[test]
+    fn find_git_conflicts() {
+        let diff = [
+            "diff --git a/file b/file",
+            "@@ -1,2 +1,8 @@",
+            " a",
+            "+<<<<<<< Conflict 1 of 1",
+            "+%%%%%%% Changes from base to side #1",
+            "+-b",
+            "+ c",
+            "++++++++ Contents of side #2",
+            "+d",
+            "+>>>>>>> Conflict 1 of 1 ends",
+            "-<<<<<<< resolved",
+        ];
+        assert_eq!(
+            conflict_regions(diff, &diff_line_numbers(diff)),
+            vec![3..10]
+        );
+    }
+
+    #[test]
+    fn find_color_words_conflicts() {
+        let diff = [
+            "Created conflict in file:",
+            "        1: <<<<<<< Conflict 1 of 2",
+            "        2: a",
+            "        3: >>>>>>> Conflict 1 of 2 ends",
+            "   4     : <<<<<<< removed",
+            "   5    4: <<<<<<<",
+            "   6    5: b",
+        ];
+        assert_eq!(
+            conflict_regions(diff, &diff_line_numbers(diff)),
+            [1..4, 5..7]
+        );
+    }
+
+    const SNAPSHOT: &str = "a\n\
+        <<<<<<< Conflict 1 of 1\n\
+        +++++++ Contents of side #1\n\
+        b1\n\
+        ------- Contents of base\n\
+        b\n\
+        +++++++ Contents of side #2\n\
+        b2\n\
+        b3\n\
+        >>>>>>> Conflict 1 of 1 ends\n\
+        c";
+
+    #[test]
+    fn parse_snapshot_conflicts() {
+        let chunks = parse_conflicts(SNAPSHOT).expect("the conflict is simple");
+        assert_eq!(
+            chunks,
+            [
+                ConflictChunk::Resolved("a\n".to_owned()),
+                ConflictChunk::Conflict {
+                    left: "b1\n".to_owned(),
+                    base: "b\n".to_owned(),
+                    right: "b2\nb3\n".to_owned(),
+                },
+                ConflictChunk::Resolved("c".to_owned()),
+            ]
+        );
+
+        assert_eq!(resolve_conflicts(&chunks, &[None]), None);
+        assert_eq!(
+            resolve_conflicts(&chunks, &[Some(ConflictChoice::Left)]).as_deref(),
+            Some("a\nb1\nc")
+        );
+        assert_eq!(
+            resolve_conflicts(&chunks, &[Some(ConflictChoice::Both)]).as_deref(),
+            Some("a\nb1\nb2\nb3\nc")
+        );
+    }
+
+    #[test]
+    fn parse_complex_conflicts() {
+        // Three sides
+        let three_sided = SNAPSHOT.replace(
+            ">>>>>>>",
+            "------- Contents of base #2\nb\n+++++++ Contents of side #3\nb4\n>>>>>>>",
+        );
+        assert_eq!(parse_conflicts(&three_sided), None);
+        // Diff style
+        assert_eq!(
+            parse_conflicts("<<<<<<<\n%%%%%%%\n-b\n+b1\n+++++++\nb2\n>>>>>>>\n"),
+            None
+        );
+        // No end marker
+        assert_eq!(parse_conflicts("<<<<<<<\n+++++++\nb1\n"), None);
+        // Longer markers, with marker-like lines in the sides
+        assert_eq!(
+            parse_conflicts(
+                "<<<<<<<<\n++++++++\n+++++++\n--------\n-------\n++++++++\n>>>>>>>\n>>>>>>>>\n"
+            ),
+            Some(vec![ConflictChunk::Conflict {
+                left: "+++++++\n".to_owned(),
+                base: "-------\n".to_owned(),
+                right: ">>>>>>>\n".to_owned(),
+            }])
+        );
+        assert_eq!(
+            parse_conflicts("no conflicts\n"),
+            Some(vec![ConflictChunk::Resolved("no conflicts\n".to_owned())])
+        );
+    }
+}
@@ -0,0 +1,177 @@
+/*! Find the file sections of a diff.
+
+Both the git format and the color words format of jj start the diff
+of each file with a header line. Other formats, e.g. output from a
+diff tool, have no known headers and yield no file sections.
+
+A section of a binary file has a marker line instead of a diff. Files
+whose diff is left out because it is too long only have their header,
+inserted where jj shows the file.
+*/
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::commander::files::DiffType;
+use crate::env::DiffFormat;
+use crate::ui::utils::strip_ansi;
+
+/// Start of the diff of one file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffFile {
+    /// Line number (or scroll position) of the file header
+    pub line: usize,
+    /// Path of the file, as shown in the header
+    pub path: String,
+}
+
+/// Header of a file in the color words format, e.g.
+/// "Modified regular file src/main.rs:"
+static COLOR_WORDS_HEADER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(?:Added|Removed|Modified|Copied|Renamed) (?:regular file|executable file|symlink|conflict|git submodule|file|tree)(?: at)? (.+):$",
+    )
+    .unwrap()
+});
+
+/// Return the file path if the line (without ANSI codes) is a file header
+pub fn diff_file_path(line: &str) -> Option<String> {
+    if let Some(paths) = line.strip_prefix("diff --git a/") {
+        // Use the new path of "a/old b/new"
+        let path = paths.rsplit(" b/").next().unwrap_or(paths);
+        return Some(path.to_owned());
+    }
+    COLOR_WORDS_HEADER
+        .captures(line)
+        .map(|captures| captures[1].to_owned())
+}
+
+/// Header of a file in the diff format, for a file whose diff is left
+/// out. The git format has no file status in the first header line.
+pub fn diff_file_header(path: &str, diff_type: Option<&DiffType>, format: &DiffFormat) -> String {
+    if *format == DiffFormat::ColorWords {
+        let status = match diff_type {
+            Some(DiffType::Added) => "Added",
+            Some(DiffType::Deleted) => "Removed",
+            _ => "Modified",
+        };
+        format!("{status} regular file {path}:")
+    } else {
+        format!("diff --git a/{path} b/{path}")
+    }
+}
+
+/// Insert the headers of files left out of a diff where jj shows the
+/// files, given the paths of all files of the diff in its order, e.g.
+/// from `jj diff --stat`. The headers are `(path, header)` in the same
+/// order. Headers of files after the last section end the diff.
+pub fn insert_file_headers(diff: &str, order: &[String], headers: &[(String, String)]) -> String {
+    let position = |path: &str| order.iter().position(|file| file == path);
+    let mut pending = headers
+        .iter()
+        .map(|(path, header)| (position(path), header))
+        .peekable();
+    let mut output = String::with_capacity(diff.len());
+    for line in diff.split_inclusive('\n') {
+        let section = diff_file_path(strip_ansi(line).trim_end_matches(['\r', '\n']))
+            .and_then(|path| position(&path));
+        if let Some(section) = section {
+            while let Some((_, header)) =
+                pending.next_if(|(file, _)| file.is_some_and(|file| file < section))
+            {
+                output.push_str(header);
+                output.push('\n');
+            }
+        }
+        output.push_str(line);
+    }
+    for (_, header) in pending {
+        output.push('\n');
+        output.push_str(header);
+    }
+    output
+}
+
+/// First line after the section of file `index`, given the total
+/// number of lines
+pub fn section_end(files: &[DiffFile], index: usize, line_count: usize) -> usize {
+    files
+        .get(index + 1)
+        .map_or(line_count, |next_file| next_file.line)
+}
+
+/// Return true if the line (without ANSI codes) tells that a binary
+/// file changed, e.g. "Binary files a/logo.png and b/logo.png differ"
+pub fn is_binary_marker(line: &str) -> bool {
+    let line = line.trim();
+    (line.starts_with("Binary files ") && line.ends_with(" differ"))
+        || line == "GIT binary patch"
+        || line == "(binary)"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_headers() {
+        assert_eq!(
+            diff_file_path("diff --git a/src/old.rs b/src/new.rs"),
+            Some("src/new.rs".to_owned())
+        );
+        assert_eq!(
+            diff_file_path("Modified regular file README:"),
+            Some("README".to_owned())
+        );
+        assert_eq!(
+            diff_file_path("Added executable file bin/run.sh:"),
+            Some("bin/run.sh".to_owned())
+        );
+        assert_eq!(diff_file_path("   1     : AAA"), None);
+        assert_eq!(diff_file_path("+++ b/README"), None);
+    }
+
+    #[test]
+    fn file_headers() {
+        for format in [DiffFormat::ColorWords, DiffFormat::Git] {
+            let header = diff_file_header("src/a.rs", Some(&DiffType::Added), &format);
+            assert_eq!(diff_file_path(&header), Some("src/a.rs".to_owned()));
+        }
+    }
+
+    #[test]
+    fn insert_headers() {
+        let order = ["a", "b", "c", "d"].map(str::to_owned);
+        let header = |path: &str| (path.to_owned(), format!("diff --git a/{path} b/{path}"));
+        let diff = "description\n\
+                    diff --git a/b b/b\n\
+                    +B\n\
+                    diff --git a/d b/d\n\
+                    +D";
+        assert_eq!(
+            insert_file_headers(diff, &order, &[header("a"), header("c")]),
+            "description\n\
+             diff --git a/a b/a\n\
+             diff --git a/b b/b\n\
+             +B\n\
+             diff --git a/c b/c\n\
+             diff --git a/d b/d\n\
+             +D"
+        );
+        assert_eq!(
+            insert_file_headers("diff --git a/b b/b\n+B", &order, &[header("d")]),
+            "diff --git a/b b/b\n+B\ndiff --git a/d b/d"
+        );
+    }
+
+    #[test]
+    fn binary_markers() {
+        assert!(is_binary_marker(
+            "Binary files a/logo.png and b/logo.png differ"
+        ));
+        assert!(is_binary_marker("    (binary)"));
+        assert!(!is_binary_marker("   1    1: (binary)"));
+        assert!(!is_binary_marker("+Binary files differ in size"));
+    }
+}
@@ -0,0 +1,409 @@
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::commander::files::Conflict;
+use crate::commander::files::File;
+
+/// A row of the file tree, a directory or a file, as shown in the files
+/// tab
+#[derive(Clone, Debug, PartialEq)]
+pub struct TreeRow {
+    /// Number of directories above the row
+    pub depth: usize,
+    /// Last component of the path
+    pub name: String,
+    /// Path of the directory, or of the file in the revision
+    pub path: String,
+    pub kind: TreeRowKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TreeRowKind {
+    Directory {
+        expanded: bool,
+        /// Changed or conflicted files in the directory and its
+        /// subdirectories
+        changes: usize,
+    },
+    File {
+        file: File,
+        conflict: bool,
+    },
+}
+
+/// Files of a revision as a tree of directories, which can be expanded
+/// and collapsed. Directories with changes are expanded at first.
+pub struct FileTree {
+    /// Files sorted by path, with their path in the revision and whether
+    /// they are conflicted
+    files: Vec<(String, File, bool)>,
+    expanded: HashSet<String>,
+    /// Rows of the expanded directories
+    rows: Vec<TreeRow>,
+    selected: usize,
+}
+
+/// Directory of the tree being built, with the indexes of its files
+#[derive(Default)]
+struct Node<'a> {
+    directories: BTreeMap<&'a str, Node<'a>>,
+    files: Vec<usize>,
+}
+
+impl FileTree {
+    /// Tree of the `paths` of a revision (`jj file list`), with the
+    /// `changed` files of its diff summary, which also has the deleted
+    /// ones
+    pub fn new(paths: Vec<String>, changed: &[File], conflicts: &[Conflict]) -> Self {
+        let mut changed_files: HashMap<String, &File> = HashMap::new();
+        for file in changed {
+            if let Some(path) = file.paths().pop() {
+                changed_files.insert(path, file);
+            }
+        }
+        let conflicts: HashSet<&str> = conflicts
+            .iter()
+            .map(|conflict| conflict.path.as_str())
+            .collect();
+
+        let mut paths = paths;
+        paths.extend(changed_files.keys().cloned());
+        paths.sort();
+        paths.dedup();
+
+        let files: Vec<(String, File, bool)> = paths
+            .into_iter()
+            .map(|path| {
+                let file = match changed_files.get(&path) {
+                    Some(file) => (*file).clone(),
+                    None => File {
+                        line: path.clone(),
+                        path: Some(path.clone()),
+                        diff_type: None,
+                    },
+                };
+                let conflict = conflicts.contains(path.as_str());
+                (path, file, conflict)
+            })
+            .collect();
+
+        let expanded = files
+            .iter()
+            .filter(|(_, file, conflict)| file.diff_type.is_some() || *conflict)
+            .flat_map(|(path, _, _)| parent_directories(path))
+            .collect();
+
+        let mut tree = Self {
+            files,
+            expanded,
+            rows: vec![],
+            selected: 0,
+        };
+        tree.build_rows();
+        tree
+    }
+
+    pub fn rows(&self) -> &[TreeRow] {
+        &self.rows
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected_row(&self) -> Option<&TreeRow> {
+        self.rows.get(self.selected)
+    }
+
+    /// The selected file, None for a directory
+    pub fn selected_file(&self) -> Option<&File> {
+        match &self.selected_row()?.kind {
+            TreeRowKind::File { file, .. } => Some(file),
+            TreeRowKind::Directory { .. } => None,
+        }
+    }
+
+    pub fn select(&mut self, index: usize) {
+        self.selected = index.min(self.rows.len().saturating_sub(1));
+    }
+
+    /// Select the row of a file, expanding its directories. Returns
+    /// false if the file is not in the tree.
+    pub fn select_file(&mut self, file: &File) -> bool {
+        let Some(path) = file.paths().pop() else {
+            return false;
+        };
+        if !self
+            .files
+            .iter()
+            .any(|(file_path, _, _)| *file_path == path)
+        {
+            return false;
+        }
+        self.expanded.extend(parent_directories(&path));
+        self.build_rows();
+        self.select_path(&path)
+    }
+
+    /// Expand or collapse the selected directory
+    pub fn toggle_selected(&mut self) {
+        if let Some(TreeRowKind::Directory { expanded, .. }) =
+            self.selected_row().map(|row| &row.kind)
+        {
+            self.set_selected_expanded(!expanded);
+        }
+    }
+
+    /// Expand or collapse the selected directory. Collapsing a file or a
+    /// collapsed directory selects its parent directory instead.
+    pub fn set_selected_expanded(&mut self, expand: bool) {
+        let Some(row) = self.selected_row() else {
+            return;
+        };
+        match &row.kind {
+            TreeRowKind::Directory { expanded, .. } if *expanded != expand => {
+                let path = row.path.clone();
+                if expand {
+                    self.expanded.insert(path);
+                } else {
+                    self.expanded.remove(&path);
+                }
+                self.build_rows();
+            }
+            _ if !expand => {
+                let depth = row.depth;
+                if let Some(parent) = self.rows[..self.selected]
+                    .iter()
+                    .rposition(|row| row.depth < depth)
+                {
+                    self.selected = parent;
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Keep the expanded directories and the selection of the tree of
+    /// the same revision before a refresh
+    pub fn keep_state(&mut self, previous: &FileTree) {
+        self.expanded.extend(previous.expanded.iter().cloned());
+        self.build_rows();
+        match previous.selected_row() {
+            Some(row) if self.select_path(&row.path) => (),
+            _ => self.select(previous.selected),
+        }
+    }
+
+    fn select_path(&mut self, path: &str) -> bool {
+        match self.rows.iter().position(|row| row.path == path) {
+            Some(index) => {
+                self.selected = index;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn build_rows(&mut self) {
+        let mut root = Node::default();
+        for (index, (path, _, _)) in self.files.iter().enumerate() {
+            let mut node = &mut root;
+            let mut components: Vec<&str> = path.split('/').collect();
+            components.pop();
+            for component in components {
+                node = node.directories.entry(component).or_default();
+            }
+            node.files.push(index);
+        }
+
+        let mut rows = vec![];
+        self.push_rows(&root, "", 0, &mut rows);
+        self.rows = rows;
+        self.select(self.selected);
+    }
+
+    /// Push the rows of a directory, its subdirectories first
+    fn push_rows(&self, node: &Node, prefix: &str, depth: usize, rows: &mut Vec<TreeRow>) {
+        for (name, directory) in &node.directories {
+            let path = format!("{prefix}{name}");
+            let expanded = self.expanded.contains(&path);
+            rows.push(TreeRow {
+                depth,
+                name: (*name).to_owned(),
+                path: path.clone(),
+                kind: TreeRowKind::Directory {
+                    expanded,
+                    changes: self.count_changes(directory),
+                },
+            });
+            if expanded {
+                self.push_rows(directory, &format!("{path}/"), depth + 1, rows);
+            }
+        }
+        for &index in &node.files {
+            let (path, file, conflict) = &self.files[index];
+            rows.push(TreeRow {
+                depth,
+                name: path.rsplit('/').next().unwrap_or(path).to_owned(),
+                path: path.clone(),
+                kind: TreeRowKind::File {
+                    file: file.clone(),
+                    conflict: *conflict,
+                },
+            });
+        }
+    }
+
+    fn count_changes(&self, node: &Node) -> usize {
+        let files = node
+            .files
+            .iter()
+            .filter(|&&index| {
+                let (_, file, conflict) = &self.files[index];
+                file.diff_type.is_some() || *conflict
+            })
+            .count();
+        files
+            + node
+                .directories
+                .values()
+                .map(|directory| self.count_changes(directory))
+                .sum::<usize>()
+    }
+}
+
+/// Directories containing a path, e.g. `src` and `src/ui` for
+/// `src/ui/mod.rs`
+fn parent_directories(path: &str) -> impl Iterator<Item = String> + '_ {
+    path.match_indices('/')
+        .map(|(index, _)| path[..index].to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commander::files::DiffType;
+
+    fn names(tree: &FileTree) -> Vec<String> {
+        tree.rows()
+            .iter()
+            .map(|row| {
+                let indent = "  ".repeat(row.depth);
+                match &row.kind {
+                    TreeRowKind::Directory {
+                        expanded, changes, ..
+                    } => {
+                        let arrow = if *expanded { "▾" } else { "▸" };
+                        format!("{indent}{arrow} {}/ ({changes})", row.name)
+                    }
+                    TreeRowKind::File { file, conflict } => {
+                        let marker = match (&file.diff_type, conflict) {
+                            (_, true) => "C",
+                            (diff_type, false) => diff_type.as_ref().map_or(" ", DiffType::marker),
+                        };
+                        format!("{indent}{marker} {}", row.name)
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn changed(line: &str, diff_type: DiffType) -> File {
+        File {
+            line: line.to_owned(),
+            path: Some(line[2..].to_owned()),
+            diff_type: Some(diff_type),
+        }
+    }
+
+    fn tree() -> FileTree {
+        FileTree::new(
+            [
+                "README.md",
+                "docs/guide.md",
+                "src/main.rs",
+                "src/ui/mod.rs",
+                "src/ui/tab.rs",
+            ]
+            .map(str::to_owned)
+            .to_vec(),
+            &[
+                changed("M src/main.rs", DiffType::Modified),
+                changed("R src/ui/{tabs.rs => tab.rs}", DiffType::Renamed),
+                changed("D src/old.rs", DiffType::Deleted),
+            ],
+            &[Conflict {
+                path: "README.md".to_owned(),
+            }],
+        )
+    }
+
+    #[test]
+    fn changed_directories_are_expanded() {
+        assert_eq!(
+            names(&tree()),
+            [
+                "▸ docs/ (0)",
+                "▾ src/ (3)",
+                "  ▾ ui/ (1)",
+                "      mod.rs",
+                "    R tab.rs",
+                "  M main.rs",
+                "  D old.rs",
+                "C README.md",
+            ]
+        );
+    }
+
+    #[test]
+    fn expand_and_collapse() {
+        let mut tree = tree();
+        tree.toggle_selected();
+        assert_eq!(
+            names(&tree)[..3],
+            ["▾ docs/ (0)", "    guide.md", "▾ src/ (3)"]
+        );
+
+        // Collapsing a file selects its directory, then collapses it
+        tree.select(1);
+        tree.set_selected_expanded(false);
+        assert_eq!(tree.selected(), 0);
+        tree.set_selected_expanded(false);
+        assert_eq!(names(&tree)[..2], ["▸ docs/ (0)", "▾ src/ (3)"]);
+
+        tree.select(1);
+        tree.toggle_selected();
+        assert_eq!(names(&tree), ["▸ docs/ (0)", "▸ src/ (3)", "C README.md"]);
+    }
+
+    #[test]
+    fn select_and_keep_state() {
+        let mut tree = tree();
+        let guide = File {
+            line: "docs/guide.md".to_owned(),
+            path: Some("docs/guide.md".to_owned()),
+            diff_type: None,
+        };
+        assert!(tree.select_file(&guide));
+        assert_eq!(tree.selected(), 1);
+        assert_eq!(tree.selected_file(), Some(&guide));
+        assert!(!tree.select_file(&changed("A missing.rs", DiffType::Added)));
+        let renamed = changed("R src/ui/{tabs.rs => tab.rs}", DiffType::Renamed);
+        assert!(tree.select_file(&renamed));
+        assert_eq!(tree.selected_file(), Some(&renamed));
+        assert!(tree.select_file(&guide));
+
+        let mut refreshed = FileTree::new(
+            vec!["docs/guide.md".to_owned(), "src/main.rs".to_owned()],
+            &[],
+            &[],
+        );
+        refreshed.keep_state(&tree);
+        assert_eq!(refreshed.selected_file(), Some(&guide));
+        assert_eq!(
+            names(&refreshed),
+            ["▾ docs/ (0)", "    guide.md", "▾ src/ (0)", "    main.rs"]
+        );
+    }
+}
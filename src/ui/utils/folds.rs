@@ -0,0 +1,241 @@
+/*! Folding of file sections in a diff.
+
+A folded file section only shows its header line. [FileFolds] holds
+which sections are folded, and [FoldedView] maps between the lines of
+the content and the lines that are visible with the folds applied.
+
+Binary files and very long diffs start out folded, so they are only
+rendered when the user unfolds them. A file whose diff is too long to
+even load before it is asked for is left out of the content: only its
+header is there, and it stays folded until loaded.
+*/
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::ops::Range;
+
+use crate::ui::utils::DiffFile;
+use crate::ui::utils::LargeString;
+use crate::ui::utils::section_end;
+
+/// The set of folded file sections, by index into the file list
+#[derive(Default, Debug, Clone)]
+pub struct FileFolds {
+    folded: HashSet<usize>,
+    /// Sections left out of the content, with the number of lines of
+    /// their diff
+    unloaded: HashMap<usize, usize>,
+    /// Unloaded section the user asked to unfold
+    load_request: Option<usize>,
+}
+
+impl FileFolds {
+    /// Fold the files that are better not shown until asked for: binary
+    /// files and files with more than `max_lines` lines of diff. A limit
+    /// of 0 leaves long files unfolded.
+    pub fn collapsed(large_string: &LargeString, max_lines: usize) -> Self {
+        let files = large_string.diff_files();
+        let mut folded: HashSet<usize> = large_string.binary_files().keys().copied().collect();
+        if max_lines > 0 {
+            folded.extend((0..files.len()).filter(|&index| {
+                section_end(files, index, large_string.lines()) - files[index].line - 1 > max_lines
+            }));
+        }
+        Self {
+            folded,
+            ..Self::default()
+        }
+    }
+
+    /// Fold the file if unfolded, or unfold it if folded. An unloaded
+    /// file stays folded, and is requested to be loaded instead.
+    pub fn toggle(&mut self, file_index: usize) {
+        if self.unloaded.contains_key(&file_index) {
+            self.load_request = Some(file_index);
+        } else if !self.folded.remove(&file_index) {
+            self.folded.insert(file_index);
+        }
+    }
+
+    /// Fold all files, unless all are already folded. Then unfold all
+    /// loaded files.
+    pub fn toggle_all(&mut self, file_count: usize) {
+        if (0..file_count).all(|index| self.folded.contains(&index)) {
+            self.folded
+                .retain(|index| self.unloaded.contains_key(index));
+        } else {
+            self.folded = (0..file_count).collect();
+        }
+    }
+
+    pub fn is_folded(&self, file_index: usize) -> bool {
+        self.folded.contains(&file_index)
+    }
+
+    /// Mark the file as left out of the content, with the number of
+    /// lines of its diff
+    pub fn set_unloaded(&mut self, file_index: usize, lines: usize) {
+        self.unloaded.insert(file_index, lines);
+        self.folded.insert(file_index);
+    }
+
+    /// Number of lines of the diff of a file left out of the content
+    pub fn unloaded_lines(&self, file_index: usize) -> Option<usize> {
+        self.unloaded.get(&file_index).copied()
+    }
+
+    /// Mark the file as loaded, and unfold it
+    pub fn set_loaded(&mut self, file_index: usize) {
+        self.unloaded.remove(&file_index);
+        self.folded.remove(&file_index);
+    }
+
+    /// The unloaded file the user asked to unfold, if any
+    pub fn take_load_request(&mut self) -> Option<usize> {
+        self.load_request.take()
+    }
+}
+
+/// The visible lines of content with folds applied
+pub struct FoldedView {
+    /// Ranges of content lines that are visible, in order
+    ranges: Vec<Range<usize>>,
+}
+
+impl FoldedView {
+    /// Build view of content with `line_count` lines and the given files
+    pub fn new(line_count: usize, files: &[DiffFile], folds: &FileFolds) -> Self {
+        let mut ranges = vec![];
+        let mut start = 0;
+        for (index, file) in files.iter().enumerate() {
+            if !folds.is_folded(index) {
+                continue;
+            }
+            let end = section_end(files, index, line_count);
+            // Keep the header line visible
+            ranges.push(start..file.line + 1);
+            start = end;
+        }
+        ranges.push(start..line_count);
+        ranges.retain(|range| !range.is_empty());
+        Self { ranges }
+    }
+
+    /// Number of visible lines
+    pub fn len(&self) -> usize {
+        self.ranges.iter().map(|range| range.len()).sum()
+    }
+
+    /// Content line of a visible line
+    pub fn to_content(&self, visible_line: usize) -> usize {
+        let mut offset = visible_line;
+        for range in self.ranges.iter() {
+            if offset < range.len() {
+                return range.start + offset;
+            }
+            offset -= range.len();
+        }
+        self.ranges.last().map_or(0, |range| range.end)
+    }
+
+    /// Visible line of a content line. A line inside a fold is
+    /// shown by the header line of the fold.
+    pub fn to_visible(&self, content_line: usize) -> usize {
+        let mut visible_line: usize = 0;
+        for range in self.ranges.iter() {
+            if content_line < range.start {
+                // Inside the fold ending the previous range
+                return visible_line.saturating_sub(1);
+            }
+            if content_line < range.end {
+                return visible_line + content_line - range.start;
+            }
+            visible_line += range.len();
+        }
+        visible_line.saturating_sub(1)
+    }
+
+    /// Content lines of all visible lines, in order
+    pub fn content_lines(&self) -> impl Iterator<Item = usize> + '_ {
+        self.ranges.iter().flat_map(|range| range.clone())
+    }
+
+    /// Split a range of visible lines into ranges of content lines
+    pub fn content_ranges(&self, visible: Range<usize>) -> Vec<Range<usize>> {
+        let mut result = vec![];
+        let mut offset = 0;
+        for range in self.ranges.iter() {
+            let start = visible.start.max(offset);
+            let end = visible.end.min(offset + range.len());
+            if start < end {
+                result.push(range.start + start - offset..range.start + end - offset);
+            }
+            offset += range.len();
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(line: usize) -> DiffFile {
+        DiffFile {
+            line,
+            path: String::new(),
+        }
+    }
+
+    #[test]
+    fn folded_view() {
+        // Lines 0-1 header, file 0 at 2-4, file 1 at 5-7
+        let files = [file(2), file(5)];
+        let mut folds = FileFolds::default();
+        folds.toggle(0);
+        let view = FoldedView::new(8, &files, &folds);
+        assert_eq!(view.len(), 6);
+        assert_eq!(view.to_content(3), 5);
+        assert_eq!(view.to_visible(4), 2);
+        assert_eq!(view.to_visible(6), 4);
+        assert_eq!(view.content_ranges(1..5), [1..3, 5..7]);
+
+        folds.toggle_all(files.len());
+        let view = FoldedView::new(8, &files, &folds);
+        assert_eq!(view.len(), 4);
+        folds.toggle_all(files.len());
+        assert!(!folds.is_folded(0) && !folds.is_folded(1));
+    }
+
+    #[test]
+    fn collapse_binary_and_long_files() {
+        let large_string = LargeString::new(
+            "Modified regular file a.txt:\n   1    1: a\n   2    2: b\n\
+             Added regular file logo.png:\n    (binary)\n\
+             Modified regular file b.txt:\n   1    1: a\n"
+                .to_string(),
+        );
+        let folds = FileFolds::collapsed(&large_string, 1);
+        assert!(folds.is_folded(0) && folds.is_folded(1) && !folds.is_folded(2));
+        let folds = FileFolds::collapsed(&large_string, 0);
+        assert!(!folds.is_folded(0) && folds.is_folded(1) && !folds.is_folded(2));
+    }
+
+    #[test]
+    fn unloaded_stays_folded() {
+        let mut folds = FileFolds::default();
+        folds.set_unloaded(1, 5000);
+        folds.toggle(1);
+        assert!(folds.is_folded(1));
+        assert_eq!(folds.take_load_request(), Some(1));
+        assert_eq!(folds.take_load_request(), None);
+
+        folds.toggle_all(2);
+        folds.toggle_all(2);
+        assert!(!folds.is_folded(0) && folds.is_folded(1));
+
+        folds.set_loaded(1);
+        assert!(!folds.is_folded(1));
+        assert_eq!(folds.unloaded_lines(1), None);
+    }
+}
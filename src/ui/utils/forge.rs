@@ -0,0 +1,289 @@
+/*! Web pages of a repository on its forge, to open them in the browser.
+
+The forge is recognized from the host of the remote URL: GitHub,
+GitLab, and Gitea or Forgejo, e.g. Codeberg. SSH URLs like
+`git@github.com:owner/repo.git` are turned into the https URL of the
+repository.
+
+Pull requests are created with the CLI of the forge, `gh`, `glab` or
+`tea`, which has to be installed and logged in.
+*/
+
+use std::process::Command;
+use std::process::Stdio;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+
+use crate::commander::new_commander;
+
+/// Forges, which differ in the paths of their pages
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ForgeKind {
+    GitHub,
+    GitLab,
+    Gitea,
+}
+
+/// A repository on a forge
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Forge {
+    kind: ForgeKind,
+    /// https URL of the repository, e.g. `https://github.com/owner/repo`
+    repository_url: String,
+}
+
+impl Forge {
+    /// Recognize the forge of a remote URL, None if it is unknown
+    pub fn from_remote_url(url: &str) -> Option<Self> {
+        let url = url.trim().trim_end_matches('/');
+        let url = url.strip_suffix(".git").unwrap_or(url);
+        let (host, path) = if let Some((_, rest)) = url.split_once("://") {
+            // e.g. https://host/owner/repo or ssh://git@host:22/owner/repo
+            let (authority, path) = rest.split_once('/')?;
+            let host = authority.rsplit('@').next()?;
+            (host.split(':').next()?, path)
+        } else {
+            // scp-like, e.g. git@host:owner/repo
+            let (authority, path) = url.split_once(':')?;
+            (authority.rsplit('@').next()?, path)
+        };
+        let kind = if host.contains("github") {
+            ForgeKind::GitHub
+        } else if host.contains("gitlab") {
+            ForgeKind::GitLab
+        } else if ["gitea", "forgejo", "codeberg"]
+            .iter()
+            .any(|name| host.contains(name))
+        {
+            ForgeKind::Gitea
+        } else {
+            return None;
+        };
+        Some(Self {
+            kind,
+            repository_url: format!("https://{host}/{}", path.trim_start_matches('/')),
+        })
+    }
+
+    pub fn commit_url(&self, commit_id: &str) -> String {
+        match self.kind {
+            ForgeKind::GitHub | ForgeKind::Gitea => {
+                format!("{}/commit/{commit_id}", self.repository_url)
+            }
+            ForgeKind::GitLab => format!("{}/-/commit/{commit_id}", self.repository_url),
+        }
+    }
+
+    pub fn branch_url(&self, branch: &str) -> String {
+        match self.kind {
+            ForgeKind::GitHub => format!("{}/tree/{branch}", self.repository_url),
+            ForgeKind::GitLab => format!("{}/-/tree/{branch}", self.repository_url),
+            ForgeKind::Gitea => format!("{}/src/branch/{branch}", self.repository_url),
+        }
+    }
+
+    /// Page of a file at a commit, scrolled to the line if any
+    pub fn file_url(&self, commit_id: &str, path: &str, line: Option<usize>) -> String {
+        let url = match self.kind {
+            ForgeKind::GitHub => format!("{}/blob/{commit_id}/{path}", self.repository_url),
+            ForgeKind::GitLab => format!("{}/-/blob/{commit_id}/{path}", self.repository_url),
+            ForgeKind::Gitea => format!("{}/src/commit/{commit_id}/{path}", self.repository_url),
+        };
+        match line {
+            Some(line) => format!("{url}#L{line}"),
+            None => url,
+        }
+    }
+
+    /// Command listing the check runs of a commit as tab separated name,
+    /// status and conclusion, None if the forge has no checks API that
+    /// is supported
+    pub fn checks_command(&self, commit_id: &str) -> Option<Command> {
+        if self.kind != ForgeKind::GitHub {
+            return None;
+        }
+        // e.g. github.com/owner/repo
+        let repository = self.repository_url.trim_start_matches("https://");
+        let (host, slug) = repository.split_once('/')?;
+        let mut command = Command::new("gh");
+        command.args(["api", "--hostname", host]);
+        command.arg(format!("repos/{slug}/commits/{commit_id}/check-runs"));
+        command.args([
+            "--jq",
+            r#".check_runs[] | [.name, .status, .conclusion // ""] | @tsv"#,
+        ]);
+        Some(command)
+    }
+
+    /// What the forge calls pull requests
+    pub fn pull_request_name(&self) -> &'static str {
+        match self.kind {
+            ForgeKind::GitHub | ForgeKind::Gitea => "pull request",
+            ForgeKind::GitLab => "merge request",
+        }
+    }
+
+    /// Command creating a pull request from a pushed branch into the
+    /// default branch, with the CLI of the forge
+    pub fn pull_request_command(&self, branch: &str, title: &str, body: &str) -> Command {
+        // e.g. github.com/owner/repo
+        let repository = self.repository_url.trim_start_matches("https://");
+        match self.kind {
+            ForgeKind::GitHub => {
+                let mut command = Command::new("gh");
+                command.args(["pr", "create", "--repo", repository, "--head", branch]);
+                command.args(["--title", title, "--body", body]);
+                command
+            }
+            ForgeKind::GitLab => {
+                let mut command = Command::new("glab");
+                command.args(["mr", "create", "--repo", repository]);
+                command.args(["--source-branch", branch, "--title", title]);
+                command.args(["--description", body, "--yes"]);
+                command
+            }
+            ForgeKind::Gitea => {
+                // tea takes the owner/repo slug, and the host from its login
+                let slug = repository
+                    .split_once('/')
+                    .map_or(repository, |(_, slug)| slug);
+                let mut command = Command::new("tea");
+                command.args(["pulls", "create", "--repo", slug, "--head", branch]);
+                command.args(["--title", title, "--description", body]);
+                command
+            }
+        }
+    }
+}
+
+/// Find the forge of a remote, or by default of `origin`, or else of
+/// the first remote. Returns the name of the remote with it.
+pub fn find_forge(remote: Option<&str>) -> Result<(String, Forge)> {
+    let remotes = new_commander().get_git_remote_urls()?;
+    let found = match remote {
+        Some(remote) => remotes.iter().find(|(name, _)| name == remote),
+        None => remotes
+            .iter()
+            .find(|(name, _)| name == "origin")
+            .or_else(|| remotes.iter().find(|(name, _)| name != "git")),
+    };
+    let Some((remote, url)) = found else {
+        match remote {
+            Some(remote) => bail!("There is no git remote {remote}"),
+            None => bail!("There are no remotes"),
+        }
+    };
+    let Some(forge) = Forge::from_remote_url(url) else {
+        bail!("Remote {remote} is not on GitHub, GitLab or Gitea: {url}");
+    };
+    Ok((remote.clone(), forge))
+}
+
+/// Open a URL with the program of the system that opens URLs
+pub fn open_in_browser(url: &str) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    command
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("Failed opening {url}"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remote_urls() {
+        let github = Forge::from_remote_url("git@github.com:owner/repo.git").unwrap();
+        assert_eq!(github.repository_url, "https://github.com/owner/repo");
+        assert_eq!(github.kind, ForgeKind::GitHub);
+        let gitlab = Forge::from_remote_url("ssh://git@gitlab.example.com:2222/group/sub/repo.git");
+        assert_eq!(
+            gitlab.unwrap().repository_url,
+            "https://gitlab.example.com/group/sub/repo"
+        );
+        let codeberg = Forge::from_remote_url("https://codeberg.org/owner/repo/").unwrap();
+        assert_eq!(codeberg.kind, ForgeKind::Gitea);
+        assert_eq!(codeberg.repository_url, "https://codeberg.org/owner/repo");
+        assert_eq!(Forge::from_remote_url("/home/alice/repo"), None);
+        assert_eq!(Forge::from_remote_url("https://example.com/repo"), None);
+    }
+
+    #[test]
+    fn page_urls() {
+        let github = Forge::from_remote_url("https://github.com/owner/repo").unwrap();
+        assert_eq!(
+            github.commit_url("3ba7e7fd"),
+            "https://github.com/owner/repo/commit/3ba7e7fd"
+        );
+        assert_eq!(
+            github.file_url("3ba7e7fd", "src/main.rs", Some(12)),
+            "https://github.com/owner/repo/blob/3ba7e7fd/src/main.rs#L12"
+        );
+        let gitlab = Forge::from_remote_url("https://gitlab.com/owner/repo").unwrap();
+        assert_eq!(
+            gitlab.branch_url("main"),
+            "https://gitlab.com/owner/repo/-/tree/main"
+        );
+        let gitea = Forge::from_remote_url("https://gitea.com/owner/repo").unwrap();
+        assert_eq!(
+            gitea.file_url("3ba7e7fd", "README.md", None),
+            "https://gitea.com/owner/repo/src/commit/3ba7e7fd/README.md"
+        );
+    }
+
+    #[test]
+    fn pull_request_commands() {
+        let args = |command: &Command| -> Vec<String> {
+            let args = command.get_args();
+            args.map(|arg| arg.to_string_lossy().to_string()).collect()
+        };
+        let github = Forge::from_remote_url("git@github.com:owner/repo.git").unwrap();
+        let command = github.pull_request_command("feature", "Add feature", "Body");
+        assert_eq!(command.get_program(), "gh");
+        assert_eq!(
+            args(&command),
+            [
+                "pr",
+                "create",
+                "--repo",
+                "github.com/owner/repo",
+                "--head",
+                "feature",
+                "--title",
+                "Add feature",
+                "--body",
+                "Body"
+            ]
+        );
+        let gitlab = Forge::from_remote_url("https://gitlab.com/group/repo").unwrap();
+        assert_eq!(gitlab.pull_request_name(), "merge request");
+        let command = gitlab.pull_request_command("feature", "Add feature", "");
+        assert_eq!(command.get_program(), "glab");
+        assert!(args(&command).ends_with(&[
+            "--description".to_owned(),
+            String::new(),
+            "--yes".to_owned()
+        ]));
+        let codeberg = Forge::from_remote_url("https://codeberg.org/owner/repo").unwrap();
+        let command = codeberg.pull_request_command("feature", "Add feature", "");
+        assert_eq!(
+            args(&command)[..4],
+            ["pulls", "create", "--repo", "owner/repo"]
+        );
+    }
+}
@@ -0,0 +1,46 @@
+/*! Fuzzy matching of a typed query against the entries of a list.
+
+The query matches an entry when its characters appear in the entry in
+the same order, ignoring case. Entries where the matched characters are
+close together, or start the entry, score better.
+*/
+
+/// Score how well `query` matches `text`, lower is better, or None if
+/// it doesn't match
+pub fn fuzzy_match(query: &str, text: &str) -> Option<usize> {
+    let mut text_chars = text.chars().flat_map(char::to_lowercase).enumerate();
+    let mut score = 0;
+    let mut last_index = None;
+    for query_char in query.chars().flat_map(char::to_lowercase) {
+        let (index, _) = text_chars.find(|(_, text_char)| *text_char == query_char)?;
+        // Gaps between the matched characters cost their length
+        score += match last_index {
+            Some(last_index) => index - last_index - 1,
+            None => index,
+        };
+        last_index = Some(index);
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_subsequence() {
+        assert_eq!(fuzzy_match("", "main"), Some(0));
+        assert_eq!(fuzzy_match("main", "main"), Some(0));
+        assert_eq!(fuzzy_match("MN", "main"), Some(2));
+        assert_eq!(fuzzy_match("ft", "feature"), Some(2));
+        assert_eq!(fuzzy_match("nm", "main"), None);
+        assert_eq!(fuzzy_match("mains", "main"), None);
+    }
+
+    #[test]
+    fn prefer_close_matches() {
+        let close = fuzzy_match("fix", "fix-login").unwrap();
+        let spread = fuzzy_match("fix", "feature-index").unwrap();
+        assert!(close < spread);
+    }
+}
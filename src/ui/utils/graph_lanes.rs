@@ -0,0 +1,91 @@
+/*! Colors for the lanes of the log graph.
+
+jj draws the whole graph in one color, which makes it hard to follow
+an edge over long distances. With `blazingjj.graph-lane-colors`, each
+lane of the graph gets its own color. jj uses two columns per lane, so
+the lane of a character is its column divided by two. Nodes keep the
+colors of jj, e.g. the green `@` of the working copy.
+*/
+
+use ratatui::style::Color;
+use ratatui::text::Line;
+use ratatui::text::Span;
+
+/// Characters of the edges, in all graph styles of jj
+const EDGE_CHARS: &str = "│─├┤┬┴┼╭╮╰╯┌┐└┘|/\\-'.~";
+/// Characters of the nodes, in all graph styles and the builtin node
+/// templates of jj
+const NODE_CHARS: &str = "@○◆×◉●◌*ox";
+/// Colors of the lanes, starting with the leftmost
+const LANE_COLORS: [Color; 6] = [
+    Color::Magenta,
+    Color::Cyan,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Red,
+];
+
+/// Color the edges at the start of a log line by their lane. The graph
+/// ends at the first character that is not part of it.
+pub fn color_graph_lanes(line: Line<'_>) -> Line<'_> {
+    let mut spans = Vec::with_capacity(line.spans.len());
+    let mut column = 0;
+    let mut in_graph = true;
+    for span in line.spans {
+        if !in_graph {
+            spans.push(span);
+            continue;
+        }
+        for (index, char) in span.content.char_indices() {
+            if EDGE_CHARS.contains(char) {
+                let color = LANE_COLORS[column / 2 % LANE_COLORS.len()];
+                spans.push(Span::styled(char.to_string(), span.style.fg(color)));
+            } else if char == ' ' || NODE_CHARS.contains(char) {
+                spans.push(Span::styled(char.to_string(), span.style));
+            } else {
+                in_graph = false;
+                spans.push(Span::styled(span.content[index..].to_owned(), span.style));
+                break;
+            }
+            column += 1;
+        }
+    }
+    Line { spans, ..line }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Style;
+    use ratatui::style::Stylize;
+
+    use super::*;
+
+    #[test]
+    fn color_lanes() {
+        let line = Line::from(vec![
+            Span::raw("│ "),
+            Span::raw("@").green(),
+            Span::raw("  qpvuntsm │ not a lane"),
+        ]);
+        let spans = color_graph_lanes(line).spans;
+        assert_eq!(spans[0].content, "│");
+        assert_eq!(spans[0].style.fg, Some(Color::Magenta));
+        assert_eq!(spans[2].style, Style::new().green());
+        assert_eq!(spans[5].content, "qpvuntsm │ not a lane");
+        assert_eq!(spans[5].style.fg, None);
+
+        let spans = color_graph_lanes(Line::raw("│ ├─╯")).spans;
+        let colors: Vec<_> = spans.iter().map(|span| span.style.fg).collect();
+        assert_eq!(
+            colors,
+            [
+                Some(Color::Magenta),
+                None,
+                Some(Color::Cyan),
+                Some(Color::Cyan),
+                Some(Color::Green)
+            ]
+        );
+    }
+}
@@ -4,10 +4,30 @@ output to a Text but this require more space. Instead, the LargeString
 findes all line breaks, and provide methods for converting only the
 visible lines into a Text. */
 
+use std::cell::Ref;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::OnceLock;
+
 use ansi_to_tui::IntoText;
 use ratatui::text::Text;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::Wrap;
 use tracing::error;
 
+use crate::ui::utils::BinaryInfo;
+use crate::ui::utils::DiffFile;
+use crate::ui::utils::DiffLineNumber;
+use crate::ui::utils::SearchPattern;
+use crate::ui::utils::conflict_regions;
+use crate::ui::utils::diff_file_path;
+use crate::ui::utils::diff_line_numbers;
+use crate::ui::utils::is_binary_marker;
+use crate::ui::utils::is_hunk_header;
+use crate::ui::utils::section_end;
+use crate::ui::utils::strip_ansi;
+
 /// Store a large ANSI colour coded string in a way that allows you
 /// to quickly extract a small range and convert it into Text
 pub struct LargeString {
@@ -15,6 +35,19 @@ pub struct LargeString {
     content: String,
     /// First byte of each line in content
     line_start: Vec<usize>,
+    /// File sections of content, found on first use
+    diff_files: OnceLock<Vec<DiffFile>>,
+    /// Line numbers of content, found on first use
+    line_numbers: OnceLock<Vec<DiffLineNumber>>,
+    /// Lines with a hunk header, found on first use
+    hunks: OnceLock<Vec<usize>>,
+    /// Line ranges of conflicts, found on first use
+    conflicts: OnceLock<Vec<Range<usize>>>,
+    /// File sections of binary files and what changed, found on first use
+    binary_files: OnceLock<HashMap<usize, BinaryInfo>>,
+    /// Number of rows each line takes when wrapped, and the width
+    /// used for wrapping. Found on first use of each width.
+    row_counts: RefCell<(u16, Vec<u16>)>,
 }
 
 impl LargeString {
@@ -46,6 +79,12 @@ impl LargeString {
         Self {
             content,
             line_start,
+            diff_files: OnceLock::new(),
+            line_numbers: OnceLock::new(),
+            hunks: OnceLock::new(),
+            conflicts: OnceLock::new(),
+            binary_files: OnceLock::new(),
+            row_counts: RefCell::new((0, vec![])),
         }
     }
 
@@ -69,4 +108,224 @@ impl LargeString {
             }
         }
     }
+
+    /// The content with a range of lines replaced by `text`, e.g. the
+    /// diff of a file that was left out
+    pub fn replace_lines(&self, lines: Range<usize>, text: &str) -> LargeString {
+        let end_of_content = self.content.len();
+        let get_line_start = |line| self.line_start.get(line).copied().unwrap_or(end_of_content);
+        let start = get_line_start(lines.start);
+        let end = get_line_start(lines.end);
+        let mut content = String::with_capacity(start + text.len() + 1 + end_of_content - end);
+        content.push_str(&self.content[..start]);
+        content.push_str(text);
+        if end < end_of_content && !text.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str(&self.content[end..]);
+        LargeString::new(content)
+    }
+
+    /// Text of a range of lines, without colour codes
+    pub fn plain_text(&self, lines: Range<usize>) -> String {
+        let end_of_content = self.content.len();
+        let get_line_start = |line| self.line_start.get(line).copied().unwrap_or(end_of_content);
+        strip_ansi(&self.content[get_line_start(lines.start)..get_line_start(lines.end)])
+    }
+
+    /// Find the first line matching the pattern, scanning from `start_line`
+    /// and wrapping around the end of the content. Set `forward` to false to
+    /// scan backwards. Only the visible text is matched, not colour codes.
+    pub fn find(&self, pattern: &SearchPattern, start_line: usize, forward: bool) -> Option<usize> {
+        let lines = self.lines();
+        if lines == 0 {
+            return None;
+        }
+        let start_line = start_line % lines;
+        (0..lines)
+            .map(|step| {
+                if forward {
+                    (start_line + step) % lines
+                } else {
+                    (start_line + lines - step) % lines
+                }
+            })
+            .find(|&line| pattern.is_match(&strip_ansi(self.line(line))))
+    }
+
+    /// The file sections of the content, if it is a diff.
+    /// The content is only scanned the first time.
+    pub fn diff_files(&self) -> &[DiffFile] {
+        self.diff_files.get_or_init(|| {
+            (0..self.lines())
+                .filter_map(|line| {
+                    let text = strip_ansi(self.line(line));
+                    diff_file_path(text.trim_end_matches(['\r', '\n']))
+                        .map(|path| DiffFile { line, path })
+                })
+                .collect()
+        })
+    }
+
+    /// Old and new line number of each line, if the content is a git or
+    /// color words diff.
+    /// The content is only scanned the first time.
+    pub fn line_numbers(&self) -> &[DiffLineNumber] {
+        self.line_numbers.get_or_init(|| {
+            diff_line_numbers((0..self.lines()).map(|line| {
+                strip_ansi(self.line(line))
+                    .trim_end_matches(['\r', '\n'])
+                    .to_owned()
+            }))
+        })
+    }
+
+    /// Lines starting a hunk, if the content is a git diff.
+    /// The content is only scanned the first time.
+    pub fn hunks(&self) -> &[usize] {
+        self.hunks.get_or_init(|| {
+            (0..self.lines())
+                .filter(|&line| is_hunk_header(&strip_ansi(self.line(line))))
+                .collect()
+        })
+    }
+
+    /// Line ranges of the conflicts shown in the content.
+    /// The content is only scanned the first time.
+    pub fn conflicts(&self) -> &[Range<usize>] {
+        self.conflicts.get_or_init(|| {
+            conflict_regions(
+                (0..self.lines()).map(|line| {
+                    strip_ansi(self.line(line))
+                        .trim_end_matches(['\r', '\n'])
+                        .to_owned()
+                }),
+                self.line_numbers(),
+            )
+        })
+    }
+
+    /// What changed in each file section of a binary file, by index of
+    /// the section. Only the first lines of other sections are scanned,
+    /// and only the first time.
+    pub fn binary_files(&self) -> &HashMap<usize, BinaryInfo> {
+        self.binary_files.get_or_init(|| {
+            let files = self.diff_files();
+            let plain_line = |line| strip_ansi(self.line(line));
+            (0..files.len())
+                .filter_map(|index| {
+                    let lines = files[index].line..section_end(files, index, self.lines());
+                    let is_binary = lines
+                        .clone()
+                        .skip(1)
+                        .take(4)
+                        .any(|line| is_binary_marker(&plain_line(line)));
+                    is_binary.then(|| (index, BinaryInfo::parse(lines.map(plain_line))))
+                })
+                .collect()
+        })
+    }
+
+    /// Number of rows each line takes, when wrapped into `width` columns.
+    /// The content is only scanned the first time a width is used.
+    pub fn row_counts(&self, width: u16) -> Ref<'_, [u16]> {
+        if self.row_counts.borrow().0 != width {
+            let counts = (0..self.lines())
+                .map(|line| {
+                    let text = strip_ansi(self.line(line));
+                    Paragraph::new(text.trim_end_matches(['\r', '\n']))
+                        .wrap(Wrap { trim: false })
+                        .line_count(width)
+                        .clamp(1, u16::MAX as usize) as u16
+                })
+                .collect();
+            *self.row_counts.borrow_mut() = (width, counts);
+        }
+        Ref::map(self.row_counts.borrow(), |(_, counts)| counts.as_slice())
+    }
+
+    /// Text of a single line, including the line break
+    fn line(&self, line: usize) -> &str {
+        let start = self.line_start[line];
+        let end = self
+            .line_start
+            .get(line + 1)
+            .copied()
+            .unwrap_or(self.content.len());
+        &self.content[start..end]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_wraps_around() {
+        let large_string = LargeString::new("foo\n\x1b[1mbar\x1b[0m\nbaz\nbar\n".to_string());
+        let pattern = SearchPattern::new("bar").unwrap();
+        assert_eq!(large_string.find(&pattern, 0, true), Some(1));
+        assert_eq!(large_string.find(&pattern, 2, true), Some(3));
+        assert_eq!(large_string.find(&pattern, 4, true), Some(1));
+        assert_eq!(large_string.find(&pattern, 0, false), Some(3));
+        // Escape sequences are not part of the text
+        let pattern = SearchPattern::new("1m").unwrap();
+        assert_eq!(large_string.find(&pattern, 0, true), None);
+    }
+
+    #[test]
+    fn replace_lines() {
+        let large_string = LargeString::new("a\nb\nc\nd".to_string());
+        let replaced = large_string.replace_lines(1..3, "x\ny");
+        assert_eq!(replaced.plain_text(0..replaced.lines()), "a\nx\ny\nd");
+        let replaced = large_string.replace_lines(3..4, "x");
+        assert_eq!(replaced.plain_text(0..replaced.lines()), "a\nb\nc\nx");
+    }
+
+    #[test]
+    fn plain_text() {
+        let large_string = LargeString::new("a\n\x1b[1mb\x1b[0m\nc\n".to_string());
+        assert_eq!(large_string.plain_text(1..3), "b\nc\n");
+        assert_eq!(large_string.plain_text(2..5), "c\n");
+    }
+
+    #[test]
+    fn row_counts() {
+        let large_string = LargeString::new("short\nthis line is wrapped\n".to_string());
+        assert_eq!(*large_string.row_counts(10), [1, 2]);
+        assert_eq!(*large_string.row_counts(40), [1, 1]);
+    }
+
+    #[test]
+    fn diff_files() {
+        let large_string = LargeString::new(
+            "Commit ID: 1234\n\nModified regular file a.txt:\n   1    1: a\n\x1b[1mAdded regular file b.txt:\x1b[0m\n        1: b\n"
+                .to_string(),
+        );
+        assert_eq!(
+            large_string.diff_files(),
+            [
+                DiffFile {
+                    line: 2,
+                    path: "a.txt".to_owned()
+                },
+                DiffFile {
+                    line: 4,
+                    path: "b.txt".to_owned()
+                },
+            ]
+        );
+        assert!(large_string.binary_files().is_empty());
+    }
+
+    #[test]
+    fn binary_files() {
+        let large_string = LargeString::new(
+            "Modified regular file a.txt:\n   1    1: a\nAdded regular file logo.png:\n    (binary)\n"
+                .to_string(),
+        );
+        let binary_files = large_string.binary_files();
+        assert_eq!(binary_files.keys().collect::<Vec<_>>(), [&1]);
+        assert_eq!(binary_files[&1].to_string(), "binary file added");
+    }
 }
@@ -0,0 +1,211 @@
+/*! Old and new line numbers of a git format diff.
+
+The numbers are found by parsing the `@@ -old,count +new,count @@`
+header of each hunk, and counting the lines that follow it. The same
+headers are used to navigate between the hunks of a diff.
+
+The color words format of jj prints the numbers at the start of each
+line instead, e.g. `  10   12: text`, so they are read from there.
+*/
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+/// Line numbers of a single line of a diff. Both are None outside of hunks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DiffLineNumber {
+    /// Line number in the old file, if the line is not added
+    pub old: Option<usize>,
+    /// Line number in the new file, if the line is not removed
+    pub new: Option<usize>,
+}
+
+static HUNK_HEADER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^@@ -(\d+)(?:,\d+)? \+(\d+)(?:,\d+)? @@").unwrap());
+
+/// Old and new line number of a color words line. One of them is
+/// missing for removed and added lines.
+static COLOR_WORDS_LINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^ *(\d+)? +(\d+)?: ").unwrap());
+
+/// Return true if the line (without ANSI codes) starts a hunk
+pub fn is_hunk_header(line: &str) -> bool {
+    HUNK_HEADER.is_match(line)
+}
+
+/// Find line numbers of all lines (without ANSI codes) of a diff
+pub fn diff_line_numbers<S: AsRef<str>>(lines: impl IntoIterator<Item = S>) -> Vec<DiffLineNumber> {
+    let mut numbers = vec![];
+    // Next old and new line number, while in a hunk
+    let mut next: Option<(usize, usize)> = None;
+    for line in lines {
+        let line = line.as_ref();
+        if let Some(captures) = HUNK_HEADER.captures(line) {
+            next = Some((
+                captures[1].parse().unwrap_or(0),
+                captures[2].parse().unwrap_or(0),
+            ));
+            numbers.push(DiffLineNumber::default());
+            continue;
+        }
+        let number = match (next.as_mut(), line.chars().next()) {
+            (Some((old, new)), Some(' ') | None) => {
+                let number = DiffLineNumber {
+                    old: Some(*old),
+                    new: Some(*new),
+                };
+                *old += 1;
+                *new += 1;
+                number
+            }
+            (Some((old, _)), Some('-')) => {
+                *old += 1;
+                DiffLineNumber {
+                    old: Some(*old - 1),
+                    new: None,
+                }
+            }
+            (Some((_, new)), Some('+')) => {
+                *new += 1;
+                DiffLineNumber {
+                    old: None,
+                    new: Some(*new - 1),
+                }
+            }
+            // "\ No newline at end of file"
+            (Some(_), Some('\\')) => DiffLineNumber::default(),
+            _ => {
+                next = None;
+                color_words_line_number(line)
+            }
+        };
+        numbers.push(number);
+    }
+    numbers
+}
+
+/// Line numbers at the start of a color words line
+fn color_words_line_number(line: &str) -> DiffLineNumber {
+    let Some(captures) = COLOR_WORDS_LINE.captures(line) else {
+        return DiffLineNumber::default();
+    };
+    let number = |index: usize| captures.get(index).and_then(|m| m.as_str().parse().ok());
+    DiffLineNumber {
+        old: number(1),
+        new: number(2),
+    }
+}
+
+/// Text of a color words line after its line numbers, None if the line
+/// has no line numbers
+pub fn color_words_text(line: &str) -> Option<&str> {
+    COLOR_WORDS_LINE
+        .find(line)
+        .map(|prefix| &line[prefix.end()..])
+}
+
+/// Line of the new file to open for line `index` of a diff. Removed
+/// lines and hunk headers use the next line that is in the new file.
+pub fn new_file_line(numbers: &[DiffLineNumber], index: usize) -> Option<usize> {
+    for (offset, number) in numbers.get(index..)?.iter().enumerate() {
+        match number {
+            DiffLineNumber { new: Some(new), .. } => return Some(*new),
+            DiffLineNumber { old: Some(_), .. } => continue,
+            // A hunk header is only skipped at the start
+            _ if offset == 0 => continue,
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Text of the line number gutter, with room for numbers of `width` digits
+pub fn line_number_gutter(number: &DiffLineNumber, width: usize) -> String {
+    let format = |number: Option<usize>| number.map(|n| n.to_string()).unwrap_or_default();
+    format!(
+        "{:>width$} {:>width$} │ ",
+        format(number.old),
+        format(number.new)
+    )
+}
+
+/// Number of digits needed to show all line numbers
+pub fn line_number_width(numbers: &[DiffLineNumber]) -> usize {
+    numbers
+        .iter()
+        .flat_map(|number| [number.old, number.new])
+        .flatten()
+        .max()
+        .map_or(1, |max| max.to_string().len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_hunk_lines() {
+        let diff = [
+            "diff --git a/README b/README",
+            "@@ -10,3 +20,3 @@",
+            " same",
+            "-old",
+            "+new",
+            "+more",
+            "diff --git a/other b/other",
+        ];
+        let numbers: Vec<_> = diff_line_numbers(diff)
+            .iter()
+            .map(|number| (number.old, number.new))
+            .collect();
+        assert_eq!(
+            numbers,
+            [
+                (None, None),
+                (None, None),
+                (Some(10), Some(20)),
+                (Some(11), None),
+                (None, Some(21)),
+                (None, Some(22)),
+                (None, None),
+            ]
+        );
+        assert_eq!(line_number_width(&diff_line_numbers(diff)), 2);
+        assert!(is_hunk_header("@@ -1 +1,2 @@ fn main() {"));
+        assert!(!is_hunk_header(" @@ -1 +1 @@"));
+
+        let numbers = diff_line_numbers(diff);
+        assert_eq!(new_file_line(&numbers, 1), Some(20));
+        assert_eq!(new_file_line(&numbers, 3), Some(21));
+        assert_eq!(new_file_line(&numbers, 5), Some(22));
+        assert_eq!(new_file_line(&numbers, 0), None);
+        assert_eq!(new_file_line(&numbers, 6), None);
+    }
+
+    #[test]
+    fn read_color_words_numbers() {
+        let diff = [
+            "Modified regular file README:",
+            "   9    9: same",
+            "  10     : old",
+            "        10: new",
+            "",
+        ];
+        let numbers: Vec<_> = diff_line_numbers(diff)
+            .iter()
+            .map(|number| (number.old, number.new))
+            .collect();
+        assert_eq!(
+            numbers,
+            [
+                (None, None),
+                (Some(9), Some(9)),
+                (Some(10), None),
+                (None, Some(10)),
+                (None, None),
+            ]
+        );
+        assert_eq!(new_file_line(&diff_line_numbers(diff), 2), Some(10));
+    }
+}
@@ -0,0 +1,103 @@
+/*! Changes pinned in the log tab.
+
+The pins are kept per repository in `.jj/blazingjj/pins`, one change id
+per line in the order they were pinned. A change stays pinned when it
+is rewritten, since its change id is kept.
+*/
+
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use tracing::error;
+
+use crate::commander::ids::ChangeId;
+
+#[derive(Debug, Default)]
+pub struct Pins {
+    /// File the pins are stored in, None to keep them in memory
+    path: Option<PathBuf>,
+    /// Pinned changes, in the order they were pinned
+    change_ids: Vec<ChangeId>,
+}
+
+impl Pins {
+    /// Load the pins of the repository at `root`. A missing or
+    /// unreadable file means no pins.
+    pub fn load(root: &str) -> Self {
+        let path = Path::new(root).join(".jj").join("blazingjj").join("pins");
+        let change_ids = fs::read_to_string(&path)
+            .map(|content| {
+                content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(|line| ChangeId(line.to_owned()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            change_ids,
+        }
+    }
+
+    /// Pinned changes, in the order they were pinned
+    pub fn change_ids(&self) -> &[ChangeId] {
+        &self.change_ids
+    }
+
+    pub fn is_pinned(&self, change_id: &ChangeId) -> bool {
+        self.change_ids.contains(change_id)
+    }
+
+    /// Pin a change, or unpin it if it is pinned, and save the pins.
+    /// Tells if the change is pinned now.
+    pub fn toggle(&mut self, change_id: &ChangeId) -> bool {
+        let pinned = if self.is_pinned(change_id) {
+            self.change_ids.retain(|pinned| pinned != change_id);
+            false
+        } else {
+            self.change_ids.push(change_id.clone());
+            true
+        };
+        self.save();
+        pinned
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let content: String = self
+            .change_ids
+            .iter()
+            .map(|change_id| format!("{change_id}\n"))
+            .collect();
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(path, content));
+        if let Err(err) = result {
+            error!("Error saving pins to {}: {err}", path.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggle_pins() {
+        let first = ChangeId("qpvuntsm".to_owned());
+        let second = ChangeId("zzzzzzzz".to_owned());
+        let mut pins = Pins::default();
+        assert!(pins.toggle(&first));
+        assert!(pins.toggle(&second));
+        assert_eq!(pins.change_ids(), [first.clone(), second.clone()]);
+        assert!(!pins.toggle(&first));
+        assert!(!pins.is_pinned(&first));
+        assert_eq!(pins.change_ids(), [second]);
+    }
+}
@@ -0,0 +1,240 @@
+/*! Chips for the bookmarks and tags in the log.
+
+jj shows the refs of a change as plain words in the log. With
+`blazingjj.log-ref-chips`, each ref is drawn on a background colored by
+its kind: local bookmarks, remote bookmarks, conflicted bookmarks and
+tags. A change with many refs shows only the first few, followed by a
+`+N` chip counting the others.
+
+A local bookmark that differs from its tracked remote bookmark ends
+with `↓N`, the commits to fetch, and `↑N`, the commits to push, like
+in the bookmarks tab.
+*/
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use ratatui::style::Color;
+use ratatui::style::Style;
+use ratatui::text::Line;
+use ratatui::text::Span;
+
+use crate::commander::bookmarks::TrackingDistance;
+use crate::commander::log::LogRef;
+use crate::commander::log::RefKind;
+
+/// Refs shown as chips in a line, the others are counted
+const MAX_CHIPS: usize = 3;
+
+fn chip_style(kind: RefKind) -> Style {
+    let style = Style::default().fg(Color::Black);
+    match kind {
+        RefKind::Local => style.bg(Color::Magenta),
+        RefKind::Remote => style.bg(Color::Cyan),
+        RefKind::Conflicted => style.fg(Color::White).bg(Color::Red),
+        RefKind::Tag => style.bg(Color::Green),
+    }
+}
+
+/// Text of a chip, with the distance of a local bookmark to its tracked
+/// remote bookmark, e.g. `main* ↓1 ↑2`. jj marks such bookmarks with `*`.
+fn chip_text(log_ref: &LogRef, tracking: &HashMap<String, TrackingDistance>) -> String {
+    let mut text = log_ref.name.clone();
+    if log_ref.kind == RefKind::Local
+        && let Some(distance) = tracking.get(log_ref.name.trim_end_matches('*'))
+    {
+        if distance.ahead > 0 {
+            text.push_str(&format!(" ↓{}", distance.ahead));
+        }
+        if distance.behind > 0 {
+            text.push_str(&format!(" ↑{}", distance.behind));
+        }
+    }
+    text
+}
+
+/// Byte range of the first occurrence of `word` in `text` that is a
+/// whole word, i.e. between whitespace or at the ends of the text
+fn find_word(text: &str, word: &str) -> Option<Range<usize>> {
+    text.match_indices(word)
+        .map(|(start, _)| start..start + word.len())
+        .find(|range| {
+            text[..range.start]
+                .chars()
+                .next_back()
+                .is_none_or(char::is_whitespace)
+                && text[range.end..]
+                    .chars()
+                    .next()
+                    .is_none_or(char::is_whitespace)
+        })
+}
+
+/// Replace byte ranges of a line, which must not overlap, with spans
+fn replace_ranges<'a>(
+    line: Line<'a>,
+    mut edits: Vec<(Range<usize>, Option<Span<'a>>)>,
+) -> Line<'a> {
+    edits.sort_by_key(|(range, _)| range.start);
+    let mut edits = edits.into_iter().peekable();
+    let mut spans = Vec::with_capacity(line.spans.len() + edits.len() * 2);
+    let mut offset = 0;
+    let mut skip_until = 0;
+    for span in line.spans {
+        let content = span.content.as_ref();
+        let (start, end) = (offset, offset + content.len());
+        offset = end;
+        let mut cursor = start.max(skip_until);
+        while cursor < end {
+            match edits.next_if(|(range, _)| range.start < end) {
+                Some((range, replacement)) => {
+                    if range.start > cursor {
+                        spans.push(Span::styled(
+                            content[cursor - start..range.start - start].to_owned(),
+                            span.style,
+                        ));
+                    }
+                    spans.extend(replacement);
+                    skip_until = range.end;
+                    cursor = range.end;
+                }
+                None => {
+                    spans.push(Span::styled(
+                        content[cursor - start..].to_owned(),
+                        span.style,
+                    ));
+                    cursor = end;
+                }
+            }
+        }
+    }
+    Line { spans, ..line }
+}
+
+/// Draw the refs found in a line as chips. Tells if any ref was found,
+/// so the other lines of the change can be left as they are.
+pub fn ref_chips<'a>(
+    line: Line<'a>,
+    refs: &[LogRef],
+    tracking: &HashMap<String, TrackingDistance>,
+) -> (Line<'a>, bool) {
+    let text: String = line
+        .spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect();
+    let mut found: Vec<(Range<usize>, &LogRef)> = refs
+        .iter()
+        .filter_map(|log_ref| Some((find_word(&text, &log_ref.name)?, log_ref)))
+        .collect();
+    if found.is_empty() {
+        return (line, false);
+    }
+    found.sort_by_key(|(range, _)| range.start);
+
+    let hidden = found.len().saturating_sub(MAX_CHIPS);
+    let mut edits = vec![];
+    for (index, (range, log_ref)) in found.into_iter().enumerate() {
+        if index < MAX_CHIPS {
+            let chip = Span::styled(chip_text(log_ref, tracking), chip_style(log_ref.kind));
+            edits.push((range, Some(chip)));
+        } else if index == MAX_CHIPS {
+            let chip = Span::styled(
+                format!("+{hidden}"),
+                Style::default().fg(Color::Black).bg(Color::Gray),
+            );
+            edits.push((range, Some(chip)));
+        } else {
+            // Remove the ref and the space before it
+            let start = if text[..range.start].ends_with(' ') {
+                range.start - 1
+            } else {
+                range.start
+            };
+            edits.push((start..range.end, None));
+        }
+    }
+    (replace_ranges(line, edits), true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_ref(name: &str, kind: RefKind) -> LogRef {
+        LogRef {
+            name: name.to_owned(),
+            kind,
+        }
+    }
+
+    fn content(line: &Line) -> Vec<String> {
+        line.spans
+            .iter()
+            .map(|span| span.content.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn whole_words() {
+        assert_eq!(find_word("main-fix main", "main"), Some(9..13));
+        assert_eq!(find_word("mainline", "main"), None);
+    }
+
+    #[test]
+    fn chips() {
+        let refs = [
+            log_ref("main", RefKind::Local),
+            log_ref("main@origin", RefKind::Remote),
+        ];
+        let line = Line::from(vec![
+            Span::raw("○  qpv alice "),
+            Span::raw("main main@origin 3ba"),
+        ]);
+        let (line, found) = ref_chips(line, &refs, &HashMap::new());
+        assert!(found);
+        assert_eq!(
+            content(&line),
+            ["○  qpv alice ", "main", " ", "main@origin", " 3ba"]
+        );
+        assert_eq!(line.spans[1].style, chip_style(RefKind::Local));
+        assert_eq!(line.spans[3].style, chip_style(RefKind::Remote));
+
+        let (_, found) = ref_chips(Line::raw("│  fix the parser"), &refs, &HashMap::new());
+        assert!(!found);
+    }
+
+    #[test]
+    fn count_hidden_refs() {
+        let refs = ["a", "b", "c", "d", "e"].map(|name| log_ref(name, RefKind::Local));
+        let (line, _) = ref_chips(Line::raw("x a b c d e y"), &refs, &HashMap::new());
+        assert_eq!(
+            content(&line),
+            ["x ", "a", " ", "b", " ", "c", " ", "+2", " y"]
+        );
+    }
+
+    #[test]
+    fn tracking_distances() {
+        let refs = [
+            log_ref("main*", RefKind::Local),
+            log_ref("main@origin", RefKind::Remote),
+            log_ref("fix", RefKind::Local),
+        ];
+        let tracking = HashMap::from([
+            (
+                "main".to_owned(),
+                TrackingDistance {
+                    ahead: 1,
+                    behind: 2,
+                },
+            ),
+            ("fix".to_owned(), TrackingDistance::default()),
+        ]);
+        let (line, _) = ref_chips(Line::raw("x main* main@origin fix"), &refs, &tracking);
+        assert_eq!(
+            content(&line),
+            ["x ", "main* ↓1 ↑2", " ", "main@origin", " ", "fix"]
+        );
+    }
+}
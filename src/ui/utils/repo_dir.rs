@@ -0,0 +1,116 @@
+/*! Location and size of the jj repository of a workspace.
+
+The repository is usually in `.jj/repo` of the workspace, but a
+secondary workspace made with `jj workspace add` only has a file there
+with the path of the repository of the main workspace. The size counts
+the git repository too, so maintenance can tell how much it reclaimed.
+*/
+
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Directory of the jj repository of the workspace at `root`, usually
+/// `.jj/repo`. In secondary workspaces `.jj/repo` is a file with the
+/// path of the repository.
+pub fn repo_dir(root: &Path) -> Option<PathBuf> {
+    let jj_dir = root.join(".jj");
+    let repo_dir = jj_dir.join("repo");
+    if repo_dir.is_file() {
+        Some(jj_dir.join(fs::read_to_string(&repo_dir).ok()?.trim()))
+    } else {
+        Some(repo_dir).filter(|repo_dir| repo_dir.is_dir())
+    }
+}
+
+/// Bytes taken by the repository, including the git repository it is
+/// colocated with or stored in
+pub fn repo_size(root: &Path) -> u64 {
+    let Some(repo_dir) = repo_dir(root).and_then(|repo_dir| repo_dir.canonicalize().ok()) else {
+        return 0;
+    };
+    let store_dir = repo_dir.join("store");
+    let git_dir = fs::read_to_string(store_dir.join("git_target"))
+        .ok()
+        .and_then(|git_target| store_dir.join(git_target.trim()).canonicalize().ok());
+    let git_size = match git_dir {
+        // Counted with the repository already
+        Some(git_dir) if git_dir.starts_with(&repo_dir) => 0,
+        Some(git_dir) => dir_size(&git_dir),
+        None => 0,
+    };
+    dir_size(&repo_dir) + git_size
+}
+
+/// Bytes taken by the files of a directory and its subdirectories,
+/// without following symbolic links
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => dir_size(&entry.path()),
+            Ok(file_type) if file_type.is_file() => entry.metadata().map_or(0, |meta| meta.len()),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Format a number of bytes for people, e.g. `1.5 MiB`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn repo_sizes() -> std::io::Result<()> {
+        let directory = TempDir::new()?;
+        assert_eq!(repo_dir(directory.path()), None);
+        assert_eq!(repo_size(directory.path()), 0);
+
+        let store_dir = directory.path().join(".jj/repo/store");
+        fs::create_dir_all(&store_dir)?;
+        fs::write(store_dir.join("git_target"), "../../../.git")?;
+        fs::write(store_dir.join("objects"), [0; 100])?;
+        fs::create_dir_all(directory.path().join(".git"))?;
+        fs::write(directory.path().join(".git/pack"), [0; 1000])?;
+        assert_eq!(
+            repo_dir(directory.path()),
+            Some(directory.path().join(".jj/repo"))
+        );
+        assert_eq!(repo_size(directory.path()), 100 + 13 + 1000);
+
+        let workspace = directory.path().join("workspace");
+        fs::create_dir_all(workspace.join(".jj"))?;
+        fs::write(workspace.join(".jj/repo"), "../../.jj/repo")?;
+        assert_eq!(repo_size(&workspace), 100 + 13 + 1000);
+
+        Ok(())
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MiB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+}
@@ -0,0 +1,163 @@
+/*! History of the revsets shown in the log tab.
+
+The history is kept per repository in `.jj/blazingjj/revset-history`,
+one revset per line with the most recent first. Revsets typed over
+several lines are stored on one line, which jj reads the same way.
+
+In the revset popup, Up and Down step through the history like in a
+shell: Up recalls older revsets, Down newer ones and finally the text
+that was typed before stepping into the history.
+*/
+
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use tracing::error;
+
+/// Revsets kept in the history
+const MAX_ENTRIES: usize = 100;
+
+#[derive(Debug, Default)]
+pub struct RevsetHistory {
+    /// File the history is stored in, None to keep it in memory
+    path: Option<PathBuf>,
+    /// Revsets, most recent first
+    entries: Vec<String>,
+    /// Entry recalled with Up/Down, None while editing the draft
+    position: Option<usize>,
+    /// Text typed before stepping into the history
+    draft: String,
+}
+
+impl RevsetHistory {
+    /// Load the history of the repository at `root`. A missing or
+    /// unreadable file is an empty history.
+    pub fn load(root: &str) -> Self {
+        let path = Path::new(root)
+            .join(".jj")
+            .join("blazingjj")
+            .join("revset-history");
+        let entries = fs::read_to_string(&path)
+            .map(|content| {
+                content
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            entries,
+            ..Self::default()
+        }
+    }
+
+    /// Revsets, most recent first
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Add a revset that was shown, or move it to the front if it was
+    /// used before, and save the history
+    pub fn push(&mut self, revset: &str) {
+        let revset = revset.split_whitespace().collect::<Vec<_>>().join(" ");
+        if revset.is_empty() {
+            return;
+        }
+        self.entries.retain(|entry| *entry != revset);
+        self.entries.insert(0, revset);
+        self.entries.truncate(MAX_ENTRIES);
+        self.reset();
+        self.save();
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let content: String = self
+            .entries
+            .iter()
+            .map(|entry| format!("{entry}\n"))
+            .collect();
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(path, content));
+        if let Err(err) = result {
+            error!("Error saving revset history to {}: {err}", path.display());
+        }
+    }
+
+    /// Start again from the draft, e.g. when the revset popup opens
+    pub fn reset(&mut self) {
+        self.position = None;
+        self.draft.clear();
+    }
+
+    /// Recall the next older revset. `text` is the text being edited,
+    /// kept as the draft when stepping into the history.
+    pub fn older(&mut self, text: &str) -> Option<&str> {
+        let position = match self.position {
+            None => {
+                self.draft = text.to_owned();
+                0
+            }
+            Some(position) => position + 1,
+        };
+        let entry = self.entries.get(position)?;
+        self.position = Some(position);
+        Some(entry)
+    }
+
+    /// Recall the next newer revset, or the draft after the most recent
+    pub fn newer(&mut self) -> Option<&str> {
+        match self.position? {
+            0 => {
+                self.position = None;
+                Some(&self.draft)
+            }
+            position => {
+                self.position = Some(position - 1);
+                Some(&self.entries[position - 1])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_and_recall() {
+        let mut history = RevsetHistory::default();
+        history.push("mine()");
+        history.push("::@");
+        history.push("  mine()\n & ~empty()  ");
+        history.push("::@");
+        assert_eq!(history.entries(), ["::@", "mine() & ~empty()", "mine()"]);
+
+        assert_eq!(history.newer(), None);
+        assert_eq!(history.older("trunk()"), Some("::@"));
+        assert_eq!(history.older("::@"), Some("mine() & ~empty()"));
+        assert_eq!(history.older("mine() & ~empty()"), Some("mine()"));
+        assert_eq!(history.older("mine()"), None);
+        assert_eq!(history.newer(), Some("mine() & ~empty()"));
+        assert_eq!(history.newer(), Some("::@"));
+        assert_eq!(history.newer(), Some("trunk()"));
+        assert_eq!(history.newer(), None);
+    }
+
+    #[test]
+    fn keep_most_recent() {
+        let mut history = RevsetHistory::default();
+        for index in 0..MAX_ENTRIES + 5 {
+            history.push(&format!("@-{index}"));
+        }
+        assert_eq!(history.entries().len(), MAX_ENTRIES);
+        assert_eq!(history.entries()[0], format!("@-{}", MAX_ENTRIES + 4));
+    }
+}
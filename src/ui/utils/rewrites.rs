@@ -0,0 +1,61 @@
+/*! Preview of the changes a command rewrites.
+
+jj has no dry run for rebase, abandon or squash, so the confirmations of
+these commands run them with `--ignore-working-copy`, list the changes
+that got rewritten or abandoned, and revert the operation they made.
+The preview is made when the dialog opens, not while drawing it. The
+lists are cut to a few changes each, so the dialogs keep fitting.
+*/
+
+use anyhow::Result;
+use ratatui::style::Color;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+
+use crate::commander::Commander;
+use crate::commander::new_commander;
+
+/// Number of changes listed in each section
+const LISTED_CHANGES: usize = 6;
+
+/// Lines listing the changes that `run` would rewrite and abandon
+pub fn rewrites_preview(run: impl FnOnce(&mut Commander) -> Result<()>) -> Vec<Line<'static>> {
+    let rewrites = match new_commander().simulate_rewrites(run) {
+        Ok(rewrites) => rewrites,
+        Err(err) => {
+            let message = err.to_string();
+            let first_line = message.lines().next().unwrap_or_default().to_owned();
+            return vec![Line::from(format!("No preview: {first_line}")).fg(Color::Red)];
+        }
+    };
+    let mut lines = vec![];
+    for (verb, changes, color) in [
+        ("Rewrites", &rewrites.rewritten, Color::Yellow),
+        ("Abandons", &rewrites.abandoned, Color::Red),
+    ] {
+        if changes.is_empty() {
+            continue;
+        }
+        let noun = if changes.len() == 1 {
+            "change"
+        } else {
+            "changes"
+        };
+        lines.push(Line::from(format!("{verb} {} {noun}:", changes.len())));
+        lines.extend(
+            changes
+                .iter()
+                .take(LISTED_CHANGES)
+                .map(|summary| Line::from(format!("  {summary}")).fg(color)),
+        );
+        if changes.len() > LISTED_CHANGES {
+            lines.push(
+                Line::from(format!("  … and {} more", changes.len() - LISTED_CHANGES)).fg(color),
+            );
+        }
+    }
+    if lines.is_empty() {
+        lines.push(Line::from("No changes are rewritten").fg(Color::DarkGray));
+    }
+    lines
+}
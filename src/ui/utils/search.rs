@@ -0,0 +1,136 @@
+/*! Text search used by the details panel.
+
+Matching is "smart case": a query without upper case letters matches
+case insensitively, otherwise the case must match exactly. Only ASCII
+letters are folded, so byte offsets of matches are the same in the
+folded and the original text.
+*/
+
+use std::ops::Range;
+
+use ratatui::style::Color;
+use ratatui::style::Style;
+use ratatui::text::Line;
+use ratatui::text::Span;
+
+/// A compiled search query
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchPattern {
+    needle: String,
+    ignore_case: bool,
+}
+
+impl SearchPattern {
+    /// Create a pattern. Returns None for an empty query.
+    pub fn new(query: &str) -> Option<Self> {
+        if query.is_empty() {
+            return None;
+        }
+        let ignore_case = !query.chars().any(char::is_uppercase);
+        let needle = if ignore_case {
+            query.to_ascii_lowercase()
+        } else {
+            query.to_owned()
+        };
+        Some(Self {
+            needle,
+            ignore_case,
+        })
+    }
+
+    /// Return true if the text contains at least one match
+    pub fn is_match(&self, text: &str) -> bool {
+        if self.ignore_case {
+            text.to_ascii_lowercase().contains(&self.needle)
+        } else {
+            text.contains(&self.needle)
+        }
+    }
+
+    /// Byte ranges of all non-overlapping matches in text
+    pub fn find_all(&self, text: &str) -> Vec<Range<usize>> {
+        let haystack = if self.ignore_case {
+            text.to_ascii_lowercase()
+        } else {
+            text.to_owned()
+        };
+        haystack
+            .match_indices(&self.needle)
+            .map(|(start, found)| start..start + found.len())
+            .collect()
+    }
+
+    /// Split the spans of a line so all matches are rendered with the
+    /// search highlight style.
+    pub fn highlight_line<'a>(&self, line: Line<'a>) -> Line<'a> {
+        let text: String = line
+            .spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect();
+        let matches = self.find_all(&text);
+        if matches.is_empty() {
+            return line;
+        }
+
+        let highlight = Style::default().fg(Color::Black).bg(Color::Yellow);
+        let mut spans = Vec::with_capacity(line.spans.len() + matches.len() * 2);
+        let mut offset = 0;
+        for span in line.spans {
+            let content = span.content.as_ref();
+            let span_range = offset..offset + content.len();
+            // Cut points inside this span, relative to the span start
+            let mut cuts = vec![0];
+            for m in matches.iter() {
+                for point in [m.start, m.end] {
+                    if point > span_range.start && point < span_range.end {
+                        cuts.push(point - offset);
+                    }
+                }
+            }
+            cuts.push(content.len());
+            for part in cuts.windows(2) {
+                if part[0] == part[1] {
+                    continue;
+                }
+                let start = offset + part[0];
+                let in_match = matches.iter().any(|m| m.start <= start && start < m.end);
+                let style = if in_match {
+                    span.style.patch(highlight)
+                } else {
+                    span.style
+                };
+                spans.push(Span::styled(content[part[0]..part[1]].to_owned(), style));
+            }
+            offset = span_range.end;
+        }
+
+        Line { spans, ..line }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smart_case() {
+        let lower = SearchPattern::new("readme").unwrap();
+        assert!(lower.is_match("A README"));
+        let upper = SearchPattern::new("README").unwrap();
+        assert!(!upper.is_match("readme"));
+        assert!(SearchPattern::new("").is_none());
+    }
+
+    #[test]
+    fn highlight_across_spans() {
+        let pattern = SearchPattern::new("lo wo").unwrap();
+        let line =
+            pattern.highlight_line(Line::from(vec![Span::raw("hello "), Span::raw("world")]));
+        let contents: Vec<&str> = line.spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(contents, ["hel", "lo ", "wo", "rld"]);
+        assert_eq!(line.spans[1].style.bg, Some(Color::Yellow));
+        assert_eq!(line.spans[2].style.bg, Some(Color::Yellow));
+        assert_eq!(line.spans[3].style.bg, None);
+    }
+}
@@ -0,0 +1,196 @@
+/*! Convert a git format diff into a side by side diff.
+
+jj has no built-in side by side format, so the details panel asks jj
+for a git diff and rearranges the hunks into two columns: the old
+content to the left and the new content to the right. Everything
+outside of hunks (change description, file headers) is kept as is.
+
+The output is ANSI colour coded text, so it can be stored in a
+[LargeString](super::LargeString) like any other `jj show` output.
+*/
+
+use unicode_width::UnicodeWidthChar;
+use unicode_width::UnicodeWidthStr;
+
+use crate::ui::utils::strip_ansi;
+
+/// Narrower than this, the columns are too small to be useful
+/// and the git diff is returned unchanged.
+const MIN_WIDTH: usize = 20;
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const CYAN: &str = "\x1b[36m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+const SEPARATOR: &str = "│";
+
+/// Rearrange the hunks of a git diff into two columns that fit into
+/// `width` terminal columns.
+pub fn side_by_side(diff: &str, width: usize) -> String {
+    if width < MIN_WIDTH {
+        return diff.to_owned();
+    }
+    let mut renderer = Renderer {
+        left_width: (width - 1) / 2,
+        right_width: width - 1 - (width - 1) / 2,
+        output: String::with_capacity(diff.len() * 2),
+        removed: vec![],
+        added: vec![],
+    };
+
+    let mut in_hunk = false;
+    for line in diff.lines() {
+        let plain = strip_ansi(line);
+        if plain.starts_with("@@") {
+            renderer.flush();
+            in_hunk = true;
+            renderer.push_raw(&format!("{CYAN}{plain}{RESET}"));
+            continue;
+        }
+        if !in_hunk {
+            renderer.push_raw(line);
+            continue;
+        }
+        match plain.chars().next() {
+            Some('-') => renderer.removed.push(plain[1..].to_owned()),
+            Some('+') => renderer.added.push(plain[1..].to_owned()),
+            Some(' ') => {
+                renderer.flush();
+                renderer.push_row(&plain[1..], "", &plain[1..], "");
+            }
+            // Git removes the space of empty context lines in some setups
+            None => {
+                renderer.flush();
+                renderer.push_row("", "", "", "");
+            }
+            // "\ No newline at end of file"
+            Some('\\') => {
+                renderer.flush();
+                renderer.push_raw(&format!("{DIM}{plain}{RESET}"));
+            }
+            Some(_) => {
+                renderer.flush();
+                in_hunk = false;
+                renderer.push_raw(line);
+            }
+        }
+    }
+    renderer.flush();
+    // Like the input, the output does not end with a line break
+    renderer.output.pop();
+    renderer.output
+}
+
+/// Accumulates output of side by side rendering
+struct Renderer {
+    left_width: usize,
+    right_width: usize,
+    output: String,
+    /// Removed lines not yet paired with added lines
+    removed: Vec<String>,
+    /// Added lines not yet paired with removed lines
+    added: Vec<String>,
+}
+
+impl Renderer {
+    /// Add a full width line
+    fn push_raw(&mut self, line: &str) {
+        self.output.push_str(line);
+        self.output.push('\n');
+    }
+
+    /// Add a row with content in both columns. Content too wide for its
+    /// column is wrapped into extra rows.
+    fn push_row(&mut self, left: &str, left_color: &str, right: &str, right_color: &str) {
+        let left = split_at_width(left, self.left_width);
+        let right = split_at_width(right, self.right_width);
+        for i in 0..left.len().max(right.len()) {
+            let left_part = left.get(i).map(String::as_str).unwrap_or_default();
+            let right_part = right.get(i).map(String::as_str).unwrap_or_default();
+            let padding = self.left_width.saturating_sub(left_part.width());
+            self.output.push_str(&format!(
+                "{left_color}{left_part}{RESET}{:padding$}{DIM}{SEPARATOR}{RESET}{right_color}{right_part}{RESET}\n",
+                ""
+            ));
+        }
+    }
+
+    /// Pair up removed and added lines, and add them as rows
+    fn flush(&mut self) {
+        let removed = std::mem::take(&mut self.removed);
+        let added = std::mem::take(&mut self.added);
+        for i in 0..removed.len().max(added.len()) {
+            let left = removed.get(i).map(String::as_str).unwrap_or_default();
+            let right = added.get(i).map(String::as_str).unwrap_or_default();
+            self.push_row(left, RED, right, GREEN);
+        }
+    }
+}
+
+/// Split text into parts that take at most width terminal columns.
+/// Wide characters, like CJK or emoji, take two columns. A character
+/// wider than width gets a part of its own. Always returns at least
+/// one part.
+fn split_at_width(text: &str, width: usize) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut part_width = 0;
+    for c in text.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if part_width + char_width > width && part_width > 0 {
+            parts.push(String::new());
+            part_width = 0;
+        }
+        if let Some(part) = parts.last_mut() {
+            part.push(c);
+        }
+        part_width += char_width;
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairs_removed_and_added_lines() {
+        let diff = "\
+diff --git a/file b/file
+--- a/file
++++ b/file
+@@ -1,3 +1,3 @@
+ same
+-old
++new
++extra";
+        let output = strip_ansi(&side_by_side(diff, 21));
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "diff --git a/file b/file",
+                "--- a/file",
+                "+++ b/file",
+                "@@ -1,3 +1,3 @@",
+                "same      │same",
+                "old       │new",
+                "          │extra",
+            ]
+        );
+    }
+
+    #[test]
+    fn columns_use_display_width() {
+        let diff = "@@ -1 +1 @@\n-日本語の文字列\n+new";
+        let output = strip_ansi(&side_by_side(diff, 21));
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines, ["@@ -1 +1 @@", "日本語の文│new", "字列      │"]);
+    }
+
+    #[test]
+    fn too_narrow_is_unchanged() {
+        let diff = "@@ -1 +1 @@\n-old\n+new";
+        assert_eq!(side_by_side(diff, 10), diff);
+    }
+}
@@ -0,0 +1,105 @@
+/*! Sparse patterns of the working copy, as edited in the sparse popup.
+
+A pattern is the path of a directory or file relative to the root, and
+the working copy has the files under one of its patterns. `.` is the
+root, i.e. all files.
+*/
+
+/// Check a pattern to add, and normalize it, e.g. `src/` to `src`
+pub fn validate_sparse_pattern(pattern: &str, patterns: &[String]) -> Result<String, &'static str> {
+    let pattern = pattern.trim();
+    if pattern.is_empty() {
+        return Err("The pattern is empty");
+    }
+    if pattern.starts_with('/') || pattern.contains('\\') {
+        return Err("Patterns are paths relative to the root, with / between directories");
+    }
+    let pattern = match pattern.trim_end_matches('/') {
+        "" => ".",
+        pattern => pattern,
+    };
+    if pattern != "."
+        && pattern
+            .split('/')
+            .any(|component| matches!(component, "" | "." | ".."))
+    {
+        return Err("Patterns can't have empty, . or .. components");
+    }
+    if patterns.iter().any(|existing| existing == pattern) {
+        return Err("The pattern is already in the list");
+    }
+    Ok(pattern.to_owned())
+}
+
+/// Whether the working copy has a file with the patterns
+pub fn sparse_matches(path: &str, patterns: &[String]) -> bool {
+    patterns.iter().any(|pattern| {
+        pattern == "."
+            || path
+                .strip_prefix(pattern.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })
+}
+
+/// Files of the working copy that changing the patterns would remove
+pub fn sparse_removed_files<'a>(
+    files: &'a [String],
+    old: &[String],
+    new: &[String],
+) -> Vec<&'a str> {
+    files
+        .iter()
+        .filter(|file| sparse_matches(file, old) && !sparse_matches(file, new))
+        .map(String::as_str)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(patterns: &[&str]) -> Vec<String> {
+        patterns
+            .iter()
+            .map(|pattern| (*pattern).to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn validate_patterns() {
+        let existing = patterns(&["src"]);
+        assert_eq!(
+            validate_sparse_pattern(" docs/ ", &existing),
+            Ok("docs".to_owned())
+        );
+        assert_eq!(validate_sparse_pattern("./", &existing), Ok(".".to_owned()));
+        assert!(validate_sparse_pattern("", &existing).is_err());
+        assert!(validate_sparse_pattern("/etc", &existing).is_err());
+        assert!(validate_sparse_pattern("src/../..", &existing).is_err());
+        assert!(validate_sparse_pattern("src//ui", &existing).is_err());
+        assert!(validate_sparse_pattern("src/", &existing).is_err());
+    }
+
+    #[test]
+    fn removed_files() {
+        let files = patterns(&[
+            "README.md",
+            "src/main.rs",
+            "src-old/main.rs",
+            "docs/guide.md",
+        ]);
+        assert_eq!(
+            sparse_removed_files(&files, &patterns(&["."]), &patterns(&["src"])),
+            ["README.md", "src-old/main.rs", "docs/guide.md"]
+        );
+        assert_eq!(
+            sparse_removed_files(
+                &files,
+                &patterns(&["src", "docs"]),
+                &patterns(&["docs/guide.md"])
+            ),
+            ["src/main.rs"]
+        );
+        assert!(sparse_removed_files(&files, &patterns(&["src"]), &patterns(&["."])).is_empty());
+    }
+}
@@ -0,0 +1,178 @@
+/*! Language aware colouring of git format diffs, and of file contents.
+
+jj colours a git diff by line type only. This module replaces the colour
+of the lines inside hunks with syntax highlighting based on the file
+extension, and marks added and removed lines with a background tint.
+Everything outside of hunks is kept as is.
+
+The old and new version of a file are highlighted separately, so a
+removed line never changes the parse state of the added lines.
+*/
+
+use std::sync::OnceLock;
+
+use syntect::easy::HighlightLines;
+use syntect::highlighting::Theme;
+use syntect::highlighting::ThemeSet;
+use syntect::parsing::SyntaxReference;
+use syntect::parsing::SyntaxSet;
+use syntect::util::as_24_bit_terminal_escaped;
+
+use crate::ui::utils::strip_ansi;
+
+/// Larger diffs are not highlighted, as it would take too long
+const MAX_HIGHLIGHT_BYTES: usize = 4 * 1024 * 1024;
+
+const THEME_NAME: &str = "base16-ocean.dark";
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const REMOVED_BG: &str = "\x1b[48;2;63;0;1m";
+const ADDED_BG: &str = "\x1b[48;2;0;40;0m";
+const RESET: &str = "\x1b[0m";
+
+/// Syntax definitions and theme are expensive to load, so they are
+/// loaded once on first use.
+fn syntax_set() -> &'static SyntaxSet {
+    static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAX_SET.get_or_init(SyntaxSet::load_defaults_nonewlines)
+}
+
+fn theme() -> &'static Theme {
+    static THEME: OnceLock<Theme> = OnceLock::new();
+    THEME.get_or_init(|| {
+        ThemeSet::load_defaults()
+            .themes
+            .remove(THEME_NAME)
+            .unwrap_or_default()
+    })
+}
+
+/// Highlight the hunks of a git diff
+pub fn highlight_diff(diff: &str) -> String {
+    if diff.len() > MAX_HIGHLIGHT_BYTES {
+        return diff.to_owned();
+    }
+    let syntax_set = syntax_set();
+    let theme = theme();
+
+    let mut output = String::with_capacity(diff.len() * 3);
+    // Highlighters for old and new file content. None outside of hunks
+    // and for files of unknown type.
+    let mut file: Option<(HighlightLines, HighlightLines)> = None;
+    let mut syntax: Option<&SyntaxReference> = None;
+    let mut in_hunk = false;
+    for line in diff.lines() {
+        let plain = strip_ansi(line);
+        if let Some(paths) = plain.strip_prefix("diff --git ") {
+            in_hunk = false;
+            syntax = syntax_for_paths(syntax_set, paths);
+            file = syntax.map(|syntax| {
+                (
+                    HighlightLines::new(syntax, theme),
+                    HighlightLines::new(syntax, theme),
+                )
+            });
+        } else if plain.starts_with("@@") {
+            in_hunk = syntax.is_some();
+        } else if in_hunk && let Some((old, new)) = file.as_mut() {
+            let highlighted = match plain.chars().next() {
+                Some('-') => Some(format!(
+                    "{REMOVED_BG}{RED}-{}",
+                    highlight(old, syntax_set, &plain[1..])
+                )),
+                Some('+') => Some(format!(
+                    "{ADDED_BG}{GREEN}+{}",
+                    highlight(new, syntax_set, &plain[1..])
+                )),
+                Some(' ') => {
+                    // Keep the old file parse state in sync
+                    highlight(old, syntax_set, &plain[1..]);
+                    Some(format!(" {}", highlight(new, syntax_set, &plain[1..])))
+                }
+                Some('\\') => None,
+                _ => {
+                    in_hunk = false;
+                    None
+                }
+            };
+            if let Some(highlighted) = highlighted {
+                output.push_str(&highlighted);
+                output.push_str(RESET);
+                output.push('\n');
+                continue;
+            }
+        }
+        output.push_str(line);
+        output.push('\n');
+    }
+    // Like the input, the output does not end with a line break
+    output.pop();
+    output
+}
+
+/// Highlight the content of a file, by the extension of its path
+pub fn highlight_file(path: &str, content: &str) -> String {
+    let syntax_set = syntax_set();
+    let syntax = match syntax_for_paths(syntax_set, path) {
+        Some(syntax) if content.len() <= MAX_HIGHLIGHT_BYTES => syntax,
+        _ => return content.to_owned(),
+    };
+    let mut highlighter = HighlightLines::new(syntax, theme());
+    content
+        .lines()
+        .map(|line| format!("{}{RESET}", highlight(&mut highlighter, syntax_set, line)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Find syntax of the new path in "a/old b/new"
+fn syntax_for_paths<'a>(syntax_set: &'a SyntaxSet, paths: &str) -> Option<&'a SyntaxReference> {
+    let path = paths.rsplit(" b/").next()?;
+    let file_name = path.rsplit('/').next()?;
+    let extension = file_name.rsplit_once('.').map_or(file_name, |(_, ext)| ext);
+    syntax_set.find_syntax_by_extension(extension)
+}
+
+/// Highlight a single line of code as ANSI text
+fn highlight(highlighter: &mut HighlightLines, syntax_set: &SyntaxSet, code: &str) -> String {
+    match highlighter.highlight_line(code, syntax_set) {
+        Ok(ranges) => as_24_bit_terminal_escaped(&ranges, false),
+        Err(_) => code.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn highlights_only_hunks_of_known_files() {
+        let diff = "\
+diff --git a/main.rs b/main.rs
+--- a/main.rs
++++ b/main.rs
+@@ -1 +1 @@
+-fn old() {}
++fn new() {}
+diff --git a/notes.unknown b/notes.unknown
+@@ -1 +1 @@
+-old";
+        let output = highlight_diff(diff);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0..4], diff.lines().collect::<Vec<_>>()[0..4]);
+        assert!(lines[4].starts_with(REMOVED_BG));
+        assert!(lines[5].starts_with(ADDED_BG));
+        assert_eq!(strip_ansi(&output), diff);
+        assert_eq!(lines[8], "-old");
+    }
+
+    #[test]
+    fn highlights_files_of_known_types() {
+        let code = "fn main() {\n    let a = 1;\n}";
+        let output = highlight_file("src/main.rs", code);
+        assert_ne!(output, code);
+        assert_eq!(strip_ansi(&output), code);
+        assert_eq!(highlight_file("notes.unknown", code), code);
+    }
+}
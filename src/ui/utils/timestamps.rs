@@ -0,0 +1,112 @@
+/*! Relative timestamps in the log, and colors for ages.
+
+The log is loaded with ISO 8601 timestamps, see
+[get_log][crate::commander::Commander::get_log], so it can switch
+between absolute and relative timestamps without running `jj log`
+again. Relative timestamps are written like `timestamp.ago()` of jj
+templates, e.g. "3 hours ago".
+*/
+
+use std::borrow::Cow;
+use std::sync::LazyLock;
+
+use chrono::DateTime;
+use chrono::Utc;
+use ratatui::style::Color;
+use regex::Captures;
+use regex::Regex;
+
+/// Timestamp as formatted by the log template
+static ISO_TIMESTAMP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(?:Z|[+-]\d{2}:\d{2})").unwrap()
+});
+
+/// Duration in words, like "3 hours ago"
+pub fn format_ago(seconds: i64) -> String {
+    const UNITS: [(&str, i64); 7] = [
+        ("year", 365 * 24 * 3600),
+        ("month", 30 * 24 * 3600),
+        ("week", 7 * 24 * 3600),
+        ("day", 24 * 3600),
+        ("hour", 3600),
+        ("minute", 60),
+        ("second", 1),
+    ];
+    if seconds < 1 {
+        return "now".to_owned();
+    }
+    let (unit, length) = UNITS
+        .into_iter()
+        .find(|(_, length)| seconds >= *length)
+        .unwrap_or(UNITS[UNITS.len() - 1]);
+    match seconds / length {
+        1 => format!("1 {unit} ago"),
+        count => format!("{count} {unit}s ago"),
+    }
+}
+
+/// Color of something `seconds` old, brighter for newer, e.g. the lines
+/// of the annotate tab
+pub fn age_color(seconds: i64) -> Color {
+    const AGES: [(i64, Color); 4] = [
+        (24 * 3600, Color::LightGreen),
+        (7 * 24 * 3600, Color::Green),
+        (30 * 24 * 3600, Color::Yellow),
+        (365 * 24 * 3600, Color::Blue),
+    ];
+    AGES.into_iter()
+        .find(|(age, _)| seconds < *age)
+        .map_or(Color::DarkGray, |(_, color)| color)
+}
+
+/// Replace the ISO timestamps of the log with the time passed since
+/// `now`
+pub fn relative_timestamps(log: &str, now: DateTime<Utc>) -> Cow<'_, str> {
+    ISO_TIMESTAMP.replace_all(log, |captures: &Captures| {
+        let timestamp = &captures[0];
+        match DateTime::parse_from_rfc3339(timestamp) {
+            Ok(time) => format_ago((now - time.with_timezone(&Utc)).num_seconds()),
+            Err(_) => timestamp.to_owned(),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_durations() {
+        assert_eq!(format_ago(-5), "now");
+        assert_eq!(format_ago(1), "1 second ago");
+        assert_eq!(format_ago(59), "59 seconds ago");
+        assert_eq!(format_ago(3 * 3600 + 59), "3 hours ago");
+        assert_eq!(format_ago(24 * 3600), "1 day ago");
+        assert_eq!(format_ago(15 * 24 * 3600), "2 weeks ago");
+        assert_eq!(format_ago(800 * 24 * 3600), "2 years ago");
+    }
+
+    #[test]
+    fn age_colors() {
+        assert_eq!(age_color(-5), Color::LightGreen);
+        assert_eq!(age_color(3600), Color::LightGreen);
+        assert_eq!(age_color(3 * 24 * 3600), Color::Green);
+        assert_eq!(age_color(10 * 24 * 3600), Color::Yellow);
+        assert_eq!(age_color(100 * 24 * 3600), Color::Blue);
+        assert_eq!(age_color(800 * 24 * 3600), Color::DarkGray);
+    }
+
+    #[test]
+    fn replace_timestamps() {
+        let now = DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let log = "@  qpvuntsm alice 2024-05-01T13:30:00+02:00 3ba7e7fd\n\
+                   ○  zzzzzzzz root() 1970-01-01T00:00:00Z 00000000";
+        assert_eq!(
+            relative_timestamps(log, now),
+            "@  qpvuntsm alice 30 minutes ago 3ba7e7fd\n\
+             ○  zzzzzzzz root() 54 years ago 00000000"
+        );
+    }
+}